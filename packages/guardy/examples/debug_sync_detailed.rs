@@ -11,10 +11,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         dest_path: ".".to_string(),
         include: vec!["*".to_string()],
         exclude: vec![".git".to_string()],
+        map: Vec::new(),
+        auth: None,
+        on_conflict: Default::default(),
+        prune: false,
+        preserve_symlinks: false,
+        post_sync: Vec::new(),
+        verify: None,
     };
 
     let sync_config = SyncConfig {
         repos: vec![sync_repo],
+        ..Default::default()
     };
 
     let manager = SyncManager::with_config(sync_config)?;
@@ -23,6 +23,14 @@ pub enum ConfigCommand {
     Get { key: String },
     /// Validate configuration file
     Validate,
+    /// List the GUARDY_* environment variables that override config keys
+    EnvVars,
+    /// Update deprecated keys in the config files, keeping comments
+    Fix {
+        /// Rewrite the files instead of only listing the changes
+        #[arg(long)]
+        write: bool,
+    },
 }
 
 pub async fn execute(
@@ -110,8 +118,90 @@ pub async fn execute(
         ConfigCommand::Validate => {
             styled!("Validating {} configuration...", ("guardy", "primary"));
             let _config = GuardyConfig::load(None, None::<&()>, verbosity_level)?; // This will fail if config is invalid
+
+            // The env layer ignores variables that match no key, so typos go unnoticed
+            let unknown = crate::config::env_vars::unknown(
+                &crate::config::env_vars::documented()?,
+                std::env::vars().map(|(name, _)| name),
+            );
+            for var in &unknown {
+                let hint = var
+                    .suggestion
+                    .as_ref()
+                    .map(|name| format!(" - did you mean {name}?"))
+                    .unwrap_or_default();
+                warning!(&format!(
+                    "{} (environment) matches no configuration key{hint}",
+                    var.name
+                ));
+            }
+            if !unknown.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "{} unknown GUARDY_* environment variable(s)",
+                    unknown.len()
+                ));
+            }
             styled!("{} Configuration is valid!", ("✅", "success_symbol"));
         }
+        ConfigCommand::EnvVars => {
+            use crate::config::env_vars;
+
+            for var in env_vars::documented()? {
+                styled!(
+                    "{}  {}",
+                    (var.to_string(), "property"),
+                    (format!("→ {}", var.key), "muted")
+                );
+            }
+            styled!(
+                "{}",
+                (
+                    "Hook settings (hooks.pre-commit, ...) can only be set in config files",
+                    "muted"
+                )
+            );
+        }
+        ConfigCommand::Fix { write } => {
+            use crate::config::deprecations;
+            use crate::shared::atomic_io;
+
+            let (mut applied, mut manual) = (0, 0);
+            for path in deprecations::config_files(custom_config) {
+                let content = std::fs::read_to_string(&path)?;
+                let fixed = deprecations::fix(&content, ConfigFormat::of(&path))
+                    .map_err(|e| anyhow::anyhow!("{}: {e:#}", path.display()))?;
+
+                for deprecation in &fixed.applied {
+                    styled!(
+                        "{} {} → {}",
+                        (path.display().to_string(), "file_path"),
+                        (&deprecation.key, "property"),
+                        (&deprecation.replacement, "accent")
+                    );
+                }
+                for deprecation in &fixed.manual {
+                    warning!(&format!(
+                        "{}: move '{}' to '{}' by hand - the file already has settings there",
+                        path.display(),
+                        deprecation.key,
+                        deprecation.replacement
+                    ));
+                }
+
+                if write && !fixed.applied.is_empty() {
+                    atomic_io::write_atomic(&path, &fixed.content)?;
+                    success!(&format!("Updated {}", path.display()));
+                }
+                applied += fixed.applied.len();
+                manual += fixed.manual.len();
+            }
+
+            if applied + manual == 0 {
+                styled!("{} No deprecated keys found", ("✅", "success_symbol"));
+            } else if applied > 0 && !write {
+                info!("Run 'guardy config fix --write' to apply these changes");
+            }
+        }
     }
 
     Ok(())
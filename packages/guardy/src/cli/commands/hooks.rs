@@ -0,0 +1,202 @@
+use anyhow::{Result, anyhow};
+use clap::{Args, Subcommand};
+use std::path::{Path, PathBuf};
+
+use crate::cli::output;
+use crate::hooks::{ImportSource, ImportedHooks, Preset};
+use crate::shared::atomic_io;
+
+#[derive(Args)]
+pub struct HooksArgs {
+    #[command(subcommand)]
+    pub command: HooksCommand,
+}
+
+#[derive(Subcommand)]
+pub enum HooksCommand {
+    /// Import hooks from husky or lefthook into guardy.yaml
+    Import {
+        /// Hook manager to import from
+        #[arg(long, value_enum)]
+        from: ImportSource,
+
+        /// Husky directory or lefthook config file (default: auto-detected)
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Configuration file to write the hooks section to
+        #[arg(short, long, default_value = "guardy.yaml")]
+        output: PathBuf,
+
+        /// Replace hooks already defined in the output file
+        #[arg(long)]
+        force: bool,
+
+        /// Print the resulting configuration instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Add curated fmt/lint/test commands for an ecosystem to guardy.yaml
+    AddPreset {
+        /// Presets to add (default: detected from the project files)
+        #[arg(value_enum)]
+        presets: Vec<Preset>,
+
+        /// Configuration file to add the commands to
+        #[arg(short, long, default_value = "guardy.yaml")]
+        output: PathBuf,
+
+        /// Print the resulting configuration instead of writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+pub async fn execute(args: HooksArgs) -> Result<()> {
+    match args.command {
+        HooksCommand::Import {
+            from,
+            path,
+            output: output_path,
+            force,
+            dry_run,
+        } => execute_import(from, path.as_deref(), &output_path, force, dry_run),
+        HooksCommand::AddPreset {
+            presets,
+            output: output_path,
+            dry_run,
+        } => execute_add_preset(presets, &output_path, dry_run),
+    }
+}
+
+fn execute_import(
+    from: ImportSource,
+    path: Option<&Path>,
+    output_path: &Path,
+    force: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let is_yaml = output_path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if !is_yaml {
+        return Err(anyhow!(
+            "Imported hooks can only be written to a YAML file (got {})",
+            output_path.display()
+        ));
+    }
+
+    let imported = ImportedHooks::load(from, path, Path::new("."))?;
+    for warning in &imported.warnings {
+        output::warning!(warning);
+    }
+
+    let existing = if output_path.exists() {
+        std::fs::read_to_string(output_path)?
+    } else {
+        String::new()
+    };
+    let (yaml, kept) = imported.merge_into(&existing, force)?;
+
+    if dry_run {
+        print!("{yaml}");
+        return Ok(());
+    }
+
+    atomic_io::write_atomic(output_path, &yaml)?;
+
+    for (hook, definition) in &imported.hooks {
+        if kept.contains(hook) {
+            output::warning!(&format!(
+                "Kept existing '{hook}' hook in {} (use --force to replace it)",
+                output_path.display()
+            ));
+        } else {
+            output::success!(&format!(
+                "Imported '{hook}' hook ({} commands)",
+                definition.custom.len()
+            ));
+        }
+    }
+    if !existing.trim().is_empty() {
+        output::info!(&format!(
+            "Rewrote {} - comments in the original file are not preserved",
+            output_path.display()
+        ));
+    }
+
+    output::info!("Next steps:");
+    println!("  - Review the imported hooks in {}", output_path.display());
+    println!("  - Run 'guardy install --force' to replace the existing hook scripts");
+
+    Ok(())
+}
+
+fn execute_add_preset(presets: Vec<Preset>, output_path: &Path, dry_run: bool) -> Result<()> {
+    let is_yaml = output_path
+        .extension()
+        .is_some_and(|ext| ext == "yaml" || ext == "yml");
+    if !is_yaml {
+        return Err(anyhow!(
+            "Presets can only be added to a YAML file (got {})",
+            output_path.display()
+        ));
+    }
+
+    let project_dir = Path::new(".");
+    let presets = if presets.is_empty() {
+        let detected = Preset::detect(project_dir);
+        if detected.is_empty() {
+            return Err(anyhow!(
+                "Could not detect the project type - pass one of: rust, node, python, go"
+            ));
+        }
+        detected
+    } else {
+        presets
+    };
+
+    let mut yaml = if output_path.exists() {
+        std::fs::read_to_string(output_path)?
+    } else {
+        String::new()
+    };
+    let had_content = !yaml.trim().is_empty();
+
+    let mut added = Vec::new();
+    let mut existing = Vec::new();
+    for preset in &presets {
+        let appended = preset.commands(project_dir).append_to(&yaml)?;
+        yaml = appended.yaml;
+        added.extend(appended.added);
+        existing.extend(appended.existing);
+    }
+
+    if dry_run {
+        print!("{yaml}");
+        return Ok(());
+    }
+
+    atomic_io::write_atomic(output_path, &yaml)?;
+
+    for (hook, name) in &existing {
+        output::warning!(&format!(
+            "Kept existing '{name}' command in the {hook} hook"
+        ));
+    }
+    for (hook, name) in &added {
+        output::success!(&format!("Added '{name}' to the {hook} hook"));
+    }
+    if had_content {
+        output::info!(&format!(
+            "Rewrote {} - comments in the original file are not preserved",
+            output_path.display()
+        ));
+    }
+
+    output::info!("Next steps:");
+    println!("  - Review the added commands in {}", output_path.display());
+    println!("  - Run 'guardy install' if the hooks aren't installed yet");
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
+use std::path::Path;
 
 #[derive(Args)]
 pub struct InstallArgs {
@@ -10,17 +11,21 @@ pub struct InstallArgs {
     /// Overwrite existing hooks
     #[arg(long)]
     pub force: bool,
+
+    /// Choose builtins and commands for the detected project type and write guardy.yaml
+    #[arg(short, long)]
+    pub interactive: bool,
 }
 
 pub async fn execute(args: InstallArgs, verbosity_level: u8) -> Result<()> {
     use crate::cli::output::*;
     use crate::config::GuardyConfig;
     use crate::git::GitRepo;
+    use crate::hooks::SUPPORTED_HOOKS;
+    use crate::shared::atomic_io;
     use std::fs;
     use std::os::unix::fs::PermissionsExt;
 
-    info!("Installing guardy hooks...");
-
     // Check if we're in a git repository
     let repo = match GitRepo::discover() {
         Ok(repo) => repo,
@@ -30,58 +35,61 @@ pub async fn execute(args: InstallArgs, verbosity_level: u8) -> Result<()> {
         }
     };
 
-    let hooks_dir = repo.git_dir().join("hooks");
-
-    // Validate .git/hooks directory exists
+    // Linked worktrees share the main checkout's hooks directory
+    let hooks_dir = repo.hooks_dir();
     if !hooks_dir.exists() {
         fs::create_dir_all(&hooks_dir)?;
-        info!("Created .git/hooks directory");
+        info!(&format!("Created {}", hooks_dir.display()));
     }
 
+    // Written before loading so the new configuration is validated below
+    let selected_hooks = if args.interactive {
+        Some(configure_interactively(&repo.path)?)
+    } else {
+        None
+    };
+
     // Parse guardy.toml configuration
     let _config = GuardyConfig::load(None, None::<&()>, verbosity_level)?;
 
+    let section = section!("Installing guardy hooks");
     if args.force {
         warning!("Force mode enabled - will overwrite existing hooks");
     }
 
     // Determine which hooks to install
-    let hooks_to_install = args.hooks.unwrap_or_else(|| {
-        vec![
-            "pre-commit".to_string(),
-            "commit-msg".to_string(),
-            "post-checkout".to_string(),
-            "pre-push".to_string(),
-        ]
-    });
+    let hooks_to_install = args
+        .hooks
+        .or(selected_hooks)
+        .unwrap_or_else(|| SUPPORTED_HOOKS.iter().map(|h| h.to_string()).collect());
 
     // Install each hook
-    for hook_name in hooks_to_install {
-        let hook_path = hooks_dir.join(&hook_name);
+    let total = hooks_to_install.len();
+    for (index, hook_name) in hooks_to_install.iter().enumerate() {
+        let step = step!(index + 1, total, hook_name);
+        let hook_path = hooks_dir.join(hook_name);
 
         // Check if hook exists and handle based on force flag
         if hook_path.exists() && !args.force {
-            warning!(&format!(
-                "Hook '{hook_name}' already exists. Use --force to overwrite."
-            ));
+            step.skip("already exists, use --force to overwrite");
             continue;
         }
 
-        // Create hook script that calls guardy
+        // Create hook script that calls guardy, forwarding git's hook arguments
         let hook_script =
-            format!("#!/bin/sh\n# Guardy hook: {hook_name}\nexec guardy run {hook_name}\n");
+            format!("#!/bin/sh\n# Guardy hook: {hook_name}\nexec guardy run {hook_name} \"$@\"\n");
 
-        fs::write(&hook_path, hook_script)?;
+        // Written atomically and already executable, so git never runs a partial stub
+        atomic_io::write_atomic_with_permissions(
+            &hook_path,
+            hook_script,
+            fs::Permissions::from_mode(0o755),
+        )?;
 
-        // Make hook executable
-        let mut permissions = fs::metadata(&hook_path)?.permissions();
-        permissions.set_mode(0o755);
-        fs::set_permissions(&hook_path, permissions)?;
-
-        success!(&format!("Installed '{hook_name}' hook"));
+        step.success();
     }
 
-    success!("Hook installation completed!");
+    section.success();
 
     // Show next steps
     info!("Next steps:");
@@ -91,3 +99,72 @@ pub async fn execute(args: InstallArgs, verbosity_level: u8) -> Result<()> {
 
     Ok(())
 }
+
+/// Prompt for the builtins and commands to enable, merge them into guardy.yaml
+/// and return the hooks that ended up with something selected
+fn configure_interactively(root: &Path) -> Result<Vec<String>> {
+    use crate::cli::output::*;
+    use crate::hooks::Proposal;
+    use crate::shared::atomic_io;
+    use std::io::IsTerminal;
+    use supercli::prompts::{self, Choice};
+
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "--interactive needs a terminal - use --hooks or 'guardy hooks add-preset' instead"
+        ));
+    }
+
+    let config_path = root.join("guardy.yaml");
+    if !config_path.exists()
+        && ["guardy.yml", "guardy.toml", "guardy.json"]
+            .iter()
+            .any(|name| root.join(name).exists())
+    {
+        return Err(anyhow!(
+            "--interactive only writes guardy.yaml, but the project is configured in another file"
+        ));
+    }
+
+    let proposals = Proposal::for_project(root);
+    let choices: Vec<Choice> = proposals
+        .iter()
+        .map(|proposal| Choice::new(proposal.label(), proposal.selected))
+        .collect();
+    let picked =
+        prompts::multi_select("Hooks to enable (space toggles, enter confirms)", &choices)?;
+    let chosen: Vec<&Proposal> = picked.iter().map(|&index| &proposals[index]).collect();
+    if chosen.is_empty() {
+        return Err(anyhow!("Nothing selected - no hooks installed"));
+    }
+
+    let existing = if config_path.exists() {
+        std::fs::read_to_string(&config_path)?
+    } else {
+        String::new()
+    };
+    let had_content = !existing.trim().is_empty();
+    if had_content
+        && !prompts::confirm(
+            "Update guardy.yaml? Comments in the file are not preserved",
+            true,
+        )?
+    {
+        return Err(anyhow!("Left guardy.yaml unchanged - no hooks installed"));
+    }
+
+    atomic_io::write_atomic(&config_path, Proposal::apply(&existing, &chosen)?)?;
+    success!(&format!(
+        "{} guardy.yaml with {}",
+        if had_content { "Updated" } else { "Created" },
+        human_count(chosen.len(), "selection")
+    ));
+
+    let mut hooks: Vec<String> = Vec::new();
+    for proposal in chosen {
+        if !hooks.iter().any(|hook| hook == proposal.hook) {
+            hooks.push(proposal.hook.to_string());
+        }
+    }
+    Ok(hooks)
+}
@@ -3,7 +3,9 @@ use clap::{Parser, Subcommand};
 use supercli::clap::create_help_styles;
 
 pub mod config;
+pub mod hooks;
 pub mod install;
+pub mod replay;
 pub mod run;
 pub mod scan;
 pub mod status;
@@ -37,6 +39,10 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub config: Option<String>,
 
+    /// Append machine-readable events as JSON Lines to `<PATH>` (`-` for stderr)
+    #[arg(long, global = true, env = "GUARDY_EVENTS", value_name = "PATH")]
+    pub events: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -47,6 +53,10 @@ pub enum Commands {
     Install(install::InstallArgs),
     /// Manually execute a specific hook for testing
     Run(run::RunArgs),
+    /// Re-run a hook session recorded with `guardy run <hook> --record`
+    Replay(replay::ReplayArgs),
+    /// Manage hook definitions (import from husky or lefthook)
+    Hooks(hooks::HooksArgs),
     /// Scan files or directories for secrets
     Scan(scan::ScanArgs),
     /// Configuration management
@@ -70,10 +80,18 @@ impl Cli {
 
         // Set up logging based on verbosity
         setup_logging(self.verbose, self.quiet);
+        if let Some(path) = &self.events {
+            crate::events::subscribe(crate::events::JsonlSink::open(path)?);
+        }
+        if self.verbose >= 2 && !self.quiet {
+            crate::events::subscribe(crate::events::TracingSink);
+        }
 
         match self.command {
             Some(Commands::Install(args)) => install::execute(args, self.verbose).await,
             Some(Commands::Run(args)) => run::execute(args, self.verbose).await,
+            Some(Commands::Replay(args)) => replay::execute(args, self.verbose).await,
+            Some(Commands::Hooks(args)) => hooks::execute(args).await,
             Some(Commands::Scan(args)) => {
                 use crate::cli::output;
                 output::styled!(
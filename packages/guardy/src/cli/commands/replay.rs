@@ -0,0 +1,26 @@
+use anyhow::{Result, anyhow};
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Session file written by `guardy run <hook> --record <FILE>`
+    pub session: PathBuf,
+
+    /// Keep the temporary worktree the hook ran in, for debugging
+    #[arg(long)]
+    pub keep: bool,
+}
+
+pub async fn execute(args: ReplayArgs, verbosity_level: u8) -> Result<()> {
+    use crate::hooks::replay_session;
+
+    let replay = replay_session(&args.session, args.keep, verbosity_level).await?;
+    replay.print_comparison();
+
+    // Mirror the replayed run, like `guardy run` would
+    match replay.replayed.error {
+        Some(error) if !replay.replayed.success => Err(anyhow!(error)),
+        _ => Ok(()),
+    }
+}
@@ -1,23 +1,72 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Args;
+use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct RunArgs {
     /// Hook name to run
     pub hook: String,
 
-    /// Additional arguments for the hook
+    /// Additional arguments for the hook (e.g. `--root` from pre-rebase)
+    #[arg(allow_hyphen_values = true)]
     pub args: Vec<String>,
+
+    /// Show which builtins and commands would run, on which files, without running them
+    #[arg(long)]
+    pub plan: bool,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    pub format: RunFormat,
+
+    /// Save the staged files, merged config, tool versions and outcome to a session
+    /// file that `guardy replay` can re-run on another machine
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum RunFormat {
+    /// Progress output followed by a timing summary
+    Text,
+    /// Only a JSON report with per-command timings
+    Json,
 }
 
 pub async fn execute(args: RunArgs, verbosity_level: u8) -> Result<()> {
+    use crate::cli::output;
     use crate::config::GuardyConfig;
-    use crate::hooks::HookExecutor;
+    use crate::hooks::{HookExecutor, record_session};
 
     // Load configuration
     let config = GuardyConfig::load(None, None::<&()>, verbosity_level)?;
 
     // Create hook executor and run the hook
     let executor = HookExecutor::new(config);
-    executor.execute(&args.hook, &args.args).await
+    if args.plan {
+        return executor.plan(&args.hook, &args.args);
+    }
+
+    if args.format == RunFormat::Json {
+        output::set_quiet(true);
+    }
+    let report = match &args.record {
+        Some(path) => record_session(&executor, &args.hook, &args.args, path).await?,
+        None if args.format == RunFormat::Json => executor.report(&args.hook, &args.args).await,
+        None => return executor.execute(&args.hook, &args.args).await,
+    };
+
+    if args.format == RunFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if let Some(path) = &args.record {
+        output::info!(&format!("Recorded session to {}", path.display()));
+    }
+
+    if !report.success {
+        return Err(match report.error {
+            Some(error) if args.format == RunFormat::Text => anyhow!(error),
+            _ => anyhow!("Hook '{}' failed", args.hook),
+        });
+    }
+    Ok(())
 }
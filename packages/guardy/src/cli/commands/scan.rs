@@ -1,34 +1,20 @@
 use anyhow::Result;
 use clap::Args;
 use serde::Serialize;
-use std::path::PathBuf;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 use crate::cli::output;
 use crate::config::GuardyConfig;
-use crate::scanner::{Scanner, types::ScanMode};
-
-/// Format scan time intelligently - use ms for short times, mm:ss for longer times
-fn format_scan_time(duration: Duration) -> String {
-    let total_ms = duration.as_millis();
-
-    // Use milliseconds for times under 10 seconds
-    if total_ms < 10_000 {
-        format!("{total_ms}ms")
-    } else {
-        // For longer times, show minutes, seconds, and milliseconds
-        let total_seconds = duration.as_secs();
-        let minutes = total_seconds / 60;
-        let seconds = total_seconds % 60;
-        let remaining_ms = total_ms % 1000;
-
-        if minutes > 0 {
-            format!("{minutes}m {seconds}.{remaining_ms:03}s")
-        } else {
-            format!("{seconds}.{remaining_ms:03}s")
-        }
-    }
-}
+use crate::events::{self, Event};
+use crate::git::GitRepo;
+use crate::git::permalink::{PermalinkConfig, Permalinks};
+use crate::scanner::{
+    Scanner,
+    hygiene::{self, HygieneIssue},
+    types::{FindingsSummary, ScanMode, ScanResult, ScanStats, SecretMatch, SubmoduleMode},
+};
+use crate::shared::paths;
 
 #[derive(Args, Serialize)]
 pub struct ScanArgs {
@@ -36,6 +22,16 @@ pub struct ScanArgs {
     #[arg(value_name = "PATH")]
     pub paths: Vec<PathBuf>,
 
+    /// Labels for the scanned paths, one per path (e.g. app,infra,docs); findings and
+    /// per-root statistics carry them. Scanning several paths labels them by path
+    #[arg(long, value_delimiter = ',', value_name = "LABELS")]
+    #[serde(skip)]
+    pub root_labels: Vec<String>,
+
+    /// Also scan the repository's commit messages, annotated tag messages and git notes
+    #[arg(long)]
+    pub git_metadata: bool,
+
     /// Scan all files (including binary files)
     #[arg(long)]
     pub include_binary: bool,
@@ -100,6 +96,49 @@ pub struct ScanArgs {
     #[arg(long, value_enum)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<ScanMode>,
+
+    /// Scan git submodules (recurse) or leave them out with a note (skip)
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submodules: Option<SubmoduleMode>,
+
+    /// Only show the N highest-confidence findings grouped by rule (e.g. top=20);
+    /// the full results are written to report files
+    #[arg(long, value_name = "top=N")]
+    #[serde(skip)]
+    pub summary: Option<SummaryMode>,
+}
+
+/// Files scanned and secrets found under one scan root
+#[derive(Debug, Serialize)]
+struct RootStats {
+    label: String,
+    path: String,
+    files_scanned: usize,
+    files_skipped: usize,
+    files_not_scanned: usize,
+    secrets_found: usize,
+}
+
+/// `--summary top=N`
+#[derive(Clone, Debug)]
+pub struct SummaryMode {
+    pub top: usize,
+}
+
+impl std::str::FromStr for SummaryMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let top = value
+            .trim()
+            .strip_prefix("top=")
+            .ok_or_else(|| format!("expected top=N, got '{value}'"))?;
+        match top.parse::<usize>() {
+            Ok(top) if top > 0 => Ok(Self { top }),
+            _ => Err(format!("expected a positive number in '{value}'")),
+        }
+    }
 }
 
 #[derive(Clone, Debug, clap::ValueEnum, serde::Serialize)]
@@ -179,6 +218,8 @@ pub async fn execute(args: ScanArgs, verbose_level: u8, config_path: Option<&str
 
     output::styled!("{} Starting security scan...", ("ℹ", "info_symbol"));
     let start_time = Instant::now();
+    // One resource budget for all the roots of this run
+    let scanner = scanner.new_run();
 
     // Determine paths to scan
     let scan_paths = if args.paths.is_empty() {
@@ -188,50 +229,71 @@ pub async fn execute(args: ScanArgs, verbose_level: u8, config_path: Option<&str
         args.paths.clone()
     };
 
+    let labels = root_labels(&args.root_labels, &scan_paths)?;
+    let permalink_config: PermalinkConfig = config
+        .get_section("scanner.permalinks")
+        .ok()
+        .and_then(|section| serde_json::from_value(section).ok())
+        .unwrap_or_default();
+
     // Scan all paths and collect detailed results
     let mut all_scan_results = Vec::new();
-    for path in &scan_paths {
-        if path.is_file() {
-            let matches = scanner.scan_file(path)?;
-            // Check if file was actually processed (not skipped due to binary detection, etc.)
-            let was_processed = !matches.is_empty()
-                || scanner.config.include_binary
-                || !crate::scanner::directory::is_binary_file(
-                    path,
-                    &scanner.config.binary_extensions,
-                );
-
-            all_scan_results.push(crate::scanner::types::ScanResult {
-                matches,
-                stats: crate::scanner::types::ScanStats {
-                    files_scanned: if was_processed { 1 } else { 0 },
-                    files_skipped: if was_processed { 0 } else { 1 },
-                    total_matches: 0, // Will be updated below
-                    scan_duration_ms: 0,
-                },
-                warnings: Vec::new(),
+    let mut roots = Vec::new();
+    for (path, label) in scan_paths.iter().zip(&labels) {
+        let Some(mut result) = scan_root(&scanner, path)? else {
+            continue;
+        };
+        if !result.matches.is_empty()
+            && let Some(permalinks) = Permalinks::discover(path, &permalink_config)
+        {
+            for secret_match in &mut result.matches {
+                secret_match.permalink =
+                    permalinks.link(Path::new(&secret_match.file_path), secret_match.line_number);
+            }
+        }
+        if let Some(label) = label {
+            for secret_match in &mut result.matches {
+                secret_match.root = Some(label.clone());
+            }
+            roots.push(RootStats {
+                label: label.clone(),
+                path: paths::display(path),
+                files_scanned: result.stats.files_scanned,
+                files_skipped: result.stats.files_skipped,
+                files_not_scanned: result.stats.files_not_scanned,
+                secrets_found: result.matches.len(),
             });
-        } else if path.is_dir() {
-            let scan_result = scanner.scan_directory(path, None)?;
-            all_scan_results.push(scan_result);
-        } else {
-            output::styled!(
-                "{} Path not found: {}",
-                ("⚠️", "warning_symbol"),
-                (path.display().to_string(), "file_path")
-            );
         }
+        all_scan_results.push(result);
+    }
+
+    if args.git_metadata {
+        all_scan_results.push(scan_git_metadata(&scanner, &scan_paths[0])?);
     }
 
     let elapsed = start_time.elapsed();
 
+    // Suggestions only - a repository that can't be checked just has none
+    let hygiene_issues = GitRepo::discover_at(&scan_paths[0])
+        .and_then(|repo| hygiene::check(&repo))
+        .unwrap_or_default();
+
     // Aggregate results
     let all_matches: Vec<_> = all_scan_results
         .iter()
         .flat_map(|r| r.matches.iter())
         .collect();
-    let total_files: usize = all_scan_results.iter().map(|r| r.stats.files_scanned).sum();
-    let total_skipped: usize = all_scan_results.iter().map(|r| r.stats.files_skipped).sum();
+    let totals = ScanStats {
+        files_scanned: all_scan_results.iter().map(|r| r.stats.files_scanned).sum(),
+        files_skipped: all_scan_results.iter().map(|r| r.stats.files_skipped).sum(),
+        total_matches: all_matches.len(),
+        scan_duration_ms: elapsed.as_millis() as u64,
+        files_not_scanned: all_scan_results
+            .iter()
+            .map(|r| r.stats.files_not_scanned)
+            .sum(),
+        limits_hit: scanner.budget.limits_hit(),
+    };
 
     // Handle count-only mode
     if args.count_only {
@@ -253,10 +315,11 @@ pub async fn execute(args: ScanArgs, verbose_level: u8, config_path: Option<&str
         OutputFormat::Json => {
             print_json_results(
                 &all_matches,
-                total_files,
-                total_skipped,
+                &totals,
                 elapsed,
                 &all_warnings,
+                &roots,
+                &hygiene_issues,
             )?;
         }
         OutputFormat::Csv => {
@@ -268,13 +331,15 @@ pub async fn execute(args: ScanArgs, verbose_level: u8, config_path: Option<&str
         OutputFormat::Text => {
             print_text_results(
                 &all_matches,
-                total_files,
-                total_skipped,
+                &totals,
                 elapsed,
                 &args,
                 verbose_level,
                 &all_warnings,
+                &hygiene_issues,
             )?;
+            print_root_stats(&roots);
+            print_hygiene(&hygiene_issues);
         }
     }
 
@@ -286,55 +351,220 @@ pub async fn execute(args: ScanArgs, verbose_level: u8, config_path: Option<&str
     Ok(())
 }
 
+/// One label per scan path: `--root-labels` when given, otherwise the paths
+/// themselves once more than one is scanned
+fn root_labels(given: &[String], scan_paths: &[PathBuf]) -> Result<Vec<Option<String>>> {
+    if !given.is_empty() {
+        if given.len() != scan_paths.len() {
+            return Err(anyhow::anyhow!(
+                "--root-labels has {} labels for {} paths",
+                given.len(),
+                scan_paths.len()
+            ));
+        }
+        return Ok(given.iter().cloned().map(Some).collect());
+    }
+    if scan_paths.len() > 1 {
+        return Ok(scan_paths
+            .iter()
+            .map(|path| Some(paths::display(path)))
+            .collect());
+    }
+    Ok(vec![None; scan_paths.len()])
+}
+
+/// Scan one file or directory given on the command line - `None` when it doesn't exist
+fn scan_root(scanner: &Scanner, path: &Path) -> Result<Option<ScanResult>> {
+    if scanner.budget.exhausted() {
+        // A resource limit was hit while scanning an earlier path
+        return Ok(Some(ScanResult {
+            matches: Vec::new(),
+            stats: ScanStats {
+                files_not_scanned: scanner.fast_count_files(path).unwrap_or(0),
+                ..Default::default()
+            },
+            warnings: Vec::new(),
+        }));
+    }
+
+    if path.is_file() {
+        let target = paths::display(path);
+        events::emit(Event::ScanStarted {
+            target: target.clone(),
+        });
+        let matches = scanner.scan_file(path)?;
+        // Check if file was actually processed (not skipped due to binary detection, etc.)
+        let was_processed = !matches.is_empty()
+            || scanner.config.include_binary
+            || !crate::scanner::directory::is_binary_file(path, &scanner.config.binary_extensions);
+
+        let result = ScanResult {
+            matches,
+            stats: ScanStats {
+                files_scanned: if was_processed { 1 } else { 0 },
+                files_skipped: if was_processed { 0 } else { 1 },
+                total_matches: 0, // Will be updated below
+                scan_duration_ms: 0,
+                ..Default::default()
+            },
+            warnings: Vec::new(),
+        };
+        Event::scan_results(&target, &result)
+            .into_iter()
+            .for_each(events::emit);
+        Ok(Some(result))
+    } else if path.is_dir() {
+        Ok(Some(scanner.scan_directory(path, None)?))
+    } else {
+        output::styled!(
+            "{} Path not found: {}",
+            ("⚠️", "warning_symbol"),
+            (paths::display(path), "file_path")
+        );
+        Ok(None)
+    }
+}
+
+/// Scan the commit messages, tag annotations and notes of the repository holding `path`
+fn scan_git_metadata(scanner: &Scanner, path: &Path) -> Result<ScanResult> {
+    let repo = GitRepo::discover_at(path)?;
+    let mut texts = repo.commit_texts()?;
+    texts.extend(repo.tag_annotations()?);
+    output::styled!(
+        "{} Scanning {} (commit messages, tag annotations and notes)",
+        ("ℹ", "info_symbol"),
+        (output::human_count(texts.len(), "git message"), "number")
+    );
+
+    let matches: Vec<SecretMatch> = texts
+        .iter()
+        .flat_map(|text| scanner.scan_text(&text.text, &text.source))
+        .collect();
+    Ok(ScanResult {
+        stats: ScanStats {
+            total_matches: matches.len(),
+            ..Default::default()
+        },
+        matches,
+        warnings: Vec::new(),
+    })
+}
+
+/// Where a finding is, prefixed with its scan root in multi-root scans
+fn location(secret_match: &SecretMatch) -> String {
+    match &secret_match.root {
+        Some(root) => format!("[{root}] {}", secret_match.file_path),
+        None => secret_match.file_path.clone(),
+    }
+}
+
+/// Link to the finding on the code host, when the file is committed there
+fn print_permalink(secret_match: &SecretMatch, indent: &str) {
+    if let Some(link) = &secret_match.permalink {
+        output::styled!(
+            &format!("{indent}{{}} {{}}"),
+            ("🔗", "info_symbol"),
+            (link, "url")
+        );
+    }
+}
+
+/// Per-root breakdown of a multi-root scan
+fn print_root_stats(roots: &[RootStats]) {
+    if roots.is_empty() {
+        return;
+    }
+
+    println!();
+    output::styled!(
+        "{} {}",
+        ("📊", "info_symbol"),
+        ("Results by root", "property")
+    );
+    for root in roots {
+        let not_scanned = if root.files_not_scanned > 0 {
+            format!(", {} not scanned", root.files_not_scanned)
+        } else {
+            String::new()
+        };
+        output::styled!(
+            "  {} {} {}",
+            (&root.label, "id"),
+            (format!("({})", root.path), "file_path"),
+            (
+                format!(
+                    "{} scanned{not_scanned}, {}",
+                    output::human_count(root.files_scanned, "file"),
+                    output::human_count(root.secrets_found, "secret")
+                ),
+                "muted"
+            )
+        );
+    }
+}
+
+/// Repository hygiene suggestions, after the findings
+fn print_hygiene(issues: &[HygieneIssue]) {
+    if issues.is_empty() {
+        return;
+    }
+
+    println!();
+    output::styled!("{} {}", ("🧹", "info_symbol"), ("Hygiene", "property"));
+    for issue in issues {
+        output::styled!(
+            "  {} {}",
+            ("⚠", "warning_symbol"),
+            (issue.message(), "primary")
+        );
+        output::styled!("    {}", (issue.suggestion(), "accent"));
+    }
+}
+
 fn print_text_results(
     matches: &[&crate::scanner::types::SecretMatch],
-    total_files: usize,
-    total_skipped: usize,
+    totals: &ScanStats,
     elapsed: std::time::Duration,
     args: &ScanArgs,
     verbose_level: u8,
     warnings: &[&crate::scanner::types::Warning],
+    hygiene_issues: &[HygieneIssue],
 ) -> Result<()> {
     if matches.is_empty() {
-        output::styled!("{} No secrets detected!", ("✔", "success_symbol"));
-
-        // Print statistics if requested
-        if args.stats {
-            println!();
+        if totals.is_truncated() {
             output::styled!(
-                "{} {}",
-                ("📊", "info_symbol"),
-                ("Scan Statistics", "property")
+                "{} No secrets detected in scanned files ({} files not scanned)",
+                ("⚠", "warning_symbol"),
+                (totals.files_not_scanned.to_string(), "caution")
             );
-            output::styled!("  Files scanned: {}", (total_files.to_string(), "symbol"));
-            if total_skipped > 0 {
-                output::styled!("  Files skipped: {}", (total_skipped.to_string(), "symbol"));
-            }
-            output::styled!("  Secrets found: {}", ("0", "symbol"));
-            output::styled!("  Scan time: {}", (format_scan_time(elapsed), "symbol"));
-            if !warnings.is_empty() {
-                output::styled!("  Warnings: {}", (warnings.len().to_string(), "symbol"));
-            }
+        } else {
+            output::styled!("{} No secrets detected!", ("✔", "success_symbol"));
         }
 
+        print_stats(totals, 0, elapsed, args, warnings);
+
         return Ok(());
     }
 
-    // Check if we should generate a report file instead of terminal output
-    if matches.len() > 20 || warnings.len() > 20 {
-        use crate::reports::{ReportFormat, ReportGenerator};
+    // Summary mode: only the highest-confidence findings, full data goes to report files
+    if let Some(summary) = &args.summary {
+        print_summary(&FindingsSummary::rank(matches, summary.top));
+        if matches.len() > summary.top {
+            write_reports(matches, warnings, hygiene_issues, totals, elapsed)?;
+        }
 
-        let current_dir = std::env::current_dir()?;
-        let report_path = ReportGenerator::generate_report(
-            matches,
-            warnings,
-            total_files,
-            total_skipped,
-            elapsed,
-            &current_dir,
-            ReportFormat::Html,
-        )?;
+        println!();
+        output::styled!(
+            "{} Found {} potential secrets!",
+            ("⚠", "warning_symbol"),
+            (matches.len().to_string(), "caution")
+        );
+        print_stats(totals, matches.len(), elapsed, args, warnings);
+        return Ok(());
+    }
 
+    // Check if we should generate a report file instead of terminal output
+    if matches.len() > 20 || warnings.len() > 20 {
         println!();
         output::styled!(
             "{} Found {} secrets and {} warnings (too many to display)",
@@ -342,31 +572,7 @@ fn print_text_results(
             (matches.len().to_string(), "caution"),
             (warnings.len().to_string(), "warning")
         );
-        println!();
-        output::styled!(
-            "{} Full report saved to: {}",
-            ("📄", "info_symbol"),
-            (report_path.display().to_string(), "file_path")
-        );
-
-        // Also save JSON for machine processing
-        let json_path = ReportGenerator::generate_report(
-            matches,
-            warnings,
-            total_files,
-            total_skipped,
-            elapsed,
-            &current_dir,
-            ReportFormat::Json,
-        )?;
-        output::styled!(
-            "{} Machine-readable: {}",
-            ("🤖", "info_symbol"),
-            (
-                json_path.file_name().unwrap().to_string_lossy(),
-                "file_path"
-            )
-        );
+        write_reports(matches, warnings, hygiene_issues, totals, elapsed)?;
 
         return Ok(());
     }
@@ -387,13 +593,17 @@ fn print_text_results(
                 ),
                 (format!("[{}]", file_matches[0].secret_type), "id_value")
             );
+            print_permalink(file_matches[0], "  ");
         } else {
             output::styled!(
                 "{} {} {}",
                 ("📄", "info_symbol"),
                 (file_path.clone(), "file_path"),
                 (
-                    format!("[{} secrets found]", file_matches.len()),
+                    format!(
+                        "[{} found]",
+                        output::human_count(file_matches.len(), "secret")
+                    ),
                     "id_value"
                 )
             );
@@ -405,6 +615,7 @@ fn print_text_results(
                     (secret_match.line_number.to_string(), "number"),
                     (format!("[{}]", secret_match.secret_type), "id_value")
                 );
+                print_permalink(secret_match, "     ");
             }
         }
 
@@ -501,34 +712,173 @@ fn print_text_results(
         }
     }
 
-    // Print statistics if requested
-    if args.stats {
+    print_stats(totals, matches.len(), elapsed, args, warnings);
+
+    Ok(())
+}
+
+/// Statistics block for `--stats`
+fn print_stats(
+    totals: &ScanStats,
+    secrets_found: usize,
+    elapsed: std::time::Duration,
+    args: &ScanArgs,
+    warnings: &[&crate::scanner::types::Warning],
+) {
+    if !args.stats {
+        return;
+    }
+
+    println!();
+    output::styled!(
+        "{} {}",
+        ("📊", "info_symbol"),
+        ("Scan Statistics", "property")
+    );
+    output::styled!(
+        "  Files scanned: {}",
+        (totals.files_scanned.to_string(), "symbol")
+    );
+    if totals.files_skipped > 0 {
+        output::styled!(
+            "  Files skipped: {}",
+            (totals.files_skipped.to_string(), "symbol")
+        );
+    }
+    output::styled!("  Secrets found: {}", (secrets_found.to_string(), "symbol"));
+    output::styled!(
+        "  Scan time: {}",
+        (output::human_duration(elapsed), "symbol")
+    );
+    if !warnings.is_empty() {
+        output::styled!("  Warnings: {}", (warnings.len().to_string(), "symbol"));
+    }
+    print_limits_hit(totals);
+}
+
+/// Write the full HTML and JSON reports to the current directory
+fn write_reports(
+    matches: &[&crate::scanner::types::SecretMatch],
+    warnings: &[&crate::scanner::types::Warning],
+    hygiene_issues: &[HygieneIssue],
+    totals: &ScanStats,
+    elapsed: std::time::Duration,
+) -> Result<()> {
+    use crate::reports::{ReportFormat, ReportGenerator};
+
+    let current_dir = std::env::current_dir()?;
+    let report_path = ReportGenerator::generate_report(
+        matches,
+        warnings,
+        hygiene_issues,
+        totals,
+        elapsed,
+        &current_dir,
+        ReportFormat::Html,
+    )?;
+
+    println!();
+    output::styled!(
+        "{} Full report saved to: {}",
+        ("📄", "info_symbol"),
+        (report_path.display().to_string(), "file_path")
+    );
+
+    // Also save JSON for machine processing
+    let json_path = ReportGenerator::generate_report(
+        matches,
+        warnings,
+        hygiene_issues,
+        totals,
+        elapsed,
+        &current_dir,
+        ReportFormat::Json,
+    )?;
+    output::styled!(
+        "{} Machine-readable: {}",
+        ("🤖", "info_symbol"),
+        (
+            json_path.file_name().unwrap().to_string_lossy(),
+            "file_path"
+        )
+    );
+
+    Ok(())
+}
+
+/// Top-N findings grouped by rule, with per-rule counts for everything not shown
+fn print_summary(summary: &FindingsSummary) {
+    println!();
+    output::styled!(
+        "{} Top {} of {} findings by confidence",
+        ("🔝", "info_symbol"),
+        (summary.shown.to_string(), "property"),
+        (summary.total.to_string(), "caution")
+    );
+
+    for rule in &summary.rules {
         println!();
         output::styled!(
             "{} {}",
-            ("📊", "info_symbol"),
-            ("Scan Statistics", "property")
+            (rule.rule.clone(), "id_value"),
+            (format!("({} total)", rule.total), "muted")
         );
-        output::styled!("  Files scanned: {}", (total_files.to_string(), "symbol"));
-        if total_skipped > 0 {
-            output::styled!("  Files skipped: {}", (total_skipped.to_string(), "symbol"));
+        for secret_match in &rule.top {
+            output::styled!(
+                "   {} {}",
+                (format!("{:.2}", secret_match.confidence), "number"),
+                (
+                    format!("{}:{}", location(secret_match), secret_match.line_number),
+                    "file_path"
+                )
+            );
+            print_permalink(secret_match, "        ");
         }
-        output::styled!("  Secrets found: {}", (matches.len().to_string(), "symbol"));
-        output::styled!("  Scan time: {}", (format_scan_time(elapsed), "symbol"));
-        if !warnings.is_empty() {
-            output::styled!("  Warnings: {}", (warnings.len().to_string(), "symbol"));
+    }
+
+    if !summary.remaining.is_empty() {
+        println!();
+        output::styled!(
+            "{} {} more findings not shown:",
+            ("…", "info_symbol"),
+            ((summary.total - summary.shown).to_string(), "caution")
+        );
+        for (rule, count) in &summary.remaining {
+            output::styled!(
+                "   {}: {}",
+                (rule.clone(), "id_value"),
+                (count.to_string(), "number")
+            );
         }
     }
+}
 
-    Ok(())
+/// Report resource limits that truncated the scan (part of the statistics block)
+fn print_limits_hit(totals: &ScanStats) {
+    if !totals.is_truncated() {
+        return;
+    }
+
+    let limits = totals
+        .limits_hit
+        .iter()
+        .map(|limit| limit.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    output::styled!("  Limits hit: {}", (limits, "warning"));
+    output::styled!(
+        "  Files not scanned: {}",
+        (totals.files_not_scanned.to_string(), "symbol")
+    );
 }
 
 fn print_json_results(
     matches: &[&crate::scanner::types::SecretMatch],
-    total_files: usize,
-    total_skipped: usize,
+    totals: &ScanStats,
     elapsed: std::time::Duration,
     warnings: &[&crate::scanner::types::Warning],
+    roots: &[RootStats],
+    hygiene_issues: &[HygieneIssue],
 ) -> Result<()> {
     use serde_json::json;
 
@@ -540,18 +890,26 @@ fn print_json_results(
             "content": m.line_content.trim(),
             "matched_text": m.matched_text,
             "start_pos": m.start_pos,
-            "end_pos": m.end_pos
+            "end_pos": m.end_pos,
+            "confidence": m.confidence,
+            "root": m.root,
+            "permalink": m.permalink
         })).collect::<Vec<_>>(),
         "warnings": warnings.iter().map(|w| json!({
             "message": w.message
         })).collect::<Vec<_>>(),
         "statistics": {
-            "files_scanned": total_files,
-            "files_skipped": total_skipped,
+            "files_scanned": totals.files_scanned,
+            "files_skipped": totals.files_skipped,
             "secrets_found": matches.len(),
             "warnings_count": warnings.len(),
-            "scan_duration_ms": elapsed.as_millis()
-        }
+            "scan_duration_ms": elapsed.as_millis(),
+            "truncated": totals.is_truncated(),
+            "limits_hit": totals.limits_hit,
+            "files_not_scanned": totals.files_not_scanned
+        },
+        "roots": roots,
+        "hygiene": hygiene_issues
     });
 
     println!("{}", serde_json::to_string_pretty(&results)?);
@@ -559,19 +917,34 @@ fn print_json_results(
 }
 
 fn print_csv_results(matches: &[&crate::scanner::types::SecretMatch]) -> Result<()> {
+    let with_roots = matches.iter().any(|m| m.root.is_some());
+    if with_roots {
+        print!("root,");
+    }
     println!("file,line,type,content");
     for secret_match in matches {
+        if with_roots {
+            print!(
+                "{},",
+                csv_quote(secret_match.root.as_deref().unwrap_or_default())
+            );
+        }
         println!(
-            "{},{},{},\"{}\"",
+            "{},{},{},{}",
             secret_match.file_path,
             secret_match.line_number,
             secret_match.secret_type,
-            secret_match.line_content.trim().replace('"', "\"\"")
+            csv_quote(secret_match.line_content.trim())
         );
     }
     Ok(())
 }
 
+/// Wrap a free-text CSV field in quotes, doubling any quotes inside it
+fn csv_quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
 fn print_files_only(matches: &[&crate::scanner::types::SecretMatch]) {
     let mut files: std::collections::HashSet<&str> = std::collections::HashSet::new();
     for secret_match in matches {
@@ -592,7 +965,7 @@ fn group_matches_by_file<'a>(
     let mut grouped: HashMap<String, Vec<&'a crate::scanner::types::SecretMatch>> = HashMap::new();
     for secret_match in matches {
         grouped
-            .entry(secret_match.file_path.clone())
+            .entry(location(secret_match))
             .or_default()
             .push(*secret_match);
     }
@@ -601,3 +974,32 @@ fn group_matches_by_file<'a>(
     result.sort_by(|a, b| a.0.cmp(&b.0)); // Sort by file path
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_labels_default_to_paths_for_multi_root_scans() {
+        let scan_paths = [PathBuf::from("app"), PathBuf::from("infra")];
+
+        assert_eq!(
+            root_labels(&[], &scan_paths).unwrap(),
+            vec![Some("app".to_string()), Some("infra".to_string())]
+        );
+        assert_eq!(
+            root_labels(&["web".into(), "ops".into()], &scan_paths).unwrap(),
+            vec![Some("web".to_string()), Some("ops".to_string())]
+        );
+        assert_eq!(root_labels(&[], &scan_paths[..1]).unwrap(), vec![None]);
+
+        let err = root_labels(&["web".into()], &scan_paths).unwrap_err();
+        assert_eq!(err.to_string(), "--root-labels has 1 labels for 2 paths");
+    }
+
+    #[test]
+    fn test_csv_quote_keeps_commas_and_quotes_inside_the_field() {
+        assert_eq!(csv_quote("infra"), "\"infra\"");
+        assert_eq!(csv_quote("a,b \"c\""), "\"a,b \"\"c\"\"\"");
+    }
+}
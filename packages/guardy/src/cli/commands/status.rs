@@ -21,6 +21,41 @@ pub async fn execute(_args: StatusArgs, verbosity_level: u8) -> Result<()> {
 
             let branch = repo.current_branch()?;
             styled!("  Current branch: {}", (branch, "branch"));
+            if let Some(tracking) = repo.tracking()? {
+                styled!(
+                    "  Upstream: {} {}",
+                    (tracking.upstream, "branch"),
+                    (
+                        format!("({} ahead, {} behind)", tracking.ahead, tracking.behind),
+                        "muted"
+                    )
+                );
+            }
+            let unpushed = repo.unpushed_commits()?;
+            if !unpushed.is_empty() {
+                styled!(
+                    "  Unpushed: {}",
+                    (human_count(unpushed.len(), "commit"), "number")
+                );
+                for commit in unpushed.iter().take(5) {
+                    styled!(
+                        "    {} {}",
+                        (&commit.commit[..8], "muted"),
+                        (&commit.subject, "primary")
+                    );
+                }
+            }
+            if repo.is_bare() {
+                styled!(
+                    "  Bare repository: {}",
+                    (repo.git_dir().display().to_string(), "property")
+                );
+            } else if repo.is_worktree() {
+                styled!(
+                    "  Linked worktree of: {}",
+                    (repo.common_dir().display().to_string(), "property")
+                );
+            }
             repo
         }
         Err(_) => {
@@ -61,13 +96,38 @@ pub async fn execute(_args: StatusArgs, verbosity_level: u8) -> Result<()> {
         }
     }
 
+    // Check repository hygiene
+    match crate::scanner::hygiene::check(&repo) {
+        Ok(issues) if issues.is_empty() => {
+            styled!("{} Repository hygiene looks good", ("✅", "success_symbol"));
+        }
+        Ok(issues) => {
+            styled!(
+                "{} Repository hygiene: {}",
+                ("⚠️", "warning_symbol"),
+                (human_count(issues.len(), "suggestion"), "warning")
+            );
+            for issue in &issues {
+                styled!("  {}", (issue.message(), "primary"));
+                styled!("    {}", (issue.suggestion(), "accent"));
+            }
+        }
+        Err(e) => {
+            styled!(
+                "{} Hygiene check failed: {}",
+                ("⚠️", "warning_symbol"),
+                (e.to_string(), "warning")
+            );
+        }
+    }
+
     // Check hook installation
-    let hooks_dir = repo.git_dir().join("hooks");
-    let hook_names = ["pre-commit", "commit-msg", "post-checkout", "pre-push"];
+    let hooks_dir = repo.hooks_dir();
+    let hook_names = crate::hooks::SUPPORTED_HOOKS;
     let mut installed_hooks = Vec::new();
     let mut missing_hooks = Vec::new();
 
-    for hook_name in &hook_names {
+    for hook_name in hook_names {
         let hook_path = hooks_dir.join(hook_name);
         if hook_path.exists() {
             // Check if it's a guardy hook
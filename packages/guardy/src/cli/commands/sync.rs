@@ -3,7 +3,9 @@ use clap::{Parser, Subcommand};
 
 use crate::cli::output;
 use crate::config::GuardyConfig;
-use crate::sync::{manager::SyncManager, status::StatusDisplay};
+use crate::shared::paths;
+use crate::sync::manager::{LockMode, PushRequest, SyncManager};
+use crate::sync::status::StatusDisplay;
 
 #[derive(Parser)]
 #[command(about = "File synchronization from remote repositories")]
@@ -22,12 +24,32 @@ pub struct SyncArgs {
     /// Specific version to sync (tag, branch, or commit)
     #[arg(long)]
     pub version: Option<String>,
+
+    /// Delete local copies of files removed upstream without asking
+    #[arg(long, overrides_with = "no_prune")]
+    pub prune: bool,
+
+    /// Keep local copies of files removed upstream without asking
+    #[arg(long)]
+    pub no_prune: bool,
+
+    /// Only sync the repositories of this profile (`sync.profiles`)
+    #[arg(long, global = true, env = "GUARDY_SYNC_PROFILE")]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum SyncSubcommand {
-    /// Show sync status and configuration
-    Status,
+    /// Show sync status and configuration, compared offline with the cached sources
+    Status {
+        /// Output format; `json` lists the state of every file
+        #[arg(long, value_enum, default_value = "text")]
+        format: SyncFormat,
+
+        /// Fetch every source before comparing instead of using the cache as is
+        #[arg(long)]
+        refresh: bool,
+    },
 
     /// Update files from configured repositories (interactive by default)
     Update {
@@ -42,40 +64,184 @@ pub enum SyncSubcommand {
         /// Specific version to sync (tag, branch, or commit)
         #[arg(long)]
         version: Option<String>,
+
+        /// Delete local copies of files removed upstream without asking
+        #[arg(long, overrides_with = "no_prune")]
+        prune: bool,
+
+        /// Keep local copies of files removed upstream without asking
+        #[arg(long)]
+        no_prune: bool,
+
+        /// Sync exactly the commits and contents in guardy-sync.lock, failing if it is
+        /// missing an entry or doesn't match
+        #[arg(long)]
+        locked: bool,
+    },
+
+    /// Resolve the configured versions again, sync them and update guardy-sync.lock
+    Upgrade {
+        /// Only upgrade this sync repository (its `name`)
+        repo: Option<String>,
+
+        /// Update all changes without prompting
+        #[arg(long)]
+        force: bool,
     },
 
     /// Show differences between local and remote files (what has drifted)
-    Diff,
+    Diff {
+        /// Output format; `json` lists the state of every file with its diff
+        #[arg(long, value_enum, default_value = "text")]
+        format: SyncFormat,
+    },
+
+    /// Exit non-zero when any file differs from its pinned upstream version (for CI)
+    Check {
+        /// Output format for the drift summary
+        #[arg(long, value_enum, default_value = "text")]
+        format: SyncFormat,
+    },
+
+    /// Push local edits of synced files back upstream as a branch (or pull request)
+    Push {
+        /// Only push changes for this sync repository (its `name`)
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Branch to push to [default: guardy/sync-from-<directory name>]
+        #[arg(long)]
+        branch: Option<String>,
+
+        /// Commit message [default: "Update synced files from <directory name>"]
+        #[arg(short, long)]
+        message: Option<String>,
+
+        /// Open a pull request for the branch with the GitHub CLI (gh)
+        #[arg(long)]
+        pr: bool,
+
+        /// Show the local edits without pushing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SyncFormat {
+    /// Human-readable output
+    Text,
+    /// Structured output for scripts, dashboards and bots
+    Json,
+}
+
+/// Where the sync configuration comes from
+#[derive(Clone, Copy)]
+struct Source<'a> {
+    config_path: Option<&'a str>,
+    profile: Option<&'a str>,
 }
 
 pub async fn execute(args: SyncArgs, config_path: Option<&str>) -> Result<()> {
+    let profile = args.profile.clone();
+    let source = Source {
+        config_path,
+        profile: profile.as_deref(),
+    };
     match args.command {
-        Some(SyncSubcommand::Status) => execute_status(config_path).await,
+        Some(SyncSubcommand::Status { format, refresh }) => {
+            execute_status(format, refresh, source).await
+        }
         Some(SyncSubcommand::Update {
             force,
             repo,
             version,
+            prune,
+            no_prune,
+            locked,
         }) => {
             // Prefer subcommand args over main args
             let final_force = force || args.force;
             let final_repo = repo.or(args.repo);
             let final_version = version.or(args.version);
-            execute_update(final_force, final_repo, final_version, config_path).await
+            let final_prune =
+                prune_choice(prune, no_prune).or(prune_choice(args.prune, args.no_prune));
+            let lock_mode = if locked {
+                LockMode::Locked
+            } else {
+                LockMode::Follow
+            };
+            execute_update(
+                final_force,
+                final_repo,
+                final_version,
+                final_prune,
+                lock_mode,
+                source,
+            )
+            .await
         }
-        Some(SyncSubcommand::Diff) => execute_diff(config_path).await,
+        Some(SyncSubcommand::Upgrade { repo, force }) => {
+            let prune = prune_choice(args.prune, args.no_prune);
+            let lock_mode = LockMode::Upgrade(repo);
+            execute_update(force || args.force, None, None, prune, lock_mode, source).await
+        }
+        Some(SyncSubcommand::Diff { format }) => execute_diff(format, source).await,
+        Some(SyncSubcommand::Check { format }) => execute_check(format, source),
+        Some(SyncSubcommand::Push {
+            repo,
+            branch,
+            message,
+            pr,
+            dry_run,
+        }) => execute_push(repo, branch, message, pr, dry_run, source),
         // Default to update behavior when no subcommand is provided, using main args
-        None => execute_update(args.force, args.repo, args.version, config_path).await,
+        None => {
+            let prune = prune_choice(args.prune, args.no_prune);
+            let lock_mode = LockMode::Follow;
+            execute_update(
+                args.force,
+                args.repo,
+                args.version,
+                prune,
+                lock_mode,
+                source,
+            )
+            .await
+        }
+    }
+}
+
+/// `--prune` / `--no-prune`, or `None` to ask (interactive) or keep (forced)
+fn prune_choice(prune: bool, no_prune: bool) -> Option<bool> {
+    match (prune, no_prune) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
     }
 }
 
-async fn execute_status(config_path: Option<&str>) -> Result<()> {
-    let manager = create_sync_manager(config_path)?;
+async fn execute_status(format: SyncFormat, refresh: bool, source: Source<'_>) -> Result<()> {
+    let manager = create_sync_manager(source)?;
+    if format == SyncFormat::Json {
+        output::set_quiet(true);
+    }
+    if refresh {
+        manager.refresh()?;
+    }
+    if format == SyncFormat::Json {
+        return print_file_states(&manager, false);
+    }
     let status_display = StatusDisplay::new(&manager);
     status_display.show_detailed_status()
 }
 
-async fn execute_diff(config_path: Option<&str>) -> Result<()> {
-    let mut manager = create_sync_manager(config_path)?;
+async fn execute_diff(format: SyncFormat, source: Source<'_>) -> Result<()> {
+    let mut manager = create_sync_manager(source)?;
+    if format == SyncFormat::Json {
+        output::set_quiet(true);
+        return print_file_states(&manager, true);
+    }
 
     // Check if we have any configuration
     if manager.config.repos.is_empty() {
@@ -91,11 +257,182 @@ async fn execute_diff(config_path: Option<&str>) -> Result<()> {
     Ok(())
 }
 
+/// `{ "profile", "repos": [{ "name", ..., "files": [{ "path", "state", "diff" }] }] }`
+fn print_file_states(manager: &SyncManager, refresh: bool) -> Result<()> {
+    let repos = manager.file_states(refresh)?;
+    let report = serde_json::json!({
+        "profile": manager.config.active_profile,
+        "repos": repos,
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn execute_check(format: SyncFormat, source: Source<'_>) -> Result<()> {
+    let manager = create_sync_manager(source)?;
+    if manager.config.repos.is_empty() {
+        return Err(anyhow!("No sync configuration found"));
+    }
+    if format == SyncFormat::Json {
+        output::set_quiet(true);
+    }
+    let drift = manager.check_drift()?;
+    let in_sync = drift.iter().all(|repo| repo.in_sync());
+
+    if format == SyncFormat::Json {
+        let summary = serde_json::json!({ "in_sync": in_sync, "repos": drift });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        for repo in &drift {
+            if repo.in_sync() {
+                output::styled!(
+                    "{} {} matches {}",
+                    ("✅", "success_symbol"),
+                    (&repo.name, "property"),
+                    (&repo.version, "id_value")
+                );
+                continue;
+            }
+            output::styled!(
+                "{} {} drifted from {}",
+                ("❌", "error_symbol"),
+                (&repo.name, "property"),
+                (&repo.version, "id_value")
+            );
+            for (kind, files) in [
+                ("modified", &repo.modified),
+                ("missing", &repo.missing),
+                ("orphaned", &repo.orphaned),
+            ] {
+                for file in files {
+                    println!("  {kind:<9} {}", output::file_path(file.clone()));
+                }
+            }
+        }
+    }
+
+    if !in_sync {
+        // Drift is a result, not an error: the summary above is the whole report
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn execute_push(
+    repo: Option<String>,
+    branch: Option<String>,
+    message: Option<String>,
+    pr: bool,
+    dry_run: bool,
+    source: Source<'_>,
+) -> Result<()> {
+    let manager = create_sync_manager(source)?;
+    let repos: Vec<_> = manager
+        .config
+        .repos
+        .iter()
+        .filter(|candidate| repo.as_ref().is_none_or(|name| &candidate.name == name))
+        .collect();
+    if repos.is_empty() {
+        return Err(match repo {
+            Some(name) => anyhow!("No sync repository named '{name}'"),
+            None => anyhow!("No sync configuration found"),
+        });
+    }
+
+    let project = std::env::current_dir()?
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "project".to_string());
+    // Directory names like `.tmpXYZ` or `my app` aren't valid in branch names
+    let slug: String = project
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let push = PushRequest {
+        branch: branch.unwrap_or_else(|| format!("guardy/sync-from-{}", slug.trim_matches('-'))),
+        message: message.unwrap_or_else(|| format!("Update synced files from {project}")),
+        dry_run,
+    };
+
+    for repo in repos {
+        if repo.local_source().is_some() {
+            output::styled!(
+                "{} {} {}",
+                ("⚠️", "warning_symbol"),
+                (&repo.name, "property"),
+                ("is a local directory - edit it in place", "muted")
+            );
+            continue;
+        }
+        if repo.oci_reference().is_some() {
+            output::styled!(
+                "{} {} {}",
+                ("⚠️", "warning_symbol"),
+                (&repo.name, "property"),
+                (
+                    "is an OCI artifact - publish a new version to the registry",
+                    "muted"
+                )
+            );
+            continue;
+        }
+
+        let files = manager.push_changes(repo, &push)?;
+        if files.is_empty() {
+            output::styled!(
+                "{} {} has no local edits",
+                ("✅", "success_symbol"),
+                (&repo.name, "property")
+            );
+            continue;
+        }
+        if dry_run {
+            output::styled!(
+                "{} {} would push {} to {}",
+                ("🔍", "info_symbol"),
+                (&repo.name, "property"),
+                (output::human_count(files.len(), "file"), "property"),
+                (&push.branch, "id_value")
+            );
+            continue;
+        }
+
+        output::styled!(
+            "{} Pushed {} to {} of {}",
+            ("🚀", "success_symbol"),
+            (output::human_count(files.len(), "file"), "property"),
+            (&push.branch, "id_value"),
+            (&repo.repo, "property")
+        );
+        for file in &files {
+            println!("  • {}", output::file_path(paths::display(file)));
+        }
+        if pr {
+            let url = manager.open_pull_request(repo, &push, &files)?;
+            output::styled!(
+                "{} Pull request: {}",
+                ("🔗", "info_symbol"),
+                (url, "property")
+            );
+        }
+    }
+    Ok(())
+}
+
 async fn execute_update(
     force: bool,
     repo: Option<String>,
     version: Option<String>,
-    config_path: Option<&str>,
+    prune: Option<bool>,
+    lock_mode: LockMode,
+    source: Source<'_>,
 ) -> Result<()> {
     // Handle bootstrap case
     if let (Some(repo_url), Some(version_str)) = (repo, version) {
@@ -107,16 +444,18 @@ async fn execute_update(
         );
 
         let mut manager = SyncManager::bootstrap(&repo_url, &version_str)?;
-        let updated_files = manager.update_all_repos(false).await?; // Bootstrap is always non-interactive
+        let updated_files = manager
+            .update_all_repos(false, prune, LockMode::Unlocked)
+            .await?; // Bootstrap is always non-interactive
 
         if !updated_files.is_empty() {
             output::styled!(
-                "{} Synced {} files:",
+                "{} Synced {}:",
                 ("📝", "info_symbol"),
-                (updated_files.len().to_string(), "property")
+                (output::human_count(updated_files.len(), "file"), "property")
             );
             for file in &updated_files {
-                println!("  • {}", output::file_path(file.display().to_string()));
+                println!("  • {}", output::file_path(paths::display(file)));
             }
         }
         output::styled!("{} Bootstrap complete", ("✅", "success_symbol"));
@@ -124,7 +463,7 @@ async fn execute_update(
     }
 
     // Regular update case
-    let mut manager = create_sync_manager(config_path)?;
+    let mut manager = create_sync_manager(source)?;
 
     // Check if we have any configuration (without doing full status check)
     if manager.config.repos.is_empty() {
@@ -142,7 +481,14 @@ async fn execute_update(
     // Perform the update (interactive by default, force bypasses)
     let interactive = !force;
 
-    let updated_files = manager.update_all_repos(interactive).await?;
+    if let LockMode::Upgrade(Some(name)) = &lock_mode
+        && !manager.config.repos.iter().any(|repo| &repo.name == name)
+    {
+        return Err(anyhow!("No sync repository named '{name}'"));
+    }
+    let updated_files = manager
+        .update_all_repos(interactive, prune, lock_mode)
+        .await?;
 
     // Show results for force mode
     if force {
@@ -150,13 +496,13 @@ async fn execute_update(
             output::styled!("<info>  No files were updated");
         } else {
             output::styled!(
-                "{} Successfully updated {} files:",
+                "{} Successfully updated {}:",
                 ("✅", "success_symbol"),
-                (updated_files.len().to_string(), "property")
+                (output::human_count(updated_files.len(), "file"), "property")
             );
 
             for file in &updated_files {
-                println!("  • {}", output::file_path(file.display().to_string()));
+                println!("  • {}", output::file_path(paths::display(file)));
             }
         }
     }
@@ -165,12 +511,15 @@ async fn execute_update(
     Ok(())
 }
 
-fn create_sync_manager(config_path: Option<&str>) -> Result<SyncManager> {
-    let config = GuardyConfig::load::<()>(config_path, None, 0)
+fn create_sync_manager(source: Source<'_>) -> Result<SyncManager> {
+    let config = GuardyConfig::load::<()>(source.config_path, None, 0)
         .map_err(|e| anyhow!("Failed to load configuration: {}", e))?;
 
     // Extract sync config using the proper parsing method
-    let sync_config = SyncManager::parse_sync_config(&config)?;
+    let mut sync_config = SyncManager::parse_sync_config(&config)?;
+    if let Some(profile) = source.profile {
+        sync_config.select_profile(profile)?;
+    }
 
     // Create sync manager with parsed config
     SyncManager::with_config(sync_config)
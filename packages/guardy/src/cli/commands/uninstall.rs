@@ -22,12 +22,12 @@ pub async fn execute(args: UninstallArgs) -> Result<()> {
         }
     };
 
-    let hooks_dir = repo.git_dir().join("hooks");
-    let hook_names = ["pre-commit", "commit-msg", "post-checkout", "pre-push"];
+    let hooks_dir = repo.hooks_dir();
+    let hook_names = crate::hooks::SUPPORTED_HOOKS;
 
     // Find guardy hooks
     let mut guardy_hooks = Vec::new();
-    for hook_name in &hook_names {
+    for hook_name in hook_names {
         let hook_path = hooks_dir.join(hook_name);
         if hook_path.exists()
             && let Ok(content) = fs::read_to_string(&hook_path)
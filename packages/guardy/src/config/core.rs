@@ -1,9 +1,13 @@
 use anyhow::Result;
 use serde::Serialize;
+use superconfig::figment::providers::Serialized;
 use superconfig::{SuperConfig, VerbosityLevel};
 
 // Embed the default config at compile time
-const DEFAULT_CONFIG: &str = include_str!("../../default-config.toml");
+pub(super) const DEFAULT_CONFIG: &str = include_str!("../../default-config.toml");
+
+/// Prefix of environment variables that override config keys
+pub(super) const ENV_PREFIX: &str = "GUARDY_";
 
 pub struct GuardyConfig {
     config: SuperConfig,
@@ -24,9 +28,15 @@ impl GuardyConfig {
             .with_defaults_string(DEFAULT_CONFIG) // 1. Defaults (lowest)
             .with_hierarchical_config("guardy") // 2. Hierarchical: system→user→project
             .with_file_opt(custom_config) // 3. Custom config file (if provided)
-            .with_env_ignore_empty("GUARDY_") // 4. Environment variables (with empty filtering)
+            .with_env_ignore_empty(ENV_PREFIX) // 4. Environment variables (with empty filtering)
+            .merge(Serialized::defaults(super::env_vars::overrides(
+                &super::env_vars::documented()?,
+                std::env::vars(),
+            ))) // 4b. ...mapped to multi-word keys the env layer can't split
             .with_cli_opt(cli_overrides); // 5. CLI (highest priority)
 
+        super::deprecations::warn(custom_config);
+
         // Debug: Show final config (only at trace level -vvv)
         if let Ok(final_config) = config.extract::<serde_json::Value>() {
             tracing::trace!(
@@ -0,0 +1,714 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::ConfigFormat;
+use crate::hooks::SUPPORTED_HOOKS;
+
+/// Keys that moved, as `(old dotted key, new dotted key)`
+///
+/// Readers keep accepting the old key, so existing files work until rewritten.
+const RENAMED: &[(&str, &str)] = &[
+    (
+        "scanner.entropy_analysis",
+        "scanner.enable_entropy_analysis",
+    ),
+    ("security.patterns", "scanner.custom_patterns"),
+];
+
+/// A deprecated key found in a config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    /// Dotted key as written, e.g. `hooks.pre_commit`
+    pub key: String,
+    /// Where the setting lives now, e.g. `hooks.pre-commit.enabled`
+    pub replacement: String,
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' is deprecated, use '{}'",
+            self.key, self.replacement
+        )
+    }
+}
+
+/// Result of rewriting a config file
+#[derive(Debug, Default)]
+pub struct Fixed {
+    pub content: String,
+    pub applied: Vec<Deprecation>,
+    /// Deprecations that can't be rewritten in place and need a manual edit
+    pub manual: Vec<Deprecation>,
+}
+
+impl ConfigFormat {
+    /// Format of a config file, from its extension (YAML when there is none)
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::Json,
+            Some("toml") => Self::Toml,
+            _ => Self::Yaml,
+        }
+    }
+}
+
+/// Config files a run reads besides the embedded defaults: the `--config`
+/// file and guardy.{toml,json,yaml,yml} in the current directory
+pub fn config_files(custom_config: Option<&str>) -> Vec<PathBuf> {
+    custom_config
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(["toml", "json", "yaml", "yml"].map(|ext| PathBuf::from(format!("guardy.{ext}"))))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Print a migration hint for every deprecated key in the config files, once per run
+pub(super) fn warn(custom_config: Option<&str>) {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    WARNED.call_once(|| {
+        let mut found = false;
+        for path in config_files(custom_config) {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(value) = parse(&content, ConfigFormat::of(&path)) else {
+                continue;
+            };
+            for deprecation in detect(&value) {
+                eprintln!("Warning: {}: {deprecation}", path.display());
+                found = true;
+            }
+        }
+        if found {
+            eprintln!("Run 'guardy config fix --write' to update the configuration");
+        }
+    });
+}
+
+pub fn parse(content: &str, format: ConfigFormat) -> Result<Value> {
+    Ok(match format {
+        ConfigFormat::Json => serde_json::from_str(content)?,
+        ConfigFormat::Toml => toml::from_str(content)?,
+        ConfigFormat::Yaml => serde_yml::from_str(content)?,
+    })
+}
+
+/// Deprecated keys and structures in a parsed config file
+pub fn detect(config: &Value) -> Vec<Deprecation> {
+    let mut found = Vec::new();
+    for (old, new) in RENAMED {
+        if config.pointer(&pointer(old)).is_some() {
+            found.push(deprecation(old, new));
+        }
+    }
+
+    if let Some(hooks) = config.get("hooks").and_then(Value::as_object) {
+        for (name, definition) in hooks {
+            let hook = hook_name(name);
+            if definition.is_boolean() {
+                found.push(deprecation(
+                    &format!("hooks.{name}"),
+                    &format!("hooks.{hook}.enabled"),
+                ));
+            } else if hook != *name {
+                found.push(deprecation(
+                    &format!("hooks.{name}"),
+                    &format!("hooks.{hook}"),
+                ));
+            }
+        }
+    }
+    found
+}
+
+/// Rewrite the `hooks` section from the old shape (`pre_commit: true`) to
+/// hook definitions, so deprecated files keep loading
+pub fn migrate_hooks(hooks: &mut Value) {
+    let Some(map) = hooks.as_object_mut() else {
+        return;
+    };
+    let deprecated: Vec<String> = map
+        .iter()
+        .filter(|(name, definition)| definition.is_boolean() || hook_name(name) != **name)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    for name in deprecated {
+        let Some(value) = map.remove(&name) else {
+            continue;
+        };
+        let definition = map
+            .entry(hook_name(&name))
+            .or_insert_with(|| Value::Object(Default::default()));
+        match (definition, value) {
+            (Value::Object(definition), Value::Bool(enabled)) => {
+                definition.insert("enabled".to_string(), Value::Bool(enabled));
+            }
+            (Value::Object(definition), Value::Object(settings)) => definition.extend(settings),
+            (definition, value) => *definition = value,
+        }
+    }
+}
+
+/// Rewrite deprecated keys in a config file, leaving comments and layout alone
+///
+/// Edits are made line by line: keys are renamed where they are written and
+/// `pre_commit: true` becomes a definition with `enabled`. A key is only renamed
+/// when the file doesn't already have the new one, and `security.patterns` only
+/// moves when the file has no `scanner` section to merge it into; the rest is
+/// reported in [`Fixed::manual`].
+pub fn fix(content: &str, format: ConfigFormat) -> Result<Fixed> {
+    let config = parse(content, format.clone()).context("Failed to parse the configuration")?;
+    let deprecations = detect(&config);
+    let lines = key_lines(content, &format);
+    let mut edits = Vec::new();
+    let mut fixed = Fixed::default();
+    let mut renamed_to = HashSet::new();
+
+    for deprecation in deprecations {
+        let old: Vec<&str> = deprecation.key.split('.').collect();
+        let new: Vec<&str> = deprecation.replacement.split('.').collect();
+
+        // The key the rename writes: `hooks.pre-commit` for `hooks.pre-commit.enabled`
+        let target = if old[0] == "hooks" {
+            &new[..2]
+        } else {
+            &new[..]
+        }
+        .join(".");
+        if config.pointer(&pointer(&target)).is_some() || !renamed_to.insert(target) {
+            fixed.manual.push(deprecation);
+            continue;
+        }
+
+        if old[0] == "hooks" {
+            let hook = new[1];
+            if new.len() == 3 {
+                enable_hook(&lines, old[1], hook, &format, &mut edits);
+            } else {
+                rename(&lines, &old, hook, &mut edits);
+            }
+        } else if old[..old.len() - 1] == new[..new.len() - 1] {
+            rename(&lines, &old, new[new.len() - 1], &mut edits);
+        } else if old.len() == 2 && movable_section(&lines, old[0], old[1], new[0]) {
+            rename(&lines, &old, new[1], &mut edits);
+            rename(&lines, &old[..1], new[0], &mut edits);
+        } else {
+            fixed.manual.push(deprecation);
+            continue;
+        }
+        fixed.applied.push(deprecation);
+    }
+
+    fixed.content = apply(content, edits);
+    parse(&fixed.content, format)
+        .context("Rewriting deprecated keys would leave an invalid configuration")?;
+    Ok(fixed)
+}
+
+fn deprecation(key: &str, replacement: &str) -> Deprecation {
+    Deprecation {
+        key: key.to_string(),
+        replacement: replacement.to_string(),
+    }
+}
+
+fn pointer(key: &str) -> String {
+    key.split('.').map(|part| format!("/{part}")).collect()
+}
+
+/// `pre_commit` → `pre-commit` for the git hooks guardy knows; other names stay
+fn hook_name(name: &str) -> String {
+    let hyphenated = name.replace('_', "-");
+    if SUPPORTED_HOOKS.contains(&hyphenated.as_str()) {
+        hyphenated
+    } else {
+        name.to_string()
+    }
+}
+
+/// A line that writes one or more keys
+#[derive(Debug)]
+struct KeyLine {
+    line: usize,
+    /// Enclosing keys not written on this line (YAML parents, TOML table)
+    parent: Vec<String>,
+    /// Keys written on this line, with the byte range of each name
+    keys: Vec<(String, Range<usize>)>,
+    /// Scalar value written on the same line, without comment or trailing comma
+    value: Option<Range<usize>>,
+}
+
+impl KeyLine {
+    fn path(&self) -> Vec<&str> {
+        self.parent
+            .iter()
+            .map(String::as_str)
+            .chain(self.keys.iter().map(|(key, _)| key.as_str()))
+            .collect()
+    }
+
+    /// Range of the name of `path`'s last key, when this line writes it
+    fn key_range(&self, path: &[&str]) -> Option<Range<usize>> {
+        let index = path.len().checked_sub(1)?.checked_sub(self.parent.len())?;
+        let written = self.path();
+        (written.len() >= path.len() && written[..path.len()] == *path)
+            .then(|| self.keys[index].1.clone())
+    }
+}
+
+struct Edit {
+    line: usize,
+    range: Range<usize>,
+    text: String,
+}
+
+fn rename(lines: &[KeyLine], path: &[&str], new_name: &str, edits: &mut Vec<Edit>) {
+    for key_line in lines {
+        if let Some(range) = key_line.key_range(path) {
+            edits.push(Edit {
+                line: key_line.line,
+                range,
+                text: new_name.to_string(),
+            });
+        }
+    }
+}
+
+/// `hooks.<name>: <bool>` becomes `hooks.<hook>` with `enabled: <bool>`
+fn enable_hook(
+    lines: &[KeyLine],
+    name: &str,
+    hook: &str,
+    format: &ConfigFormat,
+    edits: &mut Vec<Edit>,
+) {
+    let path = ["hooks", name];
+    for key_line in lines {
+        let (Some(key), Some(value)) = (key_line.key_range(&path), key_line.value.clone()) else {
+            continue;
+        };
+        if key_line.path().len() != 2 {
+            continue;
+        }
+        edits.push(Edit {
+            line: key_line.line,
+            range: key,
+            text: hook.to_string(),
+        });
+        edits.push(Edit {
+            line: key_line.line,
+            range: value,
+            text: match format {
+                ConfigFormat::Json => "{ \"enabled\": $ }",
+                ConfigFormat::Toml => "{ enabled = $ }",
+                ConfigFormat::Yaml => "{ enabled: $ }",
+            }
+            .to_string(),
+        });
+    }
+}
+
+/// Whether `section` holds nothing but `key` and the file has no `target`
+/// section yet, so renaming the section moves the key
+fn movable_section(lines: &[KeyLine], section: &str, key: &str, target: &str) -> bool {
+    lines.iter().all(|line| {
+        let path = line.path();
+        match path.first() {
+            Some(first) if *first == target => false,
+            Some(first) if *first == section => path.get(1).is_none_or(|name| *name == key),
+            _ => true,
+        }
+    })
+}
+
+/// Apply edits, substituting `$` in replacement text with the original text
+fn apply(content: &str, mut edits: Vec<Edit>) -> String {
+    let mut lines: Vec<String> = content.split('\n').map(str::to_string).collect();
+    // Later ranges first so earlier ones stay valid
+    edits.sort_by_key(|edit| std::cmp::Reverse((edit.line, edit.range.start)));
+    for edit in edits {
+        let line = &mut lines[edit.line];
+        let text = edit.text.replace('$', &line[edit.range.clone()]);
+        line.replace_range(edit.range, &text);
+    }
+    lines.join("\n")
+}
+
+fn key_lines(content: &str, format: &ConfigFormat) -> Vec<KeyLine> {
+    match format {
+        ConfigFormat::Yaml => yaml_key_lines(content),
+        ConfigFormat::Toml => toml_key_lines(content),
+        ConfigFormat::Json => json_key_lines(content),
+    }
+}
+
+/// Mapping keys of a YAML document, nested by indentation
+///
+/// Sequence items and block scalars are skipped - no deprecated key lives
+/// inside either.
+fn yaml_key_lines(content: &str) -> Vec<KeyLine> {
+    let mut found = Vec::new();
+    let mut parents: Vec<(usize, String)> = Vec::new();
+    let mut block_scalar: Option<usize> = None;
+
+    for (index, line) in content.split('\n').enumerate() {
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(scalar_indent) = block_scalar {
+            if indent > scalar_indent {
+                continue;
+            }
+            block_scalar = None;
+        }
+
+        while parents.last().is_some_and(|(depth, _)| *depth >= indent) {
+            parents.pop();
+        }
+        if trimmed.starts_with('-') {
+            parents.push((indent, "-".to_string()));
+            continue;
+        }
+
+        let Some((name, range, after)) = yaml_key(line, indent) else {
+            continue;
+        };
+        let value = scalar(line, after, '#');
+        if value
+            .as_ref()
+            .is_some_and(|value| line[value.clone()].starts_with(['|', '>']))
+        {
+            block_scalar = Some(indent);
+        }
+        found.push(KeyLine {
+            line: index,
+            parent: parents.iter().map(|(_, key)| key.clone()).collect(),
+            keys: vec![(name.clone(), range)],
+            value,
+        });
+        parents.push((indent, name));
+    }
+    found
+}
+
+/// Key at `start` of a YAML line: its name, name range and the offset after the colon
+fn yaml_key(line: &str, start: usize) -> Option<(String, Range<usize>, usize)> {
+    let rest = &line[start..];
+    let (range, end) = if let Some(quote) = rest.chars().next().filter(|c| matches!(c, '"' | '\''))
+    {
+        let close = rest[1..].find(quote)? + 1;
+        (start + 1..start + close, start + close + 1)
+    } else {
+        let colon = rest
+            .find(": ")
+            .or_else(|| rest.strip_suffix(':').map(str::len))?;
+        (start..start + colon, start + colon)
+    };
+    line[end..]
+        .starts_with(':')
+        .then(|| (line[range.clone()].trim().to_string(), range, end + 1))
+}
+
+/// Tables and keys of a TOML document
+fn toml_key_lines(content: &str) -> Vec<KeyLine> {
+    let mut found = Vec::new();
+    let mut table: Vec<String> = Vec::new();
+    // Open brackets of a multi-line array or inline table
+    let mut depth = 0i32;
+
+    for (index, line) in content.split('\n').enumerate() {
+        if depth > 0 {
+            depth += bracket_depth(line, '#');
+            continue;
+        }
+        let trimmed = line.trim_start();
+        let start = line.len() - trimmed.len();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            let array = trimmed.starts_with("[[");
+            let open = start + if array { 2 } else { 1 };
+            let Some(close) = line[open..].find(']') else {
+                continue;
+            };
+            let keys = dotted_keys(line, open..open + close);
+            table = keys.iter().map(|(key, _)| key.clone()).collect();
+            if array {
+                table.push("-".to_string());
+            }
+            found.push(KeyLine {
+                line: index,
+                parent: Vec::new(),
+                keys,
+                value: None,
+            });
+            continue;
+        }
+
+        let Some(equals) = unquoted(line, start, |c| c == '=') else {
+            continue;
+        };
+        let value = scalar(line, equals + 1, '#');
+        depth = bracket_depth(&line[equals + 1..], '#');
+        found.push(KeyLine {
+            line: index,
+            parent: table.clone(),
+            keys: dotted_keys(line, start..equals),
+            value,
+        });
+    }
+    found
+}
+
+/// Segments of a dotted TOML key within `range`, quotes excluded from their ranges
+fn dotted_keys(line: &str, range: Range<usize>) -> Vec<(String, Range<usize>)> {
+    let mut keys = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let end = unquoted(line, start, |c| c == '.')
+            .filter(|dot| *dot < range.end)
+            .unwrap_or(range.end);
+        let segment = &line[start..end];
+        let leading = segment.len() - segment.trim_start().len();
+        let name = segment.trim().trim_matches(['"', '\'']);
+        let name_start = start + leading + usize::from(segment.trim().starts_with(['"', '\'']));
+        keys.push((name.to_string(), name_start..name_start + name.len()));
+        start = end + 1;
+    }
+    keys
+}
+
+/// Object keys of a JSON document, nested by the brackets they sit in
+fn json_key_lines(content: &str) -> Vec<KeyLine> {
+    let mut found = Vec::new();
+    // Key of each open object or array; "" for the root and for array items
+    let mut open: Vec<String> = Vec::new();
+
+    for (index, line) in content.split('\n').enumerate() {
+        let trimmed = line.trim_start();
+        let start = line.len() - trimmed.len();
+        let mut pending = None;
+        let mut rest = start;
+
+        if trimmed.starts_with('"')
+            && let Some(close) = line[start + 1..].find('"')
+            && line[start + close + 2..].trim_start().starts_with(':')
+        {
+            let range = start + 1..start + 1 + close;
+            let name = line[range.clone()].to_string();
+            let colon = start + close + 2 + line[start + close + 2..].find(':').unwrap_or(0);
+            found.push(KeyLine {
+                line: index,
+                parent: open.iter().filter(|key| !key.is_empty()).cloned().collect(),
+                keys: vec![(name.clone(), range)],
+                value: scalar(line, colon + 1, '\0'),
+            });
+            pending = Some(name);
+            rest = colon + 1;
+        }
+
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in line[rest..].chars() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '{' | '[' if !in_string => open.push(pending.take().unwrap_or_default()),
+                '}' | ']' if !in_string => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    found
+}
+
+/// Range of the scalar value after `from`, ending at an unquoted `comment`
+/// character; `None` for nested structures and empty values
+fn scalar(line: &str, from: usize, comment: char) -> Option<Range<usize>> {
+    let end = unquoted(line, from, |c| c == comment).unwrap_or(line.len());
+    let text = &line[from..end];
+    let value = text.trim().trim_end_matches(',').trim_end();
+    if value.is_empty() || value.starts_with(['{', '[']) {
+        return None;
+    }
+    let start = from + (text.len() - text.trim_start().len());
+    Some(start..start + value.len())
+}
+
+/// Position of the first character matching `target` outside quotes
+fn unquoted(line: &str, from: usize, target: impl Fn(char) -> bool) -> Option<usize> {
+    let mut quote = None;
+    for (offset, c) in line[from..].char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if target(c) => return Some(from + offset),
+            None => {}
+        }
+    }
+    None
+}
+
+/// Brackets opened minus closed outside quotes, up to an unquoted `comment`
+fn bracket_depth(text: &str, comment: char) -> i32 {
+    let end = unquoted(text, 0, |c| c == comment).unwrap_or(text.len());
+    let mut depth = 0;
+    let mut start = 0;
+    while let Some(position) = unquoted(&text[..end], start, |c| matches!(c, '[' | ']' | '{' | '}'))
+    {
+        depth += if matches!(&text[position..=position], "[" | "{") {
+            1
+        } else {
+            -1
+        };
+        start = position + 1;
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_yaml_keeps_comments_and_layout() {
+        let content = r#"# Project settings
+hooks:
+  pre_commit: true   # scan before committing
+  commit_msg:
+    builtin: ["conventional_commits"]
+security:
+  patterns:
+    - "custom-[a-z]{20,}"   # internal tokens
+"#;
+
+        let fixed = fix(content, ConfigFormat::Yaml).unwrap();
+
+        assert_eq!(
+            fixed.content,
+            r#"# Project settings
+hooks:
+  pre-commit: { enabled: true }   # scan before committing
+  commit-msg:
+    builtin: ["conventional_commits"]
+scanner:
+  custom_patterns:
+    - "custom-[a-z]{20,}"   # internal tokens
+"#
+        );
+        assert_eq!(fixed.applied.len(), 3);
+        assert!(fixed.manual.is_empty());
+
+        let config = parse(&fixed.content, ConfigFormat::Yaml).unwrap();
+        assert!(detect(&config).is_empty());
+        assert_eq!(config["hooks"]["pre-commit"]["enabled"], true);
+    }
+
+    #[test]
+    fn test_fix_toml_and_json() {
+        let toml = r#"[scanner]
+mode = "auto"
+entropy_analysis = false # noisy on fixtures
+
+[hooks]
+pre_push = false
+"#;
+        let fixed = fix(toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(
+            fixed.content,
+            r#"[scanner]
+mode = "auto"
+enable_entropy_analysis = false # noisy on fixtures
+
+[hooks]
+pre-push = { enabled = false }
+"#
+        );
+
+        let json = r#"{
+  "scanner": { "mode": "auto" },
+  "security": {
+    "patterns": ["custom-[a-z]{20,}"]
+  },
+  "hooks": {
+    "commit_msg": true
+  }
+}"#;
+        let fixed = fix(json, ConfigFormat::Json).unwrap();
+        let config = parse(&fixed.content, ConfigFormat::Json).unwrap();
+        assert_eq!(config["hooks"]["commit-msg"]["enabled"], true);
+        assert_eq!(
+            fixed.manual,
+            vec![deprecation("security.patterns", "scanner.custom_patterns")]
+        );
+        assert!(config["security"]["patterns"].is_array());
+    }
+
+    #[test]
+    fn test_fix_leaves_keys_whose_replacement_exists_to_the_user() {
+        let content = r#"scanner:
+  entropy_analysis: false
+  enable_entropy_analysis: true
+hooks:
+  pre_commit: true
+  pre-commit:
+    builtin: ["scan_secrets"]
+  pre_push: true
+"#;
+
+        let fixed = fix(content, ConfigFormat::Yaml).unwrap();
+
+        assert_eq!(
+            fixed.manual,
+            vec![
+                deprecation(
+                    "scanner.entropy_analysis",
+                    "scanner.enable_entropy_analysis"
+                ),
+                deprecation("hooks.pre_commit", "hooks.pre-commit.enabled"),
+            ]
+        );
+        assert_eq!(
+            fixed.applied,
+            vec![deprecation("hooks.pre_push", "hooks.pre-push.enabled")]
+        );
+        let config = parse(&fixed.content, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config["hooks"]["pre_commit"], true);
+        assert_eq!(config["hooks"]["pre-push"]["enabled"], true);
+    }
+
+    #[test]
+    fn test_migrate_hooks_keeps_existing_definitions() {
+        let mut hooks = serde_json::json!({
+            "pre-commit": { "enabled": false, "builtin": ["scan_secrets"] },
+            "pre_commit": true,
+            "pre_push": { "parallel": false }
+        });
+
+        migrate_hooks(&mut hooks);
+
+        assert_eq!(
+            hooks,
+            serde_json::json!({
+                "pre-commit": { "enabled": true, "builtin": ["scan_secrets"] },
+                "pre-push": { "parallel": false }
+            })
+        );
+    }
+}
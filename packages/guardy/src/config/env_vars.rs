@@ -0,0 +1,317 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fmt;
+
+use super::core::{DEFAULT_CONFIG, ENV_PREFIX};
+
+/// A configuration key that can be overridden through the environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvVar {
+    /// Variable name, e.g. `GUARDY_SCANNER_MODE`
+    pub name: String,
+    /// Dotted config key it maps to, e.g. `scanner.mode`
+    pub key: String,
+    /// `string`, `bool`, `integer`, `float` or `list`
+    pub kind: &'static str,
+    /// `null` for optional settings that are unset by default
+    pub default: Value,
+}
+
+impl fmt::Display for EnvVar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.default.is_null() {
+            return write!(f, "{} ({}, unset by default)", self.name, self.kind);
+        }
+        write!(f, "{} ({}, default {})", self.name, self.kind, self.default)
+    }
+}
+
+/// Variables guardy reads directly at runtime rather than as config overrides
+const RUNTIME_VARS: &[&str] = &[
+    "GUARDY_SKIP",
+    "GUARDY_OUTPUT_STYLE",
+    "GUARDY_EVENTS",
+    "GUARDY_SYNC_PROFILE",
+];
+
+/// Settings guardy reads that have no default value, so `default-config.toml` only
+/// shows them commented out (or not at all)
+const OPTIONAL_KEYS: &[(&str, &str)] = &[
+    ("branch_naming.hint", "string"),
+    ("scanner.enable_entropy_analysis", "bool"),
+    ("scanner.entropy_threshold", "float"),
+    ("scanner.include_binary", "bool"),
+    ("scanner.limits.max_duration", "string"),
+    ("scanner.limits.max_memory_mb", "integer"),
+    ("scanner.submodules", "string"),
+    ("signed_commits.allowed_signers_file", "string"),
+];
+
+/// A `GUARDY_*` variable that matches no setting - the env layer silently ignores it
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownVar {
+    pub name: String,
+    /// Closest known variable, when the name looks like a typo of one
+    pub suggestion: Option<String>,
+}
+
+/// Every environment variable guardy reads: the embedded defaults plus the
+/// optional settings that have none
+///
+/// Keys that can't be spelled as a variable name (hook names like `pre-commit`)
+/// are left out - those can only be set in a config file.
+pub fn documented() -> Result<Vec<EnvVar>> {
+    let defaults: Value = toml::from_str(DEFAULT_CONFIG)?;
+    let mut vars = collect(ENV_PREFIX, &defaults);
+    vars.extend(OPTIONAL_KEYS.iter().map(|(key, kind)| EnvVar {
+        name: var_name(ENV_PREFIX, key),
+        key: key.to_string(),
+        kind,
+        default: Value::Null,
+    }));
+    vars.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(vars)
+}
+
+/// The documented variables set in `env`, nested under the keys they override
+///
+/// The generic env layer only splits a name at its first `_`, which turns
+/// `GUARDY_BRANCH_NAMING_EXEMPT` into `branch.naming_exempt`; this maps each
+/// variable to the key it documents instead. Empty values are ignored.
+pub fn overrides(documented: &[EnvVar], env: impl IntoIterator<Item = (String, String)>) -> Value {
+    let mut overrides = Value::Object(Default::default());
+    for (name, raw) in env {
+        let Some(var) = documented.iter().find(|var| var.name == name) else {
+            continue;
+        };
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let mut table = &mut overrides;
+        for part in var.key.split('.') {
+            table = &mut table[part];
+        }
+        *table = parse(var.kind, raw.trim());
+    }
+    overrides
+}
+
+/// `raw` as a value of `kind`, falling back to the string so config validation
+/// reports the bad value
+fn parse(kind: &str, raw: &str) -> Value {
+    let parsed = match kind {
+        "bool" => match raw.to_lowercase().as_str() {
+            "true" | "yes" | "1" | "on" => Some(Value::Bool(true)),
+            "false" | "no" | "0" | "off" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        "integer" => raw.parse::<i64>().ok().map(Value::from),
+        "float" => raw.parse::<f64>().ok().map(Value::from),
+        "list" => serde_json::from_str::<Value>(raw)
+            .ok()
+            .filter(Value::is_array),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// Flatten `defaults` into `<PREFIX><SECTION>_<KEY>` variables, sorted by name
+pub fn collect(prefix: &str, defaults: &Value) -> Vec<EnvVar> {
+    let mut vars = Vec::new();
+    walk(prefix, "", defaults, &mut vars);
+    vars.sort_by(|a, b| a.name.cmp(&b.name));
+    vars
+}
+
+/// `GUARDY_*` names in `names` that are neither config overrides nor runtime variables
+pub fn unknown(documented: &[EnvVar], names: impl IntoIterator<Item = String>) -> Vec<UnknownVar> {
+    let known: Vec<&str> = documented
+        .iter()
+        .map(|var| var.name.as_str())
+        .chain(RUNTIME_VARS.iter().copied())
+        .collect();
+
+    let mut unknown: Vec<UnknownVar> = names
+        .into_iter()
+        .filter(|name| name.starts_with(ENV_PREFIX) && !known.contains(&name.as_str()))
+        .map(|name| {
+            let suggestion = known
+                .iter()
+                .map(|candidate| (edit_distance(&name, candidate), *candidate))
+                .filter(|(distance, _)| *distance <= 2)
+                .min()
+                .map(|(_, candidate)| candidate.to_string());
+            UnknownVar { name, suggestion }
+        })
+        .collect();
+    unknown.sort_by(|a, b| a.name.cmp(&b.name));
+    unknown
+}
+
+/// Levenshtein distance, enough to catch `GUARDY_SCANER_MODE`-style typos
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn walk(prefix: &str, key: &str, value: &Value, vars: &mut Vec<EnvVar>) {
+    if let Value::Object(table) = value {
+        for (child, value) in table {
+            if !child.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                continue;
+            }
+            let key = if key.is_empty() {
+                child.clone()
+            } else {
+                format!("{key}.{child}")
+            };
+            walk(prefix, &key, value, vars);
+        }
+        return;
+    }
+
+    let kind = match value {
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "integer",
+        Value::Array(_) => "list",
+        _ => "string",
+    };
+    vars.push(EnvVar {
+        name: var_name(prefix, key),
+        key: key.to_string(),
+        kind,
+        default: value.clone(),
+    });
+}
+
+fn var_name(prefix: &str, key: &str) -> String {
+    format!("{prefix}{}", key.replace('.', "_").to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_collect_flattens_sections_with_types_and_defaults() {
+        let defaults = json!({
+            "database": { "host": "localhost", "port": 5432, "ratio": 0.5 },
+            "debug": false,
+            "hooks": { "pre-commit": { "enabled": true } },
+            "tags": ["a"],
+        });
+
+        let vars = collect("APP_", &defaults);
+        let lines: Vec<String> = vars.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                r#"APP_DATABASE_HOST (string, default "localhost")"#,
+                "APP_DATABASE_PORT (integer, default 5432)",
+                "APP_DATABASE_RATIO (float, default 0.5)",
+                "APP_DEBUG (bool, default false)",
+                r#"APP_TAGS (list, default ["a"])"#,
+            ]
+        );
+        assert_eq!(vars[0].key, "database.host");
+    }
+
+    #[test]
+    fn test_unknown_flags_typos_with_suggestions() {
+        let documented = documented().unwrap();
+        let names = [
+            "GUARDY_SCANER_MODE",
+            "GUARDY_SCANNER_MODE",
+            "GUARDY_SKIP",
+            "GUARDY_NOT_A_SETTING_AT_ALL",
+            "PATH",
+        ]
+        .map(String::from);
+
+        let unknown = unknown(&documented, names);
+
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownVar {
+                    name: "GUARDY_NOT_A_SETTING_AT_ALL".to_string(),
+                    suggestion: None,
+                },
+                UnknownVar {
+                    name: "GUARDY_SCANER_MODE".to_string(),
+                    suggestion: Some("GUARDY_SCANNER_MODE".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_accepts_overrides_of_optional_settings() {
+        let documented = documented().unwrap();
+        let names = [
+            "GUARDY_SCANNER_LIMITS_MAX_DURATION",
+            "GUARDY_SCANNER_LIMITS_MAX_MEMORY_MB",
+            "GUARDY_SCANNER_LIMITS_FAIL_ON_TRUNCATION",
+            "GUARDY_SCANNER_SUBMODULES",
+        ]
+        .map(String::from);
+
+        assert_eq!(unknown(&documented, names), Vec::new());
+    }
+
+    #[test]
+    fn test_documented_covers_embedded_defaults() {
+        let vars = documented().unwrap();
+
+        let mode = vars
+            .iter()
+            .find(|v| v.name == "GUARDY_SCANNER_MODE")
+            .unwrap();
+        assert_eq!(mode.key, "scanner.mode");
+        assert_eq!(mode.kind, "string");
+        assert!(vars.iter().all(|v| !v.name.contains('-')));
+
+        let max_duration = vars
+            .iter()
+            .find(|v| v.name == "GUARDY_SCANNER_LIMITS_MAX_DURATION")
+            .unwrap();
+        assert_eq!(max_duration.key, "scanner.limits.max_duration");
+        assert_eq!(
+            max_duration.to_string(),
+            "GUARDY_SCANNER_LIMITS_MAX_DURATION (string, unset by default)"
+        );
+    }
+
+    #[test]
+    fn test_overrides_nest_under_the_documented_keys() {
+        let documented = documented().unwrap();
+        let env = [
+            ("GUARDY_BRANCH_NAMING_EXEMPT", r#"["main", "release/*"]"#),
+            ("GUARDY_SCANNER_LIMITS_MAX_MEMORY_MB", "512"),
+            ("GUARDY_SCANNER_LIMITS_FAIL_ON_TRUNCATION", "yes"),
+            ("GUARDY_SCANNER_MODE", ""),
+            ("GUARDY_SKIP", "fmt"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        assert_eq!(
+            overrides(&documented, env),
+            json!({
+                "branch_naming": { "exempt": ["main", "release/*"] },
+                "scanner": { "limits": { "max_memory_mb": 512, "fail_on_truncation": true } },
+            })
+        );
+    }
+}
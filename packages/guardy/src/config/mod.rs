@@ -1,4 +1,6 @@
 pub mod core;
+pub mod deprecations;
+pub mod env_vars;
 pub mod formats;
 pub mod languages;
 
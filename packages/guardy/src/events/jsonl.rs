@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::{Envelope, Sink};
+
+/// Writes one JSON object per line, flushed as it goes so readers can tail it
+pub struct JsonlSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonlSink {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Append to the file at `path`, or write to stderr for `-`
+    ///
+    /// Appending lets every guardy process in a session - e.g. each hook git
+    /// runs - share one stream.
+    pub fn open(path: &Path) -> Result<Self> {
+        if path == Path::new("-") {
+            return Ok(Self::new(std::io::stderr()));
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open event stream {}", path.display()))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl Sink for JsonlSink {
+    fn emit(&self, envelope: &Envelope) -> Result<()> {
+        let mut line = serde_json::to_vec(envelope)?;
+        line.push(b'\n');
+        let mut writer = self.writer.lock().unwrap();
+        writer.write_all(&line)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
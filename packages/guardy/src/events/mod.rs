@@ -0,0 +1,217 @@
+//! Machine-readable events shared by every subsystem
+//!
+//! Scans, hook commands and sync report what they do as [`Event`]s published on
+//! one process-wide bus. Sinks subscribe to the bus - `--events <PATH>` adds a
+//! JSON Lines sink and `-vv` logs events through tracing - so every frontend
+//! sees the same stream. Other integrations implement [`Sink`].
+//!
+//! Each line is an [`Envelope`]: the event fields plus `version`, bumped on any
+//! incompatible change to them, and a `timestamp_ms`.
+
+mod jsonl;
+
+pub use jsonl::JsonlSink;
+
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scanner::types::ScanResult;
+use crate::shared::paths;
+
+/// Version of the event schema, part of every [`Envelope`]
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    ScanStarted {
+        target: String,
+    },
+    /// Never carries the matched text, so the stream is safe to store
+    ScanFinding {
+        file: String,
+        line: usize,
+        rule: String,
+        confidence: f64,
+    },
+    ScanCompleted {
+        target: String,
+        files_scanned: usize,
+        files_skipped: usize,
+        findings: usize,
+        duration_ms: u64,
+        /// A resource limit stopped the scan early
+        truncated: bool,
+    },
+    HookCommandStarted {
+        hook: String,
+        /// Command name, with the package for per-package runs
+        command: String,
+        /// The shell command line after placeholder substitution
+        run: String,
+    },
+    HookCommandFinished {
+        hook: String,
+        command: String,
+        success: bool,
+        exit_code: Option<i32>,
+        attempts: u32,
+        duration_ms: u64,
+    },
+    SyncApplied {
+        repo: String,
+        updated: Vec<String>,
+        skipped: Vec<String>,
+        pruned: Vec<String>,
+    },
+}
+
+impl Event {
+    /// The finding events of `result` followed by its completion
+    pub fn scan_results(target: &str, result: &ScanResult) -> Vec<Self> {
+        let findings = result.matches.iter().map(|found| Self::ScanFinding {
+            file: found.file_path.clone(),
+            line: found.line_number,
+            rule: found.secret_type.clone(),
+            confidence: found.confidence,
+        });
+        let completed = Self::ScanCompleted {
+            target: target.to_string(),
+            files_scanned: result.stats.files_scanned,
+            files_skipped: result.stats.files_skipped,
+            findings: result.matches.len(),
+            duration_ms: result.stats.scan_duration_ms,
+            truncated: result.stats.is_truncated(),
+        };
+        findings.chain([completed]).collect()
+    }
+
+    pub fn sync_applied(
+        repo: &str,
+        updated: &[PathBuf],
+        skipped: &[PathBuf],
+        pruned: &[PathBuf],
+    ) -> Self {
+        let display = |files: &[PathBuf]| files.iter().map(|file| paths::display(file)).collect();
+        Self::SyncApplied {
+            repo: repo.to_string(),
+            updated: display(updated),
+            skipped: display(skipped),
+            pruned: display(pruned),
+        }
+    }
+}
+
+/// An event as sinks receive it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Envelope {
+    pub version: u32,
+    pub timestamp_ms: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// A destination for events
+pub trait Sink: Send + Sync {
+    fn emit(&self, envelope: &Envelope) -> Result<()>;
+}
+
+/// Fans events out to the subscribed sinks
+#[derive(Default)]
+pub struct EventBus {
+    sinks: RwLock<Vec<Box<dyn Sink>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self, sink: impl Sink + 'static) {
+        self.sinks.write().unwrap().push(Box::new(sink));
+    }
+
+    /// Deliver `event` to every sink; a failing sink is logged and never fails
+    /// the operation that emitted the event
+    pub fn publish(&self, event: Event) {
+        let sinks = self.sinks.read().unwrap();
+        if sinks.is_empty() {
+            return;
+        }
+        let envelope = Envelope {
+            version: SCHEMA_VERSION,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_millis() as u64),
+            event,
+        };
+        for sink in sinks.iter() {
+            if let Err(e) = sink.emit(&envelope) {
+                tracing::warn!("Failed to emit event: {e}");
+            }
+        }
+    }
+}
+
+static BUS: LazyLock<EventBus> = LazyLock::new(EventBus::default);
+
+/// Add a sink to the process-wide bus
+pub fn subscribe(sink: impl Sink + 'static) {
+    BUS.subscribe(sink);
+}
+
+/// Publish `event` on the process-wide bus
+pub fn emit(event: Event) {
+    BUS.publish(event);
+}
+
+/// Logs events at debug level, so `-vv` shows them alongside other diagnostics
+pub struct TracingSink;
+
+impl Sink for TracingSink {
+    fn emit(&self, envelope: &Envelope) -> Result<()> {
+        tracing::debug!("event: {}", serde_json::to_string(envelope)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct Recorder(Arc<Mutex<Vec<serde_json::Value>>>);
+
+    impl Sink for Recorder {
+        fn emit(&self, envelope: &Envelope) -> Result<()> {
+            self.0.lock().unwrap().push(serde_json::to_value(envelope)?);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_bus_delivers_versioned_flat_envelopes() {
+        let bus = EventBus::default();
+        // Nothing subscribed yet - dropped without error
+        bus.publish(Event::ScanStarted {
+            target: "src".into(),
+        });
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        bus.subscribe(Recorder(received.clone()));
+        bus.publish(Event::sync_applied(
+            "shared",
+            &[PathBuf::from(".github/ci.yml")],
+            &[],
+            &[],
+        ));
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        let event = &received[0];
+        assert_eq!(event["version"], SCHEMA_VERSION);
+        assert_eq!(event["type"], "sync_applied");
+        assert_eq!(event["repo"], "shared");
+        assert_eq!(event["updated"][0], ".github/ci.yml");
+        assert!(event["timestamp_ms"].as_u64().unwrap() > 0);
+    }
+}
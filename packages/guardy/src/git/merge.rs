@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use git2::{IndexEntry, IndexTime, MergeFileOptions, Odb, Oid, Repository};
+use std::path::Path;
+
+/// Outcome of merging two edited versions of a file over their common base
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMerge {
+    pub content: Vec<u8>,
+    /// Whether `content` holds conflict markers
+    pub conflicted: bool,
+}
+
+/// Three-way merge of file contents like `git merge-file -p`, done in memory with
+/// no repository; `labels` name ours, the base and theirs in conflict markers
+pub fn merge_file(ours: &[u8], base: &[u8], theirs: &[u8], labels: [&str; 3]) -> Result<FileMerge> {
+    let odb = Odb::new()?;
+    odb.add_new_mempack_backend(1)?;
+    let repo = Repository::from_odb(odb).context("Failed to set up an in-memory merge")?;
+
+    let entry = |content: &[u8]| -> Result<IndexEntry> {
+        Ok(index_entry(
+            Path::new("file"),
+            repo.blob(content)?,
+            0o100644,
+        ))
+    };
+    let mut options = MergeFileOptions::new();
+    options
+        .our_label(labels[0])
+        .ancestor_label(labels[1])
+        .their_label(labels[2]);
+    let merge = repo.merge_file_from_index(
+        &entry(base)?,
+        &entry(ours)?,
+        &entry(theirs)?,
+        Some(&mut options),
+    )?;
+    Ok(FileMerge {
+        content: merge.content().to_vec(),
+        conflicted: !merge.is_automergeable(),
+    })
+}
+
+/// Index entry of blob `id` at `path`, as the merge functions take them
+pub(super) fn index_entry(path: &Path, id: Oid, mode: u32) -> IndexEntry {
+    IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode,
+        uid: 0,
+        gid: 0,
+        file_size: 0,
+        id,
+        flags: 0,
+        flags_extended: 0,
+        path: path.to_string_lossy().into_owned().into_bytes(),
+    }
+}
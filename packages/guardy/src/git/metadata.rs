@@ -0,0 +1,81 @@
+use anyhow::{Context, Result};
+use git2::Sort;
+
+use super::GitRepo;
+
+/// Free text git keeps besides the code - commit messages, tag annotations and
+/// notes - where pasted tokens end up just as easily
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitText {
+    /// What the text belongs to, e.g. `commit 1a2b3c4d message` or `tag v1.2.0 annotation`
+    pub source: String,
+    pub text: String,
+}
+
+impl GitRepo {
+    /// Messages and notes of the commits reachable from HEAD, branches, tags and
+    /// remote-tracking branches
+    pub fn commit_texts(&self) -> Result<Vec<GitText>> {
+        if self.head_commit().is_err() {
+            return Ok(Vec::new()); // No commits yet
+        }
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TIME)?;
+        walk.push_head()?;
+        // Tags of trees and blobs are skipped, only commits are walked
+        for glob in ["refs/heads", "refs/tags", "refs/remotes"] {
+            walk.push_glob(glob)?;
+        }
+
+        let mut texts = Vec::new();
+        for commit in walk {
+            let commit = self.repo.find_commit(commit?)?;
+            let short = &commit.id().to_string()[..8];
+            let message = String::from_utf8_lossy(commit.message_bytes());
+            if !message.trim().is_empty() {
+                texts.push(GitText {
+                    source: format!("commit {short} message"),
+                    text: message.into_owned(),
+                });
+            }
+            // Notes of the default notes ref, `refs/notes/commits` unless configured
+            let notes = self.repo.find_note(None, commit.id()).ok();
+            if let Some(notes) = notes.as_ref().and_then(|note| note.message())
+                && !notes.trim().is_empty()
+            {
+                texts.push(GitText {
+                    source: format!("commit {short} note"),
+                    text: notes.to_string(),
+                });
+            }
+        }
+        Ok(texts)
+    }
+
+    /// Messages of annotated tags (lightweight tags carry no text of their own)
+    pub fn tag_annotations(&self) -> Result<Vec<GitText>> {
+        let mut texts = Vec::new();
+        for reference in self
+            .repo
+            .references_glob("refs/tags/*")
+            .context("Failed to list tags")?
+        {
+            let reference = reference?;
+            let tag = reference
+                .target()
+                .and_then(|target| self.repo.find_tag(target).ok());
+            let (Some(name), Some(tag)) = (reference.shorthand(), tag) else {
+                continue;
+            };
+            let message = String::from_utf8_lossy(tag.message_bytes().unwrap_or_default());
+            if !message.trim().is_empty() {
+                texts.push(GitText {
+                    source: format!("tag {name} annotation"),
+                    text: message.into_owned(),
+                });
+            }
+        }
+        texts.sort_by(|a, b| a.source.cmp(&b.source));
+        Ok(texts)
+    }
+}
@@ -1,50 +1,155 @@
+pub mod merge;
+pub mod metadata;
 pub mod operations;
+pub mod permalink;
 pub mod remote;
+pub mod signatures;
+pub mod snapshot;
+pub mod staged;
+pub mod submodules;
+pub mod tracking;
+pub mod unstaged;
 // TODO: Add hooks module for hook installation/management
 // TODO: Add commit module for commit operations
 
-use anyhow::{Context, Result};
-use std::path::PathBuf;
-use std::process::Command;
+use anyhow::{Result, anyhow};
+use git2::{Repository, RepositoryOpenFlags};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
+/// A repository opened with libgit2, so reading it needs no `git` binary
+///
+/// Opening honours git's environment (`GIT_INDEX_FILE`, `GIT_OBJECT_DIRECTORY`, ...)
+/// the way git sets it for hooks, e.g. the temporary index of `git commit -a`.
+/// Linked worktrees (`git worktree add`) and bare repositories open too.
 pub struct GitRepo {
+    /// Root of the working tree, or the git directory of a bare repository
     pub path: PathBuf,
+    repo: Repository,
 }
 
 impl GitRepo {
     pub fn discover() -> Result<Self> {
-        let output = Command::new("git")
-            .args(["rev-parse", "--show-toplevel"])
-            .output()
-            .context("Failed to execute git rev-parse --show-toplevel")?;
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!("Not in a git repository"));
-        }
+        Self::discover_at(Path::new("."))
+    }
 
-        let stdout = String::from_utf8(output.stdout).context("Git output is not valid UTF-8")?;
+    /// The repository containing `path`, a file or directory
+    pub fn discover_at(path: &Path) -> Result<Self> {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."))
+        };
+        let repo = Repository::open_ext(
+            dir,
+            RepositoryOpenFlags::FROM_ENV,
+            std::iter::empty::<&OsStr>(),
+        )
+        .map_err(|_| anyhow!("Not in a git repository"))?;
 
-        let path = PathBuf::from(stdout.trim());
-        Ok(GitRepo { path })
+        let path = without_trailing_separator(repo.workdir().unwrap_or(repo.path()));
+        Ok(GitRepo { path, repo })
     }
 
+    /// Name of the branch checked out, empty when HEAD is detached
     pub fn current_branch(&self) -> Result<String> {
-        let output = Command::new("git")
-            .args(["branch", "--show-current"])
-            .current_dir(&self.path)
-            .output()
-            .context("Failed to execute git branch --show-current")?;
-
-        if !output.status.success() {
+        // HEAD itself rather than what it resolves to: a new repository's branch has
+        // no commit yet
+        let Ok(head) = self.repo.find_reference("HEAD") else {
             return Ok("HEAD".to_string());
-        }
+        };
+        Ok(head
+            .symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/heads/"))
+            .unwrap_or_default()
+            .to_string())
+    }
 
-        let stdout = String::from_utf8(output.stdout).context("Git output is not valid UTF-8")?;
+    /// Full SHA of the commit checked out
+    pub fn head_commit(&self) -> Result<String> {
+        let commit = self
+            .repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|_| anyhow!("No commit checked out"))?;
+        Ok(commit.id().to_string())
+    }
 
-        Ok(stdout.trim().to_string())
+    /// URL of a remote, e.g. `origin`
+    pub fn remote_url(&self, remote: &str) -> Result<String> {
+        let remote = self
+            .repo
+            .find_remote(remote)
+            .map_err(|_| anyhow!("No '{remote}' remote"))?;
+        remote
+            .url()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("The remote's URL is not valid UTF-8"))
     }
 
+    /// This checkout's git directory: `.git`, `.git/worktrees/<name>` of a linked
+    /// worktree, or the repository itself when bare
     pub fn git_dir(&self) -> PathBuf {
-        self.path.join(".git")
+        without_trailing_separator(self.repo.path())
+    }
+
+    /// Git directory shared by all worktrees, holding the refs, objects and hooks
+    pub fn common_dir(&self) -> PathBuf {
+        without_trailing_separator(self.repo.commondir())
+    }
+
+    /// Where git runs hooks from: the common directory's, whichever worktree commits
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.common_dir().join("hooks")
+    }
+
+    pub fn is_bare(&self) -> bool {
+        self.repo.is_bare()
+    }
+
+    /// Whether this is a linked worktree rather than the main checkout
+    pub fn is_worktree(&self) -> bool {
+        self.repo.is_worktree()
+    }
+}
+
+/// libgit2 reports directories with a trailing separator
+fn without_trailing_separator(path: &Path) -> PathBuf {
+    path.components().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_worktrees_and_bare_repositories_share_the_common_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let main = Repository::init(root.join("main")).unwrap();
+        let signature = git2::Signature::now("Guardy Test", "test@guardy.dev").unwrap();
+        let tree = main
+            .find_tree(main.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        main.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+            .unwrap();
+        main.worktree("feature", &root.join("feature"), None)
+            .unwrap();
+
+        let worktree = GitRepo::discover_at(&root.join("feature")).unwrap();
+        assert_eq!(worktree.path, root.join("feature"));
+        assert!(worktree.is_worktree() && !worktree.is_bare());
+        assert_eq!(worktree.git_dir(), root.join("main/.git/worktrees/feature"));
+        assert_eq!(worktree.common_dir(), root.join("main/.git"));
+        assert_eq!(worktree.hooks_dir(), root.join("main/.git/hooks"));
+        assert_eq!(worktree.current_branch().unwrap(), "feature");
+
+        Repository::init_bare(root.join("bare.git")).unwrap();
+        let bare = GitRepo::discover_at(&root.join("bare.git")).unwrap();
+        assert!(bare.is_bare() && !bare.is_worktree());
+        assert_eq!(bare.path, root.join("bare.git"));
+        assert_eq!(bare.hooks_dir(), root.join("bare.git/hooks"));
     }
 }
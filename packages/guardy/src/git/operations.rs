@@ -1,32 +1,104 @@
 use super::GitRepo;
 use anyhow::{Context, Result};
-use std::path::PathBuf;
-use std::process::Command;
+use git2::{Diff, Tree};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
 impl GitRepo {
     /// Get list of files that are staged for commit (primary use case for pre-commit hooks)
     pub fn get_staged_files(&self) -> Result<Vec<PathBuf>> {
-        let output = Command::new("git")
-            .args(["diff", "--cached", "--name-only"])
-            .current_dir(&self.path)
-            .output()
-            .context("Failed to execute git diff --cached --name-only")?;
-
-        if !output.status.success() {
-            return Err(anyhow::anyhow!(
-                "Git command failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
-        }
+        let head = self.head_tree()?;
+        let mut diff = self
+            .repo
+            .diff_tree_to_index(head.as_ref(), None, None)
+            .context("Failed to diff the index against HEAD")?;
+        Ok(self.changed_paths(&mut diff)?)
+    }
 
-        let stdout = String::from_utf8(output.stdout).context("Git output is not valid UTF-8")?;
+    /// Get list of files tracked by git (`{all_files}` in custom commands)
+    pub fn get_tracked_files(&self) -> Result<Vec<PathBuf>> {
+        let index = self.repo.index().context("Failed to read the index")?;
 
-        let files = stdout
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| self.path.join(line.trim()))
+        // Conflicted files have an entry per stage
+        let files: BTreeSet<PathBuf> = index
+            .iter()
+            .map(|entry| {
+                self.path
+                    .join(String::from_utf8_lossy(&entry.path).as_ref())
+            })
             .collect();
+        Ok(files.into_iter().collect())
+    }
+
+    /// Whether `path` (relative to the repository root) is excluded by the ignore rules
+    pub fn is_ignored(&self, path: &Path) -> Result<bool> {
+        // Like `git check-ignore`: tracked files are never ignored
+        let index = self.repo.index().context("Failed to read the index")?;
+        if index.get_path(path, 0).is_some() {
+            return Ok(false);
+        }
+        self.repo
+            .is_path_ignored(path)
+            .with_context(|| format!("Failed to check whether {} is ignored", path.display()))
+    }
+
+    /// Whether `ancestor` is reachable from `descendant`; false when either commit
+    /// isn't available locally
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool> {
+        let (Some(ancestor), Some(descendant)) =
+            (self.resolve_ref(ancestor)?, self.resolve_ref(descendant)?)
+        else {
+            return Ok(false);
+        };
+        let (ancestor, descendant) = (ancestor.parse()?, descendant.parse()?);
+        Ok(ancestor == descendant
+            || self
+                .repo
+                .graph_descendant_of(descendant, ancestor)
+                .unwrap_or(false))
+    }
+
+    /// Commit a ref such as `refs/remotes/origin/main` points at, if it exists
+    pub fn resolve_ref(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .repo
+            .revparse_single(name)
+            .and_then(|object| object.peel_to_commit())
+            .ok()
+            .map(|commit| commit.id().to_string()))
+    }
+
+    /// Get list of files that changed between two revisions (post-checkout/post-merge use case)
+    pub fn get_changed_files_between(&self, from: &str, to: &str) -> Result<Vec<PathBuf>> {
+        let tree = |revision: &str| {
+            self.repo
+                .revparse_single(revision)
+                .and_then(|object| object.peel_to_tree())
+                .with_context(|| format!("Unknown revision '{revision}'"))
+        };
+        let mut diff = self
+            .repo
+            .diff_tree_to_tree(Some(&tree(from)?), Some(&tree(to)?), None)
+            .with_context(|| format!("Failed to diff {from} and {to}"))?;
+        Ok(self.changed_paths(&mut diff)?)
+    }
+
+    /// Tree of the commit checked out, `None` before the first commit
+    pub(super) fn head_tree(&self) -> Result<Option<Tree<'_>>> {
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_tree()?)),
+            Err(error) if error.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+            Err(error) => Err(error).context("Failed to read HEAD"),
+        }
+    }
 
-        Ok(files)
+    /// Paths `diff` touches, renames reported by their new name as `git diff` does
+    fn changed_paths(&self, diff: &mut Diff) -> Result<Vec<PathBuf>, git2::Error> {
+        diff.find_similar(None)?;
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+            .map(|path| self.path.join(path))
+            .collect())
     }
 }
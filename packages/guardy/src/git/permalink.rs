@@ -0,0 +1,196 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::GitRepo;
+
+const GITHUB: &str = "https://{host}/{repo}/blob/{commit}/{path}#L{line}";
+const GITLAB: &str = "https://{host}/{repo}/-/blob/{commit}/{path}#L{line}";
+const BITBUCKET: &str = "https://{host}/{repo}/src/{commit}/{path}#lines-{line}";
+
+/// `scanner.permalinks`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PermalinkConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// URL templates by remote host, for self-hosted code hosts; `{host}`,
+    /// `{repo}`, `{commit}`, `{path}` and `{line}` are filled in
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+}
+
+impl Default for PermalinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            templates: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Links to a line of a file on the code host, at the commit that was scanned
+#[derive(Debug)]
+pub struct Permalinks {
+    root: PathBuf,
+    template: String,
+    host: String,
+    repo: String,
+    commit: String,
+    /// Files committed at `commit`, relative to `root` - other files have no page to link to
+    tracked: HashSet<PathBuf>,
+}
+
+impl Permalinks {
+    /// Permalinks for the repository containing `path`, when its `origin` remote
+    /// is on GitHub, GitLab, Bitbucket or a host with a configured template
+    pub fn discover(path: &Path, config: &PermalinkConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let repo = GitRepo::discover_at(path).ok()?;
+        let (host, name) = parse_remote(&repo.remote_url("origin").ok()?)?;
+        let template = config
+            .templates
+            .get(&host)
+            .cloned()
+            .or_else(|| known_template(&host).map(str::to_string))?;
+        let commit = repo.head_commit().ok()?;
+        let tracked = repo
+            .get_tracked_files()
+            .ok()?
+            .into_iter()
+            .filter_map(|file| file.strip_prefix(&repo.path).ok().map(Path::to_path_buf))
+            .collect();
+
+        Some(Self {
+            root: repo.path,
+            template,
+            host,
+            repo: name,
+            commit,
+            tracked,
+        })
+    }
+
+    /// Link to `line` of `file` (a path as scanned), if the file is committed
+    pub fn link(&self, file: &Path, line: usize) -> Option<String> {
+        let file = std::fs::canonicalize(file).ok()?;
+        let relative = file.strip_prefix(&self.root).ok()?;
+        if !self.tracked.contains(relative) {
+            return None;
+        }
+
+        let path = relative
+            .components()
+            .map(|part| encode(&part.as_os_str().to_string_lossy()))
+            .collect::<Vec<_>>()
+            .join("/");
+        Some(
+            self.template
+                .replace("{host}", &self.host)
+                .replace("{repo}", &self.repo)
+                .replace("{commit}", &self.commit)
+                .replace("{path}", &path)
+                .replace("{line}", &line.to_string()),
+        )
+    }
+}
+
+/// Host and `owner/repo` path of a remote URL (https, ssh or scp-like `git@host:owner/repo`)
+fn parse_remote(url: &str) -> Option<(String, String)> {
+    let url = url.trim();
+    let (authority, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => url.split_once(':')?,
+    };
+    let host = authority.rsplit('@').next()?.split(':').next()?;
+    let path = path.trim_matches('/').trim_end_matches(".git");
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some((host.to_lowercase(), path.to_string()))
+}
+
+/// URL format of well-known hosts; GitHub Enterprise and self-hosted GitLab are
+/// recognised by name
+fn known_template(host: &str) -> Option<&'static str> {
+    if host == "bitbucket.org" {
+        Some(BITBUCKET)
+    } else if host.contains("gitlab") {
+        Some(GITLAB)
+    } else if host.contains("github") {
+        Some(GITHUB)
+    } else {
+        None
+    }
+}
+
+/// Percent-encode the characters that would end or corrupt a URL path segment
+fn encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        match c {
+            '%' => encoded.push_str("%25"),
+            ' ' => encoded.push_str("%20"),
+            '#' => encoded.push_str("%23"),
+            '?' => encoded.push_str("%3F"),
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_urls() {
+        for (url, host, repo) in [
+            ("git@github.com:acme/api.git", "github.com", "acme/api"),
+            ("https://github.com/acme/api", "github.com", "acme/api"),
+            (
+                "https://ci-token@gitlab.example.com/group/sub/app.git",
+                "gitlab.example.com",
+                "group/sub/app",
+            ),
+            (
+                "ssh://git@bitbucket.org:22/team/site.git",
+                "bitbucket.org",
+                "team/site",
+            ),
+        ] {
+            assert_eq!(
+                parse_remote(url),
+                Some((host.to_string(), repo.to_string())),
+                "{url}"
+            );
+        }
+        assert_eq!(parse_remote("/srv/git/api.git"), None);
+    }
+
+    #[test]
+    fn test_link_uses_host_template() {
+        let root = std::fs::canonicalize(".").unwrap();
+        let permalinks = Permalinks {
+            root: root.clone(),
+            template: known_template("gitlab.example.com").unwrap().to_string(),
+            host: "gitlab.example.com".to_string(),
+            repo: "group/app".to_string(),
+            commit: "0123abc".to_string(),
+            tracked: HashSet::from([PathBuf::from("src/lib.rs")]),
+        };
+
+        assert_eq!(
+            permalinks.link(Path::new("src/lib.rs"), 42).as_deref(),
+            Some("https://gitlab.example.com/group/app/-/blob/0123abc/src/lib.rs#L42")
+        );
+        assert_eq!(permalinks.link(Path::new("Cargo.toml"), 1), None);
+        assert_eq!(encode("my file#1.txt"), "my%20file%231.txt");
+        assert_eq!(known_template("git.example.com"), None);
+    }
+}
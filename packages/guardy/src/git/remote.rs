@@ -1,22 +1,59 @@
+//! Cached clones of sync repositories
+//!
+//! Clones are inspected with libgit2, but cloning, fetching, checking out and
+//! pushing still run the `git` binary: libgit2 has neither partial clones nor
+//! sparse checkouts, and credentials reach git through its environment
+//! (`GIT_SSH_COMMAND`, credential helpers) - see `sync::auth`.
+
 use anyhow::{Result, anyhow};
+use git2::Repository;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::shared::atomic_io::{self, TempDirGuard};
+
 pub struct RemoteOperations {
     cache_dir: PathBuf,
+    /// Extra environment for every git command, e.g. credentials
+    env: Vec<(String, String)>,
 }
 
 impl RemoteOperations {
     pub fn new(cache_dir: PathBuf) -> Self {
-        Self { cache_dir }
+        Self {
+            cache_dir,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = env;
+        self
+    }
+
+    fn git(&self) -> Command {
+        let mut command = Command::new("git");
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
     }
 
     /// Clone repository using system git command
-    fn clone_with_system_git(&self, repo_url: &str, repo_path: &Path, version: &str) -> Result<()> {
-        // Create parent directory if it doesn't exist
-        if let Some(parent) = repo_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
+    ///
+    /// The clone is built in a temporary directory next to `repo_path` and only moved
+    /// into place once checkout succeeds, so an interrupted clone never leaves a broken
+    /// cache entry that later runs would try to fetch into.
+    ///
+    /// With `sparse` paths only their trees are checked out, and a partial clone
+    /// fetches just the blobs they need.
+    fn clone_with_system_git(
+        &self,
+        repo_url: &str,
+        repo_path: &Path,
+        version: &str,
+        sparse: &[String],
+    ) -> Result<()> {
+        let parent = repo_path.parent().unwrap_or(Path::new("."));
+        let staging = TempDirGuard::new_in(parent, "clone")?;
 
         // Clone with system git - shallow clone for speed
         // For tags, we need to clone with tags to ensure they're available
@@ -26,12 +63,13 @@ impl RemoteOperations {
         {
             clone_args.push("--tags");
         }
+        if !sparse.is_empty() {
+            // Nothing is checked out until the sparse paths are set
+            clone_args.extend(["--filter=blob:none", "--no-checkout"]);
+        }
         clone_args.push(repo_url);
 
-        let output = Command::new("git")
-            .args(&clone_args)
-            .arg(repo_path)
-            .output()?;
+        let output = self.git().args(&clone_args).arg(staging.path()).output()?;
 
         if !output.status.success() {
             let error_msg = String::from_utf8_lossy(&output.stderr);
@@ -42,23 +80,27 @@ impl RemoteOperations {
             ));
         }
 
+        self.sparse_checkout_in(staging.path(), sparse)?;
+
         // Checkout the specified version
-        self.checkout_version_system_git(repo_path, version)?;
+        self.checkout_version_system_git(staging.path(), version)?;
 
-        Ok(())
+        staging.persist(repo_path)
     }
 
     /// Checkout a specific version using system git
     fn checkout_version_system_git(&self, repo_path: &Path, version: &str) -> Result<()> {
         // Try to checkout the version - git will try branches, tags, and commits
-        let output = Command::new("git")
+        let output = self
+            .git()
             .args(["checkout", "--quiet", version])
             .current_dir(repo_path)
             .output()?;
 
         if !output.status.success() {
             // Try fetching the specific branch/tag if it's not local
-            let fetch_output = Command::new("git")
+            let fetch_output = self
+                .git()
                 .args([
                     "fetch",
                     "--quiet",
@@ -70,7 +112,8 @@ impl RemoteOperations {
 
             if fetch_output.status.success() {
                 // Try checkout again
-                let output = Command::new("git")
+                let output = self
+                    .git()
                     .args(["checkout", "--quiet", version])
                     .current_dir(repo_path)
                     .output()?;
@@ -97,15 +140,59 @@ impl RemoteOperations {
         Ok(())
     }
 
-    /// Clone repository (called when it doesn't exist in cache)
-    pub fn clone_repository(&self, repo_url: &str, repo_name: &str, version: &str) -> Result<()> {
+    /// Clone repository (called when it doesn't exist in cache), checking out only the
+    /// `sparse` directories when there are any
+    pub fn clone_repository(
+        &self,
+        repo_url: &str,
+        repo_name: &str,
+        version: &str,
+        sparse: &[String],
+    ) -> Result<()> {
         let repo_path = self.cache_dir.join(repo_name);
-        self.clone_with_system_git(repo_url, &repo_path, version)?;
+        self.clone_with_system_git(repo_url, &repo_path, version, sparse)?;
+        Ok(())
+    }
+
+    /// Limit an existing clone's working tree to the `sparse` directories, or restore
+    /// the full tree when there are none
+    pub fn sparse_checkout(&self, repo_name: &str, sparse: &[String]) -> Result<()> {
+        self.sparse_checkout_in(&self.cache_dir.join(repo_name), sparse)
+    }
+
+    fn sparse_checkout_in(&self, repo_path: &Path, sparse: &[String]) -> Result<()> {
+        let output = if sparse.is_empty() {
+            let enabled = Repository::open(repo_path)?
+                .config()?
+                .get_bool("core.sparseCheckout")
+                .unwrap_or(false);
+            if !enabled {
+                return Ok(());
+            }
+            self.git()
+                .args(["sparse-checkout", "disable"])
+                .current_dir(repo_path)
+                .output()?
+        } else {
+            self.git()
+                .args(["sparse-checkout", "set", "--cone"])
+                .args(sparse)
+                .current_dir(repo_path)
+                .output()?
+        };
+
+        // Git before 2.25 has no sparse-checkout: the full tree works, just slower
+        if !output.status.success() {
+            tracing::warn!(
+                "sparse-checkout failed, using the full tree: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
         Ok(())
     }
 
     /// Check if version is immutable (tag or commit SHA)
-    fn is_immutable_version(&self, version: &str) -> bool {
+    pub fn is_immutable_version(&self, version: &str) -> bool {
         // Tag pattern: v1.0.0, v2.1.3-beta, 1.0.0, etc.
         if version.starts_with('v')
             && version.len() > 1
@@ -131,6 +218,97 @@ impl RemoteOperations {
         false // Assume it's a mutable branch
     }
 
+    /// Commit `files` (relative to the clone) on top of the checked-out version and
+    /// push the commit to `branch`, leaving the clone as it was
+    pub fn push_commit(
+        &self,
+        repo_name: &str,
+        files: &[PathBuf],
+        branch: &str,
+        message: &str,
+    ) -> Result<()> {
+        let repo_path = self.cache_dir.join(repo_name);
+        let head = commit_of(&Repository::open(&repo_path)?, "HEAD")
+            .ok_or_else(|| anyhow!("No commit checked out in {}", repo_path.display()))?;
+
+        let result = (|| {
+            let mut add = vec!["add", "--"];
+            add.extend(files.iter().filter_map(|file| file.to_str()));
+            self.run_git(&repo_path, &add)?;
+            self.run_git(
+                &repo_path,
+                &["commit", "--quiet", "--no-verify", "-m", message],
+            )?;
+            // The branch belongs to guardy: pushing again replaces it
+            let refspec = format!("HEAD:refs/heads/{branch}");
+            self.run_git(
+                &repo_path,
+                &["push", "--quiet", "--force", "origin", &refspec],
+            )
+        })();
+
+        self.run_git(&repo_path, &["reset", "--quiet", "--hard", &head])?;
+        result.map(|_| ())
+    }
+
+    /// Open a pull request from `branch` with the GitHub CLI, returning its URL
+    ///
+    /// `base` defaults to the repository's default branch.
+    pub fn open_pull_request(
+        &self,
+        repo_name: &str,
+        branch: &str,
+        base: Option<&str>,
+        title: &str,
+        body: &str,
+    ) -> Result<String> {
+        let gh = which::which("gh").map_err(|_| {
+            anyhow!(
+                "Opening pull requests needs the GitHub CLI (gh); the branch '{branch}' was pushed"
+            )
+        })?;
+        let mut command = Command::new(gh);
+        command
+            .args([
+                "pr", "create", "--head", branch, "--title", title, "--body", body,
+            ])
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(self.cache_dir.join(repo_name));
+        if let Some(base) = base {
+            command.args(["--base", base]);
+        }
+
+        let output = command.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to open a pull request for '{}': {}",
+                branch,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// SHA and commit time (seconds since the epoch) of the clone's checked-out commit
+    pub fn head_commit(&self, repo_name: &str) -> Result<(String, u64)> {
+        let repo = Repository::open(self.cache_dir.join(repo_name))?;
+        let head = repo.head()?.peel_to_commit()?;
+        Ok((head.id().to_string(), head.time().seconds().try_into()?))
+    }
+
+    /// Stdout of a git command that must succeed
+    fn run_git(&self, repo_path: &Path, args: &[&str]) -> Result<String> {
+        let output = self.git().args(args).current_dir(repo_path).output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
     /// Fetch and reset to remote version (ensures cache matches remote exactly)
     pub fn fetch_and_reset(&self, repo_name: &str, version: &str) -> Result<()> {
         let repo_path = self.cache_dir.join(repo_name);
@@ -146,59 +324,36 @@ impl RemoteOperations {
             tracing::debug!("Version '{}' appears to be immutable (tag/commit)", version);
 
             // Check if we have this version locally
-            let has_version_output = Command::new("git")
-                .args(["rev-parse", "--verify", "--quiet", version])
-                .current_dir(&repo_path)
-                .output()?;
-
-            if has_version_output.status.success() {
-                // Get the SHA of this version
-                let version_sha_output = Command::new("git")
-                    .args(["rev-parse", version])
-                    .current_dir(&repo_path)
-                    .output()?;
-
-                // Get current HEAD SHA
-                let head_sha_output = Command::new("git")
-                    .args(["rev-parse", "HEAD"])
+            let repo = Repository::open(&repo_path)?;
+            if let (Some(version_sha), Some(head_sha)) =
+                (commit_of(&repo, version), commit_of(&repo, "HEAD"))
+            {
+                if version_sha == head_sha {
+                    tracing::info!(
+                        "Cache already has immutable version: {} ({})",
+                        version,
+                        &version_sha[..8]
+                    );
+                    return Ok(());
+                }
+                tracing::debug!(
+                    "Cache has version {} but HEAD is different, need to reset",
+                    version
+                );
+                // Reset to the correct version (no fetch needed for immutable versions)
+                let reset_output = self
+                    .git()
+                    .args(["reset", "--hard", version])
                     .current_dir(&repo_path)
                     .output()?;
 
-                if version_sha_output.status.success() && head_sha_output.status.success() {
-                    let version_sha = String::from_utf8_lossy(&version_sha_output.stdout)
-                        .trim()
-                        .to_string();
-                    let head_sha = String::from_utf8_lossy(&head_sha_output.stdout)
-                        .trim()
-                        .to_string();
-
-                    if version_sha == head_sha {
-                        tracing::info!(
-                            "Cache already has immutable version: {} ({})",
-                            version,
-                            &version_sha[..8]
-                        );
-                        return Ok(());
-                    } else {
-                        tracing::debug!(
-                            "Cache has version {} but HEAD is different, need to reset",
-                            version
-                        );
-                        // Reset to the correct version (no fetch needed for immutable versions)
-                        let reset_output = Command::new("git")
-                            .args(["reset", "--hard", version])
-                            .current_dir(&repo_path)
-                            .output()?;
-
-                        if reset_output.status.success() {
-                            tracing::info!(
-                                "Reset cache to immutable version: {} ({})",
-                                version,
-                                &version_sha[..8]
-                            );
-                            return Ok(());
-                        }
-                    }
+                if reset_output.status.success() {
+                    tracing::info!(
+                        "Reset cache to immutable version: {} ({})",
+                        version,
+                        &version_sha[..8]
+                    );
+                    return Ok(());
                 }
             }
 
@@ -213,20 +368,17 @@ impl RemoteOperations {
             );
 
             // Slow path: For mutable versions (branches), compare with remote
-            let local_sha_output = Command::new("git")
-                .args(["rev-parse", "HEAD"])
-                .current_dir(&repo_path)
-                .output()?;
+            let local_sha = commit_of(&Repository::open(&repo_path)?, "HEAD");
 
-            let remote_sha_output = Command::new("git")
+            let remote_sha_output = self
+                .git()
                 .args(["ls-remote", "origin", version])
                 .current_dir(&repo_path)
                 .output()?;
 
-            if local_sha_output.status.success() && remote_sha_output.status.success() {
-                let local_sha = String::from_utf8_lossy(&local_sha_output.stdout)
-                    .trim()
-                    .to_string();
+            if let Some(local_sha) = local_sha
+                && remote_sha_output.status.success()
+            {
                 let remote_output = String::from_utf8_lossy(&remote_sha_output.stdout);
 
                 // Parse remote SHA (format: "commit_sha\trefs/heads/branch_name" or just "commit_sha")
@@ -266,8 +418,11 @@ impl RemoteOperations {
         tracing::debug!("Fetching {} from origin", version);
         let mut fetch_args = vec!["fetch", "--depth", "1"];
 
-        // For immutable versions that look like tags, fetch tags
+        // For immutable versions that look like tags, fetch tags; a full commit SHA
+        // (e.g. from guardy-sync.lock) is fetched directly
+        let full_sha = version.len() == 40 && version.chars().all(|c| c.is_ascii_hexdigit());
         if self.is_immutable_version(version)
+            && !full_sha
             && (version.starts_with('v') || version.chars().next().unwrap_or('a').is_ascii_digit())
         {
             fetch_args.extend_from_slice(&["--tags", "origin"]);
@@ -275,7 +430,8 @@ impl RemoteOperations {
             fetch_args.extend_from_slice(&["origin", version]);
         }
 
-        let output = Command::new("git")
+        let output = self
+            .git()
             .args(&fetch_args)
             .current_dir(&repo_path)
             .output()?;
@@ -288,7 +444,8 @@ impl RemoteOperations {
         tracing::debug!("Fetch completed, resetting to fetched commit");
 
         // Reset to FETCH_HEAD (what we just fetched) - this is guaranteed to work
-        let reset_output = Command::new("git")
+        let reset_output = self
+            .git()
             .args(["reset", "--hard", "FETCH_HEAD"])
             .current_dir(&repo_path)
             .output()?;
@@ -303,28 +460,18 @@ impl RemoteOperations {
         }
 
         // Clean any untracked files
-        Command::new("git")
+        self.git()
             .args(["clean", "-fd"])
             .current_dir(&repo_path)
             .output()?;
 
         // Get and log the current commit SHA
-        let sha_output = Command::new("git")
-            .args(["rev-parse", "HEAD"])
-            .current_dir(&repo_path)
-            .output()?;
-
-        if sha_output.status.success() {
-            let sha = String::from_utf8_lossy(&sha_output.stdout)
-                .trim()
-                .to_string();
+        if let Some(sha) = commit_of(&Repository::open(&repo_path)?, "HEAD") {
             tracing::info!("Reset cache to version: {} ({})", version, &sha[..8]);
 
             // Store the SHA in .guardy directory for later reference
-            let guardy_dir = PathBuf::from(".guardy");
-            std::fs::create_dir_all(&guardy_dir)?;
-            let sha_file = guardy_dir.join(format!("sync_sha_{repo_name}"));
-            std::fs::write(sha_file, format!("{version}\n{sha}"))?;
+            let sha_file = PathBuf::from(".guardy").join(format!("sync_sha_{repo_name}"));
+            atomic_io::write_atomic(&sha_file, format!("{version}\n{sha}"))?;
         } else {
             tracing::info!("Reset cache to version: {}", version);
         }
@@ -332,3 +479,11 @@ impl RemoteOperations {
         Ok(())
     }
 }
+
+/// SHA of the commit `revision` names in `repo`, if it is there
+fn commit_of(repo: &Repository, revision: &str) -> Option<String> {
+    repo.revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .ok()
+        .map(|commit| commit.id().to_string())
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use super::GitRepo;
+
+/// A commit with the result of verifying its GPG or SSH signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSignature {
+    pub commit: String,
+    pub subject: String,
+    /// git's `%G?` verdict: `G` good, `U` good with unknown trust, `B` bad, `X` expired
+    /// signature, `Y` expired key, `R` revoked key, `E` unverifiable, `N` unsigned
+    pub status: char,
+    /// Signer identity - the key's user id for GPG, the allowed signers principal for SSH
+    pub signer: String,
+    pub key: String,
+    pub fingerprint: String,
+}
+
+impl GitRepo {
+    /// Signatures of the commits `git rev-list <revisions>` selects, newest first
+    ///
+    /// SSH signatures can only be verified against an allowed signers file, taken
+    /// from `allowed_signers_file` or git's own `gpg.ssh.allowedSignersFile`.
+    pub fn commit_signatures(
+        &self,
+        revisions: &[String],
+        allowed_signers_file: Option<&Path>,
+    ) -> Result<Vec<CommitSignature>> {
+        let mut command = Command::new("git");
+        if let Some(file) = allowed_signers_file {
+            command.arg("-c").arg(format!(
+                "gpg.ssh.allowedSignersFile={}",
+                self.path.join(file).display()
+            ));
+        }
+        let output = command
+            .args(["log", "--format=%H%x1f%G?%x1f%GS%x1f%GK%x1f%GF%x1f%s"])
+            .args(revisions)
+            .current_dir(&self.path)
+            .output()
+            .context("Failed to execute git log")?;
+
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "Git command failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+
+        let stdout = String::from_utf8(output.stdout).context("Git output is not valid UTF-8")?;
+        Ok(stdout.lines().filter_map(parse_line).collect())
+    }
+}
+
+fn parse_line(line: &str) -> Option<CommitSignature> {
+    let mut fields = line.splitn(6, '\x1f');
+    let mut next = || fields.next().map(str::to_string);
+    Some(CommitSignature {
+        commit: next()?,
+        status: next()?.chars().next().unwrap_or('N'),
+        signer: next()?,
+        key: next()?,
+        fingerprint: next()?,
+        subject: next().unwrap_or_default(),
+    })
+}
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result, anyhow};
+use git2::{BranchType, DiffOptions, ObjectType, Oid, Repository, WorktreePruneOptions};
+use std::path::Path;
+
+use super::GitRepo;
+use super::merge::index_entry;
+
+/// An index entry that differs from HEAD, as `git diff --cached --raw` reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexChange {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Git file mode, e.g. `0o100644`; the old mode of a deleted entry
+    pub mode: u32,
+    /// Blob (or submodule commit) id, `None` when the entry is deleted
+    pub id: Option<String>,
+}
+
+impl GitRepo {
+    /// Index entries that differ from HEAD, or every entry before the first commit,
+    /// without rename detection
+    pub fn index_changes(&self) -> Result<Vec<IndexChange>> {
+        let head = self.head_tree()?;
+        let mut options = DiffOptions::new();
+        options.include_typechange(true);
+        let diff = self
+            .repo
+            .diff_tree_to_index(head.as_ref(), None, Some(&mut options))
+            .context("Failed to diff the index against HEAD")?;
+
+        let mut changes = Vec::new();
+        for delta in diff.deltas() {
+            let deleted = delta.status() == git2::Delta::Deleted;
+            let file = if deleted {
+                delta.old_file()
+            } else {
+                delta.new_file()
+            };
+            let Some(path) = file.path() else {
+                continue;
+            };
+            changes.push(IndexChange {
+                path: path.to_string_lossy().into_owned(),
+                mode: u32::from(file.mode()),
+                id: (!deleted).then(|| file.id().to_string()),
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Whether the object `id` is in the repository and is a `kind`
+    pub fn has_object(&self, id: &str, kind: ObjectType) -> bool {
+        Oid::from_str(id)
+            .and_then(|id| self.repo.find_object(id, Some(kind)))
+            .is_ok()
+    }
+
+    /// Content of the blob `id`
+    pub fn blob_content(&self, id: &str) -> Result<Vec<u8>> {
+        let blob = self
+            .repo
+            .find_blob(Oid::from_str(id)?)
+            .with_context(|| format!("Failed to read blob {id}"))?;
+        Ok(blob.content().to_vec())
+    }
+
+    /// Put `changes` in the index as they are, like `git update-index --cacheinfo`
+    /// and `--force-remove`; the working tree is left alone
+    pub fn apply_index_changes(&self, changes: &[IndexChange]) -> Result<()> {
+        let mut index = self.repo.index().context("Failed to read the index")?;
+        for change in changes {
+            let path = Path::new(&change.path);
+            match &change.id {
+                Some(id) => index.add(&index_entry(path, Oid::from_str(id)?, change.mode)),
+                None => index.remove_path(path),
+            }
+            .with_context(|| format!("Failed to update {} in the index", change.path))?;
+        }
+        index.write().context("Failed to write the index")
+    }
+
+    /// Check out `commit` with a detached HEAD in a new linked worktree at `path`,
+    /// like `git worktree add --detach`; the worktree is named after the directory
+    pub fn add_detached_worktree(&self, path: &Path, commit: &str) -> Result<GitRepo> {
+        let name = worktree_name(path)?;
+        let commit = self
+            .repo
+            .revparse_single(commit)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("Commit {commit} is not in this repository"))?;
+
+        // libgit2 checks worktrees out on a new branch: it is created at `commit`,
+        // then swapped for a detached HEAD and deleted
+        let mut branch = self
+            .repo
+            .branch(name, &commit, false)
+            .with_context(|| format!("Failed to create worktree branch {name}"))?;
+        let mut options = git2::WorktreeAddOptions::new();
+        options.reference(Some(branch.get()));
+        let added = self
+            .repo
+            .worktree(name, path, Some(&options))
+            .and_then(|_| {
+                Repository::open(path)?.set_head_detached(commit.id())?;
+                self.repo.find_branch(name, BranchType::Local)?.delete()
+            });
+        if let Err(e) = added {
+            let _ = branch.delete();
+            return Err(e).with_context(|| format!("Failed to add worktree {}", path.display()));
+        }
+        GitRepo::discover_at(path)
+    }
+
+    /// Unregister the linked worktree at `path` and delete its files, like
+    /// `git worktree remove --force`
+    pub fn remove_worktree(&self, path: &Path) -> Result<()> {
+        let worktree = self.repo.find_worktree(worktree_name(path)?)?;
+        let mut options = WorktreePruneOptions::new();
+        options.valid(true).locked(true).working_tree(true);
+        worktree
+            .prune(Some(&mut options))
+            .with_context(|| format!("Failed to remove worktree {}", path.display()))
+    }
+}
+
+fn worktree_name(path: &Path) -> Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Invalid worktree path {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_changes_replay_in_a_detached_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let main = Repository::init(root.join("main")).unwrap();
+        for (file, content) in [("kept.txt", "kept\n"), ("gone.txt", "gone\n")] {
+            std::fs::write(root.join("main").join(file), content).unwrap();
+        }
+        let mut index = main.index().unwrap();
+        index.add_path(Path::new("kept.txt")).unwrap();
+        index.add_path(Path::new("gone.txt")).unwrap();
+        index.write().unwrap();
+        let signature = git2::Signature::now("Guardy Test", "test@guardy.dev").unwrap();
+        let tree = main.find_tree(index.write_tree().unwrap()).unwrap();
+        main.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+            .unwrap();
+
+        let repo = GitRepo::discover_at(&root.join("main")).unwrap();
+        std::fs::write(root.join("main/kept.txt"), "changed\n").unwrap();
+        index.add_path(Path::new("kept.txt")).unwrap();
+        index.remove_path(Path::new("gone.txt")).unwrap();
+        index.write().unwrap();
+
+        let changes = repo.index_changes().unwrap();
+        let changed = main.blob("changed\n".as_bytes()).unwrap().to_string();
+        assert_eq!(
+            changes,
+            vec![
+                IndexChange {
+                    path: "gone.txt".to_string(),
+                    mode: 0o100644,
+                    id: None,
+                },
+                IndexChange {
+                    path: "kept.txt".to_string(),
+                    mode: 0o100644,
+                    id: Some(changed.clone()),
+                },
+            ]
+        );
+        assert!(repo.has_object(&changed, ObjectType::Blob));
+        assert!(!repo.has_object(&changed, ObjectType::Commit));
+        assert_eq!(repo.blob_content(&changed).unwrap(), b"changed\n");
+
+        let path = root.join("replay");
+        let head = repo.head_commit().unwrap();
+        let worktree = repo.add_detached_worktree(&path, &head).unwrap();
+        assert_eq!(worktree.current_branch().unwrap(), "");
+        assert_eq!(worktree.head_commit().unwrap(), head);
+        assert!(main.find_branch("replay", BranchType::Local).is_err());
+        assert_eq!(
+            std::fs::read_to_string(path.join("kept.txt")).unwrap(),
+            "kept\n"
+        );
+
+        worktree.apply_index_changes(&changes).unwrap();
+        assert_eq!(worktree.index_changes().unwrap(), changes);
+
+        repo.remove_worktree(&path).unwrap();
+        assert!(!path.exists());
+        assert!(main.find_worktree("replay").is_err());
+    }
+}
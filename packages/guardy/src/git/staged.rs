@@ -0,0 +1,236 @@
+use anyhow::{Context, Result, anyhow};
+use git2::{Delta, DiffOptions, FileMode, Patch};
+use std::path::{Path, PathBuf};
+
+use super::GitRepo;
+
+/// A file as it is staged in the index, which is what the commit will contain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedBlob {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    /// Size of the staged content in bytes
+    pub size: u64,
+    /// Whether git treats the content as binary (NUL bytes or a `-diff` attribute)
+    pub binary: bool,
+}
+
+/// How a staged file differs from HEAD
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StagedStatus {
+    Added,
+    Modified,
+    Deleted,
+    /// Moved from `from`, possibly with changes
+    Renamed {
+        from: PathBuf,
+    },
+    /// Switched between a file, a symlink and a submodule
+    TypeChanged,
+}
+
+impl GitRepo {
+    /// Files of the index that differ from HEAD, relative to the repository root,
+    /// with renames detected as `git diff --cached` does
+    pub fn staged_files_with_status(&self) -> Result<Vec<(PathBuf, StagedStatus)>> {
+        let head = self.head_tree()?;
+        let mut diff = self
+            .repo
+            .diff_tree_to_index(head.as_ref(), None, None)
+            .context("Failed to diff the index against HEAD")?;
+        diff.find_similar(None)?;
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let (old, new) = (delta.old_file().path(), delta.new_file().path());
+            let status = match delta.status() {
+                Delta::Deleted => StagedStatus::Deleted,
+                Delta::Modified => StagedStatus::Modified,
+                Delta::Renamed => StagedStatus::Renamed {
+                    from: old.map(Path::to_path_buf).unwrap_or_default(),
+                },
+                Delta::Typechange => StagedStatus::TypeChanged,
+                _ => StagedStatus::Added,
+            };
+            if let Some(path) = new.or(old) {
+                files.push((path.to_path_buf(), status));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Content of `path` (relative to the repository root, or absolute) as staged,
+    /// which may differ from the working tree when only some changes were added
+    pub fn staged_file_content(&self, path: &Path) -> Result<Vec<u8>> {
+        let relative = path.strip_prefix(&self.path).unwrap_or(path);
+        let index = self.repo.index().context("Failed to read the index")?;
+        let entry = index
+            .get_path(relative, 0)
+            .ok_or_else(|| anyhow!("{} is not staged", relative.display()))?;
+        let blob = self
+            .repo
+            .find_blob(entry.id)
+            .with_context(|| format!("Failed to read the staged {}", relative.display()))?;
+        Ok(blob.content().to_vec())
+    }
+
+    /// Stage the working tree content of `files` (relative to the repository root,
+    /// or absolute), like `git add`
+    pub fn stage_files(&self, files: &[PathBuf]) -> Result<()> {
+        let mut index = self.repo.index().context("Failed to read the index")?;
+        for file in files {
+            let relative = file.strip_prefix(&self.path).unwrap_or(file);
+            index
+                .add_path(relative)
+                .with_context(|| format!("Failed to stage {}", relative.display()))?;
+        }
+        index.write().context("Failed to write the index")
+    }
+
+    /// Added and modified files of the index, sized from their staged blobs
+    ///
+    /// Deletions and submodules are left out; neither adds content to the commit.
+    pub fn staged_blobs(&self) -> Result<Vec<StagedBlob>> {
+        let head = self.head_tree()?;
+        let mut options = DiffOptions::new();
+        options.ignore_submodules(true);
+        let diff = self
+            .repo
+            .diff_tree_to_index(head.as_ref(), None, Some(&mut options))
+            .context("Failed to diff the index against HEAD")?;
+        let odb = self.repo.odb()?;
+
+        let mut blobs = Vec::new();
+        for (index, delta) in diff.deltas().enumerate() {
+            let file = delta.new_file();
+            if !matches!(delta.status(), Delta::Added | Delta::Modified)
+                || file.mode() == FileMode::Commit
+            {
+                continue;
+            }
+            let Some(path) = file.path() else {
+                continue;
+            };
+            let (size, _) = odb.read_header(file.id())?;
+            // Loading the patch settles binary-ness the way `git diff` does: from
+            // the attributes, then the content
+            let binary = Patch::from_diff(&diff, index)?
+                .is_some_and(|patch| patch.delta().flags().is_binary());
+            blobs.push(StagedBlob {
+                path: path.to_path_buf(),
+                size: size as u64,
+                binary,
+            });
+        }
+        Ok(blobs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexEntry, IndexTime, Oid, Repository};
+    use std::path::Path;
+
+    #[test]
+    fn test_staged_content_and_status_come_from_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        for (file, content) in [("lib.rs", "pub fn a() {}\n"), ("old.rs", "// moved\n")] {
+            std::fs::write(dir.path().join(file), content).unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        index.add_path(Path::new("old.rs")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Guardy Test", "test@guardy.dev").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "initial", &tree, &[])
+            .unwrap();
+
+        std::fs::write(dir.path().join("lib.rs"), "pub fn b() {}\n").unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        std::fs::rename(dir.path().join("old.rs"), dir.path().join("new.rs")).unwrap();
+        index.remove_path(Path::new("old.rs")).unwrap();
+        index.add_path(Path::new("new.rs")).unwrap();
+        index.write().unwrap();
+        // Unstaged edits don't show
+        std::fs::write(dir.path().join("lib.rs"), "pub fn c() {}\n").unwrap();
+
+        let git = GitRepo::discover_at(dir.path()).unwrap();
+        let mut files = git.staged_files_with_status().unwrap();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            files,
+            vec![
+                (PathBuf::from("lib.rs"), StagedStatus::Modified),
+                (
+                    PathBuf::from("new.rs"),
+                    StagedStatus::Renamed {
+                        from: PathBuf::from("old.rs"),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            git.staged_file_content(Path::new("lib.rs")).unwrap(),
+            b"pub fn b() {}\n"
+        );
+        assert_eq!(
+            git.staged_file_content(&git.path.join("new.rs")).unwrap(),
+            b"// moved\n"
+        );
+        assert!(git.staged_file_content(Path::new("old.rs")).is_err());
+    }
+
+    #[test]
+    fn test_staged_blobs_skip_submodules_and_flag_binaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::create_dir(dir.path().join("assets")).unwrap();
+        std::fs::write(dir.path().join("assets/logo.png"), b"\x89PNG\0\0\x01").unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("assets/logo.png")).unwrap();
+        index.add_path(Path::new("main.rs")).unwrap();
+        let commit = Oid::from_str(&"1".repeat(40)).unwrap();
+        index
+            .add(&IndexEntry {
+                ctime: IndexTime::new(0, 0),
+                mtime: IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o160000,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: commit,
+                flags: 0,
+                flags_extended: 0,
+                path: b"vendor/lib".to_vec(),
+            })
+            .unwrap();
+        index.write().unwrap();
+
+        let mut blobs = GitRepo::discover_at(dir.path())
+            .unwrap()
+            .staged_blobs()
+            .unwrap();
+        blobs.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            blobs,
+            vec![
+                StagedBlob {
+                    path: PathBuf::from("assets/logo.png"),
+                    size: 7,
+                    binary: true,
+                },
+                StagedBlob {
+                    path: PathBuf::from("main.rs"),
+                    size: 13,
+                    binary: false,
+                },
+            ]
+        );
+    }
+}
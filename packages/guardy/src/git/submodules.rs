@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use super::GitRepo;
+
+/// A submodule registered in `.gitmodules`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submodule {
+    /// Path relative to the superproject's root
+    pub path: PathBuf,
+    pub url: Option<String>,
+    /// Commit the superproject's HEAD records
+    pub head: Option<String>,
+    /// Commit staged in the superproject's index
+    pub staged: Option<String>,
+    /// Whether the submodule's working tree is checked out (`git submodule update`)
+    pub checked_out: bool,
+}
+
+impl GitRepo {
+    pub fn submodules(&self) -> Result<Vec<Submodule>> {
+        let submodules = self
+            .repo
+            .submodules()
+            .context("Failed to read .gitmodules")?;
+        Ok(submodules
+            .iter()
+            .map(|submodule| Submodule {
+                path: submodule.path().to_path_buf(),
+                url: submodule.url().map(str::to_string),
+                head: submodule.head_id().map(|id| id.to_string()),
+                staged: submodule.index_id().map(|id| id.to_string()),
+                checked_out: submodule.open().is_ok(),
+            })
+            .collect())
+    }
+
+    /// Files of a checked-out submodule that its staged commit changes since the
+    /// one in HEAD - every tracked file of a newly added submodule - as paths into
+    /// its working tree
+    pub fn staged_submodule_files(&self, submodule: &Submodule) -> Result<Vec<PathBuf>> {
+        if !submodule.checked_out {
+            return Ok(Vec::new());
+        }
+        let repo = GitRepo::discover_at(&self.path.join(&submodule.path))?;
+        match (&submodule.head, &submodule.staged) {
+            // A shallow submodule may lack the old commit: check all of it instead
+            (Some(head), Some(staged)) if head != staged => repo
+                .get_changed_files_between(head, staged)
+                .or_else(|_| repo.get_tracked_files()),
+            (None, Some(_)) => repo.get_tracked_files(),
+            _ => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use git2::{Branch, Oid, Sort};
+
+use super::GitRepo;
+
+/// Where the checked-out branch stands against its upstream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tracking {
+    /// Short name of the upstream, e.g. `origin/main`
+    pub upstream: String,
+    /// Commits on the branch the upstream lacks
+    pub ahead: usize,
+    /// Commits on the upstream the branch lacks
+    pub behind: usize,
+}
+
+/// A local commit no remote-tracking branch contains yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnpushedCommit {
+    pub commit: String,
+    pub subject: String,
+}
+
+impl GitRepo {
+    /// Ahead/behind counts of the checked-out branch; `None` when HEAD is detached
+    /// or the branch tracks nothing
+    pub fn tracking(&self) -> Result<Option<Tracking>> {
+        let Some((local, upstream, remote)) = self.upstream()? else {
+            return Ok(None);
+        };
+        let (ahead, behind) = self.repo.graph_ahead_behind(local, remote)?;
+        Ok(Some(Tracking {
+            upstream,
+            ahead,
+            behind,
+        }))
+    }
+
+    /// Commits of HEAD missing from its upstream, newest first; without an upstream,
+    /// those missing from every remote-tracking branch
+    pub fn unpushed_commits(&self) -> Result<Vec<UnpushedCommit>> {
+        if self.head_commit().is_err() {
+            return Ok(Vec::new()); // No commits yet
+        }
+        let mut walk = self.repo.revwalk()?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+        walk.push_head()?;
+        match self.upstream()? {
+            Some((_, _, remote)) => walk.hide(remote)?,
+            None => walk.hide_glob("refs/remotes")?,
+        }
+
+        walk.map(|commit| {
+            let commit = self.repo.find_commit(commit?)?;
+            Ok(UnpushedCommit {
+                commit: commit.id().to_string(),
+                subject: commit.summary().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+    }
+
+    /// Commit of the checked-out branch, its upstream's name and commit
+    fn upstream(&self) -> Result<Option<(Oid, String, Oid)>> {
+        let Ok(head) = self.repo.head() else {
+            return Ok(None);
+        };
+        if !head.is_branch() {
+            return Ok(None);
+        }
+        let branch = Branch::wrap(head);
+        let Ok(upstream) = branch.upstream() else {
+            return Ok(None);
+        };
+        let (Some(local), Some(remote), Some(name)) = (
+            branch.get().target(),
+            upstream.get().target(),
+            upstream.name()?,
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some((local, name.to_string(), remote)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+
+    fn commit(repo: &Repository, message: &str) -> Oid {
+        let signature = Signature::now("Guardy Test", "test@guardy.dev").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ahead_behind_and_unpushed_commits_follow_the_upstream() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        repo.remote("origin", "https://example.com/acme/api.git")
+            .unwrap();
+        let base = commit(&repo, "chore: initial commit");
+        let git = GitRepo::discover_at(dir.path()).unwrap();
+        assert_eq!(git.tracking().unwrap(), None);
+        assert_eq!(git.unpushed_commits().unwrap().len(), 1);
+
+        // origin/main moved on by one commit, main by two
+        repo.reference("refs/remotes/origin/main", base, true, "test")
+            .unwrap();
+        repo.find_branch("main", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/main"))
+            .unwrap();
+        commit(&repo, "feat: remote work");
+        let remote = repo.head().unwrap().target().unwrap();
+        repo.reference("refs/remotes/origin/main", remote, true, "test")
+            .unwrap();
+        repo.reference("refs/heads/main", base, true, "test")
+            .unwrap();
+        commit(&repo, "feat: first");
+        commit(&repo, "feat: second");
+
+        assert_eq!(
+            git.tracking().unwrap(),
+            Some(Tracking {
+                upstream: "origin/main".to_string(),
+                ahead: 2,
+                behind: 1,
+            })
+        );
+        let subjects: Vec<String> = git
+            .unpushed_commits()
+            .unwrap()
+            .into_iter()
+            .map(|commit| commit.subject)
+            .collect();
+        assert_eq!(subjects, ["feat: second", "feat: first"]);
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::{Context, Result, anyhow};
+use git2::build::CheckoutBuilder;
+use git2::{ApplyLocation, Diff, DiffFormat, DiffOptions, IndexEntryExtendedFlag, Oid};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::GitRepo;
+use super::merge::index_entry;
+
+/// Working tree changes to tracked files that aren't staged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnstagedChanges {
+    /// The changes as `git diff --binary` prints them
+    pub patch: Vec<u8>,
+    /// Files the patch touches, relative to the repository root
+    pub files: Vec<PathBuf>,
+}
+
+/// A file of a patch merged with the working tree's own edits to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedFile {
+    /// Path relative to the repository root
+    pub path: PathBuf,
+    /// Content and git file mode to write; `None` when the patch deletes the file
+    pub result: Option<(Vec<u8>, u32)>,
+    /// Whether the working tree's edits clashed with the patch and were left out
+    pub conflicted: bool,
+}
+
+impl GitRepo {
+    /// Unstaged changes of the whole working tree, `None` when there are none
+    ///
+    /// Files added with `git add -N` and submodules are left out: neither has
+    /// staged content to fall back to.
+    pub fn unstaged_changes(&self) -> Result<Option<UnstagedChanges>> {
+        self.unstaged_changes_of(None)
+    }
+
+    /// Unstaged changes to `files` (relative to the repository root) only
+    pub fn unstaged_changes_in(&self, files: &[PathBuf]) -> Result<Option<UnstagedChanges>> {
+        if files.is_empty() {
+            return Ok(None);
+        }
+        self.unstaged_changes_of(Some(files))
+    }
+
+    /// Overwrite `files` in the working tree with their staged content, like
+    /// `git checkout -- <files>`
+    pub fn checkout_from_index(&self, files: &[PathBuf]) -> Result<()> {
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().disable_pathspec_match(true);
+        for file in files {
+            checkout.path(file);
+        }
+        self.repo
+            .checkout_index(None, Some(&mut checkout))
+            .context("Failed to check out files from the index")
+    }
+
+    /// Apply a patch to the working tree; nothing is written unless every hunk
+    /// applies cleanly
+    pub fn apply_to_workdir(&self, patch: &[u8]) -> Result<()> {
+        let diff = Diff::from_buffer(patch).context("Failed to parse patch")?;
+        self.repo
+            .apply(&diff, ApplyLocation::WorkDir, None)
+            .map_err(|e| anyhow!("Patch does not apply: {}", e.message()))
+    }
+
+    /// Tree of the index as it stands, to merge against later
+    pub fn index_tree(&self) -> Result<String> {
+        let mut index = self.repo.index().context("Failed to read the index")?;
+        Ok(index.write_tree()?.to_string())
+    }
+
+    /// Each file of `patch`, made against the index tree `base`, merged three ways
+    /// with the working tree as `git apply --3way` would; where the two clash, the
+    /// patched content wins
+    pub fn merge_patch(&self, base: &str, patch: &[u8]) -> Result<Vec<MergedFile>> {
+        let diff = Diff::from_buffer(patch).context("Failed to parse patch")?;
+        let base = self.repo.find_tree(Oid::from_str(base)?)?;
+        let patched = self
+            .repo
+            .apply_to_tree(&base, &diff, None)
+            .context("Patch does not apply to the tree it was made against")?;
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+                continue;
+            };
+            let ancestor = base
+                .get_path(path)
+                .ok()
+                .map(|entry| (entry.id(), entry.filemode() as u32));
+            let theirs = patched
+                .get_path(path, 0)
+                .map(|entry| (entry.id, entry.mode));
+            let ours = read_worktree_file(&self.path.join(path))?;
+
+            let content =
+                |id| -> Result<Vec<u8>> { Ok(self.repo.find_blob(id)?.content().to_vec()) };
+            let result = theirs
+                .map(|(id, mode)| Ok::<_, anyhow::Error>((content(id)?, mode)))
+                .transpose()?;
+            let untouched = match (&ancestor, &ours) {
+                (Some((id, _)), Some(ours)) => content(*id)? == *ours,
+                (None, None) => true,
+                _ => false,
+            };
+            if untouched {
+                files.push(MergedFile {
+                    path: path.to_path_buf(),
+                    result,
+                    conflicted: false,
+                });
+                continue;
+            }
+
+            // Only regular files whose mode the patch keeps merge line by line
+            let merged = match (ancestor, &ours, theirs) {
+                (Some((ancestor, mode)), Some(ours), Some((theirs, theirs_mode)))
+                    if mode == theirs_mode && (mode == 0o100644 || mode == 0o100755) =>
+                {
+                    let ours = self.repo.blob(ours)?;
+                    let merge = self.repo.merge_file_from_index(
+                        &index_entry(path, ancestor, mode),
+                        &index_entry(path, ours, mode),
+                        &index_entry(path, theirs, mode),
+                        None,
+                    )?;
+                    merge
+                        .is_automergeable()
+                        .then(|| (merge.content().to_vec(), mode))
+                }
+                _ => None,
+            };
+            files.push(MergedFile {
+                path: path.to_path_buf(),
+                conflicted: merged.is_none(),
+                result: merged.or(result),
+            });
+        }
+        Ok(files)
+    }
+
+    fn unstaged_changes_of(&self, only: Option<&[PathBuf]>) -> Result<Option<UnstagedChanges>> {
+        let index = self.repo.index().context("Failed to read the index")?;
+        let intent_to_add: HashSet<PathBuf> = index
+            .iter()
+            .filter(|entry| {
+                IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended).is_intent_to_add()
+            })
+            .map(|entry| PathBuf::from(String::from_utf8_lossy(&entry.path).as_ref()))
+            .collect();
+
+        let diff_of = |paths: Option<&[PathBuf]>| {
+            let mut options = DiffOptions::new();
+            options.show_binary(true).ignore_submodules(true);
+            if let Some(paths) = paths {
+                options.disable_pathspec_match(true);
+                for path in paths {
+                    options.pathspec(path);
+                }
+            }
+            self.repo
+                .diff_index_to_workdir(Some(&index), Some(&mut options))
+                .context("Failed to diff the working tree against the index")
+        };
+
+        let mut diff = diff_of(only)?;
+        let files: Vec<PathBuf> = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or(delta.old_file().path()))
+            .filter(|path| !intent_to_add.contains(*path))
+            .map(Path::to_path_buf)
+            .collect();
+        if files.is_empty() {
+            return Ok(None);
+        }
+        if files.len() != diff.deltas().len() {
+            diff = diff_of(Some(&files))?;
+        }
+
+        let mut patch = Vec::new();
+        diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin() as u8);
+            }
+            patch.extend_from_slice(line.content());
+            true
+        })?;
+        Ok(Some(UnstagedChanges { patch, files }))
+    }
+}
+
+/// Content of a working tree file - a symlink's target - or `None` when missing
+fn read_worktree_file(path: &Path) -> Result<Option<Vec<u8>>> {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(None);
+    };
+    let content = if metadata.file_type().is_symlink() {
+        fs::read_link(path)?
+            .to_string_lossy()
+            .into_owned()
+            .into_bytes()
+    } else {
+        fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?
+    };
+    Ok(Some(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Repository;
+
+    #[test]
+    fn test_patch_round_trip_and_three_way_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let lines = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+        std::fs::write(dir.path().join("lib.txt"), lines).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "notes\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.txt")).unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        index.write().unwrap();
+
+        let git = GitRepo::discover_at(dir.path()).unwrap();
+        assert_eq!(git.unstaged_changes().unwrap(), None);
+
+        // The user edits the first line, then the changes are hidden
+        std::fs::write(dir.path().join("lib.txt"), lines.replace("one", "ONE")).unwrap();
+        let changes = git.unstaged_changes().unwrap().unwrap();
+        assert_eq!(changes.files, [PathBuf::from("lib.txt")]);
+        let base = git.index_tree().unwrap();
+        git.checkout_from_index(&changes.files).unwrap();
+        assert_eq!(git.unstaged_changes().unwrap(), None);
+
+        // A command edits the last line: the patch no longer applies as is
+        std::fs::write(dir.path().join("lib.txt"), lines.replace("seven", "SEVEN")).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "formatted\n").unwrap();
+        let merged = git.merge_patch(&base, &changes.patch).unwrap();
+        assert_eq!(
+            merged,
+            vec![MergedFile {
+                path: PathBuf::from("lib.txt"),
+                result: Some((
+                    lines
+                        .replace("one", "ONE")
+                        .replace("seven", "SEVEN")
+                        .into_bytes(),
+                    0o100644
+                )),
+                conflicted: false,
+            }]
+        );
+
+        // Both editing the same line: the patched content wins
+        std::fs::write(dir.path().join("lib.txt"), lines.replace("one", "uno")).unwrap();
+        let merged = git.merge_patch(&base, &changes.patch).unwrap();
+        assert!(merged[0].conflicted);
+        assert_eq!(
+            merged[0].result,
+            Some((lines.replace("one", "ONE").into_bytes(), 0o100644))
+        );
+
+        git.checkout_from_index(&changes.files).unwrap();
+        git.apply_to_workdir(&changes.patch).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("lib.txt")).unwrap(),
+            lines.replace("one", "ONE")
+        );
+        assert_eq!(
+            git.unstaged_changes_in(&changes.files).unwrap().unwrap(),
+            changes
+        );
+    }
+}
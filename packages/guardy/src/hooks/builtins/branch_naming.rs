@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use globset::Glob;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GuardyConfig;
+
+/// Configuration for the `validate_branch_name` builtin (`branch_naming` section)
+///
+/// ```yaml
+/// branch_naming:
+///   patterns:
+///     - "^(feature|fix|chore)/[a-z0-9._-]+$"
+///     - "^[A-Z]+-\\d+-[a-z0-9-]+$"    # PROJ-123-short-description
+///   exempt: ["main", "release/*"]
+///   hint: "See CONTRIBUTING.md#branches"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BranchNamingConfig {
+    /// Regexes a branch name must match (any one of them)
+    #[serde(default = "default_patterns")]
+    pub patterns: Vec<String>,
+
+    /// Branch globs that are never checked, such as long-lived branches
+    #[serde(default = "default_exempt")]
+    pub exempt: Vec<String>,
+
+    /// Extra line shown when a branch name is rejected
+    #[serde(default)]
+    pub hint: Option<String>,
+}
+
+fn default_patterns() -> Vec<String> {
+    vec![
+        r"^(feature|fix|hotfix|chore|docs|refactor|test|release)/[a-z0-9._-]+(/[a-z0-9._-]+)*$"
+            .to_string(),
+    ]
+}
+
+fn default_exempt() -> Vec<String> {
+    vec![
+        "main".to_string(),
+        "master".to_string(),
+        "develop".to_string(),
+    ]
+}
+
+impl Default for BranchNamingConfig {
+    fn default() -> Self {
+        Self {
+            patterns: default_patterns(),
+            exempt: default_exempt(),
+            hint: None,
+        }
+    }
+}
+
+impl BranchNamingConfig {
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("branch_naming") {
+            Ok(value) if !value.is_null() => {
+                serde_json::from_value(value).context("Failed to parse branch_naming configuration")
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Whether `branch` is exempt or matches one of the patterns
+    pub fn allows(&self, branch: &str) -> Result<bool> {
+        for exempt in &self.exempt {
+            let glob = Glob::new(exempt)
+                .with_context(|| format!("Invalid branch_naming.exempt glob '{exempt}'"))?;
+            if glob.compile_matcher().is_match(branch) {
+                return Ok(true);
+            }
+        }
+        if self.patterns.is_empty() {
+            return Ok(true);
+        }
+        for pattern in &self.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid branch_naming.patterns regex '{pattern}'"))?;
+            if regex.is_match(branch) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// A name close to `branch` that the policy accepts, to suggest as a rename
+    pub fn suggest(&self, branch: &str) -> Result<Option<String>> {
+        let cleaned = slug(branch);
+        let lower = cleaned.to_lowercase();
+        let candidates = [
+            cleaned.clone(),
+            lower.clone(),
+            format!("feature/{lower}"),
+            format!("feature/{}", lower.replace('/', "-")),
+        ];
+        for candidate in candidates {
+            if candidate != branch && self.allows(&candidate)? {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// `branch` with whitespace and unusual characters turned into single dashes
+fn slug(branch: &str) -> String {
+    branch
+        .split('/')
+        .map(|segment| {
+            segment
+                .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("-")
+                .replace('_', "-")
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let config = BranchNamingConfig::default();
+
+        assert!(config.allows("feature/login-form").unwrap());
+        assert!(config.allows("fix/api/timeouts").unwrap());
+        assert!(config.allows("main").unwrap());
+        assert!(!config.allows("Login Form").unwrap());
+        assert!(!config.allows("wip").unwrap());
+    }
+
+    #[test]
+    fn test_suggest_renames_into_policy() {
+        let mut config = BranchNamingConfig::default();
+
+        assert_eq!(
+            config.suggest("Feature/Login Form").unwrap().as_deref(),
+            Some("feature/login-form")
+        );
+        assert_eq!(
+            config.suggest("add_login").unwrap().as_deref(),
+            Some("feature/add-login")
+        );
+
+        config.patterns = vec![r"^[A-Z]+-\d+-[a-z0-9-]+$".to_string()];
+        assert_eq!(
+            config.suggest("PROJ-12 fix login").unwrap().as_deref(),
+            Some("PROJ-12-fix-login")
+        );
+        assert_eq!(config.suggest("wip").unwrap(), None);
+
+        config.patterns = vec!["(".to_string()];
+        assert!(config.allows("anything").is_err());
+    }
+}
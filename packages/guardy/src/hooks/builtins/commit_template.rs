@@ -0,0 +1,335 @@
+use anyhow::{Context, Result};
+use globset::Glob;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GuardyConfig;
+
+/// Configuration for the `commit_template` builtin (`commit_template` section)
+///
+/// ```yaml
+/// commit_template:
+///   ticket_pattern: "[A-Z][A-Z0-9]+-\\d+"
+///   co_authors: ["Jane Doe <jane@example.com>"]
+///   rules:
+///     - branch: ["feature/*"]
+///       prefix: "[{ticket}] "
+///       trailers: ["Refs: {ticket}"]
+///     - branch: ["docs/*"]
+///       scope: "docs"
+///       prefix: "docs({scope}): "
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitTemplateConfig {
+    /// Regex used to extract a ticket id from the branch name (capture group 1 if present)
+    #[serde(default = "default_ticket_pattern")]
+    pub ticket_pattern: String,
+
+    /// Rules matched against the current branch, first match wins
+    #[serde(default)]
+    pub rules: Vec<TemplateRule>,
+
+    /// Co-authors appended as `Co-authored-by:` trailers on every commit
+    #[serde(default)]
+    pub co_authors: Vec<String>,
+
+    /// Commit message sources (as passed by git) that are left untouched
+    #[serde(default = "default_skip_sources")]
+    pub skip_sources: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TemplateRule {
+    /// Branch globs this rule applies to (empty matches every branch)
+    #[serde(default)]
+    pub branch: Vec<String>,
+
+    /// Subject prefix, supports `{ticket}`, `{branch}` and `{scope}` placeholders
+    #[serde(default)]
+    pub prefix: Option<String>,
+
+    /// Value substituted for `{scope}`
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// Extra trailer lines, supports the same placeholders as `prefix`
+    #[serde(default)]
+    pub trailers: Vec<String>,
+}
+
+fn default_ticket_pattern() -> String {
+    r"[A-Z][A-Z0-9]+-\d+".to_string()
+}
+
+fn default_skip_sources() -> Vec<String> {
+    vec![
+        "merge".to_string(),
+        "squash".to_string(),
+        "commit".to_string(),
+    ]
+}
+
+impl Default for CommitTemplateConfig {
+    fn default() -> Self {
+        Self {
+            ticket_pattern: default_ticket_pattern(),
+            rules: vec![],
+            co_authors: vec![],
+            skip_sources: default_skip_sources(),
+        }
+    }
+}
+
+impl CommitTemplateConfig {
+    /// Load the `commit_template` section, falling back to defaults when absent
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("commit_template") {
+            Ok(value) if !value.is_null() => serde_json::from_value(value)
+                .context("Failed to parse commit_template configuration"),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Whether a message coming from `source` (second prepare-commit-msg argument) should be templated
+    pub fn applies_to_source(&self, source: Option<&str>) -> bool {
+        match source {
+            Some(source) => !self.skip_sources.iter().any(|s| s == source),
+            None => true,
+        }
+    }
+
+    /// Apply the template to a commit message buffer for the given branch.
+    ///
+    /// Returns `None` when nothing needs to change, so the caller can leave the file alone.
+    pub fn apply(&self, message: &str, branch: &str) -> Result<Option<String>> {
+        let ticket = self.extract_ticket(branch)?;
+        let rule = self.matching_rule(branch)?;
+
+        let render = |template: &str| -> Option<String> {
+            if template.contains("{ticket}") && ticket.is_none() {
+                return None;
+            }
+            let scope = rule.and_then(|r| r.scope.as_deref()).unwrap_or("");
+            Some(
+                template
+                    .replace("{ticket}", ticket.as_deref().unwrap_or(""))
+                    .replace("{branch}", branch)
+                    .replace("{scope}", scope),
+            )
+        };
+
+        // Git places its help text after the editable content, keep it there
+        let lines: Vec<&str> = message.lines().collect();
+        let comment_start = lines
+            .iter()
+            .position(|line| line.starts_with('#'))
+            .unwrap_or(lines.len());
+        let mut content: Vec<String> = lines[..comment_start]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let comments = &lines[comment_start..];
+
+        while content.last().is_some_and(|l| l.trim().is_empty()) {
+            content.pop();
+        }
+
+        let mut changed = false;
+
+        if let Some(prefix) = rule.and_then(|r| r.prefix.as_deref()).and_then(render) {
+            let subject = content.first().cloned().unwrap_or_default();
+            let already_tagged = subject.starts_with(prefix.trim_end())
+                || ticket.as_deref().is_some_and(|t| subject.contains(t));
+            if !already_tagged {
+                let new_subject = format!("{prefix}{subject}");
+                match content.first_mut() {
+                    Some(first) => *first = new_subject,
+                    None => content.push(new_subject),
+                }
+                changed = true;
+            }
+        }
+
+        let mut trailers: Vec<String> = rule
+            .map(|r| r.trailers.iter().filter_map(|t| render(t)).collect())
+            .unwrap_or_default();
+        trailers.extend(
+            self.co_authors
+                .iter()
+                .map(|author| format!("Co-authored-by: {author}")),
+        );
+        trailers.retain(|t| !content.iter().any(|l| l.trim() == t.trim()));
+        trailers.dedup();
+
+        if !trailers.is_empty() {
+            let ends_with_trailer = content.last().is_some_and(|l| is_trailer(l));
+            if content.is_empty() {
+                // Leave the subject line free for the editor
+                content.push(String::new());
+            }
+            if !ends_with_trailer {
+                content.push(String::new());
+            }
+            content.extend(trailers);
+            changed = true;
+        }
+
+        if !changed {
+            return Ok(None);
+        }
+
+        let mut result = content.join("\n");
+        result.push('\n');
+        if !comments.is_empty() {
+            result.push('\n');
+            result.push_str(&comments.join("\n"));
+            result.push('\n');
+        }
+        Ok(Some(result))
+    }
+
+    fn extract_ticket(&self, branch: &str) -> Result<Option<String>> {
+        let regex = Regex::new(&self.ticket_pattern)
+            .with_context(|| format!("Invalid ticket_pattern '{}'", self.ticket_pattern))?;
+
+        Ok(regex.captures(branch).map(|caps| {
+            caps.get(1)
+                .or_else(|| caps.get(0))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default()
+        }))
+    }
+
+    fn matching_rule(&self, branch: &str) -> Result<Option<&TemplateRule>> {
+        for rule in &self.rules {
+            if rule.branch.is_empty() {
+                return Ok(Some(rule));
+            }
+            for pattern in &rule.branch {
+                let matcher = Glob::new(pattern)
+                    .with_context(|| format!("Invalid branch pattern '{pattern}'"))?
+                    .compile_matcher();
+                if matcher.is_match(branch) {
+                    return Ok(Some(rule));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A `Key: value` line where the key has no whitespace, as git interprets trailers
+fn is_trailer(line: &str) -> bool {
+    match line.split_once(": ") {
+        Some((key, value)) => {
+            !key.is_empty() && !key.contains(char::is_whitespace) && !value.trim().is_empty()
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket_rule() -> TemplateRule {
+        TemplateRule {
+            branch: vec!["feature/*".to_string()],
+            prefix: Some("[{ticket}] ".to_string()),
+            scope: None,
+            trailers: vec!["Refs: {ticket}".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_trailer_from_branch_ticket() {
+        let config = CommitTemplateConfig {
+            rules: vec![ticket_rule()],
+            ..Default::default()
+        };
+
+        let result = config
+            .apply("add login\n", "feature/AUTH-42-login")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result, "[AUTH-42] add login\n\nRefs: AUTH-42\n");
+    }
+
+    #[test]
+    fn test_git_comments_stay_at_the_end() {
+        let config = CommitTemplateConfig {
+            co_authors: vec!["Jane Doe <jane@example.com>".to_string()],
+            ..Default::default()
+        };
+
+        let message =
+            "\n# Please enter the commit message\n# Lines starting with '#' are ignored\n";
+        let result = config.apply(message, "main").unwrap().unwrap();
+
+        assert_eq!(
+            result,
+            "\n\nCo-authored-by: Jane Doe <jane@example.com>\n\n# Please enter the commit message\n# Lines starting with '#' are ignored\n"
+        );
+    }
+
+    #[test]
+    fn test_existing_prefix_and_trailers_are_not_duplicated() {
+        let config = CommitTemplateConfig {
+            rules: vec![ticket_rule()],
+            ..Default::default()
+        };
+
+        let message = "[AUTH-42] add login\n\nRefs: AUTH-42\n";
+        assert!(
+            config
+                .apply(message, "feature/AUTH-42-login")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ticket_placeholders_skipped_without_ticket() {
+        let config = CommitTemplateConfig {
+            rules: vec![ticket_rule()],
+            ..Default::default()
+        };
+
+        assert!(
+            config
+                .apply("add login\n", "feature/login")
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_scope_prefix_and_first_matching_rule() {
+        let config = CommitTemplateConfig {
+            rules: vec![
+                TemplateRule {
+                    branch: vec!["docs/*".to_string()],
+                    prefix: Some("docs({scope}): ".to_string()),
+                    scope: Some("readme".to_string()),
+                    trailers: vec![],
+                },
+                ticket_rule(),
+            ],
+            ..Default::default()
+        };
+
+        let result = config.apply("", "docs/install").unwrap().unwrap();
+        assert_eq!(result, "docs(readme): \n");
+    }
+
+    #[test]
+    fn test_skip_sources() {
+        let config = CommitTemplateConfig::default();
+
+        assert!(config.applies_to_source(None));
+        assert!(config.applies_to_source(Some("message")));
+        assert!(!config.applies_to_source(Some("merge")));
+        assert!(!config.applies_to_source(Some("commit")));
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::GuardyConfig;
+
+/// Configuration for the `install_dependencies` builtin (`package_manager` section)
+///
+/// ```yaml
+/// package_manager:
+///   preferred: pnpm       # Used when package.json changes without a known lockfile
+///   auto_install: true    # false = only print the install commands
+///   commands:             # Per-file overrides of the detected install command
+///     Cargo.lock: "cargo build"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PackageManagerConfig {
+    #[serde(default = "default_preferred")]
+    pub preferred: String,
+
+    #[serde(default = "default_auto_install")]
+    pub auto_install: bool,
+
+    #[serde(default)]
+    pub commands: HashMap<String, String>,
+}
+
+/// An install command to run because a dependency file changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallAction {
+    /// Directory to run the command in, relative to the repository root
+    pub dir: PathBuf,
+    /// The changed file that triggered the install
+    pub trigger: String,
+    pub command: String,
+}
+
+fn default_preferred() -> String {
+    "pnpm".to_string()
+}
+
+fn default_auto_install() -> bool {
+    true
+}
+
+impl Default for PackageManagerConfig {
+    fn default() -> Self {
+        Self {
+            preferred: default_preferred(),
+            auto_install: default_auto_install(),
+            commands: HashMap::new(),
+        }
+    }
+}
+
+/// Node lockfiles in detection order, with the package manager that owns them
+const NODE_LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lockb", "bun"),
+    ("bun.lock", "bun"),
+    ("package-lock.json", "npm"),
+];
+
+/// Package manager owning the Node project in `dir`, judged by its lockfile
+pub fn node_package_manager(dir: &Path) -> Option<&'static str> {
+    NODE_LOCKFILES
+        .iter()
+        .find(|(lock, _)| dir.join(lock).exists())
+        .map(|(_, manager)| *manager)
+}
+
+impl PackageManagerConfig {
+    /// Load the `package_manager` section, falling back to defaults when absent
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("package_manager") {
+            Ok(value) if !value.is_null() => serde_json::from_value(value)
+                .context("Failed to parse package_manager configuration"),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Work out which install commands the changed files call for.
+    ///
+    /// `changed` holds paths relative to `repo_root`; the root is only consulted to
+    /// pick the right package manager when a manifest changed without its lockfile.
+    pub fn install_plan(&self, changed: &[PathBuf], repo_root: &Path) -> Vec<InstallAction> {
+        let mut actions: Vec<InstallAction> = Vec::new();
+
+        for path in changed {
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+            let command = match self.commands.get(file_name) {
+                Some(command) => Some(command.clone()),
+                None => self.detect_command(file_name, &repo_root.join(&dir)),
+            };
+
+            if let Some(command) = command
+                && !actions.iter().any(|a| a.dir == dir && a.command == command)
+            {
+                actions.push(InstallAction {
+                    dir,
+                    trigger: file_name.to_string(),
+                    command,
+                });
+            }
+        }
+
+        actions
+    }
+
+    fn detect_command(&self, file_name: &str, dir: &Path) -> Option<String> {
+        if let Some((_, manager)) = NODE_LOCKFILES.iter().find(|(lock, _)| *lock == file_name) {
+            return Some(format!("{manager} install"));
+        }
+
+        let command = match file_name {
+            "package.json" => {
+                let manager = node_package_manager(dir).unwrap_or(&self.preferred);
+                format!("{manager} install")
+            }
+            "Cargo.lock" => "cargo fetch".to_string(),
+            "uv.lock" => "uv sync".to_string(),
+            "poetry.lock" => "poetry install".to_string(),
+            "Pipfile.lock" => "pipenv install".to_string(),
+            "pyproject.toml" => {
+                if dir.join("uv.lock").exists() {
+                    "uv sync".to_string()
+                } else if dir.join("poetry.lock").exists() {
+                    "poetry install".to_string()
+                } else {
+                    "pip install -e .".to_string()
+                }
+            }
+            "requirements.txt" => "pip install -r requirements.txt".to_string(),
+            "Gemfile.lock" => "bundle install".to_string(),
+            "go.sum" => "go mod download".to_string(),
+            "composer.lock" => "composer install".to_string(),
+            _ => return None,
+        };
+
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plan(config: &PackageManagerConfig, root: &Path, files: &[&str]) -> Vec<(String, String)> {
+        let changed: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+        config
+            .install_plan(&changed, root)
+            .into_iter()
+            .map(|a| (a.dir.to_string_lossy().to_string(), a.command))
+            .collect()
+    }
+
+    #[test]
+    fn test_lockfiles_map_to_install_commands() {
+        let temp = TempDir::new().unwrap();
+        let config = PackageManagerConfig::default();
+
+        let actions = plan(
+            &config,
+            temp.path(),
+            &[
+                "Cargo.lock",
+                "web/yarn.lock",
+                "api/poetry.lock",
+                "README.md",
+            ],
+        );
+
+        assert_eq!(
+            actions,
+            vec![
+                ("".to_string(), "cargo fetch".to_string()),
+                ("web".to_string(), "yarn install".to_string()),
+                ("api".to_string(), "poetry install".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_package_json_uses_lockfile_on_disk_or_preferred() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("app")).unwrap();
+        std::fs::write(temp.path().join("app/package-lock.json"), "{}").unwrap();
+        let config = PackageManagerConfig::default();
+
+        let actions = plan(&config, temp.path(), &["app/package.json", "package.json"]);
+
+        assert_eq!(
+            actions,
+            vec![
+                ("app".to_string(), "npm install".to_string()),
+                ("".to_string(), "pnpm install".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_manifest_and_lockfile_install_once() {
+        let temp = TempDir::new().unwrap();
+        let config = PackageManagerConfig::default();
+
+        let actions = plan(&config, temp.path(), &["package.json", "pnpm-lock.yaml"]);
+
+        assert_eq!(actions, vec![("".to_string(), "pnpm install".to_string())]);
+    }
+
+    #[test]
+    fn test_command_overrides() {
+        let temp = TempDir::new().unwrap();
+        let mut config = PackageManagerConfig::default();
+        config
+            .commands
+            .insert("Cargo.lock".to_string(), "cargo build".to_string());
+
+        let actions = plan(&config, temp.path(), &["Cargo.lock"]);
+
+        assert_eq!(actions, vec![("".to_string(), "cargo build".to_string())]);
+    }
+}
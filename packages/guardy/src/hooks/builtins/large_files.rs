@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GuardyConfig;
+use crate::git::staged::StagedBlob;
+
+/// Configuration for the `block_large_files` builtin (`large_files` section)
+///
+/// ```yaml
+/// large_files:
+///   max_size_kb: 1024
+///   block_binaries: true
+///   allow_extensions: ["png", "svg", "ico"]
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LargeFilesConfig {
+    /// Largest staged file accepted, in kilobytes
+    #[serde(default = "default_max_size_kb")]
+    pub max_size_kb: u64,
+
+    /// Reject binary files of any size
+    #[serde(default)]
+    pub block_binaries: bool,
+
+    /// Extensions (without the dot) exempt from both checks
+    #[serde(default)]
+    pub allow_extensions: Vec<String>,
+}
+
+fn default_max_size_kb() -> u64 {
+    5 * 1024
+}
+
+impl Default for LargeFilesConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: default_max_size_kb(),
+            block_binaries: false,
+            allow_extensions: Vec::new(),
+        }
+    }
+}
+
+impl LargeFilesConfig {
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("large_files") {
+            Ok(value) if !value.is_null() => {
+                serde_json::from_value(value).context("Failed to parse large_files configuration")
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Why `blob` may not be committed, or `None` when it is fine
+    pub fn problem(&self, blob: &StagedBlob) -> Option<String> {
+        let allowed = blob
+            .path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.allow_extensions.iter().any(|allowed| {
+                    allowed
+                        .trim_start_matches('.')
+                        .eq_ignore_ascii_case(extension)
+                })
+            });
+        if allowed {
+            return None;
+        }
+
+        if blob.size > self.max_size_kb * 1024 {
+            Some(format!(
+                "{} KB exceeds the {} KB limit",
+                blob.size.div_ceil(1024),
+                self.max_size_kb
+            ))
+        } else if self.block_binaries && blob.binary {
+            Some("binary file".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// The `git lfs track` pattern for `blob`: its extension, or the path itself
+pub fn lfs_pattern(blob: &StagedBlob) -> String {
+    match blob
+        .path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some(extension) => format!("*.{extension}"),
+        None => blob.path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn blob(path: &str, size: u64, binary: bool) -> StagedBlob {
+        StagedBlob {
+            path: PathBuf::from(path),
+            size,
+            binary,
+        }
+    }
+
+    #[test]
+    fn test_problem_checks_size_binaries_and_allowlist() {
+        let mut config = LargeFilesConfig {
+            max_size_kb: 100,
+            ..LargeFilesConfig::default()
+        };
+
+        assert_eq!(config.problem(&blob("src/main.rs", 2048, false)), None);
+        assert_eq!(config.problem(&blob("assets/logo.png", 2048, true)), None);
+        assert_eq!(
+            config
+                .problem(&blob("data/dump.sql", 150 * 1024, false))
+                .as_deref(),
+            Some("150 KB exceeds the 100 KB limit")
+        );
+
+        config.block_binaries = true;
+        assert_eq!(
+            config
+                .problem(&blob("assets/logo.png", 2048, true))
+                .as_deref(),
+            Some("binary file")
+        );
+
+        config.allow_extensions = vec![".PNG".to_string()];
+        assert_eq!(
+            config.problem(&blob("assets/logo.png", 200 * 1024, true)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lfs_pattern() {
+        assert_eq!(lfs_pattern(&blob("models/weights.bin", 0, true)), "*.bin");
+        assert_eq!(lfs_pattern(&blob("bin/tool", 0, true)), "bin/tool");
+    }
+}
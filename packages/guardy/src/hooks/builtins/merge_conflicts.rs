@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GuardyConfig;
+
+/// Configuration for the `check_merge_conflicts` builtin (`merge_conflicts` section)
+///
+/// ```yaml
+/// merge_conflicts:
+///   exclude: ["docs/**/*.md", "tests/fixtures/**"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MergeConflictsConfig {
+    /// Globs (relative to the repository root) of files allowed to contain markers
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// A conflict marker line in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    /// 1-based line number
+    pub line: usize,
+    pub marker: &'static str,
+}
+
+const OPENING: &str = "<<<<<<<";
+const BASE: &str = "|||||||";
+const SEPARATOR: &str = "=======";
+const CLOSING: &str = ">>>>>>>";
+
+impl MergeConflictsConfig {
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("merge_conflicts") {
+            Ok(value) if !value.is_null() => serde_json::from_value(value)
+                .context("Failed to parse merge_conflicts configuration"),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Matcher for the `exclude` globs
+    pub fn excludes(&self) -> Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &self.exclude {
+            builder
+                .add(Glob::new(pattern).with_context(|| {
+                    format!("Invalid merge_conflicts.exclude glob '{pattern}'")
+                })?);
+        }
+        Ok(builder.build()?)
+    }
+}
+
+/// Conflict markers left in `content`
+///
+/// `<<<<<<<`, `|||||||` and `>>>>>>>` lines always count; a bare `=======` line
+/// only alongside one of them, since it also underlines headings in Markdown and
+/// reStructuredText.
+pub fn conflict_markers(content: &str) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    let mut separators = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim_end_matches('\r');
+        if line == SEPARATOR {
+            separators.push(Marker {
+                line: line_number,
+                marker: SEPARATOR,
+            });
+            continue;
+        }
+        for marker in [OPENING, BASE, CLOSING] {
+            if line
+                .strip_prefix(marker)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+            {
+                markers.push(Marker {
+                    line: line_number,
+                    marker,
+                });
+            }
+        }
+    }
+
+    if !markers.is_empty() {
+        markers.extend(separators);
+        markers.sort_by_key(|marker| marker.line);
+    }
+    markers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_markers() {
+        let conflicted =
+            "fn main() {\n<<<<<<< HEAD\n    ours();\n=======\n    theirs();\n>>>>>>> feature\n}\n";
+        let lines: Vec<usize> = conflict_markers(conflicted)
+            .iter()
+            .map(|marker| marker.line)
+            .collect();
+        assert_eq!(lines, vec![2, 4, 6]);
+
+        // Heading underlines and longer runs are not markers
+        assert!(conflict_markers("Title\n=======\n\nText\n").is_empty());
+        assert!(conflict_markers("<<<<<<<<<< not a marker\n").is_empty());
+    }
+
+    #[test]
+    fn test_exclude_globs() {
+        let config = MergeConflictsConfig {
+            exclude: vec!["docs/**/*.md".to_string()],
+        };
+        let excludes = config.excludes().unwrap();
+
+        assert!(excludes.is_match("docs/guide/merging.md"));
+        assert!(!excludes.is_match("docs/README.txt"));
+        assert!(!excludes.is_match("src/main.rs"));
+    }
+}
@@ -0,0 +1,26 @@
+//! Built-in hook actions
+//!
+//! Each builtin lives in its own file with its configuration section and the
+//! pure logic behind it, so it can be unit tested without a git repository.
+
+mod branch_naming;
+mod commit_template;
+mod dependencies;
+mod large_files;
+mod merge_conflicts;
+mod pre_commit;
+mod protected_branches;
+mod push;
+mod signed_commits;
+
+pub use branch_naming::BranchNamingConfig;
+pub use commit_template::CommitTemplateConfig;
+pub use dependencies::{PackageManagerConfig, node_package_manager};
+pub use large_files::{LargeFilesConfig, lfs_pattern};
+pub use merge_conflicts::{MergeConflictsConfig, conflict_markers};
+pub use pre_commit::{
+    CONFIG_FILE as PRE_COMMIT_CONFIG_FILE, HookEntry, PreCommitConfig, RepoConfig, ResolvedHook,
+};
+pub use protected_branches::{BranchProtectionConfig, PushFacts};
+pub use push::RefUpdate;
+pub use signed_commits::{SignedCommitsConfig, pushed_revisions};
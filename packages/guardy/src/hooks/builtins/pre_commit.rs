@@ -0,0 +1,505 @@
+use anyhow::{Context, Result, anyhow};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::shared::paths;
+
+use super::super::languages::file_tags;
+
+/// File name of the pre-commit framework configuration, relative to the repository root
+pub const CONFIG_FILE: &str = ".pre-commit-config.yaml";
+
+/// Hook manifest every pre-commit hook repository ships at its root
+pub const MANIFEST_FILE: &str = ".pre-commit-hooks.yaml";
+
+/// Languages guardy can run without managing a toolchain environment
+const SUPPORTED_LANGUAGES: &[&str] = &["system", "script", "fail"];
+
+/// `.pre-commit-config.yaml`, as consumed by the `pre_commit_config` builtin
+///
+/// ```yaml
+/// repos:
+///   - repo: https://github.com/pre-commit/pre-commit-hooks
+///     rev: v4.6.0
+///     hooks:
+///       - id: check-merge-conflict
+///   - repo: local
+///     hooks:
+///       - id: cargo-fmt
+///         name: cargo fmt
+///         entry: cargo fmt --check --
+///         language: system
+///         types: [rust]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreCommitConfig {
+    #[serde(default)]
+    pub repos: Vec<RepoConfig>,
+
+    #[serde(default)]
+    pub default_stages: Vec<String>,
+
+    /// Global include regex applied before each hook's own `files`
+    #[serde(default)]
+    pub files: String,
+
+    /// Global exclude regex applied before each hook's own `exclude`
+    #[serde(default)]
+    pub exclude: String,
+
+    /// Stop after the first failing hook
+    #[serde(default)]
+    pub fail_fast: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RepoConfig {
+    /// Git URL, or `local`/`meta` for hooks defined in place
+    pub repo: String,
+
+    #[serde(default)]
+    pub rev: Option<String>,
+
+    #[serde(default)]
+    pub hooks: Vec<HookEntry>,
+}
+
+/// Hook fields shared by config entries (overrides) and repository manifests (defaults)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HookEntry {
+    pub id: String,
+    pub alias: Option<String>,
+    pub name: Option<String>,
+    pub entry: Option<String>,
+    pub language: Option<String>,
+    pub files: Option<String>,
+    pub exclude: Option<String>,
+    pub types: Option<Vec<String>>,
+    pub types_or: Option<Vec<String>>,
+    pub exclude_types: Option<Vec<String>>,
+    pub args: Option<Vec<String>>,
+    pub stages: Option<Vec<String>>,
+    pub pass_filenames: Option<bool>,
+    pub always_run: Option<bool>,
+}
+
+/// A hook ready to run: manifest defaults merged with config overrides
+#[derive(Debug, Clone)]
+pub struct ResolvedHook {
+    pub id: String,
+    pub alias: Option<String>,
+    pub name: String,
+    pub entry: String,
+    pub language: String,
+    files: Regex,
+    exclude: Option<Regex>,
+    types: Vec<String>,
+    types_or: Vec<String>,
+    exclude_types: Vec<String>,
+    pub args: Vec<String>,
+    stages: Vec<String>,
+    pub pass_filenames: bool,
+    pub always_run: bool,
+    /// Checkout of the hook repository (`script` entries are relative to it)
+    pub repo_dir: PathBuf,
+}
+
+impl PreCommitConfig {
+    /// Load `.pre-commit-config.yaml` from the repository root
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let path = repo_root.join(CONFIG_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yml::from_str(&content).with_context(|| format!("Failed to parse {CONFIG_FILE}"))
+    }
+
+    /// Whether a path (relative to the repository root) passes the global filters
+    pub fn includes(&self, path: &str) -> Result<bool> {
+        let files = Regex::new(&paths::normalize_pattern(&self.files))
+            .context("Invalid top-level 'files' regex")?;
+        if !files.is_match(path) {
+            return Ok(false);
+        }
+        if self.exclude.is_empty() {
+            return Ok(true);
+        }
+        let exclude = Regex::new(&paths::normalize_pattern(&self.exclude))
+            .context("Invalid top-level 'exclude' regex")?;
+        Ok(!exclude.is_match(path))
+    }
+}
+
+impl RepoConfig {
+    /// Hooks defined in the config itself rather than fetched from a repository
+    pub fn is_local(&self) -> bool {
+        self.repo == "local"
+    }
+
+    pub fn is_meta(&self) -> bool {
+        self.repo == "meta"
+    }
+
+    /// Cache directory name for this repository at its pinned revision
+    pub fn cache_name(&self) -> String {
+        let name = self
+            .repo
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .rsplit(['/', ':'])
+            .next()
+            .unwrap_or("repo");
+        let rev = self.rev.as_deref().unwrap_or("HEAD");
+        format!("{name}-{rev}")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    /// Resolve the configured hooks against the repository's manifest (`None` for local hooks)
+    pub fn resolve(
+        &self,
+        manifest: Option<&[HookEntry]>,
+        repo_dir: &Path,
+        default_stages: &[String],
+    ) -> Result<Vec<ResolvedHook>> {
+        self.hooks
+            .iter()
+            .map(|hook| {
+                let merged = match manifest {
+                    Some(manifest) => {
+                        let base = manifest.iter().find(|m| m.id == hook.id).ok_or_else(|| {
+                            anyhow!("Hook '{}' not found in {} manifest", hook.id, self.repo)
+                        })?;
+                        hook.merged_over(base)
+                    }
+                    None => hook.clone(),
+                };
+                merged.resolve(repo_dir, default_stages)
+            })
+            .collect()
+    }
+}
+
+impl HookEntry {
+    /// Parse a hook repository's `.pre-commit-hooks.yaml`
+    pub fn load_manifest(repo_dir: &Path) -> Result<Vec<Self>> {
+        let path = repo_dir.join(MANIFEST_FILE);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_yml::from_str(&content).with_context(|| format!("Failed to parse {MANIFEST_FILE}"))
+    }
+
+    /// Fields set in the config entry win over the manifest's
+    fn merged_over(&self, base: &HookEntry) -> HookEntry {
+        HookEntry {
+            id: self.id.clone(),
+            alias: self.alias.clone().or_else(|| base.alias.clone()),
+            name: self.name.clone().or_else(|| base.name.clone()),
+            entry: self.entry.clone().or_else(|| base.entry.clone()),
+            language: self.language.clone().or_else(|| base.language.clone()),
+            files: self.files.clone().or_else(|| base.files.clone()),
+            exclude: self.exclude.clone().or_else(|| base.exclude.clone()),
+            types: self.types.clone().or_else(|| base.types.clone()),
+            types_or: self.types_or.clone().or_else(|| base.types_or.clone()),
+            exclude_types: self
+                .exclude_types
+                .clone()
+                .or_else(|| base.exclude_types.clone()),
+            args: self.args.clone().or_else(|| base.args.clone()),
+            stages: self.stages.clone().or_else(|| base.stages.clone()),
+            pass_filenames: self.pass_filenames.or(base.pass_filenames),
+            always_run: self.always_run.or(base.always_run),
+        }
+    }
+
+    fn resolve(self, repo_dir: &Path, default_stages: &[String]) -> Result<ResolvedHook> {
+        let entry = self
+            .entry
+            .ok_or_else(|| anyhow!("Hook '{}' has no entry", self.id))?;
+        let files = Regex::new(&paths::normalize_pattern(
+            self.files.as_deref().unwrap_or(""),
+        ))
+        .with_context(|| format!("Invalid 'files' regex for hook '{}'", self.id))?;
+        let exclude = match self.exclude.as_deref() {
+            Some(exclude) if !exclude.is_empty() => Some(
+                Regex::new(&paths::normalize_pattern(exclude))
+                    .with_context(|| format!("Invalid 'exclude' regex for hook '{}'", self.id))?,
+            ),
+            _ => None,
+        };
+        let stages = self
+            .stages
+            .unwrap_or_else(|| default_stages.to_vec())
+            .iter()
+            .map(|stage| normalize_stage(stage).to_string())
+            .collect();
+
+        Ok(ResolvedHook {
+            name: self.name.unwrap_or_else(|| self.id.clone()),
+            id: self.id,
+            alias: self.alias,
+            entry,
+            language: self.language.unwrap_or_else(|| "system".to_string()),
+            files,
+            exclude,
+            types: self.types.unwrap_or_else(|| vec!["file".to_string()]),
+            types_or: self.types_or.unwrap_or_default(),
+            exclude_types: self.exclude_types.unwrap_or_default(),
+            args: self.args.unwrap_or_default(),
+            stages,
+            pass_filenames: self.pass_filenames.unwrap_or(true),
+            always_run: self.always_run.unwrap_or(false),
+            repo_dir: repo_dir.to_path_buf(),
+        })
+    }
+}
+
+/// pre-commit's legacy stage names map onto git hook names
+fn normalize_stage(stage: &str) -> &str {
+    match stage {
+        "commit" => "pre-commit",
+        "push" => "pre-push",
+        "merge-commit" => "pre-merge-commit",
+        other => other,
+    }
+}
+
+impl ResolvedHook {
+    /// Hooks without `stages` run for whichever git hook invokes the builtin
+    pub fn runs_for(&self, hook_name: &str) -> bool {
+        self.stages.is_empty() || self.stages.iter().any(|stage| stage == hook_name)
+    }
+
+    pub fn is_supported_language(&self) -> bool {
+        SUPPORTED_LANGUAGES.contains(&self.language.as_str())
+    }
+
+    /// Filter candidate files (relative to the repository root) by `files`, `exclude` and types
+    pub fn select_files(&self, files: &[PathBuf], repo_root: &Path) -> Vec<PathBuf> {
+        files
+            .iter()
+            .filter(|path| {
+                let name = paths::match_key(path);
+                if !self.files.is_match(&name)
+                    || self.exclude.as_ref().is_some_and(|re| re.is_match(&name))
+                {
+                    return false;
+                }
+
+                let tags = file_tags(&repo_root.join(path));
+                self.types.iter().all(|t| tags.contains(&t.as_str()))
+                    && (self.types_or.is_empty()
+                        || self.types_or.iter().any(|t| tags.contains(&t.as_str())))
+                    && !self
+                        .exclude_types
+                        .iter()
+                        .any(|t| tags.contains(&t.as_str()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Build the command for `files`, run from the repository root.
+    ///
+    /// The entry goes through the shell (entries may contain quoting or arguments);
+    /// hook args and filenames are passed as positional parameters so they are never
+    /// re-split. `fail` hooks have no command.
+    pub fn command(&self, files: &[PathBuf]) -> Option<Command> {
+        let entry = match self.language.as_str() {
+            "fail" => return None,
+            "script" => {
+                let (script, rest) = self.entry.split_once(' ').unwrap_or((&self.entry, ""));
+                format!("{} {rest}", self.repo_dir.join(script).display())
+                    .trim()
+                    .to_string()
+            }
+            _ => self.entry.clone(),
+        };
+
+        let mut params: Vec<String> = self.args.clone();
+        if self.pass_filenames {
+            params.extend(files.iter().map(|f| f.to_string_lossy().to_string()));
+        }
+
+        let command = if cfg!(target_os = "windows") {
+            let mut c = Command::new("cmd");
+            c.arg("/C").arg(entry).args(params);
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c")
+                .arg(format!("{entry} \"$@\""))
+                .arg(&self.id)
+                .args(params);
+            c
+        };
+        Some(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(yaml: &str) -> PreCommitConfig {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_config_overrides_win_over_manifest() {
+        let config = config(
+            r#"
+repos:
+  - repo: https://github.com/pre-commit/pre-commit-hooks.git
+    rev: v4.6.0
+    hooks:
+      - id: trailing-whitespace
+        args: [--markdown-linebreak-ext=md]
+        exclude: ^vendor/
+"#,
+        );
+        let manifest: Vec<HookEntry> = serde_yml::from_str(
+            r#"
+- id: trailing-whitespace
+  name: trim trailing whitespace
+  entry: trailing-whitespace-fixer
+  language: python
+  types: [text]
+  stages: [commit, push, manual]
+"#,
+        )
+        .unwrap();
+
+        let repo = &config.repos[0];
+        assert_eq!(repo.cache_name(), "pre-commit-hooks-v4.6.0");
+
+        let hooks = repo
+            .resolve(Some(&manifest), Path::new("/cache/hooks"), &[])
+            .unwrap();
+        let hook = &hooks[0];
+        assert_eq!(hook.name, "trim trailing whitespace");
+        assert_eq!(hook.args, vec!["--markdown-linebreak-ext=md"]);
+        assert!(hook.runs_for("pre-commit") && hook.runs_for("pre-push"));
+        assert!(!hook.runs_for("commit-msg"));
+        assert!(!hook.is_supported_language());
+
+        assert!(
+            repo.resolve(
+                Some(&[]),
+                Path::new("/cache/hooks"),
+                &["pre-commit".to_string()]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_select_files_by_regex_and_types() {
+        let temp = TempDir::new().unwrap();
+        for (path, content) in [
+            ("src/main.rs", "fn main() {}\n"),
+            ("vendor/lib.rs", "fn lib() {}\n"),
+            ("README.md", "# readme\n"),
+            ("logo.png", "\u{0}\u{1}\u{2}PNG"),
+        ] {
+            let full = temp.path().join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, content).unwrap();
+        }
+        let candidates: Vec<PathBuf> = ["src/main.rs", "vendor/lib.rs", "README.md", "logo.png"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let config = config(
+            r#"
+repos:
+  - repo: local
+    hooks:
+      - id: rustfmt
+        entry: rustfmt --check
+        language: system
+        types: [rust]
+        exclude: ^vendor/
+      - id: text-only
+        entry: "true"
+        language: system
+        types: [text]
+        files: \.(md|rs)$
+"#,
+        );
+        let hooks = config.repos[0]
+            .resolve(None, temp.path(), &config.default_stages)
+            .unwrap();
+
+        assert_eq!(
+            hooks[0].select_files(&candidates, temp.path()),
+            vec![PathBuf::from("src/main.rs")]
+        );
+        assert_eq!(
+            hooks[1].select_files(&candidates, temp.path()),
+            vec![
+                PathBuf::from("src/main.rs"),
+                PathBuf::from("vendor/lib.rs"),
+                PathBuf::from("README.md"),
+            ]
+        );
+        assert!(config.includes("src/main.rs").unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_and_script_commands_receive_args_and_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let script = temp.path().join("check.sh");
+        std::fs::write(&script, "#!/bin/sh\necho \"script $*\"\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = config(
+            r#"
+repos:
+  - repo: local
+    hooks:
+      - id: echo
+        entry: echo "hello world"
+        language: system
+        args: [--flag]
+      - id: script
+        entry: check.sh --verbose
+        language: script
+        pass_filenames: false
+      - id: no-commits-to-main
+        entry: direct commits are not allowed
+        language: fail
+"#,
+        );
+        let hooks = config.repos[0].resolve(None, temp.path(), &[]).unwrap();
+        let files = vec![PathBuf::from("a file.txt")];
+
+        let output = hooks[0].command(&files).unwrap().output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "hello world --flag a file.txt\n"
+        );
+
+        // Script entries resolve against the hook repository, not the working directory
+        let output = hooks[1].command(&files).unwrap().output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "script --verbose\n"
+        );
+
+        assert!(hooks[2].command(&files).is_none());
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use globset::Glob;
+use serde::{Deserialize, Serialize};
+
+use super::push::RefUpdate;
+use crate::config::GuardyConfig;
+
+/// Configuration for the `protect_branches` builtin (`branch_protection` section)
+///
+/// ```yaml
+/// branch_protection:
+///   protected_branches: ["main", "release/*"]
+///   push_policy: lease
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BranchProtectionConfig {
+    /// Remote branch globs that can't be pushed to directly
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+
+    /// Which pushes to a protected branch are let through
+    #[serde(default)]
+    pub push_policy: PushPolicy,
+}
+
+/// Pushes `protect_branches` allows to a protected branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushPolicy {
+    /// None at all - changes go through pull requests
+    #[default]
+    Block,
+    /// Fast-forwards only, never rewriting the remote history
+    FastForward,
+    /// Fast-forwards, and rewrites that only replace commits already fetched: the
+    /// remote branch must still match its remote-tracking ref, as
+    /// `git push --force-with-lease` requires
+    Lease,
+}
+
+/// What is known about a push to a protected branch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PushFacts {
+    /// The remote commit is an ancestor of the pushed one
+    pub fast_forward: bool,
+    /// The remote commit is what the remote-tracking ref says it is
+    pub lease_held: bool,
+}
+
+fn default_protected_branches() -> Vec<String> {
+    vec![
+        "main".to_string(),
+        "master".to_string(),
+        "develop".to_string(),
+    ]
+}
+
+impl Default for BranchProtectionConfig {
+    fn default() -> Self {
+        Self {
+            protected_branches: default_protected_branches(),
+            push_policy: PushPolicy::default(),
+        }
+    }
+}
+
+impl BranchProtectionConfig {
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("branch_protection") {
+            Ok(value) if !value.is_null() => serde_json::from_value(value)
+                .context("Failed to parse branch_protection configuration"),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// The protected branch `update` pushes to, if any
+    pub fn protected_branch<'a>(&self, update: &'a RefUpdate) -> Result<Option<&'a str>> {
+        let Some(branch) = update.remote_ref.strip_prefix("refs/heads/") else {
+            return Ok(None);
+        };
+        for protected in &self.protected_branches {
+            let glob = Glob::new(protected).with_context(|| {
+                format!("Invalid branch_protection.protected_branches glob '{protected}'")
+            })?;
+            if glob.compile_matcher().is_match(branch) {
+                return Ok(Some(branch));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Why a push to a protected branch is rejected, or `None` if the policy allows it
+    pub fn problem(&self, update: &RefUpdate, facts: PushFacts) -> Option<&'static str> {
+        if update.is_delete() {
+            return Some("deleting a protected branch");
+        }
+        match self.push_policy {
+            PushPolicy::Block => Some("direct pushes are blocked"),
+            _ if update.is_new() || facts.fast_forward => None,
+            PushPolicy::FastForward => Some("not a fast-forward"),
+            PushPolicy::Lease if facts.lease_held => None,
+            PushPolicy::Lease => {
+                Some("rewrites commits you haven't fetched - fetch and review them first")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(remote_ref: &str, local: &str, remote: &str) -> RefUpdate {
+        RefUpdate {
+            local_ref: "refs/heads/topic".to_string(),
+            local_commit: local.to_string(),
+            remote_ref: remote_ref.to_string(),
+            remote_commit: remote.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_protected_branch_policies() {
+        let zero = "0".repeat(40);
+        let mut config = BranchProtectionConfig {
+            protected_branches: vec!["main".to_string(), "release/*".to_string()],
+            ..Default::default()
+        };
+        let rewrite = update("refs/heads/release/1.2", "bbb", "aaa");
+        assert_eq!(
+            config.protected_branch(&rewrite).unwrap(),
+            Some("release/1.2")
+        );
+        assert_eq!(
+            config
+                .protected_branch(&update("refs/heads/feature/x", "bbb", "aaa"))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            config.problem(&rewrite, PushFacts::default()),
+            Some("direct pushes are blocked")
+        );
+
+        config.push_policy = PushPolicy::FastForward;
+        let fast_forward = PushFacts {
+            fast_forward: true,
+            lease_held: true,
+        };
+        assert_eq!(config.problem(&rewrite, fast_forward), None);
+        assert_eq!(
+            config.problem(&rewrite, PushFacts::default()),
+            Some("not a fast-forward")
+        );
+
+        config.push_policy = PushPolicy::Lease;
+        let leased = PushFacts {
+            fast_forward: false,
+            lease_held: true,
+        };
+        assert_eq!(config.problem(&rewrite, leased), None);
+        assert!(config.problem(&rewrite, PushFacts::default()).is_some());
+        assert_eq!(
+            config.problem(&update("refs/heads/main", &zero, "aaa"), leased),
+            Some("deleting a protected branch")
+        );
+    }
+}
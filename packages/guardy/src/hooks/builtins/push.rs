@@ -0,0 +1,49 @@
+/// A ref the pre-push hook was told about on standard input
+/// (`<local ref> <local sha> <remote ref> <remote sha>` per line)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefUpdate {
+    pub local_ref: String,
+    pub local_commit: String,
+    pub remote_ref: String,
+    pub remote_commit: String,
+}
+
+impl RefUpdate {
+    /// Refs being pushed, ignoring malformed lines
+    pub fn parse_all(input: &str) -> Vec<Self> {
+        input
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let [local_ref, local_commit, remote_ref, remote_commit] = fields[..] else {
+                    return None;
+                };
+                Some(Self {
+                    local_ref: local_ref.to_string(),
+                    local_commit: local_commit.to_string(),
+                    remote_ref: remote_ref.to_string(),
+                    remote_commit: remote_commit.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// The push deletes the remote ref
+    pub fn is_delete(&self) -> bool {
+        is_zero(&self.local_commit)
+    }
+
+    /// The remote ref does not exist yet
+    pub fn is_new(&self) -> bool {
+        is_zero(&self.remote_commit)
+    }
+
+    /// Local branch name being pushed, for `refs/heads/*` refs
+    pub fn branch(&self) -> Option<&str> {
+        self.local_ref.strip_prefix("refs/heads/")
+    }
+}
+
+fn is_zero(commit: &str) -> bool {
+    commit.chars().all(|c| c == '0')
+}
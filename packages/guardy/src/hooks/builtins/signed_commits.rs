@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::GuardyConfig;
+use crate::git::signatures::CommitSignature;
+
+use super::push::RefUpdate;
+
+/// Configuration for the `require_signed_commits` builtin (`signed_commits` section)
+///
+/// ```yaml
+/// signed_commits:
+///   allowed_signers: ["jane@example.com", "SHA256:hH1Dn..."]
+///   allowed_signers_file: .github/allowed_signers   # Verifies SSH signatures
+///   trusted_only: false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SignedCommitsConfig {
+    /// Emails, signer identities, key ids or fingerprints allowed to sign pushed
+    /// commits (empty accepts any valid signature)
+    #[serde(default)]
+    pub allowed_signers: Vec<String>,
+
+    /// SSH allowed signers file (relative to the repository root), used instead
+    /// of git's `gpg.ssh.allowedSignersFile`
+    #[serde(default)]
+    pub allowed_signers_file: Option<PathBuf>,
+
+    /// Reject good GPG signatures whose key is not trusted in the local keyring
+    #[serde(default)]
+    pub trusted_only: bool,
+}
+
+impl SignedCommitsConfig {
+    pub fn from_config(config: &GuardyConfig) -> Result<Self> {
+        match config.get_section("signed_commits") {
+            Ok(value) if !value.is_null() => serde_json::from_value(value)
+                .context("Failed to parse signed_commits configuration"),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Why `signature` is not acceptable, or `None` when the commit may be pushed
+    pub fn problem(&self, signature: &CommitSignature) -> Option<String> {
+        let verdict = match signature.status {
+            'G' => None,
+            'U' if self.trusted_only => Some("signed with an untrusted key"),
+            'U' => None,
+            'N' => Some("not signed"),
+            'B' => Some("bad signature"),
+            'X' => Some("signature has expired"),
+            'Y' => Some("signed with an expired key"),
+            'R' => Some("signed with a revoked key"),
+            _ => Some("signature cannot be verified (unknown key or no allowed signers file)"),
+        };
+        if let Some(verdict) = verdict {
+            return Some(verdict.to_string());
+        }
+
+        if self.allowed_signers.is_empty()
+            || self
+                .allowed_signers
+                .iter()
+                .any(|allowed| signed_by(signature, allowed))
+        {
+            None
+        } else {
+            Some(format!(
+                "signer '{}' is not allowed",
+                signer_name(signature)
+            ))
+        }
+    }
+}
+
+/// Revisions of the commits a push sends
+///
+/// Deleted refs send nothing; new branches send what no branch of `remote` has
+/// yet. Without updates (a manual `guardy run pre-push`) every commit missing from
+/// the remotes is checked.
+pub fn pushed_revisions(updates: &[RefUpdate], remote: Option<&str>) -> Vec<Vec<String>> {
+    // A push to a URL rather than a named remote compares against every remote
+    let remotes = match remote {
+        Some(name) if !name.contains(['/', ':']) => format!("--remotes={name}"),
+        _ => "--remotes".to_string(),
+    };
+    let unpushed = |commit: &str| vec![commit.to_string(), "--not".to_string(), remotes.clone()];
+
+    if updates.is_empty() {
+        return vec![unpushed("HEAD")];
+    }
+    updates
+        .iter()
+        .filter(|update| !update.is_delete())
+        .map(|update| {
+            if update.is_new() {
+                unpushed(&update.local_commit)
+            } else {
+                vec![format!("{}..{}", update.remote_commit, update.local_commit)]
+            }
+        })
+        .collect()
+}
+
+fn signed_by(signature: &CommitSignature, allowed: &str) -> bool {
+    [&signature.key, &signature.fingerprint, &signature.signer]
+        .iter()
+        .any(|value| !value.is_empty() && value.eq_ignore_ascii_case(allowed))
+        || signature
+            .signer
+            .to_lowercase()
+            .contains(&format!("<{}>", allowed.to_lowercase()))
+}
+
+fn signer_name(signature: &CommitSignature) -> &str {
+    [&signature.signer, &signature.fingerprint, &signature.key]
+        .into_iter()
+        .find(|value| !value.is_empty())
+        .map_or("unknown", String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(status: char, signer: &str, key: &str) -> CommitSignature {
+        CommitSignature {
+            commit: "4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string(),
+            subject: "feat: add login".to_string(),
+            status,
+            signer: signer.to_string(),
+            key: key.to_string(),
+            fingerprint: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_pushed_revisions_from_hook_input() {
+        let zero = "0".repeat(40);
+        let input = format!(
+            "refs/heads/main aaa refs/heads/main bbb\n\
+             refs/heads/feature ccc refs/heads/feature {zero}\n\
+             (delete) {zero} refs/heads/old ddd\n"
+        );
+
+        assert_eq!(
+            pushed_revisions(&RefUpdate::parse_all(&input), Some("origin")),
+            vec![
+                vec!["bbb..aaa".to_string()],
+                vec![
+                    "ccc".to_string(),
+                    "--not".to_string(),
+                    "--remotes=origin".to_string()
+                ],
+            ]
+        );
+        assert_eq!(
+            pushed_revisions(&[], Some("git@github.com:acme/api.git")),
+            vec![vec![
+                "HEAD".to_string(),
+                "--not".to_string(),
+                "--remotes".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_problem_checks_status_and_allowed_signers() {
+        let mut config = SignedCommitsConfig::default();
+        let gpg = signature('G', "Jane Doe <Jane@Example.com>", "ABCD1234");
+
+        assert_eq!(config.problem(&gpg), None);
+        assert_eq!(
+            config.problem(&signature('N', "", "")).as_deref(),
+            Some("not signed")
+        );
+        assert_eq!(config.problem(&signature('U', "", "ABCD1234")), None);
+
+        config.trusted_only = true;
+        assert!(config.problem(&signature('U', "", "ABCD1234")).is_some());
+
+        config.allowed_signers = vec!["jane@example.com".to_string()];
+        assert_eq!(config.problem(&gpg), None);
+        config.allowed_signers = vec!["abcd1234".to_string()];
+        assert_eq!(config.problem(&gpg), None);
+        config.allowed_signers = vec!["bob@example.com".to_string()];
+        assert_eq!(
+            config.problem(&gpg).as_deref(),
+            Some("signer 'Jane Doe <Jane@Example.com>' is not allowed")
+        );
+    }
+}
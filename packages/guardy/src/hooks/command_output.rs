@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+
+use crate::cli::output;
+
+/// How the output of custom commands is shown (`output:` on a hook)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Print each line live, prefixed with the command name
+    Stream,
+    /// Print a command's output as one block once it finishes
+    Group,
+    /// Only show output (stderr) when a command fails
+    #[default]
+    Quiet,
+}
+
+impl OutputMode {
+    pub fn is_quiet(&self) -> bool {
+        *self == Self::Quiet
+    }
+}
+
+/// Run `command` to completion, capturing its output and showing it per `mode`
+pub fn run(command: &mut Command, label: &str, mode: OutputMode) -> io::Result<Output> {
+    let output = match mode {
+        OutputMode::Stream => stream(command, label)?,
+        OutputMode::Group | OutputMode::Quiet => command.output()?,
+    };
+
+    if mode == OutputMode::Group {
+        print_group(label, &output);
+    }
+    Ok(output)
+}
+
+fn stream(command: &mut Command, label: &str) -> io::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().map(|pipe| forward(pipe, label));
+    let stderr = child.stderr.take().map(|pipe| forward(pipe, label));
+    let status = child.wait()?;
+
+    let collect = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        reader
+            .map(|handle| handle.join().unwrap_or_default())
+            .unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Echo each line as `[label] line` while keeping a copy of everything read
+fn forward<R: Read + Send + 'static>(pipe: R, label: &str) -> thread::JoinHandle<Vec<u8>> {
+    let prefix = format!("[{label}]");
+    thread::spawn(move || {
+        let mut captured = Vec::new();
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+            let text = String::from_utf8_lossy(&line);
+            output::styled!(
+                "{} {}",
+                (&prefix, "muted"),
+                (text.trim_end_matches(['\n', '\r']), "primary")
+            );
+            captured.append(&mut line);
+        }
+        captured
+    })
+}
+
+fn print_group(label: &str, output: &Output) {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stdout.trim().is_empty() && stderr.trim().is_empty() {
+        return;
+    }
+
+    // Stdout's lock is re-entrant: holding it keeps other commands from interleaving
+    let _stdout = io::stdout().lock();
+    output::styled!("{}", (format!("┌─ {label}"), "muted"));
+    for line in stdout.lines().chain(stderr.lines()) {
+        output::styled!("{} {}", ("│", "muted"), (line, "primary"));
+    }
+    output::styled!("{}", ("└─", "muted"));
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_captures_both_pipes() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo out; echo err >&2; exit 3"]);
+
+        let output = run(&mut command, "check", OutputMode::Stream).unwrap();
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+}
@@ -1,5 +1,25 @@
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use super::command_output::OutputMode;
+use super::user_hooks;
+use crate::config::{GuardyConfig, deprecations};
+
+/// Git hooks guardy knows how to install, report on and uninstall
+pub const SUPPORTED_HOOKS: &[&str] = &[
+    "applypatch-msg",
+    "pre-applypatch",
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+];
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HookConfig {
@@ -7,6 +27,20 @@ pub struct HookConfig {
     pub hooks: HashMap<String, HookDefinition>,
 }
 
+impl HookConfig {
+    /// The `hooks:` section of the loaded config, with personal hooks from
+    /// `~/.config/guardy/hooks.yaml` merged in
+    pub fn load(config: &GuardyConfig) -> Result<Self> {
+        let mut section = config.get_section("hooks")?;
+        deprecations::migrate_hooks(&mut section);
+        let mut hooks: Self = serde_json::from_value(section)?;
+        if let Some(user) = user_hooks::load()? {
+            user_hooks::merge(&mut hooks, user);
+        }
+        Ok(hooks)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HookDefinition {
     #[serde(default = "default_enabled")]
@@ -15,7 +49,16 @@ pub struct HookDefinition {
     #[serde(default = "default_parallel")]
     pub parallel: bool,
 
-    #[serde(default)]
+    /// How custom command output is shown: `stream`, `group` or `quiet`
+    #[serde(default, skip_serializing_if = "OutputMode::is_quiet")]
+    pub output: OutputMode,
+
+    /// Revert unstaged changes while the hook runs, so checks see only the staged
+    /// content (`pre-commit` only)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub stash_unstaged: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub builtin: Vec<String>,
 
     #[serde(default)]
@@ -26,20 +69,97 @@ pub struct HookDefinition {
 pub struct CustomCommand {
     pub command: String,
 
+    /// Short identifier used to skip the command at runtime (`GUARDY_SKIP=<name>`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
     #[serde(default)]
     pub description: String,
 
     #[serde(default = "default_fail_on_error")]
     pub fail_on_error: bool,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub all_files: bool,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub glob: Vec<String>,
 
-    #[serde(default)]
+    /// Only pass files in these languages (`rust`, `typescript`, ...), detected from
+    /// the file name, extension or shebang - combined with `glob` when both are set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
     pub stage_fixed: bool,
+
+    /// Run once per affected workspace package instead of once overall, with
+    /// `{package_dir}` and `{files}` limited to that package
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub workspaces: bool,
+
+    /// Commands (by `name`, or `description` when unnamed) that must finish first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// Extra attempts after a failure, for flaky (usually network-bound) commands
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for each further one
+    #[serde(
+        default = "default_retry_backoff_ms",
+        skip_serializing_if = "is_default_retry_backoff"
+    )]
+    pub retry_backoff_ms: u64,
+
+    /// Only retry failures matching these conditions (any failure when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_on: Option<RetryOn>,
+
+    /// Environment variables for the command; values expand `${VAR}` and
+    /// `${VAR:-default}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Dotenv files (relative to the repository root) loaded before `env`;
+    /// missing files are skipped
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_file: Vec<PathBuf>,
+}
+
+impl CustomCommand {
+    /// A command with every option at its default
+    pub fn new(command: String, name: Option<String>, description: String) -> Self {
+        Self {
+            command,
+            name,
+            description,
+            fail_on_error: default_fail_on_error(),
+            all_files: false,
+            glob: vec![],
+            languages: vec![],
+            stage_fixed: false,
+            workspaces: false,
+            depends_on: vec![],
+            retries: 0,
+            retry_backoff_ms: default_retry_backoff_ms(),
+            retry_on: None,
+            env: BTreeMap::new(),
+            env_file: vec![],
+        }
+    }
+}
+
+/// Failures worth retrying - a match on either condition triggers a retry
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RetryOn {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exit_codes: Vec<i32>,
+
+    /// Regex matched against the command's stdout and stderr
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
 }
 
 fn default_enabled() -> bool {
@@ -54,6 +174,22 @@ fn default_fail_on_error() -> bool {
     true
 }
 
+fn default_retry_backoff_ms() -> u64 {
+    1000
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn is_default_retry_backoff(value: &u64) -> bool {
+    *value == default_retry_backoff_ms()
+}
+
 impl Default for HookConfig {
     fn default() -> Self {
         let mut hooks = HashMap::new();
@@ -64,6 +200,8 @@ impl Default for HookConfig {
             HookDefinition {
                 enabled: true,
                 parallel: true,
+                output: OutputMode::default(),
+                stash_unstaged: false,
                 builtin: vec!["scan_secrets".to_string()],
                 custom: vec![],
             },
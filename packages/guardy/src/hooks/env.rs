@@ -0,0 +1,167 @@
+use anyhow::{Context, Result, anyhow};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use super::config::CustomCommand;
+
+static REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?::-([^}]*))?\}").unwrap());
+
+/// Variables to set for `cmd`: its `env_file`s (relative to `root`, missing ones
+/// skipped), then its `env` map
+///
+/// Env files follow the dotenv convention and never override variables already
+/// set in guardy's environment; `env` entries always do. Values expand `${VAR}`
+/// and `${VAR:-default}` from guardy's environment and the env files - `env`
+/// entries can't reference each other, so their order doesn't matter.
+pub fn command_env(cmd: &CustomCommand, root: &Path) -> Result<BTreeMap<String, String>> {
+    resolve(cmd, root, |name| std::env::var(name).ok())
+}
+
+fn resolve(
+    cmd: &CustomCommand,
+    root: &Path,
+    process: impl Fn(&str) -> Option<String>,
+) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for file in &cmd.env_file {
+        let path = root.join(file);
+        if !path.exists() {
+            continue; // e.g. a gitignored .env that only exists on developer machines
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read env file {}", path.display()))?;
+        for (name, value, expand_value) in parse_dotenv(&content)
+            .with_context(|| format!("Invalid env file {}", path.display()))?
+        {
+            if process(&name).is_some() {
+                continue;
+            }
+            let value = if expand_value {
+                expand(&value, |var| {
+                    vars.get(var).cloned().or_else(|| process(var))
+                })
+            } else {
+                value
+            };
+            vars.insert(name, value);
+        }
+    }
+
+    let expanded: Vec<(String, String)> = cmd
+        .env
+        .iter()
+        .map(|(name, value)| {
+            let value = expand(value, |var| vars.get(var).cloned().or_else(|| process(var)));
+            (name.clone(), value)
+        })
+        .collect();
+    vars.extend(expanded);
+    Ok(vars)
+}
+
+/// `${VAR}` and `${VAR:-default}` references in `value` replaced via `lookup`;
+/// unset variables without a default expand to nothing
+fn expand(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    REFERENCE
+        .replace_all(value, |captures: &regex::Captures| {
+            lookup(&captures[1])
+                .filter(|value| !value.is_empty() || captures.get(2).is_none())
+                .or_else(|| captures.get(2).map(|default| default.as_str().to_string()))
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// `(name, value, expand)` entries of a dotenv file: `KEY=value` lines, optionally
+/// prefixed with `export`, with `#` comments and single or double quotes.
+/// Single-quoted values are taken literally.
+fn parse_dotenv(content: &str) -> Result<Vec<(String, String, bool)>> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("line {}: expected KEY=value", index + 1))?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(anyhow!(
+                "line {}: invalid variable name '{name}'",
+                index + 1
+            ));
+        }
+
+        let value = value.trim();
+        let entry = if let Some(quoted) = value.strip_prefix('\'') {
+            let end = quoted
+                .find('\'')
+                .ok_or_else(|| anyhow!("line {}: unterminated quote", index + 1))?;
+            (quoted[..end].to_string(), false)
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            let end = quoted
+                .find('"')
+                .ok_or_else(|| anyhow!("line {}: unterminated quote", index + 1))?;
+            (quoted[..end].replace("\\n", "\n"), true)
+        } else {
+            // Unquoted values end at an inline comment
+            let value = value.split(" #").next().unwrap_or_default().trim_end();
+            (value.to_string(), true)
+        };
+        entries.push((name.to_string(), entry.0, entry.1));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = "# Test database\nexport DATABASE_URL=\"postgres://localhost/${DB_NAME}\"\nDB_NAME=app_test # local only\nLITERAL='${HOME}'\n\n";
+
+        assert_eq!(
+            parse_dotenv(content).unwrap(),
+            vec![
+                (
+                    "DATABASE_URL".to_string(),
+                    "postgres://localhost/${DB_NAME}".to_string(),
+                    true
+                ),
+                ("DB_NAME".to_string(), "app_test".to_string(), true),
+                ("LITERAL".to_string(), "${HOME}".to_string(), false),
+            ]
+        );
+        assert!(parse_dotenv("NOT A VARIABLE\n").is_err());
+    }
+
+    #[test]
+    fn test_resolve_layers_env_files_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".env.test"),
+            "DB_NAME=app_test\nDATABASE_URL=postgres://${DB_HOST:-localhost}/${DB_NAME}\nCI=false\n",
+        )
+        .unwrap();
+
+        let mut cmd = CustomCommand::new("cargo test".into(), None, "test".into());
+        cmd.env_file = vec![".env.test".into(), ".env.missing".into()];
+        cmd.env
+            .insert("RUST_LOG".into(), "${LOG_LEVEL:-info}".into());
+        cmd.env.insert("TEST_DB".into(), "${DATABASE_URL}".into());
+
+        let process = |name: &str| (name == "CI").then(|| "true".to_string());
+        let vars = resolve(&cmd, dir.path(), process).unwrap();
+
+        assert_eq!(vars["TEST_DB"], "postgres://localhost/app_test");
+        assert_eq!(vars["RUST_LOG"], "info");
+        // Already set in the process environment - the file doesn't override it
+        assert!(!vars.contains_key("CI"));
+    }
+}
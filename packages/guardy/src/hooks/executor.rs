@@ -1,27 +1,122 @@
 use anyhow::{Context, Result, anyhow};
 use globset::{Glob, GlobSet, GlobSetBuilder};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
 
 use crate::cli::output;
 use crate::config::GuardyConfig;
+use crate::events::{self, Event};
 use crate::git::GitRepo;
+use crate::git::remote::RemoteOperations;
+use crate::git::staged::StagedStatus;
+use crate::parallel::ExecutionStrategy;
+use crate::profiling::{ProfilingConfig, WorkloadProfiler};
 use crate::scanner::Scanner;
-
-use super::config::{CustomCommand, HookConfig};
+use crate::scanner::types::SubmoduleMode;
+use crate::shared::atomic_io::{self, LockFile};
+use crate::shared::paths;
+
+use super::builtins::{
+    BranchNamingConfig, BranchProtectionConfig, CommitTemplateConfig, HookEntry, LargeFilesConfig,
+    MergeConflictsConfig, PRE_COMMIT_CONFIG_FILE, PackageManagerConfig, PreCommitConfig, PushFacts,
+    RefUpdate, RepoConfig, ResolvedHook, SignedCommitsConfig, conflict_markers, lfs_pattern,
+    pushed_revisions,
+};
+use super::command_output::{self, OutputMode};
+use super::config::{CustomCommand, HookConfig, HookDefinition};
+use super::env;
+use super::graph::{CommandGraph, command_id};
+use super::languages;
+use super::placeholders::{self, FileLists, FileSource};
+use super::retry::RetryPolicy;
+use super::skip::SkipList;
+use super::stash::UnstagedStash;
+use super::timing::{HookReport, HookTimings, StepKind, StepOutcome};
+use super::workspaces::Workspace;
 
 pub struct HookExecutor {
     config: GuardyConfig,
+    /// Fixed hook definitions (a replayed session) instead of loading them
+    hooks: Option<HookConfig>,
+    /// Refs being pushed, read once from the pre-push hook's standard input
+    push_updates: OnceLock<Vec<RefUpdate>>,
 }
 
 impl HookExecutor {
     pub fn new(config: GuardyConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            hooks: None,
+            push_updates: OnceLock::new(),
+        }
+    }
+
+    /// Run exactly `hooks`, ignoring the config's `hooks:` section and personal hooks
+    pub(super) fn with_hooks(config: GuardyConfig, hooks: HookConfig) -> Self {
+        Self {
+            config,
+            hooks: Some(hooks),
+            push_updates: OnceLock::new(),
+        }
+    }
+
+    pub(super) fn config(&self) -> &GuardyConfig {
+        &self.config
+    }
+
+    /// Hook definitions in effect, personal hooks included
+    pub(super) fn hooks(&self) -> Result<HookConfig> {
+        match &self.hooks {
+            Some(hooks) => Ok(hooks.clone()),
+            None => HookConfig::load(&self.config),
+        }
     }
 
     pub async fn execute(&self, hook_name: &str, args: &[String]) -> Result<()> {
-        let hook_config_value = self.config.get_section("hooks")?;
-        let hook_config: HookConfig = serde_json::from_value(hook_config_value)?;
+        let timings = HookTimings::default();
+        let started = Instant::now();
+        let mut skipped = Vec::new();
+
+        let result = self
+            .run_steps(hook_name, args, &timings, &mut skipped)
+            .await;
+        timings.print_summary(started.elapsed());
+        result
+    }
+
+    /// Run the hook without printing progress and return its timings and outcome,
+    /// for `guardy run <hook> --format json`
+    pub async fn report(&self, hook_name: &str, args: &[String]) -> HookReport {
+        let timings = HookTimings::default();
+        let started = Instant::now();
+        let mut skipped = Vec::new();
+
+        let result = self
+            .run_steps(hook_name, args, &timings, &mut skipped)
+            .await;
+
+        HookReport {
+            hook: hook_name.to_string(),
+            success: result.is_ok(),
+            error: result.err().map(|e| format!("{e:#}")),
+            total: started.elapsed(),
+            steps: timings.steps(),
+            skipped,
+        }
+    }
+
+    async fn run_steps(
+        &self,
+        hook_name: &str,
+        args: &[String],
+        timings: &HookTimings,
+        skipped: &mut Vec<String>,
+    ) -> Result<()> {
+        let hook_config = self.hooks()?;
 
         let hook = hook_config
             .hooks
@@ -33,40 +128,285 @@ impl HookExecutor {
             return Ok(());
         }
 
-        output::info!(&format!("Executing {hook_name} hook..."));
+        let skip = SkipList::from_env();
+        if skip.contains(hook_name) {
+            output::warning!(&format!("⏭ Skipping {hook_name} hook ({})", skip.source()));
+            skipped.push(hook_name.to_string());
+            return Ok(());
+        }
+
+        let section = output::section!(&format!("Running {hook_name} hook"));
+        let stash = match hook_name {
+            "pre-commit" if hook.stash_unstaged => {
+                GitRepo::discover().and_then(|repo| UnstagedStash::save(&repo))
+            }
+            _ => Ok(None),
+        };
+        let result = match stash {
+            Ok(stash) => {
+                let result = self
+                    .run_hook_steps(hook, hook_name, args, &skip, timings, skipped)
+                    .await;
+                // Restored whatever the outcome; a failed restore still gets reported
+                // when the hook itself failed
+                match stash {
+                    Some(stash) => {
+                        let restored = stash.restore();
+                        if let (Err(_), Err(e)) = (&result, &restored) {
+                            output::error!(&format!("{e:#}"));
+                        }
+                        result.and(restored)
+                    }
+                    None => result,
+                }
+            }
+            Err(e) => Err(e),
+        };
+        section.finish(result.is_ok());
+        result
+    }
+
+    /// Builtins then custom commands of an enabled hook, each reported as a step
+    async fn run_hook_steps(
+        &self,
+        hook: &HookDefinition,
+        hook_name: &str,
+        args: &[String],
+        skip: &SkipList,
+        timings: &HookTimings,
+        skipped: &mut Vec<String>,
+    ) -> Result<()> {
+        let mut builtins = Vec::new();
+        for builtin in &hook.builtin {
+            if skip.contains(builtin) {
+                skipped.push(builtin.clone());
+            } else if builtin_applies(builtin, hook_name, args) {
+                builtins.push(builtin);
+            }
+        }
+
+        let (skipped_custom, custom): (Vec<CustomCommand>, Vec<CustomCommand>) = hook
+            .custom
+            .iter()
+            .cloned()
+            .partition(|cmd| skip.skips_command(cmd));
+        skipped.extend(
+            skipped_custom
+                .into_iter()
+                .map(|cmd| cmd.name.unwrap_or(cmd.description)),
+        );
+
+        for name in skipped.iter() {
+            output::warning!(&format!("⏭ Skipped {name} ({})", skip.source()));
+        }
+
+        let steps = Steps {
+            first: builtins.len() + 1,
+            total: builtins.len() + custom.len(),
+        };
 
         // Execute builtin commands
+        for (index, builtin) in builtins.into_iter().enumerate() {
+            let step = output::step!(index + 1, steps.total, builtin);
+            let result = self.execute_builtin(builtin, hook_name, args).await;
+            let outcome = if result.is_ok() {
+                StepOutcome::Passed
+            } else {
+                StepOutcome::Failed
+            };
+            timings.record(builtin, StepKind::Builtin, outcome, 1, step.elapsed());
+            step.finish(result.is_ok());
+            result?;
+        }
+
+        // Execute custom commands in dependency order
+        self.execute_custom(&custom, skipped, hook, hook_name, timings, steps)
+            .await
+    }
+
+    /// Print what `execute` would do for this hook - builtins, custom commands with
+    /// their resolved files, and the execution strategy - without running anything
+    pub fn plan(&self, hook_name: &str, args: &[String]) -> Result<()> {
+        let hook_config = self.hooks()?;
+
+        let hook = hook_config
+            .hooks
+            .get(hook_name)
+            .ok_or_else(|| anyhow!("Hook '{}' not found in configuration", hook_name))?;
+
+        output::styled!(
+            "{} Plan for {} hook (nothing will be executed)",
+            ("ℹ", "info_symbol"),
+            (hook_name, "property")
+        );
+
+        if !hook.enabled {
+            output::info!(&format!(
+                "Hook '{hook_name}' is disabled - nothing would run"
+            ));
+            return Ok(());
+        }
+
+        let skip = SkipList::from_env();
+        if skip.contains(hook_name) {
+            output::warning!(&format!(
+                "⏭ Whole hook skipped via {} - nothing would run",
+                skip.source()
+            ));
+            return Ok(());
+        }
+
+        println!();
+        output::styled!("{}", ("Builtins (run first, in order):", "property"));
+        if hook.builtin.is_empty() {
+            output::styled!("  {}", ("none", "muted"));
+        }
         for builtin in &hook.builtin {
-            self.execute_builtin(builtin, hook_name, args).await?;
+            if skip.contains(builtin) {
+                output::styled!(
+                    "  {} {} {}",
+                    ("⏭", "warning_symbol"),
+                    (builtin, "id"),
+                    (format!("(skipped via {})", skip.source()), "muted")
+                );
+            } else if !BUILTINS.contains(&builtin.as_str()) {
+                output::styled!(
+                    "  {} {} {}",
+                    ("⚠", "warning_symbol"),
+                    (builtin, "id"),
+                    ("(unknown builtin)", "muted")
+                );
+            } else if builtin_applies(builtin, hook_name, args) {
+                output::styled!("  {} {}", ("✓", "success_symbol"), (builtin, "id"));
+            } else {
+                output::styled!(
+                    "  {} {} {}",
+                    ("-", "muted"),
+                    (builtin, "id"),
+                    (
+                        format!("(does not apply to {hook_name} with these arguments)"),
+                        "muted"
+                    )
+                );
+            }
+        }
+
+        let (skipped, custom): (Vec<&CustomCommand>, Vec<&CustomCommand>) =
+            hook.custom.iter().partition(|cmd| skip.skips_command(cmd));
+
+        println!();
+        output::styled!("{}", ("Custom commands:", "property"));
+        if hook.custom.is_empty() {
+            output::styled!("  {}", ("none", "muted"));
+        }
+        let repo_root = GitRepo::discover().ok().map(|repo| repo.path);
+        for cmd in &custom {
+            output::styled!(
+                "  {} {}: {}",
+                ("✓", "success_symbol"),
+                (&cmd.description, "id"),
+                (&cmd.command, "muted")
+            );
+            if cmd.retries > 0 {
+                output::styled!(
+                    "      {} {}",
+                    ("retries:", "number"),
+                    (
+                        format!(
+                            "{} (backoff from {})",
+                            cmd.retries,
+                            output::human_duration(Duration::from_millis(cmd.retry_backoff_ms))
+                        ),
+                        "muted"
+                    )
+                );
+            }
+            if !cmd.depends_on.is_empty() {
+                output::styled!(
+                    "      {} {}",
+                    ("after:", "number"),
+                    (cmd.depends_on.join(", "), "id")
+                );
+            }
+            match files_for_command(cmd, hook_name) {
+                Ok(files) => {
+                    output::styled!("      {}", (format!("files ({}):", files.len()), "number"));
+                    for file in &files {
+                        let shown = repo_root
+                            .as_deref()
+                            .and_then(|root| file.strip_prefix(root).ok())
+                            .unwrap_or(file);
+                        output::styled!("        {}", (paths::display(shown), "file_path"));
+                    }
+                    if cmd.workspaces {
+                        print_planned_packages(repo_root.as_deref(), &files);
+                    }
+                }
+                Err(e) => output::styled!("      {}", (format!("would fail: {e}"), "warning")),
+            }
+        }
+        for cmd in &skipped {
+            output::styled!(
+                "  {} {} {}",
+                ("⏭", "warning_symbol"),
+                (cmd.name.as_deref().unwrap_or(&cmd.description), "id"),
+                (format!("(skipped via {})", skip.source()), "muted")
+            );
         }
 
-        // Execute custom commands - either in parallel or sequentially
-        if hook.parallel {
-            self.execute_custom_parallel(&hook.custom, hook_name)
-                .await?;
+        println!();
+        let mut strategy = if !hook.parallel {
+            "sequential (parallel: false)".to_string()
         } else {
-            self.execute_custom_sequential(&hook.custom, hook_name)
-                .await?;
+            match parallel_strategy(custom.len()) {
+                ExecutionStrategy::Sequential => {
+                    "sequential (too few commands to parallelize)".to_string()
+                }
+                ExecutionStrategy::Parallel { workers } => {
+                    format!("parallel (max {workers} concurrent)")
+                }
+            }
+        };
+        let custom: Vec<CustomCommand> = custom.into_iter().cloned().collect();
+        let skipped: Vec<String> = skipped
+            .iter()
+            .map(|cmd| command_id(cmd).to_string())
+            .collect();
+        match CommandGraph::build(&custom, &skipped) {
+            Ok(graph) if graph.has_dependencies() => strategy.push_str(", respecting depends_on"),
+            Ok(_) => {}
+            Err(e) => strategy = format!("would fail: {e}"),
         }
+        output::styled!("{} {}", ("Execution:", "property"), (strategy, "symbol"));
 
-        output::success!("Hook execution completed!");
         Ok(())
     }
 
     async fn execute_builtin(&self, builtin: &str, hook_name: &str, args: &[String]) -> Result<()> {
+        if !builtin_applies(builtin, hook_name, args) {
+            return Ok(());
+        }
+
         match builtin {
-            "scan_secrets" => {
-                if hook_name != "pre-commit" {
-                    return Ok(()); // Only valid for pre-commit
-                }
-                self.scan_secrets().await
-            }
-            "validate_commit_msg" => {
-                if hook_name != "commit-msg" || args.is_empty() {
-                    return Ok(()); // Only valid for commit-msg with args
-                }
-                self.validate_commit_msg(&args[0]).await
+            "scan_secrets" => match hook_name {
+                "commit-msg" | "applypatch-msg" => self.scan_commit_message(&args[0]).await,
+                _ => self.scan_secrets().await,
+            },
+            "validate_commit_msg" => self.validate_commit_msg(&args[0]).await,
+            "commit_template" => {
+                self.apply_commit_template(&args[0], args.get(1).map(String::as_str))
+                    .await
             }
+            "install_dependencies" => match hook_name {
+                "post-merge" => self.install_dependencies("ORIG_HEAD", "HEAD").await,
+                _ => self.install_dependencies(&args[0], &args[1]).await,
+            },
+            "pre_commit_config" => self.run_pre_commit_config(hook_name, args).await,
+            "require_signed_commits" => self.require_signed_commits(args).await,
+            "protect_branches" => self.protect_branches(args).await,
+            "validate_branch_name" => self.validate_branch_name(hook_name).await,
+            "check_merge_conflicts" => self.check_merge_conflicts().await,
+            "block_large_files" => self.block_large_files().await,
             unknown => {
                 output::warning!(&format!("Unknown builtin command: {unknown}"));
                 Ok(())
@@ -78,14 +418,38 @@ impl HookExecutor {
         output::info!("Scanning for secrets...");
 
         let repo = GitRepo::discover()?;
-        let staged_files = repo.get_staged_files()?;
+        let scanner = Scanner::new(&self.config)?;
+        let mut staged_files = repo.get_staged_files()?;
+
+        // A staged submodule is a commit, not a file: the files that commit changes
+        // inside the submodule are scanned instead
+        for submodule in repo.submodules()? {
+            let dir = repo.path.join(&submodule.path);
+            let Some(position) = staged_files.iter().position(|file| *file == dir) else {
+                continue;
+            };
+            staged_files.remove(position);
+            let name = paths::display(&submodule.path);
+            match scanner.config.submodules {
+                SubmoduleMode::Skip => output::info!(&format!(
+                    "Skipped submodule {name} (scanner.submodules: skip)"
+                )),
+                SubmoduleMode::Recurse if !submodule.checked_out => output::warning!(&format!(
+                    "Submodule {name} is not checked out - its changes were not scanned"
+                )),
+                SubmoduleMode::Recurse => staged_files.extend(
+                    repo.staged_submodule_files(&submodule)?
+                        .into_iter()
+                        .filter(|file| file.exists()),
+                ),
+            }
+        }
 
         if staged_files.is_empty() {
             output::info!("No staged files to check");
             return Ok(());
         }
 
-        let scanner = Scanner::new(&self.config)?;
         let scan_result = scanner.scan_paths(&staged_files)?;
 
         if scan_result.stats.total_matches > 0 {
@@ -95,27 +459,93 @@ impl HookExecutor {
             ));
 
             for secret_match in scan_result.matches.iter().take(5) {
-                println!(
+                output::styled!(
                     "  🔍 {}:{} [{}]",
-                    secret_match.file_path, secret_match.line_number, secret_match.secret_type
+                    (&secret_match.file_path, "file_path"),
+                    (secret_match.line_number.to_string(), "number"),
+                    (&secret_match.secret_type, "property")
                 );
             }
 
             if scan_result.stats.total_matches > 5 {
-                println!("  ... and {} more", scan_result.stats.total_matches - 5);
+                output::styled!(
+                    "  {}",
+                    (
+                        format!("... and {} more", scan_result.stats.total_matches - 5),
+                        "muted"
+                    )
+                );
             }
 
-            println!("\nCommit aborted. Remove secrets before committing.");
+            output::styled!(
+                "\n{}",
+                ("Commit aborted. Remove secrets before committing.", "error")
+            );
             return Err(anyhow!("Secrets detected in staged files"));
         }
 
+        if scan_result.stats.is_truncated() {
+            let message = format!(
+                "No secrets in the {} scanned, {} not scanned",
+                output::human_count(scan_result.stats.files_scanned, "file"),
+                scan_result.stats.files_not_scanned
+            );
+            if scanner.config.limits.fail_on_truncation {
+                output::error!(&format!("❌ {message}"));
+                output::styled!(
+                    "\n{}",
+                    (
+                        "Commit aborted. The secret scan hit scanner.limits before finishing.",
+                        "error"
+                    )
+                );
+                return Err(anyhow!("Secret scan stopped early"));
+            }
+            output::warning!(&message);
+            return Ok(());
+        }
+
         output::success!(&format!(
-            "✅ Scanned {} files - no secrets found",
-            scan_result.stats.files_scanned
+            "✅ Scanned {} - no secrets found",
+            output::human_count(scan_result.stats.files_scanned, "file")
         ));
         Ok(())
     }
 
+    async fn scan_commit_message(&self, commit_file: &str) -> Result<()> {
+        output::info!("Scanning commit message for secrets...");
+
+        let message =
+            std::fs::read_to_string(commit_file).context("Failed to read commit message file")?;
+        let scanner = Scanner::new(&self.config)?;
+        let matches = scanner.scan_text(&commit_message_text(&message), "commit message");
+
+        if matches.is_empty() {
+            output::success!("✅ No secrets in the commit message");
+            return Ok(());
+        }
+
+        output::error!(&format!(
+            "❌ Found {} in the commit message",
+            output::human_count(matches.len(), "secret")
+        ));
+        for secret_match in &matches {
+            output::styled!(
+                "  🔍 line {} [{}]",
+                (secret_match.line_number.to_string(), "number"),
+                (&secret_match.secret_type, "property")
+            );
+        }
+        output::styled!(
+            "\n{}",
+            (
+                "Commit aborted. Remove secrets from the message before committing.",
+                "error"
+            )
+        );
+        Err(anyhow!("Secrets detected in commit message"))
+    }
+
     async fn validate_commit_msg(&self, commit_file: &str) -> Result<()> {
         output::info!("Validating commit message format...");
 
@@ -167,336 +597,1077 @@ impl HookExecutor {
         }
     }
 
-    async fn execute_custom_sequential(
-        &self,
-        commands: &[CustomCommand],
-        hook_name: &str,
-    ) -> Result<()> {
-        for cmd in commands {
-            self.execute_custom_command(cmd, hook_name).await?;
+    async fn apply_commit_template(&self, commit_file: &str, source: Option<&str>) -> Result<()> {
+        let template = CommitTemplateConfig::from_config(&self.config)?;
+
+        if !template.applies_to_source(source) {
+            return Ok(()); // Leave merge/squash/amend messages alone
+        }
+
+        let repo = GitRepo::discover()?;
+        let branch = repo.current_branch()?;
+        if branch.is_empty() || branch == "HEAD" {
+            return Ok(()); // Detached HEAD - nothing to derive from
         }
+
+        let message =
+            std::fs::read_to_string(commit_file).context("Failed to read commit message file")?;
+
+        if let Some(updated) = template.apply(&message, &branch)? {
+            atomic_io::write_atomic(Path::new(commit_file), updated)
+                .context("Failed to write commit message file")?;
+            output::success!(&format!("✅ Applied commit template for branch {branch}"));
+        }
+
         Ok(())
     }
 
-    async fn execute_custom_parallel(
-        &self,
-        commands: &[CustomCommand],
-        hook_name: &str,
-    ) -> Result<()> {
-        use crate::profiling::{ProfilingConfig, WorkloadProfiler};
-        use std::sync::Arc;
-        use tokio::sync::Mutex;
-
-        // Profile the workload to determine optimal parallelism
-        let profiling_config = ProfilingConfig {
-            max_threads: 0, // No limit
-            thread_percentage: 75,
-            min_items_for_parallel: 2, // Low threshold for hook commands
-        };
+    /// Refs the pre-push hook lists on stdin - none for a manual run
+    fn push_updates(&self) -> &[RefUpdate] {
+        self.push_updates.get_or_init(|| {
+            let mut input = String::new();
+            if !std::io::stdin().is_terminal() {
+                let _ = std::io::stdin().read_to_string(&mut input);
+            }
+            RefUpdate::parse_all(&input)
+        })
+    }
 
-        // Use custom adapter for hook-specific optimization
-        let strategy = WorkloadProfiler::profile_with_adapter(
-            commands.len(),
-            &profiling_config,
-            |count, max_workers| {
-                // Start with standard workload adaptation
-                let base_workers = WorkloadProfiler::adapt_workers_to_workload(count, max_workers);
+    async fn check_merge_conflicts(&self) -> Result<()> {
+        output::info!("Checking for merge conflict markers...");
 
-                // Apply hook-specific constraints: commands may involve I/O, so be more conservative
-                // and never exceed command count (no point in more workers than commands)
-                let hook_optimized = std::cmp::min(base_workers, count);
+        let merge_conflicts = MergeConflictsConfig::from_config(&self.config)?;
+        let excludes = merge_conflicts.excludes()?;
+        let repo = GitRepo::discover()?;
 
-                // Cap at reasonable limit for hook commands (they're usually not CPU-intensive)
-                std::cmp::min(hook_optimized, 8)
-            },
-        );
+        let mut conflicted = Vec::new();
+        let mut checked = 0;
+        for (relative, status) in repo.staged_files_with_status()? {
+            if status == StagedStatus::Deleted || excludes.is_match(&relative) {
+                continue;
+            }
+            // What the commit will contain, not the working tree; submodules and
+            // binary files can't hold markers worth reporting
+            let Some(content) = repo
+                .staged_file_content(&relative)
+                .ok()
+                .and_then(|content| String::from_utf8(content).ok())
+            else {
+                continue;
+            };
+            checked += 1;
 
-        // If profiling suggests sequential, fall back to sequential execution
-        if matches!(strategy, crate::parallel::ExecutionStrategy::Sequential) {
-            return self.execute_custom_sequential(commands, hook_name).await;
+            let markers = conflict_markers(&content);
+            if !markers.is_empty() {
+                conflicted.push((relative.display().to_string(), markers));
+            }
         }
 
-        // Extract worker count from strategy
-        let max_concurrent =
-            if let crate::parallel::ExecutionStrategy::Parallel { workers } = strategy {
-                workers
-            } else {
-                4 // Fallback default
-            };
+        if !conflicted.is_empty() {
+            output::error!(&format!(
+                "❌ Merge conflict markers in {}",
+                output::human_count(conflicted.len(), "staged file")
+            ));
+            for (path, markers) in &conflicted {
+                for marker in markers {
+                    output::styled!(
+                        "  {}:{} {}",
+                        (path, "file_path"),
+                        (marker.line.to_string(), "number"),
+                        (marker.marker, "property")
+                    );
+                }
+            }
+            output::styled!(
+                "  {}",
+                (
+                    "Resolve the conflicts, or list intentional files under merge_conflicts.exclude",
+                    "muted"
+                )
+            );
+            return Err(anyhow!("Merge conflict markers in staged files"));
+        }
 
-        output::info!(&format!(
-            "Running {} commands in parallel (max {max_concurrent} concurrent)",
-            commands.len()
+        output::success!(&format!(
+            "✅ Checked {} - no conflict markers",
+            output::human_count(checked, "file")
         ));
+        Ok(())
+    }
 
-        let errors = Arc::new(Mutex::new(Vec::new()));
-        let semaphore = Arc::new(tokio::sync::Semaphore::new(max_concurrent));
-        let mut handles = Vec::new();
-
-        for cmd in commands {
-            let cmd = cmd.clone();
-            let hook_name = hook_name.to_string();
-            let errors = errors.clone();
-            let permit = semaphore.clone().acquire_owned().await?;
-
-            // Run each command in its own task with concurrency limit
-            let handle = tokio::spawn(async move {
-                // Execute the command directly without needing self
-                let result = execute_single_command(&cmd, &hook_name).await;
-                drop(permit); // Release semaphore permit
-                if let Err(e) = result {
-                    let mut errs = errors.lock().await;
-                    errs.push(e);
-                }
-            });
+    async fn block_large_files(&self) -> Result<()> {
+        output::info!("Checking staged file sizes...");
+
+        let large_files = LargeFilesConfig::from_config(&self.config)?;
+        let blobs = GitRepo::discover()?.staged_blobs()?;
 
-            handles.push(handle);
+        let rejected: Vec<_> = blobs
+            .iter()
+            .filter_map(|blob| large_files.problem(blob).map(|problem| (blob, problem)))
+            .collect();
+
+        if rejected.is_empty() {
+            output::success!(&format!(
+                "✅ Checked {} - none too large",
+                output::human_count(blobs.len(), "staged file")
+            ));
+            return Ok(());
         }
 
-        // Wait for all tasks to complete
-        for handle in handles {
-            handle.await?;
+        output::error!(&format!(
+            "❌ {} too large to commit",
+            output::human_count(rejected.len(), "staged file")
+        ));
+        let mut patterns = Vec::new();
+        for (blob, problem) in &rejected {
+            output::styled!(
+                "  {} {}",
+                (blob.path.display().to_string(), "file_path"),
+                (format!("({problem})"), "muted")
+            );
+            let pattern = lfs_pattern(blob);
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+        output::styled!("  {}", ("Store them with Git LFS instead:", "muted"));
+        for pattern in &patterns {
+            output::styled!("    {}", (format!("git lfs track \"{pattern}\""), "accent"));
         }
+        output::styled!(
+            "    {}",
+            (
+                "git rm --cached <file> && git add .gitattributes <file>",
+                "accent"
+            )
+        );
+        Err(anyhow!("Staged files exceed the large_files limits"))
+    }
+
+    async fn validate_branch_name(&self, hook_name: &str) -> Result<()> {
+        output::info!("Validating branch name...");
+
+        let naming = BranchNamingConfig::from_config(&self.config)?;
+        let repo = GitRepo::discover()?;
 
-        // Check if there were any errors
-        let errs = errors.lock().await;
-        if !errs.is_empty() {
-            let error_msg = errs
+        // A push can send several branches; commits only ever land on the current one
+        let mut branches: Vec<String> = match hook_name {
+            "pre-push" => self
+                .push_updates()
                 .iter()
-                .map(|e| e.to_string())
-                .collect::<Vec<_>>()
-                .join("\n");
-            return Err(anyhow!("Parallel execution failed:\n{}", error_msg));
+                .filter(|update| !update.is_delete())
+                .filter_map(|update| update.branch().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if branches.is_empty() {
+            let current = repo.current_branch()?;
+            if current.is_empty() || current == "HEAD" {
+                output::info!("Detached HEAD - no branch name to check");
+                return Ok(());
+            }
+            branches.push(current);
+        }
+        branches.dedup();
+
+        let mut rejected = Vec::new();
+        for branch in &branches {
+            if !naming.allows(branch)? {
+                rejected.push(branch);
+            }
+        }
+
+        if rejected.is_empty() {
+            output::success!(&format!(
+                "✅ Branch name follows the naming policy: {}",
+                branches.join(", ")
+            ));
+            return Ok(());
+        }
+
+        for branch in &rejected {
+            output::error!(&format!(
+                "❌ Branch '{branch}' does not match the naming policy"
+            ));
+            match naming.suggest(branch)? {
+                Some(suggestion) => output::styled!(
+                    "  Rename it with: {}",
+                    (format!("git branch -m {branch} {suggestion}"), "accent")
+                ),
+                None => output::styled!(
+                    "  Rename it with: {}",
+                    (format!("git branch -m {branch} <new-name>"), "accent")
+                ),
+            }
         }
+        output::styled!("  {}", ("Allowed patterns:", "muted"));
+        for pattern in &naming.patterns {
+            output::styled!("    {}", (pattern, "property"));
+        }
+        if let Some(hint) = &naming.hint {
+            output::styled!("  {}", (hint, "muted"));
+        }
+        Err(anyhow!("Branch name does not match the naming policy"))
+    }
 
+    async fn require_signed_commits(&self, args: &[String]) -> Result<()> {
+        output::info!("Checking commit signatures...");
+
+        let signed_commits = SignedCommitsConfig::from_config(&self.config)?;
+        let repo = GitRepo::discover()?;
+
+        let mut seen = HashSet::new();
+        let mut signatures = Vec::new();
+        for revisions in pushed_revisions(self.push_updates(), args.first().map(String::as_str)) {
+            for signature in
+                repo.commit_signatures(&revisions, signed_commits.allowed_signers_file.as_deref())?
+            {
+                if seen.insert(signature.commit.clone()) {
+                    signatures.push(signature);
+                }
+            }
+        }
+
+        if signatures.is_empty() {
+            output::info!("No commits to check");
+            return Ok(());
+        }
+
+        let problems: Vec<_> = signatures
+            .iter()
+            .filter_map(|signature| {
+                signed_commits
+                    .problem(signature)
+                    .map(|problem| (signature, problem))
+            })
+            .collect();
+
+        if !problems.is_empty() {
+            output::error!(&format!(
+                "❌ {} of {} not properly signed",
+                problems.len(),
+                output::human_count(signatures.len(), "pushed commit")
+            ));
+            for (signature, problem) in &problems {
+                output::styled!(
+                    "  🔏 {} {} {}",
+                    (
+                        &signature.commit[..signature.commit.len().min(8)],
+                        "id_value"
+                    ),
+                    (&signature.subject, "property"),
+                    (format!("- {problem}"), "muted")
+                );
+            }
+            output::styled!(
+                "\n{}",
+                (
+                    "Push aborted. Re-sign the commits (e.g. git rebase --exec 'git commit --amend --no-edit -S' <base>) and push again.",
+                    "error"
+                )
+            );
+            return Err(anyhow!("Unsigned commits in push"));
+        }
+
+        output::success!(&format!(
+            "✅ {} signed",
+            output::human_count(signatures.len(), "pushed commit")
+        ));
         Ok(())
     }
 
-    async fn execute_custom_command(&self, cmd: &CustomCommand, hook_name: &str) -> Result<()> {
-        output::info!(&cmd.description);
+    async fn protect_branches(&self, args: &[String]) -> Result<()> {
+        output::info!("Checking pushes to protected branches...");
+
+        let protection = BranchProtectionConfig::from_config(&self.config)?;
+        let repo = GitRepo::discover()?;
+        // pre-push args: <remote name> <url>; the name equals the url for ad-hoc remotes
+        let remote = args.first().map(String::as_str);
+
+        let mut rejected = Vec::new();
+        let mut checked = 0;
+        for update in self.push_updates() {
+            let Some(branch) = protection.protected_branch(update)? else {
+                continue;
+            };
+            checked += 1;
+            let tracking = match remote {
+                Some(remote) => repo.resolve_ref(&format!("refs/remotes/{remote}/{branch}"))?,
+                None => None,
+            };
+            let facts = PushFacts {
+                fast_forward: !update.is_new()
+                    && !update.is_delete()
+                    && repo.is_ancestor(&update.remote_commit, &update.local_commit)?,
+                lease_held: tracking.as_deref() == Some(update.remote_commit.as_str()),
+            };
+            if let Some(problem) = protection.problem(update, facts) {
+                rejected.push((branch, problem));
+            }
+        }
 
-        // Get files to operate on
-        let files = self.get_files_for_command(cmd, hook_name)?;
+        if !rejected.is_empty() {
+            output::error!(&format!(
+                "❌ Push to {} rejected",
+                output::human_count(rejected.len(), "protected branch")
+            ));
+            for (branch, problem) in &rejected {
+                output::styled!(
+                    "  🛡️ {} {}",
+                    (branch, "property"),
+                    (format!("- {problem}"), "muted")
+                );
+            }
+            output::styled!(
+                "\n{}",
+                (
+                    "Push a branch and open a pull request instead, or bypass once with GUARDY_SKIP=protect_branches.",
+                    "error"
+                )
+            );
+            return Err(anyhow!("Push to protected branch rejected"));
+        }
 
-        // Build the command with file substitution
-        let command_str = if files.is_empty() {
-            // No files to process, run command as-is
-            cmd.command.clone()
+        if checked == 0 {
+            output::info!("No protected branches in this push");
         } else {
-            // Replace {files} placeholder with actual file list
-            let files_str = files
-                .iter()
-                .map(|p| p.to_string_lossy().to_string())
-                .collect::<Vec<_>>()
-                .join(" ");
+            output::success!(&format!(
+                "✅ {} allowed by push_policy",
+                output::human_count(checked, "protected branch push")
+            ));
+        }
+        Ok(())
+    }
 
-            cmd.command.replace("{files}", &files_str)
-        };
+    async fn install_dependencies(&self, from: &str, to: &str) -> Result<()> {
+        if from.chars().all(|c| c == '0') {
+            return Ok(()); // Fresh clone - no previous HEAD to compare against
+        }
 
-        // Execute the command
-        let mut command = if cfg!(target_os = "windows") {
-            let mut c = Command::new("cmd");
-            c.args(["/C", &command_str]);
-            c
-        } else {
-            let mut c = Command::new("sh");
-            c.args(["-c", &command_str]);
-            c
-        };
+        let repo = GitRepo::discover()?;
+        let changed: Vec<PathBuf> = repo
+            .get_changed_files_between(from, to)?
+            .iter()
+            .filter_map(|path| path.strip_prefix(&repo.path).ok().map(Path::to_path_buf))
+            .collect();
 
-        let output = command.output()?;
+        let package_manager = PackageManagerConfig::from_config(&self.config)?;
+        let actions = package_manager.install_plan(&changed, &repo.path);
+
+        if actions.is_empty() {
+            output::info!("No dependency changes detected");
+            return Ok(());
+        }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            if cmd.fail_on_error {
-                output::error!(&format!("✗ {}", cmd.description));
-                return Err(anyhow!("Command failed: {}", stderr));
+        for action in actions {
+            let location = if action.dir.as_os_str().is_empty() {
+                ".".to_string()
             } else {
-                output::warning!(&format!("⚠ {} (non-fatal)", cmd.description));
+                action.dir.display().to_string()
+            };
+
+            if !package_manager.auto_install {
+                output::info!(&format!(
+                    "{} changed - run '{}' in {location}",
+                    action.trigger, action.command
+                ));
+                continue;
             }
-        } else {
-            output::success!(&format!("✓ {}", cmd.description));
 
-            // If stage_fixed is enabled, stage any modified files
-            if cmd.stage_fixed && !files.is_empty() {
-                self.stage_modified_files(&files)?;
+            output::info!(&format!(
+                "{} changed - running '{}' in {location}",
+                action.trigger, action.command
+            ));
+
+            let mut command = if cfg!(target_os = "windows") {
+                let mut c = Command::new("cmd");
+                c.args(["/C", &action.command]);
+                c
+            } else {
+                let mut c = Command::new("sh");
+                c.args(["-c", &action.command]);
+                c
+            };
+
+            // Inherit stdio so install progress is visible; git ignores post-* hook failures anyway
+            let status = command
+                .current_dir(repo.path.join(&action.dir))
+                .status()
+                .with_context(|| format!("Failed to run '{}'", action.command))?;
+
+            if status.success() {
+                output::success!(&format!("✓ {}", action.command));
+            } else {
+                output::warning!(&format!("⚠ {} failed in {location}", action.command));
             }
         }
 
         Ok(())
     }
 
-    fn get_files_for_command(&self, cmd: &CustomCommand, hook_name: &str) -> Result<Vec<PathBuf>> {
+    /// Run the hooks from an existing `.pre-commit-config.yaml` that apply to `hook_name`
+    ///
+    /// Remote hook repositories are cloned once per pinned `rev` into
+    /// `.guardy/cache/pre-commit/`. Only `system`, `script` and `fail` hooks run - guardy
+    /// doesn't build language environments, so other hooks are reported and skipped.
+    async fn run_pre_commit_config(&self, hook_name: &str, args: &[String]) -> Result<()> {
         let repo = GitRepo::discover()?;
+        if !repo.path.join(PRE_COMMIT_CONFIG_FILE).exists() {
+            output::warning!(&format!(
+                "pre_commit_config: no {PRE_COMMIT_CONFIG_FILE} in repository root"
+            ));
+            return Ok(());
+        }
+        let config = PreCommitConfig::load(&repo.path)?;
 
-        // Get base file list
-        let mut files = if cmd.all_files {
-            // Get all files in repository matching the glob patterns
-            if cmd.glob.is_empty() {
-                return Err(anyhow!("all_files requires glob patterns to be specified"));
+        let mut candidates = Vec::new();
+        for file in self.pre_commit_candidates(&repo, hook_name, args)? {
+            if config.includes(&paths::match_key(&file))? {
+                candidates.push(file);
             }
-            self.get_all_files_matching_globs(&cmd.glob)?
-        } else {
-            // Default to staged files for pre-commit
-            if hook_name == "pre-commit" {
-                repo.get_staged_files()?
-            } else {
-                vec![]
+        }
+
+        let skip = SkipList::from_env();
+        let cache_dir = repo.path.join(".guardy/cache/pre-commit");
+        let mut failed = Vec::new();
+
+        for repo_config in &config.repos {
+            if repo_config.is_meta() {
+                output::warning!("⚠ Skipping 'meta' hooks (not supported by guardy)");
+                continue;
             }
-        };
 
-        // Apply glob filtering if specified
-        if !cmd.glob.is_empty() && !cmd.all_files {
-            files = self.filter_by_globs(&files, &cmd.glob)?;
-        }
+            let hooks = if repo_config.is_local() {
+                repo_config.resolve(None, &repo.path, &config.default_stages)?
+            } else {
+                let repo_dir = self.fetch_pre_commit_repo(&cache_dir, repo_config)?;
+                let manifest = HookEntry::load_manifest(&repo_dir)?;
+                repo_config.resolve(Some(&manifest), &repo_dir, &config.default_stages)?
+            };
 
-        Ok(files)
-    }
+            for hook in hooks.iter().filter(|hook| hook.runs_for(hook_name)) {
+                if skip.contains(&hook.id)
+                    || hook.alias.as_deref().is_some_and(|a| skip.contains(a))
+                {
+                    output::warning!(&format!("⏭ Skipped {} ({})", hook.name, skip.source()));
+                    continue;
+                }
+                if !hook.is_supported_language() {
+                    output::warning!(&format!(
+                        "⚠ Skipping {} - language '{}' is not supported (use system or script)",
+                        hook.name, hook.language
+                    ));
+                    continue;
+                }
 
-    fn get_all_files_matching_globs(&self, globs: &[String]) -> Result<Vec<PathBuf>> {
-        let glob_set = self.build_glob_set(globs)?;
-        let mut matching_files = Vec::new();
+                if !self.run_pre_commit_hook(hook, &candidates, &repo.path)? {
+                    failed.push(hook.name.clone());
+                    if config.fail_fast {
+                        break;
+                    }
+                }
+            }
 
-        // Walk the current directory recursively
-        for entry in walkdir::WalkDir::new(".")
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
-            let path = entry.path();
-            if glob_set.is_match(path) {
-                matching_files.push(path.to_path_buf());
+            if config.fail_fast && !failed.is_empty() {
+                break;
             }
         }
 
-        Ok(matching_files)
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} pre-commit hook(s) failed: {}",
+                failed.len(),
+                failed.join(", ")
+            ))
+        }
     }
 
-    fn filter_by_globs(&self, files: &[PathBuf], globs: &[String]) -> Result<Vec<PathBuf>> {
-        let glob_set = self.build_glob_set(globs)?;
+    /// Files a pre-commit hook may receive, relative to the repository root
+    fn pre_commit_candidates(
+        &self,
+        repo: &GitRepo,
+        hook_name: &str,
+        args: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let changed = match hook_name {
+            // git am applies the patch to the index before pre-applypatch
+            "pre-commit" | "pre-applypatch" => repo.get_staged_files()?,
+            // Best effort: a branch without an upstream has nothing to compare against
+            "pre-push" => repo
+                .get_changed_files_between("@{upstream}", "HEAD")
+                .unwrap_or_default(),
+            "commit-msg" | "prepare-commit-msg" | "applypatch-msg" => {
+                return Ok(args.first().map(PathBuf::from).into_iter().collect());
+            }
+            _ => Vec::new(),
+        };
 
-        Ok(files
+        // Deleted files show up in the diff but there is nothing left to check
+        Ok(changed
             .iter()
-            .filter(|path| glob_set.is_match(path.as_path()))
-            .cloned()
+            .filter(|path| path.is_file())
+            .filter_map(|path| path.strip_prefix(&repo.path).ok().map(Path::to_path_buf))
             .collect())
     }
 
-    fn build_glob_set(&self, globs: &[String]) -> Result<GlobSet> {
-        let mut builder = GlobSetBuilder::new();
-        for pattern in globs {
-            builder.add(Glob::new(pattern)?);
+    /// Cached checkout of a hook repository at its pinned `rev`, cloning it on first use
+    fn fetch_pre_commit_repo(&self, cache_dir: &Path, repo_config: &RepoConfig) -> Result<PathBuf> {
+        let name = repo_config.cache_name();
+        let repo_dir = cache_dir.join(&name);
+        if repo_dir.exists() {
+            return Ok(repo_dir);
         }
-        Ok(builder.build()?)
+
+        std::fs::create_dir_all(cache_dir)?;
+        let _lock = LockFile::acquire(&cache_dir.join(format!("{name}.lock")))?;
+        if !repo_dir.exists() {
+            output::info!(&format!("Fetching {}...", repo_config.repo));
+            let rev = repo_config.rev.as_deref().unwrap_or("HEAD");
+            RemoteOperations::new(cache_dir.to_path_buf())
+                .clone_repository(&repo_config.repo, &name, rev, &[])
+                .with_context(|| format!("Failed to fetch hook repository {}", repo_config.repo))?;
+        }
+        Ok(repo_dir)
     }
 
-    fn stage_modified_files(&self, files: &[PathBuf]) -> Result<()> {
-        let files_to_stage: Vec<String> = files
-            .iter()
-            .filter(|path| path.exists())
-            .map(|path| path.to_string_lossy().to_string())
-            .collect();
+    /// Run one hook, printing its output on failure. Returns whether it passed.
+    fn run_pre_commit_hook(
+        &self,
+        hook: &ResolvedHook,
+        candidates: &[PathBuf],
+        repo_root: &Path,
+    ) -> Result<bool> {
+        let files = hook.select_files(candidates, repo_root);
+        if files.is_empty() && !hook.always_run {
+            output::info!(&format!("{} (no files to check)", hook.name));
+            return Ok(true);
+        }
 
-        if files_to_stage.is_empty() {
-            return Ok(());
+        let Some(mut command) = hook.command(&files) else {
+            // `fail` hooks exist to reject any matching file, with the entry as the message
+            output::error!(&format!("✗ {}: {}", hook.name, hook.entry));
+            for file in &files {
+                output::styled!("  {}", (paths::display(file), "file_path"));
+            }
+            return Ok(false);
+        };
+
+        let result = command
+            .current_dir(repo_root)
+            .output()
+            .with_context(|| format!("Failed to run hook '{}'", hook.id))?;
+
+        if result.status.success() {
+            output::success!(&format!("✓ {}", hook.name));
+            return Ok(true);
         }
 
-        output::info!(&format!(
-            "Staging {} modified files...",
-            files_to_stage.len()
-        ));
+        output::error!(&format!("✗ {}", hook.name));
+        let stdout = String::from_utf8_lossy(&result.stdout);
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        for line in stdout.lines().chain(stderr.lines()) {
+            output::styled!("  {}", (line, "muted"));
+        }
+        Ok(false)
+    }
+
+    /// Run custom commands as soon as their `depends_on` commands have finished
+    ///
+    /// Independent commands run concurrently when the hook is `parallel` (bounded by
+    /// the workload profile); otherwise one at a time in declaration order, with
+    /// dependencies still moved ahead of their dependents. A failing command keeps
+    /// everything downstream of it from starting, and with `parallel: false` stops
+    /// the rest of the hook as before.
+    async fn execute_custom(
+        &self,
+        commands: &[CustomCommand],
+        skipped: &[String],
+        hook: &HookDefinition,
+        hook_name: &str,
+        timings: &HookTimings,
+        steps: Steps,
+    ) -> Result<()> {
+        use std::collections::BTreeSet;
+        use tokio::task::JoinSet;
 
-        let mut command = Command::new("git");
-        command.arg("add");
-        command.args(&files_to_stage);
+        let parallel = hook.parallel;
+        let output_mode = hook.output;
 
-        let output = command.output()?;
+        let graph = CommandGraph::build(commands, skipped)?;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            output::warning!(&format!("Failed to stage some files: {stderr}"));
-        } else {
-            output::success!(&format!("Staged {} files", files_to_stage.len()));
+        let max_concurrent = match parallel_strategy(commands.len()) {
+            ExecutionStrategy::Parallel { workers } if parallel => workers,
+            _ => 1,
+        };
+        if max_concurrent > 1 {
+            output::info!(&format!(
+                "Running {} commands in parallel (max {max_concurrent} concurrent)",
+                commands.len()
+            ));
         }
 
-        Ok(())
+        let mut pending: Vec<usize> = (0..commands.len())
+            .map(|i| graph.dependencies(i).len())
+            .collect();
+        // Lowest index first, so unconstrained commands keep their declared order
+        let mut ready: BTreeSet<usize> = (0..commands.len()).filter(|&i| pending[i] == 0).collect();
+        let mut blocked = vec![false; commands.len()];
+        let mut started = vec![false; commands.len()];
+        let mut errors = Vec::new();
+        let mut running = JoinSet::new();
+
+        loop {
+            while running.len() < max_concurrent
+                && (parallel || errors.is_empty())
+                && let Some(index) = ready.pop_first()
+            {
+                started[index] = true;
+                let cmd = commands[index].clone();
+                let hook_name = hook_name.to_string();
+                let timings = timings.clone();
+
+                running.spawn(async move {
+                    let started = Instant::now();
+                    let mut attempts = 0;
+                    let step = (steps.first + index, steps.total);
+                    let result =
+                        execute_single_command(&cmd, &hook_name, output_mode, step, &mut attempts)
+                            .await;
+                    timings.record(
+                        command_id(&cmd),
+                        StepKind::Custom,
+                        *result.as_ref().unwrap_or(&StepOutcome::Failed),
+                        attempts.max(1),
+                        started.elapsed(),
+                    );
+                    (index, result)
+                });
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let (index, result) = joined?;
+
+            match result {
+                Ok(_) => {
+                    for &dependent in graph.dependents(index) {
+                        pending[dependent] -= 1;
+                        if pending[dependent] == 0 && !blocked[dependent] {
+                            ready.insert(dependent);
+                        }
+                    }
+                }
+                Err(e) => {
+                    for downstream in graph.downstream(index) {
+                        blocked[downstream] = true;
+                        ready.remove(&downstream);
+                    }
+                    errors.push(e);
+                }
+            }
+        }
+
+        let not_run: Vec<&str> = commands
+            .iter()
+            .zip(&started)
+            .filter(|(_, started)| !**started)
+            .map(|(cmd, _)| command_id(cmd))
+            .collect();
+        if !not_run.is_empty() {
+            output::warning!(&format!(
+                "⏭ Not run after a failure: {}",
+                not_run.join(", ")
+            ));
+        }
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(anyhow!(
+                "{} commands failed:\n{}",
+                errors.len(),
+                errors
+                    .iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )),
+        }
     }
 }
 
-// Standalone function for parallel execution
-async fn execute_single_command(cmd: &CustomCommand, hook_name: &str) -> Result<()> {
-    use crate::git::GitRepo;
+/// The part of a commit message file git keeps: comment lines blanked (so line
+/// numbers still match the editor) and the `git commit -v` diff cut off
+fn commit_message_text(raw: &str) -> String {
+    raw.lines()
+        .take_while(|line| !line.starts_with("# ------------------------ >8"))
+        .map(|line| if line.starts_with('#') { "" } else { line })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Builtin names accepted in a hook's `builtin` list
+const BUILTINS: &[&str] = &[
+    "scan_secrets",
+    "validate_commit_msg",
+    "commit_template",
+    "install_dependencies",
+    "pre_commit_config",
+    "require_signed_commits",
+    "validate_branch_name",
+    "check_merge_conflicts",
+    "block_large_files",
+    "protect_branches",
+];
+
+/// Builtins only act on specific hooks (and need that hook's arguments)
+fn builtin_applies(builtin: &str, hook_name: &str, args: &[String]) -> bool {
+    match builtin {
+        "scan_secrets" => match hook_name {
+            "pre-commit" | "pre-applypatch" => true,
+            "commit-msg" | "applypatch-msg" => !args.is_empty(),
+            _ => false,
+        },
+        "validate_commit_msg" => {
+            matches!(hook_name, "commit-msg" | "applypatch-msg") && !args.is_empty()
+        }
+        "require_signed_commits" | "protect_branches" => hook_name == "pre-push",
+        "validate_branch_name" => matches!(hook_name, "pre-commit" | "pre-push"),
+        "check_merge_conflicts" | "block_large_files" => {
+            matches!(hook_name, "pre-commit" | "pre-applypatch")
+        }
+        "commit_template" => hook_name == "prepare-commit-msg" && !args.is_empty(),
+        // post-checkout args: <previous HEAD> <new HEAD> <1 = branch checkout, 0 = file checkout>
+        "install_dependencies" => match hook_name {
+            "post-checkout" => args.len() >= 3 && args[2] == "1",
+            "post-merge" => true,
+            _ => false,
+        },
+        _ => true, // pre_commit_config filters by stage itself; unknown names are reported
+    }
+}
 
-    output::info!(&cmd.description);
+/// Numbering of a hook's steps: builtins first, then custom commands
+#[derive(Debug, Clone, Copy)]
+struct Steps {
+    /// Number of the first custom command
+    first: usize,
+    total: usize,
+}
 
-    // Get files to operate on
-    let repo = GitRepo::discover()?;
-    let mut files = if cmd.all_files {
-        // Get all files in repository matching the glob patterns
-        if cmd.glob.is_empty() {
-            return Err(anyhow!("all_files requires glob patterns to be specified"));
+/// How many custom commands run at once when a hook has `parallel: true`
+fn parallel_strategy(command_count: usize) -> ExecutionStrategy {
+    // Profile the workload to determine optimal parallelism
+    let profiling_config = ProfilingConfig {
+        max_threads: 0, // No limit
+        thread_percentage: 75,
+        min_items_for_parallel: 2, // Low threshold for hook commands
+    };
+
+    // Use custom adapter for hook-specific optimization
+    WorkloadProfiler::profile_with_adapter(
+        command_count,
+        &profiling_config,
+        |count, max_workers| {
+            // Start with standard workload adaptation
+            let base_workers = WorkloadProfiler::adapt_workers_to_workload(count, max_workers);
+
+            // Apply hook-specific constraints: commands may involve I/O, so be more conservative
+            // and never exceed command count (no point in more workers than commands)
+            let hook_optimized = std::cmp::min(base_workers, count);
+
+            // Cap at reasonable limit for hook commands (they're usually not CPU-intensive)
+            std::cmp::min(hook_optimized, 8)
+        },
+    )
+}
+
+// Standalone function for parallel execution
+/// Run one custom command, retrying per its `retries`/`retry_on` policy
+///
+/// `attempts` counts every run, so it is accurate for the timing report even when
+/// the command ultimately fails.
+async fn execute_single_command(
+    cmd: &CustomCommand,
+    hook_name: &str,
+    output_mode: OutputMode,
+    (number, total): (usize, usize),
+    attempts: &mut u32,
+) -> Result<StepOutcome> {
+    let step = output::step!(number, total, &cmd.description);
+    let policy = RetryPolicy::for_command(cmd)?;
+
+    let files = files_for_command(cmd, hook_name)?;
+    let sources = placeholders::sources(&cmd.command)?;
+    let mut lists = FileLists::new();
+    for &source in &sources {
+        lists.insert(source, files_from(source, cmd, &files)?);
+    }
+    lists.insert(FileSource::Files, files);
+
+    let runs = if cmd.workspaces {
+        let root = GitRepo::discover()?.path;
+        let packages = Workspace::discover(&root)?.group_files(&root, &lists[&FileSource::Files]);
+        if packages.is_empty() {
+            step.skip("no affected packages");
+            return Ok(StepOutcome::Passed);
         }
-        get_all_files_matching_globs(&cmd.glob)?
+        let mut runs = Vec::new();
+        for (package, files) in packages {
+            // Every list is narrowed to the package, like `{files}`
+            let dir = root.join(&package);
+            let mut package_lists: FileLists = lists
+                .iter()
+                .map(|(source, list)| {
+                    let list = list.iter().filter(|file| file.starts_with(&dir));
+                    (*source, list.cloned().collect())
+                })
+                .collect();
+            package_lists.insert(FileSource::Files, files);
+            let label = format!("{} ({})", command_id(cmd), paths::display(&package));
+            runs.extend(command_runs(cmd, label, Some(&package), &package_lists)?);
+        }
+        runs
     } else {
-        // Default to staged files for pre-commit
-        if hook_name == "pre-commit" {
-            repo.get_staged_files()?
-        } else {
-            vec![]
+        // Nothing to pass to a file list - the tool would check everything instead
+        if sources.iter().any(|source| lists[source].is_empty()) {
+            step.skip("no matching files");
+            return Ok(StepOutcome::Passed);
         }
+        command_runs(cmd, command_id(cmd).to_string(), None, &lists)?
     };
 
-    // Apply glob filtering if specified
-    if !cmd.glob.is_empty() && !cmd.all_files {
-        files = filter_by_globs(&files, &cmd.glob)?;
+    let vars = env::command_env(cmd, &GitRepo::discover()?.path)?;
+    let mut failures = Vec::new();
+    for run in &runs {
+        let mut run_attempts = 0;
+        events::emit(Event::HookCommandStarted {
+            hook: hook_name.to_string(),
+            command: run.label.clone(),
+            run: run.command.clone(),
+        });
+        let started = Instant::now();
+        let output =
+            run_with_retries(cmd, run, &vars, &policy, output_mode, &mut run_attempts).await?;
+        *attempts = (*attempts).max(run_attempts);
+        events::emit(Event::HookCommandFinished {
+            hook: hook_name.to_string(),
+            command: run.label.clone(),
+            success: output.status.success(),
+            exit_code: output.status.code(),
+            attempts: run_attempts,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+
+        if output.status.success() {
+            // If stage_fixed is enabled, stage any modified files
+            if cmd.stage_fixed && !run.files.is_empty() {
+                stage_modified_files(&run.files)?;
+            }
+        } else {
+            failures.push((&run.label, output));
+        }
+    }
+
+    if failures.is_empty() {
+        step.success();
+        return Ok(StepOutcome::Passed);
+    }
+    if !cmd.fail_on_error {
+        step.warn("non-fatal");
+        return Ok(StepOutcome::NonFatal);
     }
 
-    // Build the command with file substitution
-    let command_str = if files.is_empty() {
-        cmd.command.clone()
-    } else {
-        let files_str = files
+    step.fail();
+    if runs.len() > 1 {
+        let labels: Vec<&str> = failures.iter().map(|(label, _)| label.as_str()).collect();
+        if !output_mode.is_quiet() {
+            return Err(anyhow!("Command failed in: {}", labels.join(", ")));
+        }
+        let details: Vec<String> = failures
             .iter()
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(" ");
+            .map(|(label, output)| format!("{label}: {}", String::from_utf8_lossy(&output.stderr)))
+            .collect();
+        return Err(anyhow!("Command failed in {}", details.join("\n")));
+    }
+
+    let (_, output) = &failures[0];
+    if !output_mode.is_quiet() {
+        // Its output has already been shown
+        return Err(anyhow!(
+            "Command '{}' failed ({})",
+            command_id(cmd),
+            output.status
+        ));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(anyhow!("Command failed: {}", stderr))
+}
 
-        cmd.command.replace("{files}", &files_str)
+/// The packages a `workspaces: true` command would run in, for `--plan`
+fn print_planned_packages(root: Option<&Path>, files: &[PathBuf]) {
+    let Some(root) = root else {
+        return;
     };
+    match Workspace::discover(root) {
+        Ok(workspace) => {
+            let packages = workspace.group_files(root, files);
+            output::styled!(
+                "      {}",
+                (format!("packages ({}):", packages.len()), "number")
+            );
+            for (package, files) in &packages {
+                output::styled!(
+                    "        {} {}",
+                    (paths::display(package), "file_path"),
+                    (format!("({} files)", files.len()), "muted")
+                );
+            }
+        }
+        Err(e) => output::styled!("      {}", (format!("would fail: {e}"), "warning")),
+    }
+}
+
+/// One invocation of a custom command - per package with `workspaces: true`, and
+/// per chunk when its file lists are too long for one command line
+struct CommandRun {
+    label: String,
+    command: String,
+    files: Vec<PathBuf>,
+    /// Keeps `null-separated` list files until the command has run
+    _lists: Vec<tempfile::NamedTempFile>,
+}
+
+/// Runs of `cmd` with `{package_dir}` and the file lists filled in
+fn command_runs(
+    cmd: &CustomCommand,
+    label: String,
+    package: Option<&Path>,
+    lists: &FileLists,
+) -> Result<Vec<CommandRun>> {
+    let mut command = cmd.command.clone();
+    if let Some(package) = package {
+        command = command.replace("{package_dir}", &paths::display(package));
+    }
+
+    let chunks = placeholders::expand(&command, lists)?;
+    let count = chunks.len();
+    Ok(chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| CommandRun {
+            label: if count > 1 {
+                format!("{label} [{}/{count}]", index + 1)
+            } else {
+                label.clone()
+            },
+            command: chunk.command,
+            files: chunk.files,
+            _lists: chunk.lists,
+        })
+        .collect())
+}
 
-    // Execute the command
+async fn run_with_retries(
+    cmd: &CustomCommand,
+    run: &CommandRun,
+    vars: &BTreeMap<String, String>,
+    policy: &RetryPolicy,
+    output_mode: OutputMode,
+    attempts: &mut u32,
+) -> Result<std::process::Output> {
     let mut command = if cfg!(target_os = "windows") {
         let mut c = Command::new("cmd");
-        c.args(["/C", &command_str]);
+        c.args(["/C", &run.command]);
         c
     } else {
         let mut c = Command::new("sh");
-        c.args(["-c", &command_str]);
+        c.args(["-c", &run.command]);
         c
     };
+    command.envs(vars);
+
+    loop {
+        *attempts += 1;
+        let output = command_output::run(&mut command, &run.label, output_mode)?;
+        if output.status.success() || !policy.should_retry(*attempts, &output) {
+            return Ok(output);
+        }
 
-    let output = command.output()?;
+        let delay = policy.delay(*attempts);
+        output::warning!(&format!(
+            "↻ {} failed (attempt {}/{}), retrying in {}",
+            cmd.description,
+            attempts,
+            policy.max_attempts(),
+            output::human_duration(delay)
+        ));
+        tokio::time::sleep(delay).await;
+    }
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if cmd.fail_on_error {
-            output::error!(&format!("✗ {}", &cmd.description));
-            return Err(anyhow!("Command failed: {}", stderr));
-        } else {
-            output::warning!(&format!("⚠ {} (non-fatal)", &cmd.description));
+/// Files a custom command operates on: staged files (pre-commit, pre-applypatch) or every file
+/// (`all_files`), narrowed by `glob` and `languages`
+fn files_for_command(cmd: &CustomCommand, hook_name: &str) -> Result<Vec<PathBuf>> {
+    let mut files = if cmd.all_files {
+        if cmd.glob.is_empty() && cmd.languages.is_empty() {
+            return Err(anyhow!(
+                "all_files requires glob patterns or languages to be specified"
+            ));
         }
+        get_all_files_matching_globs(&cmd.glob)?
+    } else if matches!(hook_name, "pre-commit" | "pre-applypatch") {
+        // Default to staged files - for pre-applypatch, the patch git am applied
+        return narrow(cmd, GitRepo::discover()?.get_staged_files()?);
     } else {
-        output::success!(&format!("✓ {}", &cmd.description));
+        vec![]
+    };
 
-        // If stage_fixed is enabled, stage any modified files
-        if cmd.stage_fixed && !files.is_empty() {
-            stage_modified_files(&files)?;
-        }
+    if !cmd.languages.is_empty() {
+        files.retain(|path| languages::matches_any(path, &cmd.languages));
     }
 
-    Ok(())
+    Ok(files)
+}
+
+/// Files for a `{staged_files}`, `{all_files}` or `{push_files}` placeholder,
+/// narrowed by `glob` and `languages` like `{files}`
+fn files_from(
+    source: FileSource,
+    cmd: &CustomCommand,
+    selection: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let repo = GitRepo::discover()?;
+    let files = match source {
+        FileSource::Files => return Ok(selection.to_vec()),
+        FileSource::Staged => repo.get_staged_files()?,
+        FileSource::All => repo.get_tracked_files()?,
+        // Best effort: a branch without an upstream has nothing to compare against
+        FileSource::Push => repo
+            .get_changed_files_between("@{upstream}", "HEAD")
+            .unwrap_or_default(),
+    };
+    // Deleted files show up in diffs but there is nothing left to pass on
+    let files = files.into_iter().filter(|path| path.is_file()).collect();
+    narrow(cmd, files)
+}
+
+fn narrow(cmd: &CustomCommand, files: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    let mut files = if cmd.glob.is_empty() {
+        files
+    } else {
+        filter_by_globs(&files, &cmd.glob)?
+    };
+    if !cmd.languages.is_empty() {
+        files.retain(|path| languages::matches_any(path, &cmd.languages));
+    }
+    Ok(files)
 }
 
+/// Every file under the working directory matching `globs` (all files when empty)
 fn get_all_files_matching_globs(globs: &[String]) -> Result<Vec<PathBuf>> {
     let glob_set = build_glob_set(globs)?;
     let mut matching_files = Vec::new();
@@ -507,7 +1678,7 @@ fn get_all_files_matching_globs(globs: &[String]) -> Result<Vec<PathBuf>> {
         .filter(|e| e.file_type().is_file())
     {
         let path = entry.path();
-        if glob_set.is_match(path) {
+        if globs.is_empty() || glob_set.is_match(paths::match_key(path)) {
             matching_files.push(path.to_path_buf());
         }
     }
@@ -520,7 +1691,7 @@ fn filter_by_globs(files: &[PathBuf], globs: &[String]) -> Result<Vec<PathBuf>>
 
     Ok(files
         .iter()
-        .filter(|path| glob_set.is_match(path.as_path()))
+        .filter(|path| glob_set.is_match(paths::match_key(path)))
         .cloned()
         .collect())
 }
@@ -528,17 +1699,13 @@ fn filter_by_globs(files: &[PathBuf], globs: &[String]) -> Result<Vec<PathBuf>>
 fn build_glob_set(globs: &[String]) -> Result<GlobSet> {
     let mut builder = GlobSetBuilder::new();
     for pattern in globs {
-        builder.add(Glob::new(pattern)?);
+        builder.add(Glob::new(&paths::normalize_pattern(pattern))?);
     }
     Ok(builder.build()?)
 }
 
 fn stage_modified_files(files: &[PathBuf]) -> Result<()> {
-    let files_to_stage: Vec<String> = files
-        .iter()
-        .filter(|path| path.exists())
-        .map(|path| path.to_string_lossy().to_string())
-        .collect();
+    let files_to_stage: Vec<PathBuf> = files.iter().filter(|path| path.exists()).cloned().collect();
 
     if files_to_stage.is_empty() {
         return Ok(());
@@ -549,17 +1716,12 @@ fn stage_modified_files(files: &[PathBuf]) -> Result<()> {
         files_to_stage.len()
     ));
 
-    let mut command = Command::new("git");
-    command.arg("add");
-    command.args(&files_to_stage);
-
-    let output = command.output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        output::warning!(&format!("Failed to stage some files: {stderr}"));
-    } else {
-        output::success!(&format!("Staged {} files", files_to_stage.len()));
+    match GitRepo::discover().and_then(|repo| repo.stage_files(&files_to_stage)) {
+        Err(e) => output::warning!(&format!("Failed to stage some files: {e:#}")),
+        Ok(()) => output::success!(&format!(
+            "Staged {}",
+            output::human_count(files_to_stage.len(), "file")
+        )),
     }
 
     Ok(())
@@ -0,0 +1,193 @@
+use anyhow::{Result, anyhow};
+
+use super::config::CustomCommand;
+
+/// Ordering constraints between a hook's custom commands, built from `depends_on`
+///
+/// Commands are identified by `name`, falling back to `description` - the same
+/// identifier `GUARDY_SKIP` matches. Nodes are indices into the command slice.
+#[derive(Debug)]
+pub struct CommandGraph {
+    dependencies: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+}
+
+impl CommandGraph {
+    /// Build the graph for the commands that will run
+    ///
+    /// Dependencies on commands listed in `skipped` are treated as satisfied. Unknown
+    /// names, self-references and cycles are configuration errors.
+    pub fn build(commands: &[CustomCommand], skipped: &[String]) -> Result<Self> {
+        let mut dependencies = vec![Vec::new(); commands.len()];
+        let mut dependents = vec![Vec::new(); commands.len()];
+
+        for (index, cmd) in commands.iter().enumerate() {
+            for dependency in &cmd.depends_on {
+                if command_id(cmd) == dependency {
+                    return Err(anyhow!("Command '{dependency}' depends on itself"));
+                }
+
+                let matches: Vec<usize> = commands
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| command_id(other) == dependency)
+                    .map(|(other, _)| other)
+                    .collect();
+
+                if matches.is_empty() {
+                    if skipped.iter().any(|name| name == dependency) {
+                        continue;
+                    }
+                    return Err(anyhow!(
+                        "Command '{}' depends on unknown command '{dependency}'",
+                        command_id(cmd)
+                    ));
+                }
+
+                for other in matches {
+                    if !dependencies[index].contains(&other) {
+                        dependencies[index].push(other);
+                        dependents[other].push(index);
+                    }
+                }
+            }
+        }
+
+        let graph = Self {
+            dependencies,
+            dependents,
+        };
+        graph.check_acyclic(commands)?;
+        Ok(graph)
+    }
+
+    /// Commands that must finish before `index` starts
+    pub fn dependencies(&self, index: usize) -> &[usize] {
+        &self.dependencies[index]
+    }
+
+    /// Commands waiting on `index`
+    pub fn dependents(&self, index: usize) -> &[usize] {
+        &self.dependents[index]
+    }
+
+    /// Whether any command declares `depends_on`
+    pub fn has_dependencies(&self) -> bool {
+        self.dependencies.iter().any(|deps| !deps.is_empty())
+    }
+
+    /// `index` and everything that (transitively) waits on it
+    pub fn downstream(&self, index: usize) -> Vec<usize> {
+        let mut seen = vec![false; self.dependents.len()];
+        let mut stack = vec![index];
+        let mut found = Vec::new();
+
+        while let Some(node) = stack.pop() {
+            if seen[node] {
+                continue;
+            }
+            seen[node] = true;
+            found.push(node);
+            stack.extend(&self.dependents[node]);
+        }
+        found
+    }
+
+    // Kahn's algorithm - whatever can't be ordered sits on (or behind) a cycle
+    fn check_acyclic(&self, commands: &[CustomCommand]) -> Result<()> {
+        let mut pending: Vec<usize> = self.dependencies.iter().map(Vec::len).collect();
+        let mut ready: Vec<usize> = (0..pending.len()).filter(|&i| pending[i] == 0).collect();
+        let mut ordered = 0;
+
+        while let Some(node) = ready.pop() {
+            ordered += 1;
+            for &dependent in &self.dependents[node] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        if ordered == commands.len() {
+            return Ok(());
+        }
+
+        let stuck: Vec<&str> = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, _)| command_id(&commands[i]))
+            .collect();
+        Err(anyhow!(
+            "depends_on cycle between commands: {}",
+            stuck.join(", ")
+        ))
+    }
+}
+
+/// Identifier a command is referenced by in `depends_on`, `GUARDY_SKIP` and reports
+pub fn command_id(cmd: &CustomCommand) -> &str {
+    cmd.name.as_deref().unwrap_or(&cmd.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(name: &str, depends_on: &[&str]) -> CustomCommand {
+        serde_json::from_value(serde_json::json!({
+            "command": "true",
+            "name": name,
+            "depends_on": depends_on,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_links_dependencies_and_dependents() {
+        let commands = [
+            command("fmt", &[]),
+            command("clippy", &["fmt"]),
+            command("test", &["fmt", "clippy"]),
+        ];
+
+        let graph = CommandGraph::build(&commands, &[]).unwrap();
+
+        assert!(graph.has_dependencies());
+        assert_eq!(graph.dependencies(2), &[0, 1]);
+        assert_eq!(graph.dependents(0), &[1, 2]);
+        let mut downstream = graph.downstream(1);
+        downstream.sort();
+        assert_eq!(downstream, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_build_rejects_unknown_names_and_cycles() {
+        let unknown = [command("clippy", &["fmt"])];
+        let cycle = [
+            command("a", &["c"]),
+            command("b", &["a"]),
+            command("c", &["b"]),
+            command("d", &[]),
+        ];
+
+        let err = CommandGraph::build(&unknown, &[]).unwrap_err();
+        assert!(err.to_string().contains("unknown command 'fmt'"));
+
+        let err = CommandGraph::build(&cycle, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "depends_on cycle between commands: a, b, c"
+        );
+    }
+
+    #[test]
+    fn test_dependencies_on_skipped_commands_are_satisfied() {
+        let commands = [command("clippy", &["fmt"])];
+
+        let graph = CommandGraph::build(&commands, &["fmt".to_string()]).unwrap();
+
+        assert!(!graph.has_dependencies());
+    }
+}
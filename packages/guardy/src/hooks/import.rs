@@ -0,0 +1,534 @@
+use anyhow::{Context, Result, anyhow};
+use serde_yml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config::{CustomCommand, HookDefinition, SUPPORTED_HOOKS};
+
+/// Every client-side git hook, in the order git runs them during a typical workflow
+const GIT_HOOKS: &[&str] = &[
+    "applypatch-msg",
+    "pre-applypatch",
+    "post-applypatch",
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+    "post-rewrite",
+    "pre-auto-gc",
+];
+
+const LEFTHOOK_FILES: &[&str] = &[
+    "lefthook.yml",
+    "lefthook.yaml",
+    ".lefthook.yml",
+    ".lefthook.yaml",
+];
+
+/// Shell keywords that mean a husky script can't be split into one command per line
+const SHELL_CONTROL: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac",
+];
+
+/// Hook arguments git passes to the hook script - guardy custom commands don't receive them
+const POSITIONAL_ARGS: &[&str] = &["$1", "$2", "$3", "${1}", "$@", "$*", "{0}", "{1}", "{2}"];
+
+/// Hook managers `guardy hooks import` can migrate from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportSource {
+    /// `.husky/<hook>` shell scripts
+    Husky,
+    /// `lefthook.yml` (commands, scripts and jobs)
+    Lefthook,
+}
+
+/// Hook definitions converted from another hook manager
+#[derive(Debug, Default)]
+pub struct ImportedHooks {
+    /// Hooks in the order they were found
+    pub hooks: Vec<(String, HookDefinition)>,
+    /// Anything that could not be converted faithfully
+    pub warnings: Vec<String>,
+}
+
+impl ImportedHooks {
+    /// Import from `path`, or from the tool's default location under `repo_root`
+    pub fn load(source: ImportSource, path: Option<&Path>, repo_root: &Path) -> Result<Self> {
+        let imported = match source {
+            ImportSource::Husky => {
+                let dir = path
+                    .map(Path::to_path_buf)
+                    .unwrap_or_else(|| repo_root.join(".husky"));
+                if !dir.is_dir() {
+                    return Err(anyhow!("No husky directory found at {}", dir.display()));
+                }
+                Self::from_husky_dir(&dir)?
+            }
+            ImportSource::Lefthook => {
+                let file = match path {
+                    Some(path) => path.to_path_buf(),
+                    None => find_lefthook_config(repo_root)?,
+                };
+                let content = fs::read_to_string(&file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                Self::from_lefthook_yaml(&content)
+                    .with_context(|| format!("Failed to parse {}", file.display()))?
+            }
+        };
+
+        if imported.hooks.is_empty() {
+            return Err(anyhow!("No hooks found to import"));
+        }
+        Ok(imported)
+    }
+
+    /// Convert `.husky/<hook>` scripts, one custom command per script line
+    fn from_husky_dir(dir: &Path) -> Result<Self> {
+        let mut imported = Self::default();
+
+        for hook in GIT_HOOKS {
+            let script_path = dir.join(hook);
+            if !script_path.is_file() {
+                continue;
+            }
+            let script = fs::read_to_string(&script_path)
+                .with_context(|| format!("Failed to read {}", script_path.display()))?;
+            let display_path = format!(".husky/{hook}");
+
+            let lines: Vec<&str> = script
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                // husky v4-v8 bootstrap line, not part of the user's hook
+                .filter(|line| !line.contains("husky.sh"))
+                .collect();
+
+            let commands = if lines.iter().any(|line| is_compound_shell(line)) {
+                imported.warnings.push(format!(
+                    "{display_path} uses shell control flow; imported as a single 'sh {display_path}' command, so keep the script in the repository"
+                ));
+                vec![CustomCommand::new(
+                    format!("sh {display_path}"),
+                    None,
+                    display_path.clone(),
+                )]
+            } else {
+                lines
+                    .iter()
+                    .map(|line| CustomCommand::new(line.to_string(), None, line.to_string()))
+                    .collect()
+            };
+
+            for cmd in &commands {
+                warn_positional_args(&cmd.command, &display_path, &mut imported.warnings);
+            }
+
+            imported.push_hook(
+                hook,
+                HookDefinition {
+                    enabled: true,
+                    // husky runs scripts top to bottom
+                    parallel: false,
+                    output: Default::default(),
+                    stash_unstaged: false,
+                    builtin: vec![],
+                    custom: commands,
+                },
+            );
+        }
+
+        Ok(imported)
+    }
+
+    /// Convert a lefthook configuration (`commands`, `scripts` and `jobs` per hook)
+    fn from_lefthook_yaml(content: &str) -> Result<Self> {
+        let mut imported = Self::default();
+        let root: Value = serde_yml::from_str(content)?;
+        let Some(root) = root.as_mapping() else {
+            return Ok(imported);
+        };
+
+        for (key, value) in root {
+            let Some(hook) = key.as_str() else {
+                continue;
+            };
+            let Some(hook_config) = value.as_mapping() else {
+                continue;
+            };
+            if !GIT_HOOKS.contains(&hook) {
+                // Top-level settings (colors, min_version, ...) are mappings too
+                if ["commands", "scripts", "jobs"]
+                    .iter()
+                    .any(|section| hook_config.contains_key(*section))
+                {
+                    imported
+                        .warnings
+                        .push(format!("{hook}: not a git hook, skipped"));
+                }
+                continue;
+            }
+
+            let mut custom = Vec::new();
+            let mut parallel = false;
+
+            for (option, option_value) in hook_config {
+                match option.as_str().unwrap_or_default() {
+                    "parallel" => parallel = option_value.as_bool().unwrap_or(false),
+                    "piped" => parallel = false,
+                    "commands" => {
+                        for (name, command) in option_value.as_mapping().into_iter().flatten() {
+                            let name = yaml_string(name);
+                            let context = format!("{hook}/{name}");
+                            if let Some(cmd) = lefthook_command(
+                                hook,
+                                Some(name),
+                                command,
+                                &context,
+                                &mut imported.warnings,
+                            ) {
+                                custom.push(cmd);
+                            }
+                        }
+                    }
+                    "scripts" => {
+                        for (file, script) in option_value.as_mapping().into_iter().flatten() {
+                            let file = yaml_string(file);
+                            let context = format!("{hook}/{file}");
+                            let mut script = script.as_mapping().cloned().unwrap_or_default();
+                            script.insert("script".into(), file.clone().into());
+                            if let Some(cmd) = lefthook_command(
+                                hook,
+                                Some(file),
+                                &Value::Mapping(script),
+                                &context,
+                                &mut imported.warnings,
+                            ) {
+                                custom.push(cmd);
+                            }
+                        }
+                    }
+                    "jobs" => {
+                        for (i, job) in option_value.as_sequence().into_iter().flatten().enumerate()
+                        {
+                            let name = job.get("name").map(yaml_string);
+                            let context = format!(
+                                "{hook}/{}",
+                                name.clone().unwrap_or_else(|| format!("jobs[{i}]"))
+                            );
+                            if let Some(cmd) =
+                                lefthook_command(hook, name, job, &context, &mut imported.warnings)
+                            {
+                                custom.push(cmd);
+                            }
+                        }
+                    }
+                    other => imported.warnings.push(format!(
+                        "{hook}: ignored unsupported lefthook option '{other}'"
+                    )),
+                }
+            }
+
+            imported.push_hook(
+                hook,
+                HookDefinition {
+                    enabled: true,
+                    parallel,
+                    output: Default::default(),
+                    stash_unstaged: false,
+                    builtin: vec![],
+                    custom,
+                },
+            );
+        }
+
+        Ok(imported)
+    }
+
+    fn push_hook(&mut self, hook: &str, definition: HookDefinition) {
+        if !SUPPORTED_HOOKS.contains(&hook) {
+            self.warnings.push(format!(
+                "{hook} is not installed by default; run 'guardy install --hooks {hook}' to enable it"
+            ));
+        }
+        self.hooks.push((hook.to_string(), definition));
+    }
+
+    /// Merge the imported hooks into the `hooks:` section of an existing guardy.yaml.
+    ///
+    /// Hooks already defined are kept unless `force` is set. Returns the new YAML and
+    /// the names of the hooks that were left untouched.
+    pub fn merge_into(&self, existing: &str, force: bool) -> Result<(String, Vec<String>)> {
+        let mut root = if existing.trim().is_empty() {
+            Mapping::new()
+        } else {
+            match serde_yml::from_str(existing)? {
+                Value::Mapping(mapping) => mapping,
+                Value::Null => Mapping::new(),
+                _ => return Err(anyhow!("Existing configuration is not a YAML mapping")),
+            }
+        };
+
+        let hooks = root
+            .entry("hooks".into())
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+        if hooks.is_null() {
+            *hooks = Value::Mapping(Mapping::new());
+        }
+        let hooks = hooks
+            .as_mapping_mut()
+            .ok_or_else(|| anyhow!("'hooks' in the existing configuration is not a mapping"))?;
+
+        let mut kept = Vec::new();
+        for (name, definition) in &self.hooks {
+            if hooks.contains_key(name.as_str()) && !force {
+                kept.push(name.clone());
+                continue;
+            }
+            hooks.insert(name.as_str().into(), serde_yml::to_value(definition)?);
+        }
+
+        Ok((serde_yml::to_string(&root)?, kept))
+    }
+}
+
+fn find_lefthook_config(repo_root: &Path) -> Result<PathBuf> {
+    LEFTHOOK_FILES
+        .iter()
+        .map(|name| repo_root.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            anyhow!(
+                "No lefthook configuration found (looked for {})",
+                LEFTHOOK_FILES.join(", ")
+            )
+        })
+}
+
+/// Convert one lefthook command, script or job into a guardy custom command
+fn lefthook_command(
+    hook: &str,
+    name: Option<String>,
+    config: &Value,
+    context: &str,
+    warnings: &mut Vec<String>,
+) -> Option<CustomCommand> {
+    let Some(config) = config.as_mapping() else {
+        warnings.push(format!("{context}: expected a mapping, skipped"));
+        return None;
+    };
+
+    let run = config.get("run").and_then(Value::as_str);
+    let script = config.get("script").and_then(Value::as_str);
+    let command = match (run, script) {
+        (Some(run), _) => run.to_string(),
+        (None, Some(script)) => {
+            let path = format!(".lefthook/{hook}/{script}");
+            match config.get("runner").and_then(Value::as_str) {
+                Some(runner) => format!("{runner} {path}"),
+                None => path,
+            }
+        }
+        (None, None) => {
+            warnings.push(format!(
+                "{context}: no 'run' or 'script' (groups are not supported), skipped"
+            ));
+            return None;
+        }
+    };
+
+    let description = name.clone().unwrap_or_else(|| command.clone());
+    let mut cmd = CustomCommand::new(command, name, description);
+
+    for (option, value) in config {
+        match option.as_str().unwrap_or_default() {
+            "name" | "run" | "script" | "runner" => {}
+            "glob" => {
+                cmd.glob = match value {
+                    Value::String(glob) => vec![glob.clone()],
+                    Value::Sequence(globs) => globs.iter().map(yaml_string).collect(),
+                    _ => vec![],
+                }
+            }
+            "stage_fixed" => cmd.stage_fixed = value.as_bool().unwrap_or(false),
+            other => warnings.push(format!(
+                "{context}: ignored unsupported lefthook option '{other}'"
+            )),
+        }
+    }
+
+    warn_positional_args(&cmd.command, context, warnings);
+    Some(cmd)
+}
+
+fn is_compound_shell(line: &str) -> bool {
+    let first_word = line.split_whitespace().next().unwrap_or_default();
+    SHELL_CONTROL.contains(&first_word.trim_end_matches(';'))
+        || line.ends_with('\\')
+        || line.ends_with('{')
+        || line == "}"
+}
+
+fn warn_positional_args(command: &str, context: &str, warnings: &mut Vec<String>) {
+    if POSITIONAL_ARGS.iter().any(|arg| command.contains(arg)) {
+        warnings.push(format!(
+            "{context}: uses hook arguments, which guardy custom commands do not receive; review '{command}'"
+        ));
+    }
+}
+
+fn yaml_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => serde_yml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn commands(imported: &ImportedHooks, hook: &str) -> Vec<String> {
+        imported
+            .hooks
+            .iter()
+            .find(|(name, _)| name == hook)
+            .map(|(_, def)| def.custom.iter().map(|c| c.command.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_husky_scripts_become_sequential_commands() {
+        let temp = TempDir::new().unwrap();
+        let husky = temp.path().join(".husky");
+        fs::create_dir_all(husky.join("_")).unwrap();
+        fs::write(
+            husky.join("pre-commit"),
+            "#!/usr/bin/env sh\n. \"$(dirname -- \"$0\")/_/husky.sh\"\n\n# lint\nnpx lint-staged\nnpm test\n",
+        )
+        .unwrap();
+        fs::write(
+            husky.join("commit-msg"),
+            "npx --no -- commitlint --edit $1\n",
+        )
+        .unwrap();
+
+        let imported = ImportedHooks::load(ImportSource::Husky, None, temp.path()).unwrap();
+
+        let names: Vec<&str> = imported.hooks.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["pre-commit", "commit-msg"]);
+        assert_eq!(
+            commands(&imported, "pre-commit"),
+            vec!["npx lint-staged", "npm test"]
+        );
+        assert!(!imported.hooks[0].1.parallel);
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(imported.warnings[0].contains("hook arguments"));
+    }
+
+    #[test]
+    fn test_husky_control_flow_runs_whole_script() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir(temp.path().join(".husky")).unwrap();
+        fs::write(
+            temp.path().join(".husky/pre-push"),
+            "if [ -n \"$CI\" ]; then\n  exit 0\nfi\ncargo test\n",
+        )
+        .unwrap();
+
+        let imported = ImportedHooks::load(ImportSource::Husky, None, temp.path()).unwrap();
+
+        assert_eq!(commands(&imported, "pre-push"), vec!["sh .husky/pre-push"]);
+        assert!(imported.warnings[0].contains("shell control flow"));
+    }
+
+    #[test]
+    fn test_lefthook_commands_scripts_and_jobs() {
+        let imported = ImportedHooks::from_lefthook_yaml(
+            r#"
+min_version: 1.5.0
+colors:
+  cyan: 14
+pre-commit:
+  parallel: true
+  commands:
+    eslint:
+      glob: "*.{js,ts}"
+      run: npx eslint --fix {staged_files}
+      stage_fixed: true
+    audit:
+      run: npm audit {all_files}
+      skip: merge
+  scripts:
+    "check.sh":
+      runner: bash
+pre-push:
+  jobs:
+    - name: test
+      run: cargo test
+"#,
+        )
+        .unwrap();
+
+        let (_, pre_commit) = &imported.hooks[0];
+        assert!(pre_commit.parallel);
+        let eslint = &pre_commit.custom[0];
+        assert_eq!(eslint.name.as_deref(), Some("eslint"));
+        assert_eq!(eslint.command, "npx eslint --fix {staged_files}");
+        assert_eq!(eslint.glob, vec!["*.{js,ts}"]);
+        assert!(eslint.stage_fixed);
+
+        let audit = &pre_commit.custom[1];
+        assert_eq!(audit.command, "npm audit {all_files}");
+        assert!(audit.glob.is_empty());
+
+        assert_eq!(
+            commands(&imported, "pre-commit")[2],
+            "bash .lefthook/pre-commit/check.sh"
+        );
+        assert_eq!(commands(&imported, "pre-push"), vec!["cargo test"]);
+        assert_eq!(
+            imported.warnings,
+            vec!["pre-commit/audit: ignored unsupported lefthook option 'skip'"]
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_existing_hooks_unless_forced() {
+        let imported = ImportedHooks::from_lefthook_yaml(
+            "pre-commit:\n  commands:\n    fmt:\n      run: cargo fmt --check\npre-push:\n  commands:\n    test:\n      run: cargo test\n",
+        )
+        .unwrap();
+        let existing =
+            "scanner:\n  mode: parallel\nhooks:\n  pre-commit:\n    builtin: [scan_secrets]\n";
+
+        let (yaml, kept) = imported.merge_into(existing, false).unwrap();
+        assert_eq!(kept, vec!["pre-commit"]);
+        let merged: Value = serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(merged["scanner"]["mode"].as_str(), Some("parallel"));
+        assert_eq!(
+            merged["hooks"]["pre-commit"]["builtin"][0].as_str(),
+            Some("scan_secrets")
+        );
+        assert_eq!(
+            merged["hooks"]["pre-push"]["custom"][0]["command"].as_str(),
+            Some("cargo test")
+        );
+
+        let (yaml, kept) = imported.merge_into(existing, true).unwrap();
+        assert!(kept.is_empty());
+        let merged: Value = serde_yml::from_str(&yaml).unwrap();
+        assert_eq!(
+            merged["hooks"]["pre-commit"]["custom"][0]["name"].as_str(),
+            Some("fmt")
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::Read;
+use std::path::Path;
+
+/// Names accepted in a command's `languages:` list that differ from the tag they mean
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("typescript", "ts"),
+    ("js", "javascript"),
+    ("py", "python"),
+    ("rs", "rust"),
+    ("golang", "go"),
+    ("rb", "ruby"),
+    ("cpp", "c++"),
+    ("csharp", "c#"),
+    ("yml", "yaml"),
+    ("md", "markdown"),
+];
+
+/// Whether `path` is written in any of `languages` (extension, file name or shebang)
+pub fn matches_any(path: &Path, languages: &[String]) -> bool {
+    let tags = file_tags(path);
+    languages.iter().any(|language| {
+        let language = language.to_lowercase();
+        let tag = LANGUAGE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == language)
+            .map_or(language.as_str(), |(_, tag)| tag);
+        tags.contains(&tag)
+    })
+}
+
+/// identify-style tags for a file (`file`, `text`/`binary`, `executable`, language tags)
+///
+/// Languages come from the file name or extension, falling back to the `#!` line for
+/// extensionless scripts such as `bin/deploy`.
+pub fn file_tags(path: &Path) -> Vec<&'static str> {
+    let mut tags = vec!["file"];
+
+    let mut sample = Vec::with_capacity(8192);
+    let readable = std::fs::File::open(path)
+        .and_then(|file| file.take(8192).read_to_end(&mut sample))
+        .is_ok();
+    let is_binary = readable && content_inspector::inspect(&sample).is_binary();
+    tags.push(if is_binary { "binary" } else { "text" });
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0) {
+            tags.push("executable");
+        }
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let mut language = name_tags(&file_name, &extension);
+    if language.is_empty() && !is_binary {
+        language = shebang_tags(&sample);
+    }
+    tags.extend_from_slice(language);
+    tags
+}
+
+fn name_tags(file_name: &str, extension: &str) -> &'static [&'static str] {
+    match (file_name, extension) {
+        ("dockerfile", _) => &["dockerfile"],
+        ("makefile", _) => &["makefile"],
+        ("rakefile" | "gemfile", _) => &["ruby"],
+        (_, "py") => &["python"],
+        (_, "pyi") => &["python", "pyi"],
+        (_, "js" | "mjs" | "cjs") => &["javascript"],
+        (_, "jsx") => &["javascript", "jsx"],
+        (_, "ts" | "mts" | "cts") => &["ts"],
+        (_, "tsx") => &["ts", "tsx"],
+        (_, "rs") => &["rust"],
+        (_, "go") => &["go"],
+        (_, "rb") => &["ruby"],
+        (_, "java") => &["java"],
+        (_, "kt" | "kts") => &["kotlin"],
+        (_, "c") => &["c"],
+        (_, "h") => &["c", "header"],
+        (_, "cc" | "cpp" | "cxx") => &["c++"],
+        (_, "hpp") => &["c++", "header"],
+        (_, "cs") => &["c#"],
+        (_, "php") => &["php"],
+        (_, "swift") => &["swift"],
+        (_, "lua") => &["lua"],
+        (_, "sh") => &["shell", "sh"],
+        (_, "bash") => &["shell", "bash"],
+        (_, "zsh") => &["shell", "zsh"],
+        (_, "yml" | "yaml") => &["yaml"],
+        (_, "json") => &["json"],
+        (_, "toml") => &["toml"],
+        (_, "xml") => &["xml"],
+        (_, "md" | "markdown") => &["markdown"],
+        (_, "html" | "htm") => &["html"],
+        (_, "css") => &["css"],
+        (_, "scss") => &["scss"],
+        (_, "sql") => &["sql"],
+        (_, "tf") => &["terraform"],
+        _ => &[],
+    }
+}
+
+/// Language of a script from its interpreter line (`#!/usr/bin/env python3 -u`)
+fn shebang_tags(content: &[u8]) -> &'static [&'static str] {
+    let Some(line) = content.strip_prefix(b"#!") else {
+        return &[];
+    };
+    let line = String::from_utf8_lossy(line.split(|&b| b == b'\n').next().unwrap_or_default());
+    let mut words = line.split_whitespace();
+    let mut interpreter = words
+        .next()
+        .unwrap_or_default()
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    if interpreter == "env" {
+        interpreter = words
+            .find(|word| !word.starts_with('-'))
+            .unwrap_or_default();
+    }
+    let interpreter = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    match interpreter {
+        "python" => &["python"],
+        "node" | "deno" | "bun" => &["javascript"],
+        "ts-node" | "tsx" => &["ts"],
+        "ruby" => &["ruby"],
+        "php" => &["php"],
+        "lua" => &["lua"],
+        "sh" | "dash" => &["shell", "sh"],
+        "bash" => &["shell", "bash"],
+        "zsh" => &["shell", "zsh"],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn languages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extensionless_scripts_are_detected_from_shebang() {
+        let temp = TempDir::new().unwrap();
+        let deploy = temp.path().join("deploy");
+        let serve = temp.path().join("serve");
+        std::fs::write(&deploy, "#!/usr/bin/env -S python3.12 -u\nprint('hi')\n").unwrap();
+        std::fs::write(&serve, "#!/bin/bash\nexec node server.js\n").unwrap();
+
+        assert!(file_tags(&deploy).contains(&"python"));
+        assert!(file_tags(&serve).contains(&"shell"));
+        assert!(!matches_any(&serve, &languages(&["python"])));
+    }
+
+    #[test]
+    fn test_languages_accept_common_aliases() {
+        let temp = TempDir::new().unwrap();
+        let component = temp.path().join("App.TSX");
+        std::fs::write(&component, "export const App = () => null;\n").unwrap();
+
+        assert!(matches_any(&component, &languages(&["rust", "TypeScript"])));
+        assert!(!matches_any(&component, &languages(&["javascript"])));
+    }
+}
@@ -6,8 +6,30 @@
 //!
 //! ## Built-in Actions
 //!
-//! - `scan_secrets` - Scans staged files for secrets and sensitive data
+//! - `scan_secrets` - Scans staged files for secrets and sensitive data (`pre-commit`,
+//!   `pre-applypatch`), or the commit message (`commit-msg`, `applypatch-msg`)
 //! - `validate_commit_msg` - Validates commit messages using conventional commits format
+//!   (`commit-msg`, `applypatch-msg`)
+//! - `commit_template` - Injects branch-derived ticket prefixes, scopes and trailers
+//!   into the commit message buffer (`prepare-commit-msg`)
+//! - `install_dependencies` - Runs the matching install command when lockfiles or
+//!   manifests change (`post-checkout`, `post-merge`)
+//! - `pre_commit_config` - Runs the hooks from an existing `.pre-commit-config.yaml`
+//!   for the current stage (`system`, `script` and `fail` languages)
+//! - `require_signed_commits` - Rejects pushes containing commits without a valid
+//!   GPG/SSH signature from an allowed signer (`pre-push`)
+//! - `protect_branches` - Rejects pushes to `branch_protection.protected_branches`
+//!   beyond what `branch_protection.push_policy` allows (`pre-push`)
+//! - `validate_branch_name` - Checks branch names against the `branch_naming`
+//!   regexes and suggests a rename (`pre-commit`, `pre-push`)
+//! - `check_merge_conflicts` - Rejects staged files still holding conflict markers,
+//!   except those matching `merge_conflicts.exclude` (`pre-commit`, `pre-applypatch`)
+//! - `block_large_files` - Rejects staged blobs over `large_files.max_size_kb` (and
+//!   optionally any binary), suggesting Git LFS (`pre-commit`, `pre-applypatch`)
+//!
+//! `pre-applypatch` and `applypatch-msg` run during `git am`, where the patch is
+//! staged and its message passed like a commit's. `post-commit` and `pre-rebase`
+//! take custom commands only; `post-commit` can't stop the commit.
 //!
 //! ## Custom Commands
 //!
@@ -21,9 +43,13 @@
 //!     builtin: ["scan_secrets"]
 //!     custom:
 //!       - command: "cargo fmt --check"
+//!         name: "fmt"  # GUARDY_SKIP=fmt skips this command
 //!         description: "Check formatting"
 //!         fail_on_error: true
 //!         glob: ["*.rs"]  # Only run on Rust files
+//!       - command: "cargo clippy -- -D warnings"
+//!         name: "clippy"
+//!         depends_on: ["fmt"]  # Starts once fmt has passed
 //!       - command: "eslint {files} --fix"
 //!         description: "Fix linting issues"
 //!         all_files: true  # Run on all files, not just staged
@@ -44,11 +70,89 @@
 //!   - Large workloads (6+ commands): Full parallelism (capped at 8)
 //! - **System-Aware**: Respects CPU core count and user-configured limits
 //!
+//! ### Command Dependencies
+//! - `depends_on: ["fmt"]` makes a command wait for the named commands (by `name`,
+//!   or `description` when unnamed) - independent commands still run concurrently
+//! - Commands downstream of a failed command never start; with `parallel: false`
+//!   commands run one at a time in declared order, dependencies first
+//! - Unknown names and cycles are reported before anything runs; depending on a
+//!   command skipped via `GUARDY_SKIP` counts as satisfied
+//!
+//! ### Skipping Commands
+//! - `GUARDY_SKIP=fmt,clippy guardy run pre-commit` (or the `SKIP` convention) skips
+//!   builtins and custom commands by name at runtime
+//! - Custom commands match on `name`, falling back to `description`
+//! - Naming the hook itself (`GUARDY_SKIP=pre-commit`) skips the whole hook
+//! - Skipped commands are always reported in the output
+//!
+//! ### Dry-run Plans
+//! - `guardy run <hook> --plan` prints the builtins that would run (or why they don't
+//!   apply), each custom command with its files after glob filtering, skips from
+//!   `GUARDY_SKIP`, and the sequential/parallel strategy - without executing anything
+//!
+//! ### Retrying Flaky Commands
+//! - `retries: 2` re-runs a failed command up to twice, waiting `retry_backoff_ms`
+//!   (default 1000) before the first retry and doubling the delay each time
+//! - `retry_on: { exit_codes: [75], output: "ETIMEDOUT|503" }` limits retries to
+//!   matching failures; the regex is checked against stdout and stderr
+//! - Each retry is announced, and the attempt count appears in the timing summary
+//!   and the `--format json` report
+//!
+//! ### Command Environment
+//! - `env: { RUST_LOG: "${LOG_LEVEL:-warn}" }` sets variables for one command;
+//!   values expand `${VAR}` and `${VAR:-default}`
+//! - `env_file: [".env.test"]` loads dotenv files first (relative to the repository
+//!   root, skipped when missing); they never override variables already set,
+//!   while `env` entries always do
+//!
+//! ### Command Output
+//! - `output: quiet` (default) captures custom command output and only shows stderr
+//!   when a command fails
+//! - `output: stream` prints each line as it arrives, prefixed with `[name]`
+//! - `output: group` prints each command's output as one block when it finishes, so
+//!   parallel commands never interleave
+//!
+//! ### Hiding Unstaged Changes
+//! - `stash_unstaged: true` on `pre-commit` reverts unstaged changes to tracked
+//!   files before any check runs, so commands see exactly what will be committed,
+//!   and brings them back afterwards - also when a check fails
+//! - The changes are kept as a patch in `.git/guardy-unstaged.patch` meanwhile; if
+//!   the run is killed, the next one restores it first
+//! - When a fixer touched a file that also had unstaged changes, the two are merged
+//!   three ways; where they clash the user's changes win, and the fixer's unstaged
+//!   edits are saved in `.git/guardy-hook-edits.patch` (staged fixes are kept)
+//!
+//! ### Timing Breakdown
+//! - Every builtin and custom command is timed; after the run a summary lists them
+//!   slowest-first with their share of the total, including on failure
+//! - `guardy run <hook> --format json` silences progress output and prints a JSON
+//!   report instead: `hook`, `success`, `error`, `total_ms`, `skipped` and `steps`
+//!   (`name`, `kind`, `outcome`, `attempts`, `duration_ms`)
+//!
+//! ### Recording and Replaying Runs
+//! - `guardy run <hook> --record session.json` saves the hook's inputs - HEAD, the
+//!   staged blobs, the merged config (personal hooks included) and tool versions -
+//!   along with its report
+//! - `guardy replay session.json` rebuilds that index in a temporary worktree of the
+//!   current repository and runs the hook with the recorded config, warning about
+//!   tool version drift and comparing each step's outcome with the recording
+//! - The recorded commit and blobs must be reachable locally: commit the staged
+//!   changes to a branch and push it to share a session
+//!
 //! ### File Processing
 //! - **Glob Filtering**: Use `glob` patterns to target specific file types
+//! - **Language Filtering**: `languages: ["rust", "typescript"]` keeps files by
+//!   extension, file name or shebang, so extensionless scripts are matched too
 //! - **All Files Mode**: Set `all_files: true` to process all matching files in repository
 //! - **Stage Integration**: Use `stage_fixed: true` to automatically stage modified files
-//! - **File Substitution**: Use `{files}` placeholder for command file arguments
+//! - **File Substitution**: `{files}` is the command's own selection; `{staged_files}`,
+//!   `{all_files}` (tracked files) and `{push_files}` (changed since the upstream) are
+//!   available in any hook. Modifiers combine with `|`: `{files:relative|quoted}`, or
+//!   `null-separated` for the path of a NUL-separated list file (`xargs -0 -a`).
+//!   Lists too long for one command line are split across several runs
+//! - **Workspaces**: `workspaces: true` runs the command once per affected package of
+//!   a Cargo, pnpm, npm/yarn or nx workspace, with `{package_dir}` and `{files}` limited
+//!   to that package; files outside every package don't trigger a run
 //!
 //! ### Conventional Commits Validation
 //! - Full specification compliance using `git-conventional` library
@@ -56,6 +160,49 @@
 //! - Comment filtering and scope validation
 //! - Supports all conventional commit types (feat, fix, docs, chore, etc.)
 //!
+//! ### Commit Message Templates
+//! - `prepare-commit-msg` hook with the `commit_template` builtin
+//! - Ticket ids extracted from the branch name via `commit_template.ticket_pattern`
+//! - Per-branch rules (first match wins) for subject prefixes, scopes and trailers
+//! - Co-author trailers, never duplicated when already present
+//!
+//! ### Dependency Installation
+//! - Diffs the previous and new HEAD after branch checkouts and merges
+//! - Detects npm/pnpm/yarn/bun, Cargo, uv/poetry/pip, bundler, Go and composer files
+//! - Runs the install in the directory of the changed file (monorepo friendly)
+//! - `package_manager.auto_install: false` only prints the commands to run
+//!
+//! ### pre-commit Framework Configs
+//! - Remote hook repositories are cloned per `rev` into `.guardy/cache/pre-commit/`
+//!   and their `.pre-commit-hooks.yaml` manifests merged with the config's overrides
+//! - `files`/`exclude` regexes, `types`/`types_or`/`exclude_types`, `stages`,
+//!   `pass_filenames`, `always_run` and `fail_fast` follow pre-commit's semantics
+//! - No language environments are built: hooks needing one are reported and skipped
+//!
+//! ## Personal Hooks
+//!
+//! `~/.config/guardy/hooks.yaml` (or `$XDG_CONFIG_HOME/guardy/hooks.yaml`) holds
+//! hook definitions in the same shape as the `hooks:` section, merged into every
+//! repository's configuration. Personal hooks only add checks: for a hook the
+//! repository enables, their builtins and commands run after the repository's (a
+//! repository command with the same name wins); for a hook the repository leaves
+//! disabled, the personal definition is used on its own.
+//!
+//! ## Migrating from Other Hook Managers
+//!
+//! `guardy hooks import --from husky|lefthook` converts `.husky/<hook>` scripts or
+//! `lefthook.yml` into `hooks:` entries in guardy.yaml. Lefthook's file placeholders
+//! (`{staged_files}`, `{push_files}`, `{all_files}`) carry over unchanged; anything
+//! that can't be converted faithfully is reported as a warning.
+//!
+//! ## Presets
+//!
+//! `guardy hooks add-preset rust|node|python|go` appends curated fmt/lint commands
+//! to `pre-commit` and test/typecheck commands to `pre-push`. Without arguments the
+//! presets are picked from the project files (Cargo.toml, package.json, pyproject.toml,
+//! go.mod); commands follow the detected Node package manager and uv/poetry setup.
+//! Commands already present by name are left alone, so re-running is safe.
+//!
 //! ## Integration with Sync
 //!
 //! Hooks can be used to automatically sync protected files:
@@ -73,7 +220,28 @@
 //! This ensures that protected configuration files are always synchronized
 //! with their upstream sources before pushing changes.
 
+pub mod builtins;
+mod command_output;
 mod config;
+mod env;
 mod executor;
+mod graph;
+mod import;
+mod languages;
+mod placeholders;
+mod presets;
+mod retry;
+mod session;
+mod setup;
+mod skip;
+mod stash;
+mod timing;
+mod user_hooks;
+mod workspaces;
 
+pub use config::SUPPORTED_HOOKS;
 pub use executor::HookExecutor;
+pub use import::{ImportSource, ImportedHooks};
+pub use presets::Preset;
+pub use session::{record_session, replay_session};
+pub use setup::Proposal;
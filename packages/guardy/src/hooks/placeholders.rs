@@ -0,0 +1,277 @@
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tempfile::NamedTempFile;
+
+/// Longest command guardy hands to the shell. Linux caps a single argument (the
+/// `sh -c` script) at 128 KiB and cmd.exe a command line at 8191 characters.
+const MAX_COMMAND_LEN: usize = if cfg!(target_os = "windows") {
+    8_000
+} else {
+    127 * 1024
+};
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{(files|staged_files|all_files|push_files)(?::([^{}\s]*))?\}").unwrap()
+});
+
+/// Files a placeholder expands to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileSource {
+    /// `{files}`: the command's own selection - staged files in pre-commit,
+    /// every matching file with `all_files`
+    Files,
+    /// `{staged_files}`: files staged for commit
+    Staged,
+    /// `{all_files}`: every file tracked by git
+    All,
+    /// `{push_files}`: files changed between the upstream branch and HEAD
+    Push,
+}
+
+/// File lists by source, already narrowed by the command's `glob` and `languages`
+pub type FileLists = BTreeMap<FileSource, Vec<PathBuf>>;
+
+/// A file-list placeholder in a command, e.g. `{staged_files:relative|quoted}`
+#[derive(Debug, Clone, PartialEq)]
+struct Placeholder {
+    /// Where the placeholder sits in the command
+    range: Range<usize>,
+    text: String,
+    source: FileSource,
+    /// Paths relative to the working directory instead of absolute
+    relative: bool,
+    /// Each path quoted for the shell
+    quoted: bool,
+    /// A file holding the NUL-separated list instead of the paths themselves
+    null_separated: bool,
+}
+
+/// A command with its file lists filled in
+#[derive(Debug)]
+pub struct Expanded {
+    pub command: String,
+    /// The part of the command's own selection (`{files}`) this run covers
+    pub files: Vec<PathBuf>,
+    /// Files backing `null-separated` lists, removed once dropped
+    pub lists: Vec<NamedTempFile>,
+}
+
+/// Sources of the file-list placeholders used in `command`
+pub fn sources(command: &str) -> Result<Vec<FileSource>> {
+    let mut sources: Vec<FileSource> = parse(command)?
+        .into_iter()
+        .map(|placeholder| placeholder.source)
+        .collect();
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+/// Fill in the file-list placeholders of `command`
+///
+/// When the lists would make the command longer than the OS allows, they are
+/// split into chunks and the command is run once per chunk, each run getting the
+/// same share of every list.
+pub fn expand(command: &str, lists: &FileLists) -> Result<Vec<Expanded>> {
+    let placeholders = parse(command)?;
+    let selection = lists.get(&FileSource::Files).cloned().unwrap_or_default();
+    if placeholders.is_empty() {
+        return Ok(vec![Expanded {
+            command: command.to_string(),
+            files: selection,
+            lists: Vec::new(),
+        }]);
+    }
+
+    let longest = lists.values().map(Vec::len).max().unwrap_or(0);
+    let mut chunks = 1;
+    loop {
+        let runs = (0..chunks)
+            .map(|index| expand_chunk(command, &placeholders, lists, index, chunks))
+            .collect::<Result<Vec<_>>>()?;
+        if chunks >= longest || runs.iter().all(|run| run.command.len() <= MAX_COMMAND_LEN) {
+            return Ok(runs);
+        }
+        chunks = (chunks * 2).min(longest);
+    }
+}
+
+fn parse(command: &str) -> Result<Vec<Placeholder>> {
+    PLACEHOLDER
+        .captures_iter(command)
+        .map(|captures| {
+            let source = match &captures[1] {
+                "files" => FileSource::Files,
+                "staged_files" => FileSource::Staged,
+                "all_files" => FileSource::All,
+                _ => FileSource::Push,
+            };
+            let mut placeholder = Placeholder {
+                range: captures.get(0).map_or(0..0, |m| m.range()),
+                text: captures[0].to_string(),
+                source,
+                relative: false,
+                quoted: false,
+                null_separated: false,
+            };
+            let modifiers = captures.get(2).map_or("", |m| m.as_str());
+            for modifier in modifiers.split('|').filter(|m| !m.is_empty()) {
+                match modifier {
+                    "relative" => placeholder.relative = true,
+                    "quoted" => placeholder.quoted = true,
+                    "null-separated" => placeholder.null_separated = true,
+                    other => {
+                        return Err(anyhow!(
+                            "Unknown modifier '{other}' in {} (use relative, quoted or null-separated)",
+                            placeholder.text
+                        ));
+                    }
+                }
+            }
+            Ok(placeholder)
+        })
+        .collect()
+}
+
+fn expand_chunk(
+    command: &str,
+    placeholders: &[Placeholder],
+    lists: &FileLists,
+    index: usize,
+    chunks: usize,
+) -> Result<Expanded> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let part = |source: FileSource| -> &[PathBuf] {
+        let files = lists.get(&source).map_or(&[][..], Vec::as_slice);
+        &files[index * files.len() / chunks..(index + 1) * files.len() / chunks]
+    };
+
+    let mut expanded = Expanded {
+        command: String::with_capacity(command.len()),
+        files: part(FileSource::Files).to_vec(),
+        lists: Vec::new(),
+    };
+    let mut copied = 0;
+    for placeholder in placeholders {
+        let paths: Vec<String> = part(placeholder.source)
+            .iter()
+            .map(|path| {
+                if placeholder.relative {
+                    relative(path, &cwd)
+                } else {
+                    path.to_string_lossy().to_string()
+                }
+            })
+            .collect();
+
+        let value = if placeholder.null_separated {
+            let mut list = NamedTempFile::new()?;
+            for path in &paths {
+                list.write_all(path.as_bytes())?;
+                list.write_all(b"\0")?;
+            }
+            list.flush()?;
+            let path = list.path().to_string_lossy().to_string();
+            expanded.lists.push(list);
+            if placeholder.quoted {
+                quote(&path)
+            } else {
+                path
+            }
+        } else if placeholder.quoted {
+            paths
+                .iter()
+                .map(|path| quote(path))
+                .collect::<Vec<_>>()
+                .join(" ")
+        } else {
+            paths.join(" ")
+        };
+        expanded
+            .command
+            .push_str(&command[copied..placeholder.range.start]);
+        expanded.command.push_str(&value);
+        copied = placeholder.range.end;
+    }
+    expanded.command.push_str(&command[copied..]);
+    Ok(expanded)
+}
+
+fn relative(path: &Path, cwd: &Path) -> String {
+    let path = path.strip_prefix(cwd).unwrap_or(path);
+    let path = path.strip_prefix(".").unwrap_or(path);
+    path.to_string_lossy().to_string()
+}
+
+fn quote(path: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("\"{path}\"")
+    } else {
+        format!("'{}'", path.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists(entries: &[(FileSource, &[&str])]) -> FileLists {
+        entries
+            .iter()
+            .map(|(source, files)| (*source, files.iter().map(PathBuf::from).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_sources_and_modifiers() {
+        let cwd = std::env::current_dir().unwrap();
+        let mut lists = lists(&[
+            (FileSource::Files, &["a.rs"]),
+            (FileSource::Staged, &["it's.rs", "b.rs"]),
+        ]);
+        lists.insert(FileSource::All, vec![cwd.join("src/lib.rs")]);
+
+        let runs = expand(
+            "check {files} {staged_files:quoted} {all_files:relative} && xargs -0 -a {staged_files:null-separated} echo",
+            &lists,
+        )
+        .unwrap();
+
+        assert_eq!(runs.len(), 1);
+        let list = runs[0].lists[0].path().to_string_lossy().to_string();
+        assert_eq!(
+            runs[0].command,
+            format!("check a.rs 'it'\\''s.rs' 'b.rs' src/lib.rs && xargs -0 -a {list} echo")
+        );
+        assert_eq!(std::fs::read(&list).unwrap(), b"it's.rs\0b.rs\0");
+
+        let error = expand("lint {files:absolute}", &lists).unwrap_err();
+        assert!(error.to_string().contains("Unknown modifier 'absolute'"));
+        assert_eq!(
+            sources("{push_files} {files} {push_files:quoted} {package_dir}").unwrap(),
+            vec![FileSource::Files, FileSource::Push]
+        );
+    }
+
+    #[test]
+    fn test_expand_chunks_long_file_lists() {
+        let files: Vec<String> = (0..4000)
+            .map(|i| format!("src/some/deeply/nested/module_{i:04}.rs"))
+            .collect();
+        let files: Vec<&str> = files.iter().map(String::as_str).collect();
+        let lists = lists(&[(FileSource::Files, &files)]);
+
+        let runs = expand("rustfmt --check {files}", &lists).unwrap();
+
+        assert!(runs.len() > 1);
+        assert!(runs.iter().all(|run| run.command.len() <= MAX_COMMAND_LEN));
+        let covered: usize = runs.iter().map(|run| run.files.len()).sum();
+        assert_eq!(covered, files.len());
+        assert!(runs[1].command.starts_with("rustfmt --check src/"));
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{Result, anyhow};
+use serde_yml::{Mapping, Value};
+use std::path::Path;
+
+use super::builtins::node_package_manager;
+use super::config::CustomCommand;
+
+/// Curated command sets `guardy hooks add-preset` can add to guardy.yaml
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Preset {
+    /// cargo fmt and clippy on commit, cargo test on push
+    Rust,
+    /// prettier and eslint on commit, tsc and the test script on push
+    Node,
+    /// ruff format and ruff check on commit, mypy and pytest on push
+    Python,
+    /// gofmt and go vet on commit, go test on push
+    Go,
+}
+
+/// Commands a preset adds, grouped by hook
+#[derive(Debug, Default)]
+pub struct PresetCommands {
+    pub hooks: Vec<(&'static str, Vec<CustomCommand>)>,
+}
+
+/// Result of appending preset commands to an existing configuration
+#[derive(Debug, Default)]
+pub struct AppendedCommands {
+    pub yaml: String,
+    /// `(hook, command name)` pairs that were added
+    pub added: Vec<(String, String)>,
+    /// Names already present in that hook, left as they were
+    pub existing: Vec<(String, String)>,
+}
+
+impl Preset {
+    /// Presets matching the project files in `dir` (Cargo.toml, package.json, ...)
+    pub fn detect(dir: &Path) -> Vec<Self> {
+        let has = |file: &str| dir.join(file).exists();
+
+        let mut presets = Vec::new();
+        if has("Cargo.toml") {
+            presets.push(Self::Rust);
+        }
+        if has("package.json") {
+            presets.push(Self::Node);
+        }
+        if has("pyproject.toml") || has("requirements.txt") || has("setup.py") {
+            presets.push(Self::Python);
+        }
+        if has("go.mod") {
+            presets.push(Self::Go);
+        }
+        presets
+    }
+
+    /// The preset's commands, adapted to the tooling found in `dir`
+    /// (Node package manager, uv or poetry for Python)
+    pub fn commands(self, dir: &Path) -> PresetCommands {
+        let (commit, push) = match self {
+            Self::Rust => {
+                let mut clippy = command(
+                    "clippy",
+                    "cargo clippy --all-targets -- -D warnings",
+                    "Lint Rust code",
+                    &[],
+                );
+                clippy.depends_on.push("cargo-fmt".to_string());
+                (
+                    vec![
+                        command(
+                            "cargo-fmt",
+                            "cargo fmt --all -- --check",
+                            "Check Rust formatting",
+                            &[],
+                        ),
+                        clippy,
+                    ],
+                    vec![command(
+                        "cargo-test",
+                        "cargo test --workspace",
+                        "Run Rust tests",
+                        &[],
+                    )],
+                )
+            }
+            Self::Node => {
+                let manager = node_package_manager(dir).unwrap_or("npm");
+                let exec = match manager {
+                    "pnpm" => "pnpm exec",
+                    "yarn" => "yarn",
+                    "bun" => "bunx",
+                    _ => "npx",
+                };
+                let sources = ["*.js", "*.jsx", "*.ts", "*.tsx", "*.mjs", "*.cjs"];
+                (
+                    vec![
+                        command(
+                            "prettier",
+                            &format!("{exec} prettier --check {{files}}"),
+                            "Check formatting",
+                            &[&sources[..], &["*.json", "*.css", "*.md"]].concat(),
+                        ),
+                        command(
+                            "eslint",
+                            &format!("{exec} eslint {{files}}"),
+                            "Lint JavaScript/TypeScript",
+                            &sources,
+                        ),
+                    ],
+                    vec![
+                        command("tsc", &format!("{exec} tsc --noEmit"), "Type-check", &[]),
+                        command("node-test", &format!("{manager} test"), "Run tests", &[]),
+                    ],
+                )
+            }
+            Self::Python => {
+                let run = if dir.join("uv.lock").exists() {
+                    "uv run "
+                } else if dir.join("poetry.lock").exists() {
+                    "poetry run "
+                } else {
+                    ""
+                };
+                (
+                    vec![
+                        command(
+                            "ruff-format",
+                            &format!("{run}ruff format --check {{files}}"),
+                            "Check Python formatting",
+                            &["*.py"],
+                        ),
+                        command(
+                            "ruff-check",
+                            &format!("{run}ruff check {{files}}"),
+                            "Lint Python code",
+                            &["*.py"],
+                        ),
+                    ],
+                    vec![
+                        command("mypy", &format!("{run}mypy ."), "Type-check", &[]),
+                        command("pytest", &format!("{run}pytest"), "Run tests", &[]),
+                    ],
+                )
+            }
+            Self::Go => (
+                vec![
+                    command(
+                        "gofmt",
+                        "test -z \"$(gofmt -l {files})\"",
+                        "Check Go formatting",
+                        &["*.go"],
+                    ),
+                    command("go-vet", "go vet ./...", "Vet Go code", &[]),
+                ],
+                vec![command("go-test", "go test ./...", "Run Go tests", &[])],
+            ),
+        };
+
+        PresetCommands {
+            hooks: vec![("pre-commit", commit), ("pre-push", push)],
+        }
+    }
+}
+
+impl PresetCommands {
+    /// Append the commands to the `hooks:` section of an existing guardy.yaml
+    ///
+    /// Hooks are created (and enabled) when missing. Commands whose name already
+    /// exists in the hook are left untouched, so adding a preset twice is a no-op.
+    pub fn append_to(&self, existing: &str) -> Result<AppendedCommands> {
+        let mut root = if existing.trim().is_empty() {
+            Mapping::new()
+        } else {
+            match serde_yml::from_str(existing)? {
+                Value::Mapping(mapping) => mapping,
+                Value::Null => Mapping::new(),
+                _ => return Err(anyhow!("Existing configuration is not a YAML mapping")),
+            }
+        };
+
+        let mut appended = AppendedCommands::default();
+        let hooks = mapping_entry(&mut root, "hooks")?;
+        for (hook, commands) in &self.hooks {
+            let definition = mapping_entry(hooks, hook)?;
+            if !definition.contains_key("enabled") {
+                definition.insert("enabled".into(), Value::Bool(true));
+            }
+
+            let custom = definition
+                .entry("custom".into())
+                .or_insert_with(|| Value::Sequence(vec![]));
+            if custom.is_null() {
+                *custom = Value::Sequence(vec![]);
+            }
+            let custom = custom
+                .as_sequence_mut()
+                .ok_or_else(|| anyhow!("'hooks.{hook}.custom' is not a list"))?;
+
+            for cmd in commands {
+                let name = cmd.name.clone().unwrap_or_default();
+                let exists = custom
+                    .iter()
+                    .any(|entry| entry.get("name").and_then(Value::as_str) == Some(&name));
+                if exists {
+                    appended.existing.push((hook.to_string(), name));
+                } else {
+                    custom.push(serde_yml::to_value(cmd)?);
+                    appended.added.push((hook.to_string(), name));
+                }
+            }
+        }
+
+        appended.yaml = serde_yml::to_string(&root)?;
+        Ok(appended)
+    }
+}
+
+pub(super) fn mapping_entry<'a>(parent: &'a mut Mapping, key: &str) -> Result<&'a mut Mapping> {
+    let entry = parent
+        .entry(key.into())
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    if entry.is_null() {
+        *entry = Value::Mapping(Mapping::new());
+    }
+    entry
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow!("'{key}' in the existing configuration is not a mapping"))
+}
+
+fn command(name: &str, command: &str, description: &str, glob: &[&str]) -> CustomCommand {
+    let mut cmd = CustomCommand::new(
+        command.to_string(),
+        Some(name.to_string()),
+        description.to_string(),
+    );
+    cmd.glob = glob.iter().map(|glob| glob.to_string()).collect();
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_and_adapt_to_project_tooling() {
+        let temp = TempDir::new().unwrap();
+        for file in ["Cargo.toml", "package.json", "pnpm-lock.yaml", "go.mod"] {
+            std::fs::write(temp.path().join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            Preset::detect(temp.path()),
+            vec![Preset::Rust, Preset::Node, Preset::Go]
+        );
+
+        let node = Preset::Node.commands(temp.path());
+        let (hook, commit) = &node.hooks[0];
+        assert_eq!(*hook, "pre-commit");
+        assert_eq!(commit[1].command, "pnpm exec eslint {files}");
+        assert_eq!(node.hooks[1].1[1].command, "pnpm test");
+    }
+
+    #[test]
+    fn test_append_keeps_existing_commands_and_settings() {
+        let temp = TempDir::new().unwrap();
+        let existing = r#"
+scanner:
+  mode: parallel
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "cargo fmt --check"
+        name: "cargo-fmt"
+"#;
+
+        let appended = Preset::Rust
+            .commands(temp.path())
+            .append_to(existing)
+            .unwrap();
+        let config: serde_yml::Value = serde_yml::from_str(&appended.yaml).unwrap();
+
+        assert_eq!(config["scanner"]["mode"], "parallel");
+        let commit = config["hooks"]["pre-commit"]["custom"]
+            .as_sequence()
+            .unwrap();
+        assert_eq!(commit.len(), 2);
+        assert_eq!(commit[0]["command"], "cargo fmt --check");
+        assert_eq!(commit[1]["depends_on"][0], "cargo-fmt");
+        assert_eq!(config["hooks"]["pre-push"]["enabled"], true);
+        assert_eq!(
+            appended.existing,
+            vec![("pre-commit".to_string(), "cargo-fmt".to_string())]
+        );
+
+        let again = Preset::Rust
+            .commands(temp.path())
+            .append_to(&appended.yaml)
+            .unwrap();
+        assert!(again.added.is_empty());
+    }
+}
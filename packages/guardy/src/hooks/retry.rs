@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::process::Output;
+use std::time::Duration;
+
+use super::config::CustomCommand;
+
+/// When and how often a failed custom command is run again
+#[derive(Debug)]
+pub struct RetryPolicy {
+    retries: u32,
+    backoff: Duration,
+    exit_codes: Vec<i32>,
+    output: Option<Regex>,
+}
+
+impl RetryPolicy {
+    pub fn for_command(cmd: &CustomCommand) -> Result<Self> {
+        let retry_on = cmd.retry_on.clone().unwrap_or_default();
+        let output = retry_on
+            .output
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .with_context(|| format!("Invalid retry_on.output regex for '{}'", cmd.description))?;
+
+        Ok(Self {
+            retries: cmd.retries,
+            backoff: Duration::from_millis(cmd.retry_backoff_ms),
+            exit_codes: retry_on.exit_codes,
+            output,
+        })
+    }
+
+    /// Total attempts allowed, including the first run
+    pub fn max_attempts(&self) -> u32 {
+        self.retries + 1
+    }
+
+    /// Whether a failed run after `attempt` tries should be retried
+    pub fn should_retry(&self, attempt: u32, output: &Output) -> bool {
+        if attempt >= self.max_attempts() {
+            return false;
+        }
+        if self.exit_codes.is_empty() && self.output.is_none() {
+            return true;
+        }
+
+        let code_matches = output
+            .status
+            .code()
+            .is_some_and(|code| self.exit_codes.contains(&code));
+        let output_matches = self.output.as_ref().is_some_and(|regex| {
+            regex.is_match(&String::from_utf8_lossy(&output.stdout))
+                || regex.is_match(&String::from_utf8_lossy(&output.stderr))
+        });
+        code_matches || output_matches
+    }
+
+    /// Wait before the retry following `attempt` - doubles each time
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn command(retry_on: serde_json::Value) -> CustomCommand {
+        serde_json::from_value(serde_json::json!({
+            "command": "license-check",
+            "retries": 2,
+            "retry_backoff_ms": 200,
+            "retry_on": retry_on,
+        }))
+        .unwrap()
+    }
+
+    fn failed(code: i32, stderr: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: vec![],
+            stderr: stderr.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_retries_only_matching_failures() {
+        let policy = RetryPolicy::for_command(&command(serde_json::json!({
+            "exit_codes": [75],
+            "output": "ETIMEDOUT|503",
+        })))
+        .unwrap();
+
+        assert!(policy.should_retry(1, &failed(75, "")));
+        assert!(policy.should_retry(1, &failed(1, "connect ETIMEDOUT 10.0.0.1")));
+        assert!(!policy.should_retry(1, &failed(1, "3 packages have forbidden licenses")));
+        assert!(!policy.should_retry(3, &failed(75, "")));
+    }
+
+    #[test]
+    fn test_backoff_doubles_per_attempt() {
+        let policy = RetryPolicy::for_command(&command(serde_json::Value::Null)).unwrap();
+
+        assert_eq!(policy.max_attempts(), 3);
+        assert!(policy.should_retry(2, &failed(1, "anything")));
+        assert_eq!(policy.delay(1), Duration::from_millis(200));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+    }
+}
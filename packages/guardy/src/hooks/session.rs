@@ -0,0 +1,427 @@
+use anyhow::{Context, Result, anyhow};
+use git2::ObjectType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use super::config::HookConfig;
+use super::executor::HookExecutor;
+use super::timing::{HookReport, StepOutcome, StepTiming};
+use crate::cli::output;
+use crate::config::GuardyConfig;
+use crate::git::GitRepo;
+use crate::git::snapshot::IndexChange;
+
+/// Bumped when the session format changes incompatibly
+const SESSION_VERSION: u32 = 1;
+
+/// A recorded hook run (`guardy run <hook> --record <file>`): enough to re-run the
+/// hook on another machine with `guardy replay <file>`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u32,
+    pub guardy_version: String,
+    pub hook: String,
+    pub args: Vec<String>,
+    /// Commit checked out when recording, `None` before the first commit
+    pub head: Option<String>,
+    /// Index entries that differ from `head`
+    pub staged: Vec<StagedEntry>,
+    /// Git object id of `config`, to spot configuration drift at a glance
+    pub config_hash: String,
+    /// Fully merged configuration, personal hooks included
+    pub config: serde_json::Value,
+    /// `--version` of git and of each tool the hook's custom commands start
+    pub tools: BTreeMap<String, String>,
+    pub report: Option<HookReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StagedEntry {
+    pub path: String,
+    /// Octal git file mode, e.g. `100644`
+    pub mode: String,
+    /// Blob id of the staged content, `None` when the file is staged for deletion
+    pub blob: Option<String>,
+}
+
+/// Result of `guardy replay`: the recorded outcome next to the new one
+pub struct Replay {
+    pub recorded: Option<HookReport>,
+    pub replayed: HookReport,
+}
+
+/// Run `hook` like [`HookExecutor::report`], saving its inputs and outcome to `path`
+pub async fn record_session(
+    executor: &HookExecutor,
+    hook: &str,
+    args: &[String],
+    path: &Path,
+) -> Result<HookReport> {
+    // Inputs are captured first - the hook itself may restage fixed files
+    let mut session = Session::capture(executor, hook, args)?;
+    let report = executor.report(hook, args).await;
+    session.report = Some(report.clone());
+
+    std::fs::write(path, serde_json::to_string_pretty(&session)?)
+        .with_context(|| format!("Failed to write session file {}", path.display()))?;
+    Ok(report)
+}
+
+/// Re-run a recorded session in a temporary worktree of the current repository
+///
+/// The worktree is checked out at the recorded commit and its index rebuilt from the
+/// recorded blobs, so both must be available here (e.g. pushed to a shared branch).
+/// The hook runs with the recorded configuration; tool versions that differ from the
+/// recording are reported before it starts.
+pub async fn replay_session(path: &Path, keep: bool, verbosity_level: u8) -> Result<Replay> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read session file {}", path.display()))?;
+    let session: Session = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid session file {}", path.display()))?;
+    if session.version != SESSION_VERSION {
+        return Err(anyhow!(
+            "Session format {} is not supported by this guardy (expects {SESSION_VERSION})",
+            session.version
+        ));
+    }
+
+    let repo = GitRepo::discover()?;
+    session.check_objects(&repo)?;
+    session.report_drift(verbosity_level);
+
+    let id = uuid::Uuid::new_v4();
+    let worktree = std::env::temp_dir().join(format!("guardy-replay-{id}"));
+    let config_path = std::env::temp_dir().join(format!("guardy-replay-{id}.yaml"));
+    std::fs::write(&config_path, serde_yml::to_string(&session.config)?)?;
+
+    let replayed = session
+        .run_in_worktree(&repo, &worktree, &config_path, verbosity_level)
+        .await;
+
+    let _ = std::fs::remove_file(&config_path);
+    if keep {
+        output::info!(&format!("Replay worktree kept at {}", worktree.display()));
+    } else {
+        let _ = repo.remove_worktree(&worktree);
+    }
+
+    Ok(Replay {
+        recorded: session.report,
+        replayed: replayed?,
+    })
+}
+
+impl Session {
+    fn capture(executor: &HookExecutor, hook: &str, args: &[String]) -> Result<Self> {
+        let repo = GitRepo::discover()?;
+        let head = repo.head_commit().ok();
+
+        let hooks = executor.hooks()?;
+        let mut config = executor.config().get_full_config()?;
+        config["hooks"] = serde_json::to_value(&hooks)?;
+        let config_hash = config_hash(&config)?;
+
+        let mut tools = BTreeMap::new();
+        let commands = hooks.hooks.get(hook).into_iter().flat_map(|h| &h.custom);
+        for tool in std::iter::once("git").chain(commands.filter_map(|c| tool_name(&c.command))) {
+            if let Some(version) = tool_version(tool) {
+                tools.insert(tool.to_string(), version);
+            }
+        }
+
+        Ok(Self {
+            version: SESSION_VERSION,
+            guardy_version: env!("CARGO_PKG_VERSION").to_string(),
+            hook: hook.to_string(),
+            args: args.to_vec(),
+            head,
+            staged: repo
+                .index_changes()?
+                .into_iter()
+                .map(StagedEntry::from)
+                .collect(),
+            config_hash,
+            config,
+            tools,
+            report: None,
+        })
+    }
+
+    fn check_objects(&self, repo: &GitRepo) -> Result<()> {
+        let head = self.head.as_deref().ok_or_else(|| {
+            anyhow!("Sessions recorded before the first commit can't be replayed")
+        })?;
+        if !repo.has_object(head, ObjectType::Commit) {
+            return Err(anyhow!(
+                "Commit {head} is not in this repository - fetch the branch the session was recorded on"
+            ));
+        }
+
+        let missing: Vec<&str> = self
+            .staged
+            .iter()
+            .filter(|entry| {
+                entry.blob.as_deref().is_some_and(|blob| {
+                    entry.mode != "160000" && !repo.has_object(blob, ObjectType::Blob)
+                })
+            })
+            .map(|entry| entry.path.as_str())
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Staged content is not in this repository: {}\nCommit the staged changes on the recording machine and push them to a branch, then fetch it here",
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Warn about differences between the recording machine and this one
+    fn report_drift(&self, verbosity_level: u8) {
+        if self.guardy_version != env!("CARGO_PKG_VERSION") {
+            output::warning!(&format!(
+                "Recorded with guardy {}, replaying with {}",
+                self.guardy_version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+
+        for (tool, recorded) in &self.tools {
+            match tool_version(tool) {
+                Some(local) if local == *recorded => {}
+                Some(local) => {
+                    output::warning!(&format!("{tool}: recorded '{recorded}', found '{local}'"))
+                }
+                None => output::warning!(&format!(
+                    "{tool}: recorded '{recorded}', not installed here"
+                )),
+            }
+        }
+
+        let local_hash = GuardyConfig::load(None, None::<&()>, verbosity_level)
+            .and_then(|config| {
+                let executor = HookExecutor::new(config);
+                let mut config = executor.config().get_full_config()?;
+                config["hooks"] = serde_json::to_value(executor.hooks()?)?;
+                config_hash(&config)
+            })
+            .ok();
+        if local_hash.as_deref() != Some(&self.config_hash) {
+            output::info!(
+                "This repository's configuration differs from the recorded one - replaying with the recorded configuration"
+            );
+        }
+    }
+
+    async fn run_in_worktree(
+        &self,
+        repo: &GitRepo,
+        worktree: &Path,
+        config_path: &Path,
+        verbosity_level: u8,
+    ) -> Result<HookReport> {
+        let head = self.head.as_deref().unwrap_or("HEAD");
+        let checkout = repo.add_detached_worktree(worktree, head)?;
+
+        let mut changes = Vec::new();
+        for entry in &self.staged {
+            changes.push(write_entry(&checkout, entry)?);
+        }
+        checkout.apply_index_changes(&changes)?;
+
+        output::info!(&format!(
+            "Replaying {} at {} with {} staged file(s)",
+            self.hook,
+            &head[..head.len().min(12)],
+            self.staged.len()
+        ));
+
+        // Load the config from inside the worktree so its project files are the
+        // recorded commit's, not those of the current checkout
+        let previous_dir = std::env::current_dir()?;
+        std::env::set_current_dir(worktree)?;
+        let report = self.run_hook(config_path, verbosity_level).await;
+        std::env::set_current_dir(previous_dir)?;
+        report
+    }
+
+    async fn run_hook(&self, config_path: &Path, verbosity_level: u8) -> Result<HookReport> {
+        let config_path = config_path.to_string_lossy();
+        let config = GuardyConfig::load(Some(&config_path), None::<&()>, verbosity_level)?;
+        let hooks: HookConfig = serde_json::from_value(self.config["hooks"].clone())?;
+        let executor = HookExecutor::with_hooks(config, hooks);
+        Ok(executor.report(&self.hook, &self.args).await)
+    }
+}
+
+impl Replay {
+    /// Whether the replay ended the way the recording did
+    pub fn reproduced(&self) -> bool {
+        self.recorded
+            .as_ref()
+            .is_some_and(|recorded| recorded.success == self.replayed.success)
+    }
+
+    /// Step-by-step outcomes of the recorded and replayed runs side by side
+    pub fn print_comparison(&self) {
+        let recorded_steps: &[StepTiming] = self
+            .recorded
+            .as_ref()
+            .map(|report| report.steps.as_slice())
+            .unwrap_or_default();
+
+        println!();
+        output::styled!("{}", ("Recorded → replayed:", "property"));
+        for step in &self.replayed.steps {
+            let recorded = recorded_steps
+                .iter()
+                .find(|recorded| recorded.name == step.name)
+                .map(|recorded| outcome_label(recorded.outcome))
+                .unwrap_or("not run");
+            output::styled!(
+                "  {} {} → {}",
+                (&step.name, "property"),
+                (recorded, "muted"),
+                (outcome_label(step.outcome), "primary")
+            );
+        }
+
+        let outcome = |success: bool| if success { "passed" } else { "failed" };
+        match &self.recorded {
+            Some(recorded) if self.reproduced() => output::success!(&format!(
+                "Reproduced: the hook {} as recorded",
+                outcome(recorded.success)
+            )),
+            Some(recorded) => output::warning!(&format!(
+                "Not reproduced: the hook {} when recorded but {} here",
+                outcome(recorded.success),
+                outcome(self.replayed.success)
+            )),
+            None => output::info!("The session has no recorded outcome to compare with"),
+        }
+    }
+}
+
+fn outcome_label(outcome: StepOutcome) -> &'static str {
+    match outcome {
+        StepOutcome::Passed => "passed",
+        StepOutcome::NonFatal => "failed (non-fatal)",
+        StepOutcome::Failed => "failed",
+    }
+}
+
+impl From<IndexChange> for StagedEntry {
+    fn from(change: IndexChange) -> Self {
+        Self {
+            path: change.path,
+            mode: format!("{:o}", change.mode),
+            blob: change.id,
+        }
+    }
+}
+
+/// Put an entry's recorded content in the worktree, returning its index change
+fn write_entry(checkout: &GitRepo, entry: &StagedEntry) -> Result<IndexChange> {
+    let path = checkout.path.join(&entry.path);
+    let change = IndexChange {
+        path: entry.path.clone(),
+        mode: u32::from_str_radix(&entry.mode, 8)
+            .with_context(|| format!("Invalid mode {} of {}", entry.mode, entry.path))?,
+        id: entry.blob.clone(),
+    };
+    match &entry.blob {
+        None if path.is_symlink() || path.exists() => std::fs::remove_file(&path)?,
+        Some(blob) if entry.mode != "160000" => {
+            let content = checkout.blob_content(blob)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_file(&path, &entry.mode, &content)?;
+        }
+        _ => {}
+    }
+    Ok(change)
+}
+
+#[cfg(unix)]
+fn write_file(path: &Path, mode: &str, content: &[u8]) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if path.is_symlink() || path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    if mode == "120000" {
+        std::os::unix::fs::symlink(String::from_utf8_lossy(content).as_ref(), path)?;
+        return Ok(());
+    }
+    std::fs::write(path, content)?;
+    if mode == "100755" {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_file(path: &Path, _mode: &str, content: &[u8]) -> Result<()> {
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// The program a shell command starts, skipping `!` and `VAR=value` prefixes
+fn tool_name(command: &str) -> Option<&str> {
+    command
+        .split_whitespace()
+        .find(|word| *word != "!" && !is_assignment(word))
+        .filter(|word| {
+            word.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._/-+".contains(c))
+        })
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.contains('=') && !word.starts_with(['/', '.'])
+}
+
+/// First line of `<tool> --version`, if the tool is on PATH
+fn tool_version(tool: &str) -> Option<String> {
+    let program = which::which(tool).ok()?;
+    let output = Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    String::from_utf8_lossy(&text)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// `git hash-object` of the configuration serialized as JSON
+fn config_hash(config: &serde_json::Value) -> Result<String> {
+    let json = serde_json::to_string(config)?;
+    Ok(git2::Oid::hash_object(git2::ObjectType::Blob, json.as_bytes())?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_name_skips_negation_and_env_assignments() {
+        assert_eq!(tool_name("cargo clippy -- -D warnings"), Some("cargo"));
+        assert_eq!(
+            tool_name("! RUST_LOG=debug ./scripts/check.sh"),
+            Some("./scripts/check.sh")
+        );
+        assert_eq!(tool_name("test -z \"$(gofmt -l .)\""), Some("test"));
+        assert_eq!(tool_name("$(npm bin)/eslint"), None);
+    }
+}
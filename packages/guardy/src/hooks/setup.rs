@@ -0,0 +1,200 @@
+use anyhow::{Result, anyhow};
+use serde_yml::Value;
+use std::path::Path;
+
+use super::config::CustomCommand;
+use super::presets::{Preset, PresetCommands, mapping_entry};
+
+/// Builtins offered by `guardy install --interactive`: hook, name, description and
+/// whether they start checked
+const BUILTINS: &[(&str, &str, &str, bool)] = &[
+    (
+        "pre-commit",
+        "scan_secrets",
+        "Scan staged files for secrets",
+        true,
+    ),
+    (
+        "pre-commit",
+        "check_merge_conflicts",
+        "Reject leftover merge conflict markers",
+        true,
+    ),
+    (
+        "pre-commit",
+        "block_large_files",
+        "Keep large files and binaries out of commits",
+        false,
+    ),
+    (
+        "commit-msg",
+        "validate_commit_msg",
+        "Enforce conventional commit messages",
+        false,
+    ),
+    (
+        "commit-msg",
+        "scan_secrets",
+        "Scan commit messages for secrets",
+        false,
+    ),
+    (
+        "pre-push",
+        "protect_branches",
+        "Block direct pushes to protected branches",
+        false,
+    ),
+    (
+        "pre-push",
+        "validate_branch_name",
+        "Enforce the branch naming policy",
+        false,
+    ),
+];
+
+/// What a proposal adds to its hook
+#[derive(Debug, Clone)]
+pub enum Action {
+    Builtin(&'static str),
+    Command(Box<CustomCommand>),
+}
+
+/// A hook action `guardy install --interactive` offers to enable
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub hook: &'static str,
+    pub action: Action,
+    pub description: String,
+    /// Checked when the prompt opens
+    pub selected: bool,
+}
+
+impl Proposal {
+    /// The builtins, then the preset commands for the project types detected in `dir`
+    pub fn for_project(dir: &Path) -> Vec<Self> {
+        let mut proposals: Vec<Self> = BUILTINS
+            .iter()
+            .map(|&(hook, builtin, description, selected)| Self {
+                hook,
+                action: Action::Builtin(builtin),
+                description: description.to_string(),
+                selected,
+            })
+            .collect();
+
+        for preset in Preset::detect(dir) {
+            for (hook, commands) in preset.commands(dir).hooks {
+                proposals.extend(commands.into_iter().map(|cmd| Self {
+                    hook,
+                    description: cmd.description.clone(),
+                    action: Action::Command(Box::new(cmd)),
+                    selected: true,
+                }));
+            }
+        }
+        proposals
+    }
+
+    /// `pre-commit: scan_secrets - Scan staged files for secrets`
+    pub fn label(&self) -> String {
+        let name = match &self.action {
+            Action::Builtin(builtin) => builtin.to_string(),
+            Action::Command(cmd) => cmd.name.clone().unwrap_or_else(|| cmd.command.clone()),
+        };
+        format!("{}: {name} - {}", self.hook, self.description)
+    }
+
+    /// `existing` guardy.yaml with the chosen builtins and commands added
+    ///
+    /// Hooks are created and enabled as needed; builtins and named commands that
+    /// are already configured are left alone.
+    pub fn apply(existing: &str, chosen: &[&Self]) -> Result<String> {
+        let mut commands = PresetCommands::default();
+        for proposal in chosen {
+            if let Action::Command(cmd) = &proposal.action {
+                match commands
+                    .hooks
+                    .iter_mut()
+                    .find(|(hook, _)| *hook == proposal.hook)
+                {
+                    Some((_, list)) => list.push((**cmd).clone()),
+                    None => commands.hooks.push((proposal.hook, vec![(**cmd).clone()])),
+                }
+            }
+        }
+        let yaml = commands.append_to(existing)?.yaml;
+
+        let mut root = match serde_yml::from_str(&yaml)? {
+            Value::Mapping(mapping) => mapping,
+            _ => return Err(anyhow!("Existing configuration is not a YAML mapping")),
+        };
+        let hooks = mapping_entry(&mut root, "hooks")?;
+        for proposal in chosen {
+            let Action::Builtin(builtin) = proposal.action else {
+                continue;
+            };
+            let definition = mapping_entry(hooks, proposal.hook)?;
+            if !definition.contains_key("enabled") {
+                definition.insert("enabled".into(), Value::Bool(true));
+            }
+            let list = definition
+                .entry("builtin".into())
+                .or_insert_with(|| Value::Sequence(vec![]));
+            if list.is_null() {
+                *list = Value::Sequence(vec![]);
+            }
+            let list = list
+                .as_sequence_mut()
+                .ok_or_else(|| anyhow!("'hooks.{}.builtin' is not a list", proposal.hook))?;
+            if !list.iter().any(|entry| entry.as_str() == Some(builtin)) {
+                list.push(Value::String(builtin.to_string()));
+            }
+        }
+
+        Ok(serde_yml::to_string(&root)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_proposals_follow_the_project_and_merge_into_config() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("go.mod"), "").unwrap();
+
+        let proposals = Proposal::for_project(temp.path());
+        assert_eq!(
+            proposals[0].label(),
+            "pre-commit: scan_secrets - Scan staged files for secrets"
+        );
+        let go_test = proposals
+            .iter()
+            .find(|proposal| proposal.label().starts_with("pre-push: go-test"))
+            .unwrap();
+        let branch_names = proposals
+            .iter()
+            .find(|proposal| proposal.label().contains("validate_branch_name"))
+            .unwrap();
+
+        let existing = "hooks:\n  pre-commit:\n    builtin: [scan_secrets]\n";
+        let yaml = Proposal::apply(existing, &[&proposals[0], go_test, branch_names]).unwrap();
+        let config: Value = serde_yml::from_str(&yaml).unwrap();
+
+        assert_eq!(
+            config["hooks"]["pre-commit"]["builtin"],
+            serde_yml::from_str::<Value>("[scan_secrets]").unwrap()
+        );
+        assert_eq!(config["hooks"]["pre-push"]["enabled"], true);
+        assert_eq!(
+            config["hooks"]["pre-push"]["builtin"][0],
+            "validate_branch_name"
+        );
+        assert_eq!(
+            config["hooks"]["pre-push"]["custom"][0]["command"],
+            "go test ./..."
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use super::config::CustomCommand;
+
+/// Environment variables checked for names to skip, in priority order
+const SKIP_VARS: &[&str] = &["GUARDY_SKIP", "SKIP"];
+
+/// Hook commands skipped at runtime via `GUARDY_SKIP=fmt,clippy` (or the `SKIP` convention)
+///
+/// Entries match a builtin name, a custom command's `name` (falling back to its
+/// `description`), or a whole hook name.
+#[derive(Debug, Default)]
+pub struct SkipList {
+    names: Vec<String>,
+    source: &'static str,
+}
+
+impl SkipList {
+    pub fn from_env() -> Self {
+        for var in SKIP_VARS {
+            if let Ok(value) = std::env::var(var)
+                && !value.trim().is_empty()
+            {
+                return Self::parse(&value, var);
+            }
+        }
+        Self::default()
+    }
+
+    pub fn parse(value: &str, source: &'static str) -> Self {
+        let names = value
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+        Self { names, source }
+    }
+
+    /// Name of the variable the list came from, for reporting
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        self.names.contains(&name)
+    }
+
+    pub fn skips_command(&self, cmd: &CustomCommand) -> bool {
+        match &cmd.name {
+            Some(name) => self.contains(name),
+            None => self.contains(&cmd.description),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(name: Option<&str>, description: &str) -> CustomCommand {
+        serde_json::from_value(serde_json::json!({
+            "command": "true",
+            "name": name,
+            "description": description,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_trims_and_ignores_case() {
+        let skip = SkipList::parse(" fmt, Clippy ,,", "GUARDY_SKIP");
+
+        assert!(skip.contains("fmt"));
+        assert!(skip.contains("clippy"));
+        assert!(!skip.contains("test"));
+        assert_eq!(skip.source(), "GUARDY_SKIP");
+    }
+
+    #[test]
+    fn test_commands_match_by_name_then_description() {
+        let skip = SkipList::parse("fmt,Run tests", "SKIP");
+
+        assert!(skip.skips_command(&command(Some("fmt"), "Check formatting")));
+        assert!(!skip.skips_command(&command(Some("lint"), "fmt")));
+        assert!(skip.skips_command(&command(None, "Run tests")));
+    }
+
+    #[test]
+    fn test_empty_list_skips_nothing() {
+        let skip = SkipList::default();
+
+        assert!(!skip.contains("pre-commit"));
+        assert!(!skip.skips_command(&command(None, "")));
+    }
+}
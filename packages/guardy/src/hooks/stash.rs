@@ -0,0 +1,174 @@
+//! Hiding unstaged changes while pre-commit checks run
+//!
+//! With `stash_unstaged: true`, the working tree changes that aren't staged are
+//! saved as a binary patch in the git directory and reverted, so commands see
+//! exactly what the commit will contain; the patch is re-applied once they finish.
+//! A patch left behind by a run that was killed is restored by the next one before
+//! anything else happens.
+
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::output;
+use crate::git::GitRepo;
+use crate::git::unstaged::MergedFile;
+use crate::shared::atomic_io;
+
+const PATCH_FILE: &str = "guardy-unstaged.patch";
+/// Edits commands made to files with unstaged changes, kept when they clash
+const EDITS_FILE: &str = "guardy-hook-edits.patch";
+
+/// Unstaged changes reverted for the duration of a hook run
+pub struct UnstagedStash {
+    root: PathBuf,
+    patch: PathBuf,
+    /// Files the patch touches, relative to the repository root
+    files: Vec<PathBuf>,
+    /// Index tree the patch was made against
+    base: String,
+}
+
+impl UnstagedStash {
+    /// Restore a patch an interrupted run left behind, then save and revert the
+    /// current unstaged changes; `None` when there are none
+    pub fn save(repo: &GitRepo) -> Result<Option<Self>> {
+        let patch = repo.git_dir().join(PATCH_FILE);
+        if patch.exists() {
+            recover(repo, &patch)?;
+        }
+
+        let Some(changes) = repo.unstaged_changes()? else {
+            return Ok(None);
+        };
+        let base = repo.index_tree()?;
+        // Written before anything is reverted, so a crash can't lose the changes
+        atomic_io::write_atomic(&patch, &changes.patch)?;
+        repo.checkout_from_index(&changes.files).with_context(|| {
+            format!(
+                "Failed to hide unstaged changes; they are saved in {}",
+                patch.display()
+            )
+        })?;
+        output::info!(&format!(
+            "Hiding unstaged changes to {} while checks run",
+            output::human_count(changes.files.len(), "file")
+        ));
+        Ok(Some(Self {
+            root: repo.path.clone(),
+            patch,
+            files: changes.files,
+            base,
+        }))
+    }
+
+    /// Bring the unstaged changes back, merging them with edits commands made to
+    /// the same files; where the two clash the unstaged changes win, and the
+    /// commands' edits are saved as a patch next to them
+    pub fn restore(self) -> Result<()> {
+        // The index may have changed (`stage_fixed`): read it afresh
+        let repo = GitRepo::discover_at(&self.root)?;
+        let patch = fs::read(&self.patch)
+            .with_context(|| format!("Failed to read {}", self.patch.display()))?;
+        if repo.apply_to_workdir(&patch).is_ok() {
+            return remove(&self.patch);
+        }
+
+        let edits = repo.git_dir().join(EDITS_FILE);
+        let commands_edits = repo.unstaged_changes_in(&self.files)?;
+        if let Some(commands_edits) = &commands_edits {
+            atomic_io::write_atomic(&edits, &commands_edits.patch)?;
+        }
+        let merged = repo.merge_patch(&self.base, &patch).map_err(|e| {
+            anyhow!(
+                "Failed to restore unstaged changes ({e:#}); they are saved in {} - \
+                 apply them with `git apply {0}`",
+                self.patch.display()
+            )
+        })?;
+        for file in &merged {
+            write_merged(&self.root, file)?;
+        }
+
+        let conflicted: Vec<String> = merged
+            .iter()
+            .filter(|file| file.conflicted)
+            .map(|file| file.path.display().to_string())
+            .collect();
+        match &commands_edits {
+            _ if conflicted.is_empty() => {}
+            Some(_) => output::warning!(&format!(
+                "Hook commands edited lines with unstaged changes in {}; your changes were \
+                 kept and their edits saved in {}",
+                conflicted.join(", "),
+                edits.display()
+            )),
+            None => output::warning!(&format!(
+                "Hook commands staged fixes to lines with unstaged changes in {}; your \
+                 changes were kept in the working tree",
+                conflicted.join(", ")
+            )),
+        }
+        if conflicted.is_empty() && commands_edits.is_some() {
+            remove(&edits)?;
+        }
+        remove(&self.patch)
+    }
+}
+
+/// Re-apply the patch of a run that never restored it - unless the run got as
+/// far as restoring and only missed removing it
+fn recover(repo: &GitRepo, patch: &Path) -> Result<()> {
+    let saved = fs::read(patch).with_context(|| format!("Failed to read {}", patch.display()))?;
+    let restored = repo
+        .unstaged_changes()?
+        .is_some_and(|changes| changes.patch == saved);
+    if !restored {
+        repo.apply_to_workdir(&saved).map_err(|_| {
+            anyhow!(
+                "Unstaged changes from an interrupted hook run are saved in {} but no \
+                 longer apply cleanly - apply them with `git apply {0}` (or delete it) \
+                 and commit again",
+                patch.display()
+            )
+        })?;
+        output::warning!(&format!(
+            "Restored unstaged changes from an interrupted hook run ({})",
+            patch.display()
+        ));
+    }
+    remove(patch)
+}
+
+fn write_merged(root: &Path, file: &MergedFile) -> Result<()> {
+    let path = root.join(&file.path);
+    match &file.result {
+        None => match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        },
+        Some((content, 0o120000)) => {
+            let target = String::from_utf8_lossy(content);
+            atomic_io::symlink_atomic(Path::new(target.as_ref()), &path)
+        }
+        Some((content, mode)) => write_file(&path, content, *mode),
+    }
+}
+
+#[cfg(unix)]
+fn write_file(path: &Path, content: &[u8], mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let permissions = fs::Permissions::from_mode(if mode == 0o100755 { 0o755 } else { 0o644 });
+    atomic_io::write_atomic_with_permissions(path, content, permissions)
+}
+
+#[cfg(not(unix))]
+fn write_file(path: &Path, content: &[u8], _mode: u32) -> Result<()> {
+    atomic_io::write_atomic(path, content)
+}
+
+fn remove(path: &Path) -> Result<()> {
+    fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))
+}
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cli::output;
+
+/// Whether a step was a builtin action or a custom command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    Builtin,
+    Custom,
+}
+
+/// How a step finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Passed,
+    /// Failed, but `fail_on_error: false` let the hook continue
+    NonFatal,
+    Failed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StepTiming {
+    pub name: String,
+    pub kind: StepKind,
+    pub outcome: StepOutcome,
+    /// Runs including retries - 1 unless the command has a retry policy
+    pub attempts: u32,
+    /// Wall time across all attempts and backoff delays
+    #[serde(
+        rename = "duration_ms",
+        serialize_with = "as_millis",
+        deserialize_with = "from_millis"
+    )]
+    pub duration: Duration,
+}
+
+/// Wall time of each step in a hook run
+///
+/// Cheap to clone so parallel custom commands can record into the same list.
+#[derive(Debug, Clone, Default)]
+pub struct HookTimings {
+    steps: Arc<Mutex<Vec<StepTiming>>>,
+}
+
+/// Machine-readable result of a hook run (`guardy run <hook> --format json`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HookReport {
+    pub hook: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(
+        rename = "total_ms",
+        serialize_with = "as_millis",
+        deserialize_with = "from_millis"
+    )]
+    pub total: Duration,
+    pub steps: Vec<StepTiming>,
+    pub skipped: Vec<String>,
+}
+
+impl HookTimings {
+    pub fn record(
+        &self,
+        name: &str,
+        kind: StepKind,
+        outcome: StepOutcome,
+        attempts: u32,
+        duration: Duration,
+    ) {
+        if let Ok(mut steps) = self.steps.lock() {
+            steps.push(StepTiming {
+                name: name.to_string(),
+                kind,
+                outcome,
+                attempts,
+                duration,
+            });
+        }
+    }
+
+    /// Steps in the order they finished
+    pub fn steps(&self) -> Vec<StepTiming> {
+        self.steps
+            .lock()
+            .map(|steps| steps.clone())
+            .unwrap_or_default()
+    }
+
+    /// Print steps slowest-first with their share of the total
+    pub fn print_summary(&self, total: Duration) {
+        let mut steps = self.steps();
+        if steps.is_empty() {
+            return;
+        }
+        steps.sort_by_key(|step| std::cmp::Reverse(step.duration));
+
+        println!();
+        output::styled!(
+            "{} Timing ({} total)",
+            ("⏱", "info_symbol"),
+            (output::human_duration(total), "time")
+        );
+        for step in &steps {
+            let share = if total.is_zero() {
+                0.0
+            } else {
+                step.duration.as_secs_f64() / total.as_secs_f64() * 100.0
+            };
+            let mut marker = match step.outcome {
+                StepOutcome::Passed => String::new(),
+                StepOutcome::NonFatal => " (failed, non-fatal)".to_string(),
+                StepOutcome::Failed => " (failed)".to_string(),
+            };
+            if step.attempts > 1 {
+                marker.push_str(&format!(" ({} attempts)", step.attempts));
+            }
+            output::styled!(
+                "  {} {} {}{}",
+                (
+                    format!("{:>8}", output::human_duration(step.duration)),
+                    "time"
+                ),
+                (format!("{share:>3.0}%"), "muted"),
+                (&step.name, "property"),
+                (&marker, "warning")
+            );
+        }
+    }
+}
+
+fn as_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+fn from_millis<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_serializes_durations_in_milliseconds() {
+        let timings = HookTimings::default();
+        let parallel = timings.clone();
+        timings.record(
+            "scan_secrets",
+            StepKind::Builtin,
+            StepOutcome::Passed,
+            1,
+            Duration::from_millis(120),
+        );
+        parallel.record(
+            "clippy",
+            StepKind::Custom,
+            StepOutcome::Failed,
+            3,
+            Duration::from_millis(2500),
+        );
+
+        let report = HookReport {
+            hook: "pre-commit".to_string(),
+            success: false,
+            error: Some("Command failed".to_string()),
+            total: Duration::from_millis(2650),
+            steps: timings.steps(),
+            skipped: vec!["fmt".to_string()],
+        };
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["total_ms"], 2650);
+        assert_eq!(json["steps"][0]["name"], "scan_secrets");
+        assert_eq!(json["steps"][0]["kind"], "builtin");
+        assert_eq!(json["steps"][1]["duration_ms"], 2500);
+        assert_eq!(json["steps"][1]["outcome"], "failed");
+        assert_eq!(json["steps"][1]["attempts"], 3);
+        assert_eq!(json["skipped"][0], "fmt");
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use super::config::{HookConfig, HookDefinition};
+use super::graph::command_id;
+
+/// Location of the personal hooks file: `$XDG_CONFIG_HOME/guardy/hooks.yaml`,
+/// falling back to `~/.config/guardy/hooks.yaml`
+pub fn path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(config_home.join("guardy").join("hooks.yaml"))
+}
+
+/// Read the personal hooks file, if there is one
+///
+/// The file maps hook names straight to definitions - the same shape as the
+/// `hooks:` section of guardy.yaml.
+pub fn load() -> Result<Option<HookConfig>> {
+    let Some(path) = path().filter(|path| path.is_file()) else {
+        return Ok(None);
+    };
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(None);
+    }
+    let hooks = serde_yml::from_str(&content)
+        .with_context(|| format!("Invalid user hooks file {}", path.display()))?;
+    Ok(Some(hooks))
+}
+
+/// Merge personal hooks into the repository's hooks
+///
+/// Personal hooks only ever add checks - they never disable or reconfigure the
+/// repository's:
+/// - Enabled repository hook: the user's builtins and custom commands run after
+///   the repository's; on a name clash the repository's command wins.
+///   `parallel` and `output` stay as the repository set them.
+/// - Disabled or missing repository hook: the user's definition is used as is,
+///   so a personal `commit-msg` check runs even where the repository has none.
+pub fn merge(repo: &mut HookConfig, user: HookConfig) {
+    for (name, user_hook) in user.hooks {
+        if !user_hook.enabled {
+            continue;
+        }
+
+        match repo.hooks.get_mut(&name) {
+            Some(hook) if hook.enabled => append(hook, user_hook),
+            _ => {
+                repo.hooks.insert(name, user_hook);
+            }
+        }
+    }
+}
+
+fn append(hook: &mut HookDefinition, user: HookDefinition) {
+    for builtin in user.builtin {
+        if !hook.builtin.contains(&builtin) {
+            hook.builtin.push(builtin);
+        }
+    }
+
+    for cmd in user.custom {
+        let id = command_id(&cmd);
+        let clashes = !id.is_empty()
+            && hook
+                .custom
+                .iter()
+                .any(|existing| command_id(existing) == id);
+        if !clashes {
+            hook.custom.push(cmd);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks(yaml: &str) -> HookConfig {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_merge_appends_user_commands_with_repo_precedence() {
+        let mut repo = hooks(
+            r#"
+pre-commit:
+  parallel: false
+  builtin: ["scan_secrets"]
+  custom:
+    - command: "cargo fmt --check"
+      name: "fmt"
+commit-msg:
+  enabled: false
+  custom:
+    - command: "repo-only"
+"#,
+        );
+        let user = hooks(
+            r#"
+pre-commit:
+  builtin: ["scan_secrets"]
+  custom:
+    - command: "my-fmt"
+      name: "fmt"
+    - command: "unnamed"
+    - command: "! git diff --cached | grep -q 'console.log'"
+      name: "no-console"
+commit-msg:
+  custom:
+    - command: "! grep -qi '^wip' $1"
+      name: "no-wip"
+pre-push:
+  enabled: false
+  custom:
+    - command: "off"
+"#,
+        );
+
+        merge(&mut repo, user);
+
+        let commit = &repo.hooks["pre-commit"];
+        assert!(!commit.parallel);
+        assert_eq!(commit.builtin, vec!["scan_secrets"]);
+        let commands: Vec<&str> = commit.custom.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec![
+                "cargo fmt --check",
+                "unnamed",
+                "! git diff --cached | grep -q 'console.log'"
+            ]
+        );
+
+        let msg = &repo.hooks["commit-msg"];
+        assert!(msg.enabled);
+        assert_eq!(msg.custom.len(), 1);
+        assert_eq!(msg.custom[0].name.as_deref(), Some("no-wip"));
+
+        assert!(!repo.hooks.contains_key("pre-push"));
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Result, anyhow};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Packages of a monorepo, for `workspaces: true` commands
+///
+/// Members come from the Cargo workspace (`[workspace] members`), pnpm-workspace.yaml,
+/// package.json `workspaces` (npm, yarn, bun) and, when nx.json exists, every
+/// directory with a project.json.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    /// Package directories relative to the repository root, sorted
+    packages: Vec<PathBuf>,
+}
+
+impl Workspace {
+    pub fn discover(root: &Path) -> Result<Self> {
+        let manifests = find_manifests(root);
+        let mut packages = Vec::new();
+        let mut found = false;
+
+        if let Some((members, exclude)) = cargo_members(root)? {
+            found = true;
+            packages.extend(matching_dirs(&manifests, "Cargo.toml", &members, &exclude)?);
+        }
+        if let Some(patterns) = js_members(root)? {
+            found = true;
+            let (exclude, members): (Vec<String>, Vec<String>) =
+                patterns.into_iter().partition(|p| p.starts_with('!'));
+            let exclude: Vec<String> = exclude.iter().map(|p| p[1..].to_string()).collect();
+            packages.extend(matching_dirs(
+                &manifests,
+                "package.json",
+                &members,
+                &exclude,
+            )?);
+        }
+        if root.join("nx.json").is_file() {
+            found = true;
+            packages.extend(matching_dirs(
+                &manifests,
+                "project.json",
+                &["**".into()],
+                &[],
+            )?);
+        }
+
+        if !found {
+            return Err(anyhow!(
+                "workspaces: true needs a Cargo, pnpm, npm/yarn or nx workspace at {}",
+                root.display()
+            ));
+        }
+
+        packages.sort();
+        packages.dedup();
+        Ok(Self { packages })
+    }
+
+    /// The innermost package containing `relative` (a path from the repository root)
+    pub fn package_of(&self, relative: &Path) -> Option<&Path> {
+        self.packages
+            .iter()
+            .filter(|package| relative.starts_with(package))
+            .max_by_key(|package| package.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// Group `files` by the package they belong to; files outside every package
+    /// are left out
+    pub fn group_files(&self, root: &Path, files: &[PathBuf]) -> BTreeMap<PathBuf, Vec<PathBuf>> {
+        let mut groups: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for file in files {
+            let relative = file.strip_prefix(root).unwrap_or(file);
+            if let Some(package) = self.package_of(relative) {
+                groups
+                    .entry(package.to_path_buf())
+                    .or_default()
+                    .push(file.clone());
+            }
+        }
+        groups
+    }
+}
+
+/// `[workspace] members` and `exclude` of the root Cargo.toml
+fn cargo_members(root: &Path) -> Result<Option<(Vec<String>, Vec<String>)>> {
+    let Ok(content) = std::fs::read_to_string(root.join("Cargo.toml")) else {
+        return Ok(None);
+    };
+    let manifest: toml::Value = toml::from_str(&content)?;
+    let Some(workspace) = manifest.get("workspace") else {
+        return Ok(None);
+    };
+
+    let strings = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(toml::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str().map(str::to_string))
+            .collect()
+    };
+    Ok(Some((strings("members"), strings("exclude"))))
+}
+
+/// Package patterns from pnpm-workspace.yaml or package.json `workspaces`,
+/// `!`-prefixed patterns excluding
+fn js_members(root: &Path) -> Result<Option<Vec<String>>> {
+    if let Ok(content) = std::fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        let config: serde_yml::Value = serde_yml::from_str(&content)?;
+        return Ok(Some(string_list(&config["packages"])));
+    }
+
+    let Ok(content) = std::fs::read_to_string(root.join("package.json")) else {
+        return Ok(None);
+    };
+    let package: serde_json::Value = serde_json::from_str(&content)?;
+    Ok(match &package["workspaces"] {
+        serde_json::Value::Array(patterns) => Some(strings_of(patterns)),
+        serde_json::Value::Object(config) => config
+            .get("packages")
+            .and_then(serde_json::Value::as_array)
+            .map(|patterns| strings_of(patterns)),
+        _ => None,
+    })
+}
+
+fn string_list(value: &serde_yml::Value) -> Vec<String> {
+    value
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.as_str().map(str::to_string))
+        .collect()
+}
+
+fn strings_of(values: &[serde_json::Value]) -> Vec<String> {
+    values
+        .iter()
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Relative paths of Cargo.toml, package.json and project.json files below `root`
+fn find_manifests(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .filter_entry(|entry| {
+            !matches!(entry.file_name().to_str(), Some("node_modules" | "target"))
+        })
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            matches!(
+                entry.file_name().to_str(),
+                Some("Cargo.toml" | "package.json" | "project.json")
+            )
+        })
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .collect()
+}
+
+/// Directories holding a `manifest` file that match `members` but not `exclude`
+fn matching_dirs(
+    manifests: &[PathBuf],
+    manifest: &str,
+    members: &[String],
+    exclude: &[String],
+) -> Result<Vec<PathBuf>> {
+    let members = dir_globs(members)?;
+    let exclude = dir_globs(exclude)?;
+
+    Ok(manifests
+        .iter()
+        .filter(|path| path.file_name().is_some_and(|name| name == manifest))
+        .filter_map(|path| path.parent())
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .filter(|dir| members.is_match(dir) && !exclude.is_match(dir))
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+fn dir_globs(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        // `*` stays within one directory level, as in Cargo and pnpm
+        builder.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_discover_cargo_and_pnpm_members() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/scratch\"]\n",
+        );
+        write(root, "crates/core/Cargo.toml", "");
+        write(root, "crates/scratch/Cargo.toml", "");
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - \"apps/*\"\n  - \"!apps/legacy\"\n",
+        );
+        write(root, "apps/web/package.json", "{}");
+        write(root, "apps/legacy/package.json", "{}");
+        write(root, "apps/web/node_modules/dep/package.json", "{}");
+
+        let workspace = Workspace::discover(root).unwrap();
+
+        assert_eq!(
+            workspace.packages,
+            vec![PathBuf::from("apps/web"), PathBuf::from("crates/core")]
+        );
+    }
+
+    #[test]
+    fn test_group_files_by_innermost_package() {
+        let workspace = Workspace {
+            packages: vec![
+                PathBuf::from("packages/ui"),
+                PathBuf::from("packages/ui/icons"),
+            ],
+        };
+        let root = Path::new("/repo");
+        let files = [
+            PathBuf::from("/repo/packages/ui/src/button.tsx"),
+            PathBuf::from("/repo/packages/ui/icons/star.svg"),
+            PathBuf::from("/repo/README.md"),
+        ];
+
+        let groups = workspace.group_files(root, &files);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[Path::new("packages/ui")], vec![files[0].clone()]);
+        assert_eq!(
+            groups[Path::new("packages/ui/icons")],
+            vec![files[1].clone()]
+        );
+    }
+}
@@ -157,6 +157,7 @@
 
 pub mod cli;
 pub mod config;
+pub mod events;
 pub mod external;
 pub mod git;
 pub mod hooks;
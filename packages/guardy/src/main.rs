@@ -3,6 +3,7 @@ use clap::Parser;
 
 mod cli;
 mod config;
+mod events;
 mod external;
 mod git;
 mod hooks;
@@ -1,7 +1,9 @@
-use crate::scanner::types::{SecretMatch, Warning};
+use crate::cli::output::human_duration;
+use crate::scanner::hygiene::HygieneIssue;
+use crate::scanner::types::{ScanStats, SecretMatch, Warning};
+use crate::shared::atomic_io;
 use anyhow::Result;
 use serde_json::json;
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -18,8 +20,8 @@ impl ReportGenerator {
     pub fn generate_report(
         matches: &[&SecretMatch],
         warnings: &[&Warning],
-        total_files: usize,
-        total_skipped: usize,
+        hygiene_issues: &[HygieneIssue],
+        totals: &ScanStats,
         elapsed: Duration,
         output_dir: &Path,
         format: ReportFormat,
@@ -32,8 +34,8 @@ impl ReportGenerator {
                 let content = Self::generate_html_content(
                     matches,
                     warnings,
-                    total_files,
-                    total_skipped,
+                    hygiene_issues,
+                    totals,
                     elapsed,
                 )?;
                 (filename, content)
@@ -43,8 +45,8 @@ impl ReportGenerator {
                 let content = Self::generate_json_content(
                     matches,
                     warnings,
-                    total_files,
-                    total_skipped,
+                    hygiene_issues,
+                    totals,
                     elapsed,
                 )?;
                 (filename, content)
@@ -52,7 +54,7 @@ impl ReportGenerator {
         };
 
         let report_path = output_dir.join(&report_filename);
-        fs::write(&report_path, content)?;
+        atomic_io::write_atomic(&report_path, content)?;
         Ok(report_path)
     }
 
@@ -60,8 +62,8 @@ impl ReportGenerator {
     fn generate_json_content(
         matches: &[&SecretMatch],
         warnings: &[&Warning],
-        total_files: usize,
-        total_skipped: usize,
+        hygiene_issues: &[HygieneIssue],
+        totals: &ScanStats,
         elapsed: Duration,
     ) -> Result<String> {
         let report = json!({
@@ -72,8 +74,8 @@ impl ReportGenerator {
                     .as_secs(),
                 "guardy_version": env!("CARGO_PKG_VERSION"),
                 "scan_duration_ms": elapsed.as_millis(),
-                "total_files_scanned": total_files,
-                "total_files_skipped": total_skipped
+                "total_files_scanned": totals.files_scanned,
+                "total_files_skipped": totals.files_skipped
             },
             "summary": {
                 "total_secrets": matches.len(),
@@ -87,11 +89,15 @@ impl ReportGenerator {
                 "matched_text": s.matched_text,
                 "start_pos": s.start_pos,
                 "end_pos": s.end_pos,
-                "pattern_description": s.pattern_description
+                "pattern_description": s.pattern_description,
+                "confidence": s.confidence,
+                "root": s.root,
+                "permalink": s.permalink
             })).collect::<Vec<_>>(),
             "warnings": warnings.iter().map(|w| json!({
                 "message": w.message
-            })).collect::<Vec<_>>()
+            })).collect::<Vec<_>>(),
+            "hygiene": hygiene_issues
         });
 
         Ok(serde_json::to_string_pretty(&report)?)
@@ -101,8 +107,8 @@ impl ReportGenerator {
     fn generate_html_content(
         matches: &[&SecretMatch],
         warnings: &[&Warning],
-        total_files: usize,
-        total_skipped: usize,
+        hygiene_issues: &[HygieneIssue],
+        totals: &ScanStats,
         elapsed: Duration,
     ) -> Result<String> {
         let secrets_by_type = Self::group_secrets_by_type(matches);
@@ -110,6 +116,7 @@ impl ReportGenerator {
 
         let secrets_section = Self::generate_secrets_html_section(&secrets_by_type);
         let warnings_section = Self::generate_warnings_html_section(&warnings_by_type);
+        let hygiene_section = Self::generate_hygiene_html_section(hygiene_issues);
 
         let timestamp = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -196,7 +203,7 @@ impl ReportGenerator {
                 <div>Warnings</div>
             </div>
             <div class="stat-card">
-                <div class="stat-number">{:.1}s</div>
+                <div class="stat-number">{}</div>
                 <div>Scan Time</div>
             </div>
         </div>
@@ -204,17 +211,20 @@ impl ReportGenerator {
         {}
 
         {}
+
+        {}
     </div>
 </body>
 </html>"#,
             timestamp,
             matches.len(),
-            total_files,
-            total_skipped,
+            totals.files_scanned,
+            totals.files_skipped,
             warnings.len(),
-            elapsed.as_secs_f64(),
+            human_duration(elapsed),
             secrets_section,
-            warnings_section
+            warnings_section,
+            hygiene_section
         );
 
         Ok(html)
@@ -234,7 +244,7 @@ impl ReportGenerator {
         }
 
         let mut result: Vec<_> = grouped.into_iter().collect();
-        result.sort_by(|a, b| b.1.len().cmp(&a.1.len())); // Sort by count descending
+        result.sort_by_key(|b| std::cmp::Reverse(b.1.len())); // Sort by count descending
         result
     }
 
@@ -255,7 +265,7 @@ impl ReportGenerator {
         }
 
         let mut result: Vec<_> = grouped.into_iter().collect();
-        result.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+        result.sort_by_key(|b| std::cmp::Reverse(b.1.len()));
         result
     }
 
@@ -308,6 +318,15 @@ impl ReportGenerator {
                     .replace('&', "&amp;")
                     .replace('<', "&lt;")
                     .replace('>', "&gt;");
+                // Link the line to the code host when the file is committed there
+                let line = match &secret.permalink {
+                    Some(link) => format!(
+                        r#"<a href="{}" target="_blank" rel="noopener">{}</a>"#,
+                        link.replace('&', "&amp;").replace('"', "&quot;"),
+                        secret.line_number
+                    ),
+                    None => secret.line_number.to_string(),
+                };
 
                 sections.push_str(&format!(
                     r#"
@@ -317,7 +336,7 @@ impl ReportGenerator {
                             <td>{}</td>
                         </tr>
 "#,
-                    file_path, secret.line_number, line_content
+                    file_path, line, line_content
                 ));
             }
 
@@ -400,4 +419,54 @@ impl ReportGenerator {
 
         sections
     }
+
+    fn generate_hygiene_html_section(issues: &[HygieneIssue]) -> String {
+        if issues.is_empty() {
+            return String::new();
+        }
+
+        let mut rows = String::new();
+        for issue in issues {
+            let escape = |text: String| {
+                text.replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+            };
+            rows.push_str(&format!(
+                r#"
+                        <tr>
+                            <td>{}</td>
+                            <td class="file-path">{}</td>
+                        </tr>
+"#,
+                escape(issue.message()),
+                escape(issue.suggestion())
+            ));
+        }
+
+        format!(
+            r#"
+        <div class="section">
+            <div class="section-header" onclick="toggleSection('hygiene')">
+                <span>🧹 Hygiene ({} suggestions)</span>
+                <button class="toggle-btn">▼</button>
+            </div>
+            <div id="hygiene" class="section-content collapsible">
+                <table id="table-hygiene">
+                    <thead>
+                        <tr>
+                            <th>Issue</th>
+                            <th>Fix</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+{rows}
+                    </tbody>
+                </table>
+            </div>
+        </div>
+"#,
+            issues.len()
+        )
+    }
 }
@@ -0,0 +1,167 @@
+use super::entropy::calculate_randomness_probability;
+use super::types::{FindingsSummary, RuleSummary, SecretMatch};
+use std::collections::HashMap;
+
+/// Share of the score that comes from the pattern; the rest comes from randomness
+const PATTERN_WEIGHT: f64 = 0.7;
+
+/// How much a match of this pattern says on its own.
+///
+/// Provider formats (`ghp_...`, `AKIA...`) are strong evidence; keyword-driven generic
+/// and user patterns less so, and public material (certificates, public keys) the least.
+fn pattern_score(pattern_name: &str) -> f64 {
+    if pattern_name.contains("Certificate")
+        || pattern_name.contains("Public Key")
+        || pattern_name.contains("Signing Request")
+    {
+        0.3
+    } else if pattern_name.starts_with("Generic") || pattern_name.starts_with("Custom Pattern") {
+        0.5
+    } else {
+        0.9
+    }
+}
+
+/// Confidence (0.0-1.0) that a match is a real secret, from its pattern and how random
+/// the secret content looks
+pub fn confidence(pattern_name: &str, secret: &[u8]) -> f64 {
+    let probability = calculate_randomness_probability(secret);
+    // Log scale: the default entropy threshold (1e-5) maps to 0, fully random text to 1
+    let randomness = ((probability.log10() + 5.0) / 5.0).clamp(0.0, 1.0);
+
+    (pattern_score(pattern_name) * PATTERN_WEIGHT + randomness * (1.0 - PATTERN_WEIGHT))
+        .clamp(0.0, 1.0)
+}
+
+impl<'a> FindingsSummary<'a> {
+    /// Keep the `top` highest-confidence findings, grouped by rule, and count the rest
+    pub fn rank(matches: &[&'a SecretMatch], top: usize) -> Self {
+        let mut ranked = matches.to_vec();
+        ranked.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| a.file_path.cmp(&b.file_path))
+                .then_with(|| a.line_number.cmp(&b.line_number))
+        });
+
+        let mut totals: HashMap<&str, usize> = HashMap::new();
+        for secret_match in matches {
+            *totals.entry(secret_match.secret_type.as_str()).or_default() += 1;
+        }
+
+        let shown = top.min(ranked.len());
+        let mut rules: Vec<RuleSummary<'a>> = Vec::new();
+        for secret_match in &ranked[..shown] {
+            match rules
+                .iter_mut()
+                .find(|rule| rule.rule == secret_match.secret_type)
+            {
+                Some(rule) => rule.top.push(secret_match),
+                None => rules.push(RuleSummary {
+                    rule: secret_match.secret_type.clone(),
+                    total: totals[secret_match.secret_type.as_str()],
+                    top: vec![secret_match],
+                }),
+            }
+        }
+
+        let mut remaining: HashMap<&str, usize> = HashMap::new();
+        for secret_match in &ranked[shown..] {
+            *remaining
+                .entry(secret_match.secret_type.as_str())
+                .or_default() += 1;
+        }
+        let mut remaining: Vec<(String, usize)> = remaining
+            .into_iter()
+            .map(|(rule, count)| (rule.to_string(), count))
+            .collect();
+        remaining.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            rules,
+            remaining,
+            total: matches.len(),
+            shown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(rule: &str, file: &str, confidence: f64) -> SecretMatch {
+        SecretMatch {
+            file_path: file.to_string(),
+            line_number: 1,
+            line_content: String::new(),
+            matched_text: String::new(),
+            start_pos: 0,
+            end_pos: 0,
+            secret_type: rule.to_string(),
+            pattern_description: String::new(),
+            confidence,
+            root: None,
+            permalink: None,
+        }
+    }
+
+    #[test]
+    fn test_provider_patterns_outrank_generic_matches() {
+        let token = b"wJbFxR9mK3qL7sP2vN8dH5zC4gY6tA1eXyZ9";
+
+        let github = confidence("GitHub Token", token);
+        let generic = confidence("Generic Secret Pattern", token);
+        let certificate = confidence("SSL/TLS Certificate", token);
+
+        assert!(github > generic && generic > certificate);
+        assert!((0.0..=1.0).contains(&github));
+        assert!(confidence("GitHub Token", b"aaaaaaaaaaaaaaaa") < github);
+    }
+
+    #[test]
+    fn test_rank_groups_top_findings_and_counts_the_rest() {
+        let findings = [
+            finding("Generic Secret Pattern", "a.env", 0.40),
+            finding("GitHub Token", "b.env", 0.95),
+            finding("Generic Secret Pattern", "c.env", 0.60),
+            finding("AWS Access Key", "d.env", 0.90),
+            finding("Generic Secret Pattern", "e.env", 0.30),
+        ];
+        let matches: Vec<&SecretMatch> = findings.iter().collect();
+
+        let summary = FindingsSummary::rank(&matches, 3);
+
+        assert_eq!(summary.total, 5);
+        assert_eq!(summary.shown, 3);
+        let rules: Vec<(&str, usize, usize)> = summary
+            .rules
+            .iter()
+            .map(|rule| (rule.rule.as_str(), rule.total, rule.top.len()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("GitHub Token", 1, 1),
+                ("AWS Access Key", 1, 1),
+                ("Generic Secret Pattern", 3, 1),
+            ]
+        );
+        assert_eq!(summary.rules[2].top[0].file_path, "c.env");
+        assert_eq!(
+            summary.remaining,
+            vec![("Generic Secret Pattern".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_rank_with_top_larger_than_findings() {
+        let findings = [finding("GitHub Token", "a.env", 0.9)];
+        let matches: Vec<&SecretMatch> = findings.iter().collect();
+
+        let summary = FindingsSummary::rank(&matches, 20);
+
+        assert_eq!(summary.shown, 1);
+        assert!(summary.remaining.is_empty());
+    }
+}
@@ -1,9 +1,16 @@
+use super::confidence::confidence;
 use super::entropy::is_likely_secret;
 use super::patterns::SecretPatterns;
 use super::test_detection::TestDetector;
-use super::types::{ScanResult, ScanStats, Scanner, ScannerConfig, SecretMatch, Warning};
+use super::types::{
+    ScanBudget, ScanResult, ScanStats, Scanner, ScannerConfig, SecretMatch, SubmoduleMode, Warning,
+};
 use crate::config::GuardyConfig;
+use crate::events::{self, Event};
+use crate::git::GitRepo;
+use crate::git::submodules::Submodule;
 use crate::parallel::ExecutionStrategy;
+use crate::shared::paths;
 use anyhow::{Context, Result};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
@@ -26,6 +33,7 @@ impl Scanner {
 
         Ok(Scanner {
             patterns,
+            budget: Arc::new(ScanBudget::new(scanner_config.limits.clone())),
             config: scanner_config,
             cached_path_ignorer: std::sync::OnceLock::new(),
         })
@@ -34,6 +42,7 @@ impl Scanner {
     pub fn with_config(patterns: SecretPatterns, config: ScannerConfig) -> Result<Self> {
         Ok(Scanner {
             patterns,
+            budget: Arc::new(ScanBudget::new(config.limits.clone())),
             config,
             cached_path_ignorer: std::sync::OnceLock::new(),
         })
@@ -94,8 +103,8 @@ impl Scanner {
         let mut builder = GlobSetBuilder::new();
 
         for pattern in &self.config.ignore_paths {
-            let glob =
-                Glob::new(pattern).with_context(|| format!("Invalid glob pattern: {pattern}"))?;
+            let glob = Glob::new(&paths::normalize_pattern(pattern))
+                .with_context(|| format!("Invalid glob pattern: {pattern}"))?;
             builder.add(glob);
         }
 
@@ -112,7 +121,7 @@ impl Scanner {
             .get_or_init(|| self.build_path_ignorer().map_err(|e| e.to_string()));
 
         match globset_result {
-            Ok(globset) => Ok(globset.is_match(path)),
+            Ok(globset) => Ok(globset.is_match(paths::match_key(path))),
             Err(e) => Err(anyhow::anyhow!("Failed to build path ignorer: {}", e)),
         }
     }
@@ -209,6 +218,9 @@ impl Scanner {
         if let Some(mode) = &args.mode {
             scanner_config.mode = mode.clone();
         }
+        if let Some(submodules) = &args.submodules {
+            scanner_config.submodules = submodules.clone();
+        }
 
         tracing::debug!(
             "CLI OVERRIDE: Final enable_entropy_analysis = {}",
@@ -335,6 +347,11 @@ impl Scanner {
             tracing::trace!("SCANNER CONFIG: Set mode to: {:?}", scanner_config.mode);
         }
 
+        if let Ok(submodules) = config.get_section("scanner.submodules") {
+            scanner_config.submodules = serde_json::from_value(submodules)
+                .with_context(|| "Invalid scanner.submodules, expected recurse or skip")?;
+        }
+
         if let Ok(max_threads) = config.get_section("scanner.max_threads")
             && let Some(threads) = max_threads.as_u64()
         {
@@ -353,6 +370,30 @@ impl Scanner {
             scanner_config.min_files_for_parallel = files as usize;
         }
 
+        // Per-run resource limits: durations accept "30s"/"5m"/"1h" or plain seconds
+        if let Ok(max_duration) = config.get_section("scanner.limits.max_duration") {
+            if let Some(duration) = max_duration.as_str() {
+                scanner_config.limits.max_duration = Some(
+                    super::limits::parse_duration(duration)
+                        .with_context(|| "Invalid scanner.limits.max_duration")?,
+                );
+            } else if let Some(seconds) = max_duration.as_u64() {
+                scanner_config.limits.max_duration = Some(std::time::Duration::from_secs(seconds));
+            }
+        }
+
+        if let Ok(max_memory) = config.get_section("scanner.limits.max_memory_mb")
+            && let Some(mb) = max_memory.as_u64()
+        {
+            scanner_config.limits.max_memory_mb = Some(mb);
+        }
+
+        if let Ok(fail) = config.get_section("scanner.limits.fail_on_truncation")
+            && let Some(fail) = fail.as_bool()
+        {
+            scanner_config.limits.fail_on_truncation = fail;
+        }
+
         tracing::debug!(
             "ENTROPY CONFIG: Final enable_entropy_analysis = {}",
             scanner_config.enable_entropy_analysis
@@ -360,14 +401,38 @@ impl Scanner {
         Ok(scanner_config)
     }
 
+    /// Copy of the scanner with a fresh resource budget, for one scan run
+    ///
+    /// `scan_paths` starts its own run; `scan_directory` scans within the
+    /// scanner's current one, so several directories can share a budget.
+    pub(crate) fn new_run(&self) -> Self {
+        Scanner {
+            budget: Arc::new(ScanBudget::new(self.config.limits.clone())),
+            ..self.clone()
+        }
+    }
+
     /// Scan specific paths
     pub fn scan_paths(&self, paths: &[PathBuf]) -> Result<ScanResult> {
+        self.new_run().scan_paths_in_run(paths)
+    }
+
+    fn scan_paths_in_run(&self, paths: &[PathBuf]) -> Result<ScanResult> {
+        let target = crate::cli::output::human_count(paths.len(), "path");
+        events::emit(Event::ScanStarted {
+            target: target.clone(),
+        });
         let start_time = std::time::Instant::now();
         let mut all_matches = Vec::new();
         let mut stats = ScanStats::default();
         let mut warnings: Vec<Warning> = Vec::new();
 
-        for path in paths {
+        for (index, path) in paths.iter().enumerate() {
+            if self.budget.exhausted() {
+                stats.files_not_scanned = paths.len() - index;
+                break;
+            }
+
             match self.scan_single_path(path) {
                 Ok(mut matches) => {
                     stats.files_scanned += 1;
@@ -384,12 +449,31 @@ impl Scanner {
         }
 
         stats.scan_duration_ms = start_time.elapsed().as_millis() as u64;
+        self.record_limits_hit(&mut stats, &mut warnings);
 
-        Ok(ScanResult {
+        let result = ScanResult {
             matches: all_matches,
             stats,
             warnings,
-        })
+        };
+        Event::scan_results(&target, &result)
+            .into_iter()
+            .for_each(events::emit);
+        Ok(result)
+    }
+
+    /// Flag the stats as truncated if a resource limit stopped the scan early
+    pub(crate) fn record_limits_hit(&self, stats: &mut ScanStats, warnings: &mut Vec<Warning>) {
+        stats.limits_hit = self.budget.limits_hit();
+        if stats.is_truncated() {
+            warnings.push(Warning {
+                message: format!(
+                    "Scan stopped early after hitting {}: {} files not scanned, results are partial",
+                    self.budget.describe_limits_hit(),
+                    stats.files_not_scanned
+                ),
+            });
+        }
     }
 
     /// Build a WalkBuilder with common directory filtering logic
@@ -408,6 +492,14 @@ impl Scanner {
 
         // Build ignore patterns for use in filter
         let ignore_globset = self.build_path_ignorer().ok();
+        let skipped_submodules: Vec<PathBuf> = if self.config.submodules == SubmoduleMode::Skip {
+            submodules_under(path)
+                .into_iter()
+                .map(|(dir, _)| dir)
+                .collect()
+        } else {
+            Vec::new()
+        };
 
         builder.filter_entry(move |entry| {
             // Skip directories that should always be ignored for security/performance
@@ -417,9 +509,17 @@ impl Scanner {
                 return false;
             }
 
+            if entry.file_type().is_some_and(|kind| kind.is_dir())
+                && skipped_submodules
+                    .iter()
+                    .any(|dir| same_dir(dir, entry.path()))
+            {
+                return false;
+            }
+
             // Apply ignore_paths patterns
             if let Some(ref globset) = ignore_globset
-                && globset.is_match(entry.path())
+                && globset.is_match(paths::match_key(entry.path()))
             {
                 return false;
             }
@@ -437,8 +537,28 @@ impl Scanner {
         path: &Path,
         strategy: Option<ExecutionStrategy>,
     ) -> Result<ScanResult> {
+        let target = paths::display(path);
+        events::emit(Event::ScanStarted {
+            target: target.clone(),
+        });
         let directory_handler = super::directory::DirectoryHandler::new();
-        directory_handler.scan(Arc::new(self.clone()), path, strategy)
+        let mut result = directory_handler.scan(Arc::new(self.clone()), path, strategy)?;
+        for (_, submodule) in submodules_under(path) {
+            let message = match self.config.submodules {
+                SubmoduleMode::Skip => "skipped (scanner.submodules: skip)",
+                SubmoduleMode::Recurse if !submodule.checked_out => {
+                    "not checked out - run git submodule update to scan it"
+                }
+                SubmoduleMode::Recurse => continue,
+            };
+            result.warnings.push(Warning {
+                message: format!("Submodule {} {message}", paths::display(&submodule.path)),
+            });
+        }
+        Event::scan_results(&target, &result)
+            .into_iter()
+            .for_each(events::emit);
+        Ok(result)
     }
 
     /// Scan a single file
@@ -446,6 +566,25 @@ impl Scanner {
         self.scan_single_path(path)
     }
 
+    /// Scan text that doesn't live in a file, such as a commit message; findings
+    /// report `source` as their location
+    pub fn scan_text(&self, text: &str, source: &str) -> Vec<SecretMatch> {
+        let source = Path::new(source);
+        let lines: Vec<&str> = text.lines().collect();
+        let mut matches = Vec::new();
+
+        for (line_number, line) in lines.iter().enumerate() {
+            if self.should_ignore_line(line)
+                || (line_number > 0 && lines[line_number - 1].contains("guardy:ignore-next"))
+            {
+                continue;
+            }
+            matches.extend(self.scan_line(line, source, line_number + 1));
+        }
+
+        matches
+    }
+
     /// Scan a large file using streaming approach to minimize memory usage
     fn scan_file_streaming(&self, path: &Path) -> Result<Vec<SecretMatch>> {
         use std::fs::File;
@@ -611,7 +750,7 @@ impl Scanner {
         }
 
         Some(SecretMatch {
-            file_path: file_path.to_string_lossy().into_owned(),
+            file_path: paths::display(file_path),
             line_number,
             line_content: line.to_string(),
             matched_text: matched_text.to_string(),
@@ -619,10 +758,35 @@ impl Scanner {
             end_pos: regex_match.end(),
             secret_type: pattern.name.clone(),
             pattern_description: pattern.description.clone(),
+            confidence: confidence(&pattern.name, secret_content.as_bytes()),
+            root: None,
+            permalink: None,
         })
     }
 }
 
+/// Submodules of the repository containing `dir` that lie below it, with their
+/// absolute directories; none outside a repository
+fn submodules_under(dir: &Path) -> Vec<(PathBuf, Submodule)> {
+    let Ok(repo) = GitRepo::discover_at(dir) else {
+        return Vec::new();
+    };
+    let Ok(dir) = dir.canonicalize() else {
+        return Vec::new();
+    };
+    repo.submodules()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|submodule| (repo.path.join(&submodule.path), submodule))
+        .filter(|(path, _)| path.starts_with(&dir))
+        .collect()
+}
+
+/// Whether two paths name the same directory, however they were spelled
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b || b.canonicalize().is_ok_and(|b| a == b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -676,5 +840,72 @@ FAKE_API_KEY = "test_key_not_real"
         }
     }
 
+    #[test]
+    fn test_scan_paths_stops_at_duration_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file_{i}.txt"));
+                fs::write(&path, "nothing to see here").unwrap();
+                path
+            })
+            .collect();
+
+        let config = create_test_config();
+        let mut scanner_config = Scanner::parse_scanner_config(&config).unwrap();
+        scanner_config.limits.max_duration = Some(std::time::Duration::ZERO);
+        let scanner =
+            Scanner::with_config(SecretPatterns::new(&config).unwrap(), scanner_config).unwrap();
+
+        let result = scanner.scan_paths(&files).unwrap();
+        assert!(result.stats.is_truncated());
+        assert_eq!(result.stats.files_scanned, 0);
+        assert_eq!(result.stats.files_not_scanned, 3);
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_each_scan_gets_a_fresh_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "nothing to see here").unwrap();
+
+        let config = create_test_config();
+        let mut scanner_config = Scanner::parse_scanner_config(&config).unwrap();
+        scanner_config.limits.max_duration = Some(std::time::Duration::from_millis(50));
+        let scanner =
+            Scanner::with_config(SecretPatterns::new(&config).unwrap(), scanner_config).unwrap();
+
+        // Idle time before a scan doesn't count against its limits
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        for _ in 0..2 {
+            let result = scanner.scan_paths(std::slice::from_ref(&file)).unwrap();
+            assert!(!result.stats.is_truncated());
+            assert_eq!(result.stats.files_scanned, 1);
+        }
+    }
+
+    #[test]
+    fn test_scan_text_reports_source_and_line() {
+        let scanner = Scanner::new(&create_test_config()).unwrap();
+        let token = format!("ghp_{}", "wJbFxR9mK3qL7sP2vN8dH5zC4gY6tA1eXyZ9");
+
+        let message = format!("fix: rotate credentials\n\nold token was {token}\n");
+        let matches = scanner.scan_text(&message, "commit 1a2b3c4d message");
+
+        assert!(!matches.is_empty());
+        assert!(
+            matches
+                .iter()
+                .all(|m| m.file_path == "commit 1a2b3c4d message")
+        );
+        assert!(matches.iter().all(|m| m.line_number == 3));
+        assert!(
+            scanner
+                .scan_text(&format!("guardy:ignore-next\n{token}"), "tag v1 annotation")
+                .is_empty()
+        );
+    }
+
     // Removed test_scan_directory - was causing CI timeouts and will be replaced by scan2 implementation
 }
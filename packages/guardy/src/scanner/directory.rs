@@ -1,6 +1,7 @@
 use super::types::{ScanFileResult, ScanResult, ScanStats, Scanner, Warning};
 use crate::cli::output;
 use crate::parallel::{ExecutionStrategy, progress::factories};
+use crate::shared::paths;
 use anyhow::Result;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -474,7 +475,18 @@ impl DirectoryHandler {
                     if let Some(ref progress) = enhanced_progress_for_worker
                         && progress.is_parallel
                     {
-                        progress.update_worker_file(worker_id, &file_path.to_string_lossy());
+                        progress.update_worker_file(worker_id, &paths::display(file_path));
+                    }
+
+                    // Stop picking up new files once the run's resource budget is spent
+                    if scanner.budget.exhausted() {
+                        return ScanFileResult {
+                            matches: Vec::new(),
+                            file_path: paths::display(file_path),
+                            success: false,
+                            error: None,
+                            truncated: true,
+                        };
                     }
 
                     // Check if this is a binary file first
@@ -490,9 +502,10 @@ impl DirectoryHandler {
                         }
                         return ScanFileResult {
                             matches: Vec::new(),
-                            file_path: file_path.to_string_lossy().to_string(),
+                            file_path: paths::display(file_path),
                             success: true,
                             error: None,
+                            truncated: false,
                         };
                     }
 
@@ -507,9 +520,10 @@ impl DirectoryHandler {
                             }
                             ScanFileResult {
                                 matches,
-                                file_path: file_path.to_string_lossy().to_string(),
+                                file_path: paths::display(file_path),
                                 success: true,
                                 error: None,
+                                truncated: false,
                             }
                         }
                         Err(e) => {
@@ -519,9 +533,10 @@ impl DirectoryHandler {
                             }
                             ScanFileResult {
                                 matches: Vec::new(),
-                                file_path: file_path.to_string_lossy().to_string(),
+                                file_path: paths::display(file_path),
                                 success: false,
                                 error: Some(e.to_string()),
+                                truncated: false,
                             }
                         }
                     }
@@ -545,9 +560,12 @@ impl DirectoryHandler {
         let mut all_matches = Vec::new();
         let mut files_scanned = 0;
         let mut files_skipped = 0;
+        let mut files_not_scanned = 0;
 
         for result in scan_results {
-            if result.success {
+            if result.truncated {
+                files_not_scanned += 1;
+            } else if result.success {
                 files_scanned += 1;
                 all_matches.extend(result.matches);
             } else {
@@ -561,12 +579,15 @@ impl DirectoryHandler {
         }
 
         let scan_duration = start_time.elapsed();
-        let stats = ScanStats {
+        let mut stats = ScanStats {
             files_scanned,
             files_skipped,
             total_matches: all_matches.len(),
             scan_duration_ms: scan_duration.as_millis() as u64,
+            files_not_scanned,
+            limits_hit: Vec::new(),
         };
+        scanner.record_limits_hit(&mut stats, &mut warnings);
 
         // Binary files are tracked internally but not displayed to users
 
@@ -579,14 +600,23 @@ impl DirectoryHandler {
         };
 
         output::styled!(
-            "{} Scan completed in {}s ({} files scanned, {} matches found{})",
+            "{} Scan completed in {} ({} scanned, {} found{})",
             (summary_icon, "success_symbol"),
-            (format!("{:.2}", scan_duration.as_secs_f64()), "time"),
-            (stats.files_scanned.to_string(), "number"),
-            (stats.total_matches.to_string(), "accent"),
+            (output::human_duration(scan_duration), "time"),
+            (output::human_count(stats.files_scanned, "file"), "number"),
+            (output::human_count(stats.total_matches, "match"), "accent"),
             (mode_info, "muted")
         );
 
+        if stats.is_truncated() {
+            output::styled!(
+                "{} Scan stopped early ({}) - {} files not scanned, results are partial",
+                ("⚠️", "warning_symbol"),
+                (scanner.budget.describe_limits_hit(), "warning"),
+                (stats.files_not_scanned.to_string(), "number")
+            );
+        }
+
         Ok(ScanResult {
             matches: all_matches,
             stats,
@@ -675,10 +705,9 @@ impl DirectoryAnalysis {
 
         let total_dirs = self.properly_ignored.len() + self.needs_gitignore.len();
         output::styled!(
-            "{} Discovered {} director{}:",
+            "{} Discovered {}:",
             ("📁", "info_symbol"),
-            (total_dirs.to_string(), "number"),
-            (if total_dirs == 1 { "y" } else { "ies" }, "primary")
+            (output::human_count(total_dirs, "directory"), "number")
         );
 
         // Show properly ignored directories
@@ -0,0 +1,250 @@
+//! Repository hygiene: build artifacts, dependencies and local files that
+//! belong in `.gitignore` rather than in the history
+//!
+//! Rules apply by project type (detected from marker files at the repository
+//! root) and yield suggestions rather than findings - they never fail a scan.
+
+use anyhow::Result;
+use globset::Glob;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::git::GitRepo;
+
+/// Something that should be ignored, for the projects it applies to
+struct Rule {
+    /// Project type the rule belongs to, `None` for every repository
+    project: Option<&'static str>,
+    /// Files at the repository root that identify the project type
+    markers: &'static [&'static str],
+    /// The `.gitignore` line covering it
+    ignore: &'static str,
+    /// Tracked paths the line would cover
+    glob: &'static str,
+    what: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        project: Some("Rust"),
+        markers: &["Cargo.toml"],
+        ignore: "target/",
+        glob: "target/**",
+        what: "build output",
+    },
+    Rule {
+        project: Some("Node.js"),
+        markers: &["package.json"],
+        ignore: "node_modules/",
+        glob: "**/node_modules/**",
+        what: "installed dependencies",
+    },
+    Rule {
+        project: Some("Next.js"),
+        markers: &["next.config.js", "next.config.mjs", "next.config.ts"],
+        ignore: ".next/",
+        glob: "**/.next/**",
+        what: "build output",
+    },
+    Rule {
+        project: Some("Python"),
+        markers: &["pyproject.toml", "setup.py", "requirements.txt"],
+        ignore: "__pycache__/",
+        glob: "**/__pycache__/**",
+        what: "bytecode cache",
+    },
+    Rule {
+        project: Some("Python"),
+        markers: &["pyproject.toml", "setup.py", "requirements.txt"],
+        ignore: ".venv/",
+        glob: ".venv/**",
+        what: "virtual environment",
+    },
+    Rule {
+        project: Some("Maven"),
+        markers: &["pom.xml"],
+        ignore: "target/",
+        glob: "target/**",
+        what: "build output",
+    },
+    Rule {
+        project: Some("Gradle"),
+        markers: &["build.gradle", "build.gradle.kts"],
+        ignore: "build/",
+        glob: "build/**",
+        what: "build output",
+    },
+    Rule {
+        project: Some("Gradle"),
+        markers: &["build.gradle", "build.gradle.kts"],
+        ignore: ".gradle/",
+        glob: ".gradle/**",
+        what: "local cache",
+    },
+    Rule {
+        project: None,
+        markers: &[],
+        ignore: ".env",
+        glob: "**/.env",
+        what: "local environment file",
+    },
+    Rule {
+        project: None,
+        markers: &[],
+        ignore: ".DS_Store",
+        glob: "**/.DS_Store",
+        what: "macOS folder metadata",
+    },
+];
+
+/// A hygiene suggestion for the repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HygieneIssue {
+    pub project: Option<&'static str>,
+    /// The `.gitignore` line to add
+    pub ignore: &'static str,
+    pub what: &'static str,
+    #[serde(skip)]
+    glob: &'static str,
+    /// Tracked files the line covers; empty when the path only exists unignored
+    /// in the working tree
+    pub tracked: Vec<String>,
+}
+
+impl HygieneIssue {
+    /// One-line description, e.g. `12 files tracked in target/ (Rust build output)`
+    pub fn message(&self) -> String {
+        let what = match self.project {
+            Some(project) => format!("{project} {}", self.what),
+            None => self.what.to_string(),
+        };
+        if self.tracked.is_empty() {
+            format!("{} is not ignored ({what})", self.ignore)
+        } else {
+            format!(
+                "{} tracked in {} ({what})",
+                crate::cli::output::human_count(self.tracked.len(), "file"),
+                self.ignore
+            )
+        }
+    }
+
+    /// Commands that fix the issue
+    pub fn suggestion(&self) -> String {
+        let add = format!("echo '{}' >> .gitignore", self.ignore);
+        if self.tracked.is_empty() {
+            add
+        } else {
+            // Nested matches need a glob pathspec, root ones just the path
+            let pathspec = if self.glob.starts_with("**/") {
+                format!("':(glob){}'", self.glob)
+            } else {
+                self.ignore.trim_end_matches('/').to_string()
+            };
+            format!("git rm -r --cached {pathspec} && {add}")
+        }
+    }
+}
+
+/// Hygiene issues of `repo`: tracked artifacts, then unignored ones on disk
+pub fn check(repo: &GitRepo) -> Result<Vec<HygieneIssue>> {
+    let tracked: Vec<String> = repo
+        .get_tracked_files()?
+        .iter()
+        .filter_map(|file| file.strip_prefix(&repo.path).ok())
+        .map(|file| file.to_string_lossy().replace('\\', "/"))
+        .collect();
+    let rules = applicable_rules(&repo.path);
+
+    let mut issues = tracked_artifacts(&rules, &tracked)?;
+    for rule in rules {
+        let path = rule.ignore.trim_end_matches('/');
+        if issues.iter().any(|issue| issue.ignore == rule.ignore)
+            || !repo.path.join(path).exists()
+            || repo.is_ignored(Path::new(path))?
+        {
+            continue;
+        }
+        issues.push(issue(rule, Vec::new()));
+    }
+    Ok(issues)
+}
+
+fn applicable_rules(root: &Path) -> Vec<&'static Rule> {
+    RULES
+        .iter()
+        .filter(|rule| {
+            rule.markers.is_empty() || rule.markers.iter().any(|marker| root.join(marker).exists())
+        })
+        .collect()
+}
+
+/// Issues for the rules whose glob matches tracked files, one per `.gitignore` line
+fn tracked_artifacts(rules: &[&'static Rule], tracked: &[String]) -> Result<Vec<HygieneIssue>> {
+    let mut issues: Vec<HygieneIssue> = Vec::new();
+    for rule in rules {
+        if issues.iter().any(|issue| issue.ignore == rule.ignore) {
+            continue; // e.g. target/ for a project that is both Rust and Maven
+        }
+        let matcher = Glob::new(rule.glob)?.compile_matcher();
+        let files: Vec<String> = tracked
+            .iter()
+            .filter(|file| matcher.is_match(file.as_str()))
+            .cloned()
+            .collect();
+        if !files.is_empty() {
+            issues.push(issue(rule, files));
+        }
+    }
+    Ok(issues)
+}
+
+fn issue(rule: &Rule, tracked: Vec<String>) -> HygieneIssue {
+    HygieneIssue {
+        project: rule.project,
+        ignore: rule.ignore,
+        what: rule.what,
+        glob: rule.glob,
+        tracked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracked_artifacts_by_project_type() {
+        let tracked: Vec<String> = [
+            "Cargo.toml",
+            "src/main.rs",
+            "target/debug/app",
+            "target/debug/app.d",
+            "web/node_modules/left-pad/index.js",
+            "config/.env",
+        ]
+        .iter()
+        .map(|file| file.to_string())
+        .collect();
+        let rust: Vec<&Rule> = RULES
+            .iter()
+            .filter(|rule| matches!(rule.project, Some("Rust" | "Maven") | None))
+            .collect();
+
+        let issues = tracked_artifacts(&rust, &tracked).unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0].message(),
+            "2 files tracked in target/ (Rust build output)"
+        );
+        assert_eq!(
+            issues[0].suggestion(),
+            "git rm -r --cached target && echo 'target/' >> .gitignore"
+        );
+        assert_eq!(issues[1].tracked, vec!["config/.env"]);
+        assert_eq!(
+            issues[1].suggestion(),
+            "git rm -r --cached ':(glob)**/.env' && echo '.env' >> .gitignore"
+        );
+    }
+}
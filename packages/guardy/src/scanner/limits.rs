@@ -0,0 +1,188 @@
+use super::types::{ScanBudget, ScanLimit, ScanLimits, ScanStats};
+use crate::cli::output::{human_bytes, human_duration};
+use anyhow::{Result, anyhow};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Memory is sampled every N budget checks - reading RSS is cheap but not free
+const MEMORY_CHECK_INTERVAL: usize = 16;
+
+impl ScanBudget {
+    pub fn new(limits: ScanLimits) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            checks: AtomicUsize::new(0),
+            duration_hit: AtomicBool::new(false),
+            memory_hit: AtomicBool::new(false),
+        }
+    }
+
+    /// Check the budget before scanning another file.
+    ///
+    /// Once a limit is hit the budget stays exhausted, so every worker stops
+    /// picking up new files and the scan winds down with partial results.
+    pub fn exhausted(&self) -> bool {
+        if self.duration_hit.load(Ordering::Relaxed) || self.memory_hit.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        if let Some(max_duration) = self.limits.max_duration
+            && self.started.elapsed() >= max_duration
+        {
+            self.duration_hit.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        if let Some(max_memory_mb) = self.limits.max_memory_mb
+            && self.checks.fetch_add(1, Ordering::Relaxed) % MEMORY_CHECK_INTERVAL == 0
+            && current_memory_mb().is_some_and(|used| used >= max_memory_mb)
+        {
+            self.memory_hit.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        false
+    }
+
+    /// Limits hit so far, in a stable order
+    pub fn limits_hit(&self) -> Vec<ScanLimit> {
+        let mut hit = Vec::new();
+        if self.duration_hit.load(Ordering::Relaxed) {
+            hit.push(ScanLimit::MaxDuration);
+        }
+        if self.memory_hit.load(Ordering::Relaxed) {
+            hit.push(ScanLimit::MaxMemory);
+        }
+        hit
+    }
+
+    /// Human-readable description of the limits that stopped the scan
+    pub fn describe_limits_hit(&self) -> String {
+        self.limits_hit()
+            .iter()
+            .map(|limit| match limit {
+                ScanLimit::MaxDuration => format!(
+                    "max_duration ({})",
+                    human_duration(self.limits.max_duration.unwrap_or_default())
+                ),
+                ScanLimit::MaxMemory => format!(
+                    "max_memory_mb ({})",
+                    human_bytes(self.limits.max_memory_mb.unwrap_or_default() * 1024 * 1024)
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl ScanStats {
+    /// Whether the scan stopped early and the results are partial
+    pub fn is_truncated(&self) -> bool {
+        !self.limits_hit.is_empty()
+    }
+}
+
+impl std::fmt::Display for ScanLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanLimit::MaxDuration => write!(f, "max_duration"),
+            ScanLimit::MaxMemory => write!(f, "max_memory_mb"),
+        }
+    }
+}
+
+/// Parse a duration such as `500ms`, `30s`, `5m` or `1h` (bare numbers are seconds)
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| anyhow!("Invalid duration '{value}' (expected e.g. 30s, 5m, 1h)"))?;
+
+    match unit.trim() {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        "h" => Ok(Duration::from_secs(number * 3600)),
+        other => Err(anyhow!("Invalid duration unit '{other}' in '{value}'")),
+    }
+}
+
+/// Resident memory of the current process in MB, if the platform exposes it
+fn current_memory_mb() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let kb: u64 = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()?;
+        Some(kb / 1024)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+        assert!(parse_duration("5 minutes").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_unlimited_budget_never_exhausts() {
+        let budget = ScanBudget::new(ScanLimits::default());
+        for _ in 0..100 {
+            assert!(!budget.exhausted());
+        }
+        assert!(budget.limits_hit().is_empty());
+    }
+
+    #[test]
+    fn test_duration_limit_is_sticky() {
+        let budget = ScanBudget::new(ScanLimits {
+            max_duration: Some(Duration::ZERO),
+            max_memory_mb: None,
+            ..Default::default()
+        });
+
+        assert!(budget.exhausted());
+        assert!(budget.exhausted());
+        assert_eq!(budget.limits_hit(), vec![ScanLimit::MaxDuration]);
+        assert_eq!(budget.describe_limits_hit(), "max_duration (0ms)");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_memory_limit() {
+        let budget = ScanBudget::new(ScanLimits {
+            max_duration: None,
+            max_memory_mb: Some(0),
+            ..Default::default()
+        });
+
+        assert!(budget.exhausted());
+        assert_eq!(budget.limits_hit(), vec![ScanLimit::MaxMemory]);
+    }
+}
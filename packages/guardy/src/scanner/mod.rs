@@ -1,6 +1,9 @@
+pub mod confidence;
 pub mod core;
 pub mod directory;
 pub mod entropy;
+pub mod hygiene;
+pub mod limits;
 pub mod patterns;
 pub mod test_detection;
 pub mod types;
@@ -104,8 +104,10 @@ impl SecretPatterns {
         // Add predefined patterns (extracted from ripsecrets)
         patterns.extend(Self::predefined_patterns()?);
 
-        // Add custom patterns from config
-        if let Ok(custom_patterns) = config.get_section("scanner.custom_patterns")
+        // Add custom patterns from config (`security.patterns` before they moved)
+        if let Ok(custom_patterns) = config
+            .get_section("scanner.custom_patterns")
+            .or_else(|_| config.get_section("security.patterns"))
             && let Some(array) = custom_patterns.as_array()
         {
             for (i, pattern) in array.iter().enumerate() {
@@ -9,6 +9,32 @@ pub struct SecretMatch {
     pub end_pos: usize,
     pub secret_type: String,
     pub pattern_description: String,
+    /// How likely the match is a real secret (0.0-1.0), used to rank findings
+    pub confidence: f64,
+    /// Label of the scan root the file was found under (`--root-labels`)
+    pub root: Option<String>,
+    /// Link to the line on the code host at the scanned commit
+    pub permalink: Option<String>,
+}
+
+/// Findings ranked by confidence for `guardy scan --summary top=N`
+#[derive(Debug)]
+pub struct FindingsSummary<'a> {
+    /// Rules with at least one top finding, strongest first
+    pub rules: Vec<RuleSummary<'a>>,
+    /// Findings outside the top N, counted per rule (largest first)
+    pub remaining: Vec<(String, usize)>,
+    pub total: usize,
+    pub shown: usize,
+}
+
+/// Top findings for a single rule (pattern)
+#[derive(Debug)]
+pub struct RuleSummary<'a> {
+    pub rule: String,
+    /// All findings for the rule, including those outside the top N
+    pub total: usize,
+    pub top: Vec<&'a SecretMatch>,
 }
 
 /// Statistics from a scanning operation
@@ -18,6 +44,38 @@ pub struct ScanStats {
     pub files_skipped: usize,
     pub total_matches: usize,
     pub scan_duration_ms: u64,
+    /// Files left unscanned because a resource limit stopped the scan early
+    pub files_not_scanned: usize,
+    /// Resource limits that were hit - non-empty means the results are truncated
+    pub limits_hit: Vec<ScanLimit>,
+}
+
+/// A per-run resource limit that can stop a scan early
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanLimit {
+    MaxDuration,
+    MaxMemory,
+}
+
+/// Per-run resource limits (`scanner.limits`), unset means unlimited
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct ScanLimits {
+    pub max_duration: Option<std::time::Duration>,
+    pub max_memory_mb: Option<u64>,
+    /// Fail the pre-commit secret scan instead of passing it on partial results
+    pub fail_on_truncation: bool,
+}
+
+/// Tracks one scan run against its `ScanLimits`, shared by all workers of the run
+#[derive(Debug)]
+pub struct ScanBudget {
+    pub(crate) limits: ScanLimits,
+    pub(crate) started: std::time::Instant,
+    pub(crate) checks: std::sync::atomic::AtomicUsize,
+    pub(crate) duration_hit: std::sync::atomic::AtomicBool,
+    pub(crate) memory_hit: std::sync::atomic::AtomicBool,
 }
 
 /// Warning generated during scanning
@@ -33,6 +91,8 @@ pub struct ScanFileResult {
     pub file_path: String,
     pub success: bool,
     pub error: Option<String>,
+    /// File was not scanned because the scan budget was exhausted
+    pub truncated: bool,
 }
 
 /// Result of a scanning operation
@@ -57,6 +117,19 @@ pub enum ScanMode {
     Auto,
 }
 
+/// What directory scans do with git submodules (`scanner.submodules`)
+#[derive(
+    Debug, Clone, PartialEq, clap::ValueEnum, serde::Serialize, serde::Deserialize, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmoduleMode {
+    /// Scan checked-out submodules like the rest of the tree
+    #[default]
+    Recurse,
+    /// Leave submodules out, noting each one in the report
+    Skip,
+}
+
 /// Configuration for the scanner
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(default)]
@@ -78,6 +151,9 @@ pub struct ScannerConfig {
     pub max_threads: usize,
     pub thread_percentage: u8,
     pub min_files_for_parallel: usize,
+    pub submodules: SubmoduleMode,
+    // Per-run resource limits
+    pub limits: ScanLimits,
 }
 
 impl Default for ScannerConfig {
@@ -290,6 +366,8 @@ impl Default for ScannerConfig {
             max_threads: 0, // 0 = auto-detect
             thread_percentage: 75,
             min_files_for_parallel: 50,
+            submodules: SubmoduleMode::Recurse,
+            limits: ScanLimits::default(),
         }
     }
 }
@@ -304,4 +382,6 @@ pub struct Scanner {
     pub(crate) config: ScannerConfig,
     /// Cached GlobSet for path ignoring - built once and reused
     pub(crate) cached_path_ignorer: std::sync::OnceLock<Result<globset::GlobSet, String>>,
+    /// Resource budget of the current scan run - replaced by `new_run`, shared across clones
+    pub(crate) budget: std::sync::Arc<ScanBudget>,
 }
@@ -0,0 +1,347 @@
+use anyhow::{Context, Result, anyhow};
+use std::fs::{self, OpenOptions, Permissions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `contents` to `path` atomically.
+///
+/// Data goes to a temporary sibling first, is fsynced, and is then renamed over the
+/// destination, so readers (and a crash mid-write) see either the old file or the
+/// complete new one - never a partial write. Existing permissions are preserved.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let permissions = fs::metadata(path).ok().map(|meta| meta.permissions());
+    write_with(path, contents.as_ref(), permissions)
+}
+
+/// Like [`write_atomic`], but the new file gets `permissions` (e.g. `0o755` for hook stubs)
+pub fn write_atomic_with_permissions(
+    path: &Path,
+    contents: impl AsRef<[u8]>,
+    permissions: Permissions,
+) -> Result<()> {
+    write_with(path, contents.as_ref(), Some(permissions))
+}
+
+/// Atomically copy `src` over `dst`, keeping the source file's permissions
+pub fn copy_atomic(src: &Path, dst: &Path) -> Result<()> {
+    let contents = fs::read(src).with_context(|| format!("Failed to read {}", src.display()))?;
+    let permissions = fs::metadata(src)?.permissions();
+    write_with(dst, &contents, Some(permissions))
+}
+
+/// Atomically replace `dst` with a symlink to `target`
+#[cfg(unix)]
+pub fn symlink_atomic(target: &Path, dst: &Path) -> Result<()> {
+    let parent = match dst.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+
+    let temp_path = temp_sibling(dst);
+    let result =
+        std::os::unix::fs::symlink(target, &temp_path).and_then(|()| fs::rename(&temp_path, dst));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e).with_context(|| format!("Failed to link {}", dst.display()));
+    }
+
+    sync_dir(parent);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn symlink_atomic(_target: &Path, dst: &Path) -> Result<()> {
+    Err(anyhow!(
+        "Failed to link {}: symlinks are only supported on Unix",
+        dst.display()
+    ))
+}
+
+fn write_with(path: &Path, contents: &[u8], permissions: Option<Permissions>) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+
+    let temp_path = temp_sibling(path);
+    let result = (|| -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        file.write_all(contents)?;
+        if let Some(permissions) = permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e).with_context(|| format!("Failed to write {}", path.display()));
+    }
+
+    sync_dir(parent);
+    Ok(())
+}
+
+/// Hidden, uniquely named path next to `path` (same directory, so the rename is atomic)
+fn temp_sibling(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.{}.tmp", uuid::Uuid::new_v4().simple()))
+}
+
+/// Persist the rename itself - best effort, not every filesystem supports it
+#[cfg(unix)]
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) {}
+
+/// A uniquely named temporary directory, removed on drop unless persisted
+///
+/// Work that builds a directory (e.g. a git clone) happens inside the guard and is
+/// moved into place with [`TempDirGuard::persist`] only once it is complete, so an
+/// interrupted run never leaves a half-populated directory at the final path.
+pub struct TempDirGuard {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempDirGuard {
+    /// Create `<parent>/.<prefix>-<unique>.tmp`; living next to the destination keeps
+    /// `persist` a same-filesystem rename
+    pub fn new_in(parent: &Path, prefix: &str) -> Result<Self> {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+        let path = parent.join(format!(".{prefix}-{}.tmp", uuid::Uuid::new_v4().simple()));
+        fs::create_dir(&path)
+            .with_context(|| format!("Failed to create temporary directory {}", path.display()))?;
+        Ok(Self {
+            path,
+            persisted: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move the directory to `dest` (which must not exist yet) and disarm the guard
+    pub fn persist(mut self, dest: &Path) -> Result<()> {
+        fs::rename(&self.path, dest).with_context(|| {
+            format!(
+                "Failed to move {} to {}",
+                self.path.display(),
+                dest.display()
+            )
+        })?;
+        self.persisted = true;
+        Ok(())
+    }
+}
+
+impl Drop for TempDirGuard {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// An exclusive, PID-stamped lock file, released on drop
+///
+/// A lock left behind by a process that is no longer running (e.g. after a crash or
+/// `kill -9`) is treated as stale and reclaimed.
+pub struct LockFile {
+    path: PathBuf,
+}
+
+impl LockFile {
+    pub fn acquire(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Second attempt only happens after removing a stale lock
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", std::process::id())?;
+                    file.sync_all()?;
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(path)
+                        .ok()
+                        .and_then(|content| content.trim().parse::<u32>().ok());
+                    if let Some(pid) = holder
+                        && process_alive(pid)
+                    {
+                        return Err(anyhow!(
+                            "{} is held by another guardy process (PID {pid})",
+                            path.display()
+                        ));
+                    }
+                    tracing::warn!("Removing stale lock file {}", path.display());
+                    let _ = fs::remove_file(path);
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create lock file {}", path.display()));
+                }
+            }
+        }
+
+        Err(anyhow!("Failed to acquire lock file {}", path.display()))
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Path::new(&format!("/proc/{pid}")).exists()
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        std::process::Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    // No cheap liveness check - never steal a lock we can't prove is stale
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_without_leftovers() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested/report.json");
+
+        write_atomic(&path, "first").unwrap();
+        write_atomic(&path, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert_eq!(entries(path.parent().unwrap()), vec!["report.json"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permissions_are_preserved_or_set() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let hook = temp.path().join("pre-commit");
+
+        write_atomic_with_permissions(&hook, "#!/bin/sh\n", Permissions::from_mode(0o755)).unwrap();
+        write_atomic(&hook, "#!/bin/sh\nexit 0\n").unwrap();
+        assert_eq!(
+            fs::metadata(&hook).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+
+        let copy = temp.path().join("copy");
+        copy_atomic(&hook, &copy).unwrap();
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "#!/bin/sh\nexit 0\n");
+        assert_eq!(
+            fs::metadata(&copy).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+
+    #[test]
+    fn test_temp_dir_guard_cleans_up_unless_persisted() {
+        let temp = TempDir::new().unwrap();
+
+        let dropped = TempDirGuard::new_in(temp.path(), "clone").unwrap();
+        fs::write(dropped.path().join("file"), "partial").unwrap();
+        drop(dropped);
+        assert!(entries(temp.path()).is_empty());
+
+        let kept = TempDirGuard::new_in(temp.path(), "clone").unwrap();
+        fs::write(kept.path().join("file"), "complete").unwrap();
+        kept.persist(&temp.path().join("repo")).unwrap();
+        assert_eq!(entries(temp.path()), vec!["repo"]);
+        assert_eq!(
+            fs::read_to_string(temp.path().join("repo/file")).unwrap(),
+            "complete"
+        );
+    }
+
+    #[test]
+    fn test_lock_file_is_exclusive_and_released_on_drop() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("sync.lock");
+
+        let lock = LockFile::acquire(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+        assert!(LockFile::acquire(&path).is_err());
+
+        drop(lock);
+        assert!(!path.exists());
+        assert!(LockFile::acquire(&path).is_ok());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_stale_lock_is_reclaimed() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("sync.lock");
+        fs::write(&path, format!("{}\n", u32::MAX)).unwrap();
+
+        let _lock = LockFile::acquire(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+    }
+}
@@ -1 +1,12 @@
-// TODO: Implement shared utilities
+//! Shared utilities used across guardy modules
+//!
+//! - [`atomic_io`]: crash-safe file writes (write-temp-fsync-rename), self-cleaning
+//!   temporary directories and PID-stamped lock files. Anything guardy writes into a
+//!   user's repository or cache should go through here so an interrupted run never
+//!   leaves a half-written file behind.
+//! - [`paths`]: NFC-normalized path keys for glob/regex matching and display strings
+//!   that mark non-UTF-8 names explicitly. Filters should match on
+//!   [`paths::match_key`] and user-facing output should use [`paths::display`].
+
+pub mod atomic_io;
+pub mod paths;
@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalized form of `path` for matching against user patterns
+///
+/// Names are NFC-normalized (macOS hands out decomposed NFD names, while patterns
+/// typed by users are composed) and separators become `/`. Bytes that aren't valid
+/// UTF-8 turn into U+FFFD, so they can only ever be matched by wildcards.
+pub fn match_key(path: &Path) -> String {
+    let lossy = path.to_string_lossy();
+    let key = nfc(&lossy);
+    if cfg!(windows) {
+        key.replace('\\', "/")
+    } else {
+        key.into_owned()
+    }
+}
+
+/// NFC-normalize a glob or regex so it compares like [`match_key`] output
+pub fn normalize_pattern(pattern: &str) -> String {
+    nfc(pattern).into_owned()
+}
+
+/// Human-readable form of `path` for output and reports
+///
+/// NFC-normalized like [`match_key`], but bytes that aren't valid UTF-8 are shown as
+/// `\xNN` escapes instead of being silently replaced, so a lossy name is visible as
+/// such and two different undecodable names never render the same.
+pub fn display(path: &Path) -> String {
+    nfc(&escape_invalid(path)).into_owned()
+}
+
+fn nfc(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
+
+#[cfg(unix)]
+fn escape_invalid(path: &Path) -> Cow<'_, str> {
+    use std::os::unix::ffi::OsStrExt;
+
+    if let Some(text) = path.to_str() {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::new();
+    for chunk in path.as_os_str().as_bytes().utf8_chunks() {
+        escaped.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            escaped.push_str(&format!("\\x{byte:02X}"));
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+// Windows paths are UTF-16; the only undecodable case is an unpaired surrogate
+#[cfg(not(unix))]
+fn escape_invalid(path: &Path) -> Cow<'_, str> {
+    path.to_string_lossy()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use globset::Glob;
+
+    const COMPOSED: &str = "caf\u{e9}/r\u{e9}sum\u{e9}.txt";
+    const DECOMPOSED: &str = "cafe\u{301}/re\u{301}sume\u{301}.txt";
+
+    #[test]
+    fn test_nfd_and_nfc_paths_normalize_identically() {
+        let composed = Path::new(COMPOSED);
+        let decomposed = Path::new(DECOMPOSED);
+
+        assert_eq!(match_key(decomposed), match_key(composed));
+        assert_eq!(display(decomposed), COMPOSED);
+    }
+
+    #[test]
+    fn test_normalized_patterns_match_decomposed_paths() {
+        let glob = Glob::new(&normalize_pattern("caf\u{e9}/**"))
+            .unwrap()
+            .compile_matcher();
+
+        assert!(glob.is_match(match_key(Path::new(DECOMPOSED))));
+        assert!(!glob.is_match(Path::new(DECOMPOSED)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_names_are_marked_in_display() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        // Latin-1 "café.env" - valid on disk, not valid UTF-8
+        let path = Path::new(OsStr::from_bytes(b"secrets/caf\xe9.env"));
+
+        assert_eq!(display(path), "secrets/caf\\xE9.env");
+        assert_eq!(match_key(path), "secrets/caf\u{fffd}.env");
+    }
+}
@@ -0,0 +1,132 @@
+//! Last-synced copies of upstream files
+//!
+//! Kept under `.guardy/sync-base/<repo>/<destination>`, they tell local edits apart
+//! from upstream changes and are the base of three-way merges between the two.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use crate::git;
+use crate::shared::atomic_io;
+
+/// What happens to a destination file with local edits when upstream changes it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Overwrite the local edits with upstream
+    Theirs,
+    /// Keep the local edits and skip the file
+    Ours,
+    /// Ask in interactive syncs; `--force` overwrites
+    #[default]
+    Prompt,
+    /// Three-way merge with the last-synced version; conflicts are asked about
+    /// interactively and keep the local file otherwise
+    Merge,
+}
+
+pub enum MergeOutcome {
+    Clean(Vec<u8>),
+    /// Merged content with conflict markers
+    Conflicted(Vec<u8>),
+}
+
+pub struct SyncBase {
+    root: PathBuf,
+}
+
+impl SyncBase {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, repo: &str, dest: &Path) -> PathBuf {
+        let relative: PathBuf = dest
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+        self.root.join(repo).join(relative)
+    }
+
+    /// Remember `upstream` as the synced version of `dest`
+    pub fn record(&self, repo: &str, dest: &Path, upstream: &Path) -> Result<()> {
+        let base = self.path(repo, dest);
+        if base.is_file() && std::fs::read(&base)? == std::fs::read(upstream)? {
+            return Ok(());
+        }
+        atomic_io::copy_atomic(upstream, &base)
+    }
+
+    /// Whether `dest` has a last-synced copy to merge with
+    pub fn has_base(&self, repo: &str, dest: &Path) -> bool {
+        self.path(repo, dest).is_file()
+    }
+
+    /// Whether `dest` was edited since it was last synced - assumed when it has
+    /// never been synced but exists
+    pub fn locally_modified(&self, repo: &str, dest: &Path) -> Result<bool> {
+        if !dest.is_file() {
+            return Ok(false);
+        }
+        let base = self.path(repo, dest);
+        Ok(!base.is_file() || std::fs::read(&base)? != std::fs::read(dest)?)
+    }
+
+    /// Three-way merge of the local `dest` and `upstream` over the last-synced
+    /// version, or `None` without one
+    pub fn merge(&self, repo: &str, dest: &Path, upstream: &Path) -> Result<Option<MergeOutcome>> {
+        if !self.has_base(repo, dest) {
+            return Ok(None);
+        }
+        let base = self.path(repo, dest);
+        let merge = git::merge::merge_file(
+            &std::fs::read(dest)?,
+            &std::fs::read(&base)?,
+            &std::fs::read(upstream)?,
+            ["local", "last sync", "upstream"],
+        )
+        .with_context(|| format!("Failed to merge {}", dest.display()))?;
+        Ok(Some(if merge.conflicted {
+            MergeOutcome::Conflicted(merge.content)
+        } else {
+            MergeOutcome::Clean(merge.content)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_local_and_upstream_edits_over_the_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = SyncBase::new(dir.path().join("sync-base"));
+        let dest = dir.path().join("ci.yml");
+        let upstream = dir.path().join("upstream.yml");
+
+        std::fs::write(&upstream, "a: 1\nb: 2\nc: 3\n").unwrap();
+        std::fs::copy(&upstream, &dest).unwrap();
+        assert!(base.locally_modified("shared", &dest).unwrap());
+        base.record("shared", &dest, &upstream).unwrap();
+        assert!(!base.locally_modified("shared", &dest).unwrap());
+
+        std::fs::write(&dest, "a: local\nb: 2\nc: 3\n").unwrap();
+        std::fs::write(&upstream, "a: 1\nb: 2\nc: upstream\n").unwrap();
+        assert!(base.locally_modified("shared", &dest).unwrap());
+        let Some(MergeOutcome::Clean(merged)) = base.merge("shared", &dest, &upstream).unwrap()
+        else {
+            panic!("expected a clean merge");
+        };
+        assert_eq!(merged, b"a: local\nb: 2\nc: upstream\n");
+
+        std::fs::write(&upstream, "a: upstream\nb: 2\nc: 3\n").unwrap();
+        let Some(MergeOutcome::Conflicted(merged)) =
+            base.merge("shared", &dest, &upstream).unwrap()
+        else {
+            panic!("expected a conflict");
+        };
+        assert!(String::from_utf8(merged).unwrap().contains("<<<<<<< local"));
+    }
+}
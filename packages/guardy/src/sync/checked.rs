@@ -0,0 +1,88 @@
+//! When each sync repository's source was last fetched
+//!
+//! Kept in `.guardy/sync-checked.json` and updated whenever a cache is refreshed from
+//! its remote, so `guardy sync status` - which compares with the caches as they are,
+//! without network access - can tell how current its answer is.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::output;
+use crate::shared::atomic_io;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SyncChecked {
+    /// Seconds since the epoch of the last fetch, keyed by repository `name`
+    #[serde(default)]
+    pub repos: BTreeMap<String, u64>,
+}
+
+impl SyncChecked {
+    /// The record at `path`, empty when nothing was fetched yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        atomic_io::write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Note that `repo` was fetched just now
+    pub fn record(&mut self, repo: &str) -> Result<()> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+        self.repos.insert(repo.to_string(), now);
+        Ok(())
+    }
+
+    pub fn get(&self, repo: &str) -> Option<u64> {
+        self.repos.get(repo).copied()
+    }
+}
+
+/// How long ago `checked` (seconds since the epoch) was, e.g. "3 hours ago"
+pub fn age(checked: u64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |now| now.as_secs());
+    let elapsed = now.saturating_sub(checked);
+    let (count, unit) = match elapsed {
+        0..60 => return "just now".to_string(),
+        60..3_600 => (elapsed / 60, "minute"),
+        3_600..86_400 => (elapsed / 3_600, "hour"),
+        _ => (elapsed / 86_400, "day"),
+    };
+    format!("{} ago", output::human_count(count as usize, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_survive_a_round_trip_and_read_as_ages() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sync-checked.json");
+        assert_eq!(SyncChecked::load(&path).unwrap(), SyncChecked::default());
+
+        let mut checked = SyncChecked::default();
+        checked.record("shared").unwrap();
+        checked.save(&path).unwrap();
+        let loaded = SyncChecked::load(&path).unwrap();
+        assert_eq!(loaded, checked);
+        assert_eq!(age(loaded.get("shared").unwrap()), "just now");
+
+        let now = checked.get("shared").unwrap();
+        assert_eq!(age(now - 2 * 3_600 - 30), "2 hours ago");
+        assert_eq!(age(now - 86_400), "1 day ago");
+        assert_eq!(loaded.get("other"), None);
+    }
+}
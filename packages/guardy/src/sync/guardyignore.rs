@@ -0,0 +1,90 @@
+//! `.guardyignore`: files a destination opts out of syncing
+//!
+//! A `.guardyignore` in a destination directory holds gitignore-style patterns,
+//! relative to that directory, of files sync leaves alone - never overwritten,
+//! reported as drift or pruned - so a repository can keep its own version of a
+//! shared file without editing the central configuration. Files in deeper
+//! directories take precedence, and `!pattern` syncs a file again.
+
+use anyhow::{Context, Result};
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const FILENAME: &str = ".guardyignore";
+
+pub struct GuardyIgnore {
+    /// Patterns of each directory with a `.guardyignore`
+    matchers: BTreeMap<PathBuf, Gitignore>,
+}
+
+impl GuardyIgnore {
+    /// The `.guardyignore` files below `root` that apply to any of `destinations`
+    pub fn load<'a>(root: &Path, destinations: impl IntoIterator<Item = &'a Path>) -> Result<Self> {
+        let mut matchers = BTreeMap::new();
+        for destination in destinations {
+            for dir in destination.ancestors().skip(1) {
+                if !dir.starts_with(root) {
+                    break;
+                }
+                if matchers.contains_key(dir) {
+                    continue;
+                }
+                let file = dir.join(FILENAME);
+                if !file.is_file() {
+                    continue;
+                }
+                let mut builder = GitignoreBuilder::new(dir);
+                if let Some(error) = builder.add(&file) {
+                    return Err(error).with_context(|| format!("Invalid {}", file.display()));
+                }
+                matchers.insert(dir.to_path_buf(), builder.build()?);
+            }
+        }
+        Ok(Self { matchers })
+    }
+
+    /// Whether `destination` is opted out of syncing
+    pub fn is_ignored(&self, destination: &Path) -> bool {
+        // Deepest directory first: its `.guardyignore` overrides its parents'
+        for (dir, matcher) in self.matchers.iter().rev() {
+            if !destination.starts_with(dir) {
+                continue;
+            }
+            match matcher.matched_path_or_any_parents(destination, false) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deeper_files_override_their_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("config");
+        std::fs::create_dir_all(root.join("lint")).unwrap();
+        std::fs::write(root.join(FILENAME), "*.local.yml\nlint/\n").unwrap();
+        std::fs::write(root.join("lint").join(FILENAME), "!eslint.json\n").unwrap();
+
+        let destinations = [
+            root.join("ci.yml"),
+            root.join("ci.local.yml"),
+            root.join("lint/eslint.json"),
+            root.join("lint/rustfmt.toml"),
+        ];
+        let ignore = GuardyIgnore::load(&root, destinations.iter().map(PathBuf::as_path)).unwrap();
+
+        assert!(!ignore.is_ignored(&destinations[0]));
+        assert!(ignore.is_ignored(&destinations[1]));
+        assert!(!ignore.is_ignored(&destinations[2]));
+        assert!(ignore.is_ignored(&destinations[3]));
+    }
+}
@@ -0,0 +1,116 @@
+//! File modes and symlinks of synced files
+//!
+//! Files are copied with their permissions, and a file whose executable bit differs
+//! from upstream counts as changed. Symlinks are followed by default: a link to a
+//! file syncs as a copy of it and links to directories are skipped. With
+//! `preserve_symlinks: true` relative links that resolve inside the source are
+//! recreated as links instead; any other link is still followed, so an upstream
+//! repository can't plant links to arbitrary paths of the machine syncing it.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Component, Path};
+
+/// Whether `file` (relative to the `source` root) is a link to recreate as such
+pub fn preserved(source: &Path, file: &Path, enabled: bool) -> bool {
+    let path = source.join(file);
+    if !enabled || !cfg!(unix) || !is_symlink(&path) {
+        return false;
+    }
+    let Ok(target) = fs::read_link(&path) else {
+        return false;
+    };
+    if target.is_absolute()
+        || target
+            .components()
+            .any(|c| matches!(c, Component::Prefix(_)))
+    {
+        return false;
+    }
+    match (fs::canonicalize(&path), fs::canonicalize(source)) {
+        (Ok(resolved), Ok(root)) => resolved.starts_with(root),
+        _ => false,
+    }
+}
+
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink())
+}
+
+/// Whether anything - file or (possibly dangling) link - is at `path`
+pub fn exists(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok()
+}
+
+/// The target of a preserved `link`, otherwise the file's content
+pub fn read(path: &Path, link: bool) -> Result<Vec<u8>> {
+    if link {
+        let target = fs::read_link(path)
+            .with_context(|| format!("Failed to read link {}", path.display()))?;
+        return Ok(target.to_string_lossy().into_owned().into_bytes());
+    }
+    fs::read(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Whether the local copy matches upstream: the same link target for a preserved
+/// `link`, otherwise the same content and executable bit
+pub fn matches(local: &Path, upstream: &Path, link: bool) -> Result<bool> {
+    if link {
+        return Ok(is_symlink(local) && fs::read_link(local)? == fs::read_link(upstream)?);
+    }
+    if is_symlink(local) && !local.is_file() {
+        return Ok(false);
+    }
+    Ok(executable(local) == executable(upstream) && fs::read(local)? == fs::read(upstream)?)
+}
+
+#[cfg(unix)]
+pub fn executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|meta| meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub fn executable(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    #[test]
+    fn test_only_relative_links_inside_the_source_are_preserved() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        fs::create_dir_all(source.join("scripts")).unwrap();
+        fs::write(source.join("scripts/lint.sh"), "#!/bin/sh\n").unwrap();
+        fs::write(dir.path().join("outside"), "secret\n").unwrap();
+        symlink("scripts/lint.sh", source.join("lint")).unwrap();
+        symlink("scripts", source.join("bin")).unwrap();
+        symlink("../outside", source.join("escape")).unwrap();
+        symlink(source.join("scripts/lint.sh"), source.join("absolute")).unwrap();
+
+        assert!(preserved(&source, Path::new("lint"), true));
+        assert!(preserved(&source, Path::new("bin"), true));
+        assert!(!preserved(&source, Path::new("lint"), false));
+        assert!(!preserved(&source, Path::new("escape"), true));
+        assert!(!preserved(&source, Path::new("absolute"), true));
+        assert!(!preserved(&source, Path::new("scripts/lint.sh"), true));
+        assert_eq!(read(&source.join("bin"), true).unwrap(), b"scripts");
+    }
+
+    #[test]
+    fn test_executable_bit_counts_as_a_difference() {
+        let dir = tempfile::tempdir().unwrap();
+        let (upstream, local) = (dir.path().join("upstream"), dir.path().join("local"));
+        fs::write(&upstream, "#!/bin/sh\n").unwrap();
+        fs::write(&local, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&upstream, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(!matches(&local, &upstream, false).unwrap());
+        fs::set_permissions(&local, fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(matches(&local, &upstream, false).unwrap());
+    }
+}
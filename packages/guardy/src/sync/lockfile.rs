@@ -0,0 +1,180 @@
+//! `guardy-sync.lock`: the resolved state of every sync repository
+//!
+//! Records the commit each repository's `version` resolved to, the git blob id of
+//! every synced file and when it was resolved. Committed next to `guardy.yaml`, it
+//! makes `guardy sync` reproducible: updates follow the locked commits,
+//! `guardy sync update --locked` fails instead of deviating from them, and
+//! `guardy sync upgrade` resolves the versions again.
+
+use anyhow::{Context, Result, anyhow};
+use git2::{ObjectType, Oid};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::SyncRepo;
+use crate::shared::atomic_io;
+
+pub const LOCKFILE: &str = "guardy-sync.lock";
+
+const HEADER: &str = "# Generated by guardy - the resolved state of the sync repositories.\n\
+                      # Commit this file; `guardy sync upgrade` updates it.\n\n";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SyncLock {
+    pub version: u32,
+    #[serde(default, rename = "repo")]
+    pub repos: Vec<LockedRepo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockedRepo {
+    pub name: String,
+    pub repo: String,
+    /// The configured `version` this entry was resolved from
+    #[serde(default)]
+    pub version: String,
+    /// Commit `version` resolved to; absent for local directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// Commit time, in seconds since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committed_at: Option<u64>,
+    /// When the entry was resolved, in seconds since the epoch
+    pub resolved_at: u64,
+    /// Git blob id of every synced file, keyed by its path in the source
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl Default for SyncLock {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            repos: Vec::new(),
+        }
+    }
+}
+
+impl SyncLock {
+    /// The lock at `path`, empty when there is none yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lock: Self =
+            toml::from_str(&content).with_context(|| format!("Invalid {}", path.display()))?;
+        if lock.version != 1 {
+            return Err(anyhow!(
+                "{} has version {}, this guardy understands version 1",
+                path.display(),
+                lock.version
+            ));
+        }
+        Ok(lock)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        atomic_io::write_atomic(path, format!("{HEADER}{}", toml::to_string(self)?))
+    }
+
+    /// The entry for `repo`, unless its `repo` or `version` changed since it was locked
+    pub fn get(&self, repo: &SyncRepo) -> Option<&LockedRepo> {
+        self.repos.iter().find(|locked| {
+            locked.name == repo.name && locked.repo == repo.repo && locked.version == repo.version
+        })
+    }
+
+    /// Add or replace the entry of `locked.name`
+    pub fn insert(&mut self, locked: LockedRepo) {
+        match self
+            .repos
+            .iter_mut()
+            .find(|entry| entry.name == locked.name)
+        {
+            Some(entry) => *entry = locked,
+            None => {
+                self.repos.push(locked);
+                self.repos.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+        }
+    }
+
+    /// Drop the entries of repositories no longer configured
+    pub fn retain(&mut self, repos: &[SyncRepo]) {
+        self.repos
+            .retain(|locked| repos.iter().any(|repo| repo.name == locked.name));
+    }
+}
+
+/// Git blob id of each of `files` (relative to `root`), keyed by their path
+///
+/// Files for which `is_link` holds are hashed like git hashes symlinks: by their
+/// target rather than the content it points to.
+pub fn hash_files(
+    root: &Path,
+    files: &[PathBuf],
+    is_link: impl Fn(&Path) -> bool,
+) -> Result<BTreeMap<String, String>> {
+    files
+        .iter()
+        .map(|file| {
+            let path = root.join(file);
+            let id = if is_link(file) {
+                Oid::hash_object(ObjectType::Blob, &super::links::read(&path, true)?)?
+            } else {
+                Oid::hash_file(ObjectType::Blob, &path)
+                    .with_context(|| format!("Failed to hash {}", path.display()))?
+            };
+            Ok((file.to_string_lossy().replace('\\', "/"), id.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(version: &str) -> SyncRepo {
+        serde_json::from_value(serde_json::json!({
+            "name": "shared",
+            "repo": "https://github.com/org/shared",
+            "version": version,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_entries_go_stale_when_the_version_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("ci.yml"), "cache: true\n").unwrap();
+
+        let mut lock = SyncLock::default();
+        lock.insert(LockedRepo {
+            name: "shared".to_string(),
+            repo: "https://github.com/org/shared".to_string(),
+            version: "main".to_string(),
+            commit: Some("a".repeat(40)),
+            committed_at: Some(1_700_000_000),
+            resolved_at: 1_700_000_100,
+            files: hash_files(dir.path(), &[PathBuf::from("ci.yml")], |_| false).unwrap(),
+        });
+
+        // `git hash-object` of "cache: true\n"
+        assert_eq!(
+            lock.repos[0].files["ci.yml"],
+            "c84757e683a149339d31a648e2e2c0ecc6df6bb4"
+        );
+        assert!(lock.get(&repo("main")).is_some());
+        assert!(lock.get(&repo("v2")).is_none());
+
+        let path = dir.path().join(LOCKFILE);
+        lock.save(&path).unwrap();
+        assert_eq!(SyncLock::load(&path).unwrap(), lock);
+
+        lock.retain(&[]);
+        assert!(lock.repos.is_empty());
+    }
+}
@@ -1,7 +1,9 @@
-use anyhow::{Result, anyhow};
-use dialoguer::{Select, theme::ColorfulTheme};
+use anyhow::{Context, Result, anyhow};
+use dialoguer::{Confirm, Select, theme::ColorfulTheme};
 use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
 use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use syntect::easy::HighlightLines;
@@ -9,10 +11,22 @@ use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use syntect::util::as_24_bit_terminal_escaped;
 
-use super::{SyncConfig, SyncRepo, SyncStatus};
+use super::base::{ConflictStrategy, MergeOutcome, SyncBase};
+use super::checked::SyncChecked;
+use super::guardyignore::GuardyIgnore;
+use super::links;
+use super::lockfile::{self, LOCKFILE, LockedRepo, SyncLock};
+use super::manifest::SyncManifest;
+use super::oci;
+use super::post_sync;
+use super::verify;
+use super::{FileState, FileSyncState, RepoState, SyncConfig, SyncDrift, SyncRepo, SyncStatus};
 use crate::cli::output;
 use crate::config::GuardyConfig;
+use crate::events::{self, Event};
 use crate::git::remote::RemoteOperations;
+use crate::shared::atomic_io::{self, LockFile};
+use crate::shared::paths;
 
 pub struct SyncManager {
     pub config: SyncConfig,
@@ -23,12 +37,36 @@ pub struct SyncManager {
     theme_set: ThemeSet,
 }
 
+/// How `update_all_repos` treats `guardy-sync.lock`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockMode {
+    /// Sync the locked commits, locking repositories without a current entry - once
+    /// `guardy-sync.lock` exists
+    Follow,
+    /// Sync exactly the locked commits and contents, failing when an entry is
+    /// missing, stale or doesn't match
+    Locked,
+    /// Resolve the configured versions again - of every repository, or the named one
+    Upgrade(Option<String>),
+    /// Neither read nor write the lock (bootstrapping)
+    Unlocked,
+}
+
+/// Where and how `push_changes` offers local edits upstream
+pub struct PushRequest {
+    pub branch: String,
+    pub message: String,
+    pub dry_run: bool,
+}
+
 #[derive(Debug, Clone)]
 enum FileAction {
     Update,
     Skip,
     UpdateAll,
     SkipAll,
+    /// Three-way merge the local edits with upstream
+    Merge,
     Quit,
 }
 
@@ -40,7 +78,7 @@ impl SyncManager {
         // Create .gitignore in .guardy directory to ignore all contents
         let guardy_gitignore = PathBuf::from(".guardy/.gitignore");
         if !guardy_gitignore.exists() {
-            std::fs::write(&guardy_gitignore, "*\n")?;
+            atomic_io::write_atomic(&guardy_gitignore, "*\n")?;
         }
 
         let remote_ops = RemoteOperations::new(cache_dir.clone());
@@ -63,9 +101,17 @@ impl SyncManager {
             dest_path: ".".to_string(),
             include: vec!["*".to_string()],
             exclude: vec![".git".to_string()],
+            map: Vec::new(),
+            auth: None,
+            on_conflict: Default::default(),
+            prune: false,
+            preserve_symlinks: false,
+            post_sync: Vec::new(),
+            verify: None,
         };
         Self::with_config(SyncConfig {
             repos: vec![sync_repo],
+            ..Default::default()
         })
     }
 
@@ -81,221 +127,785 @@ impl SyncManager {
         Ok(sync_config)
     }
 
-    /// Get files matching patterns using ignore crate
+    /// Get files matching patterns using ignore crate, leaving out those the
+    /// destination opts out of with `.guardyignore`
     fn get_files(&self, source: &Path, repo: &SyncRepo) -> Result<Vec<PathBuf>> {
         let mut builder = WalkBuilder::new(source);
 
         // Disable automatic ignore file discovery - only use our custom patterns
         builder.standard_filters(false);
 
-        // Create syncignore file in .guardy/ directory for patterns
-        let syncignore_file = if !repo.exclude.is_empty() {
-            let ignore_file = self.cache_dir.join(".syncignore");
-            fs::write(&ignore_file, repo.exclude.join("\n"))?;
-            // Copy the syncignore file to the source directory temporarily
-            let source_ignore = source.join(".syncignore");
-            fs::copy(&ignore_file, &source_ignore)?;
-            builder.add_custom_ignore_filename(".syncignore");
-            Some((ignore_file, source_ignore))
-        } else {
-            None
+        // Excludes are gitignore-style globs rooted at the source; matching them in
+        // memory keeps the source untouched, which matters for local directories
+        let mut excludes = OverrideBuilder::new(source);
+        for pattern in &repo.exclude {
+            excludes
+                .add(&format!("!{pattern}"))
+                .with_context(|| format!("Invalid exclude pattern '{pattern}'"))?;
+        }
+        builder.overrides(excludes.build()?);
+
+        // The cache is a clone - its .git directory is never a sync source, and
+        // copying (or pruning) it would clobber the local repository
+        builder.filter_entry(|entry| entry.file_name() != ".git");
+
+        // A signed manifest describes the synced files, it isn't one of them
+        let is_metadata = |file: &Path| {
+            repo.verify
+                .as_ref()
+                .is_some_and(|verify| verify.is_metadata(file))
         };
 
-        let result = builder
+        // Links are not walked into: a link to a directory only counts when preserved
+        let mut result: Vec<PathBuf> = builder
             .build()
             .filter_map(|entry| entry.ok())
-            .filter(|entry| entry.path().is_file())
             .filter_map(|entry| {
-                entry
-                    .path()
-                    .strip_prefix(source)
-                    .ok()
-                    .map(|p| p.to_path_buf())
+                let file = entry.path().strip_prefix(source).ok()?.to_path_buf();
+                let syncable = entry.path().is_file()
+                    || links::preserved(source, &file, repo.preserve_symlinks);
+                (syncable && !is_metadata(&file)).then_some(file)
             })
-            .filter(|path| path.file_name() != Some(".syncignore".as_ref())) // Filter out temp file
             .collect();
 
-        // Cleanup syncignore files
-        if let Some((ignore_file, source_ignore)) = syncignore_file {
-            let _ = fs::remove_file(ignore_file);
-            let _ = fs::remove_file(source_ignore);
-        }
+        let destinations: Vec<PathBuf> = result.iter().map(|file| repo.destination(file)).collect();
+        let ignore = GuardyIgnore::load(
+            Path::new(&repo.dest_path),
+            destinations.iter().map(PathBuf::as_path),
+        )?;
+        result.retain(|file| !ignore.is_ignored(&repo.destination(file)));
 
         Ok(result)
     }
 
-    /// Check which files differ between source and destination
-    fn files_differ(&self, files: &[PathBuf], src: &Path, dst: &Path) -> Vec<PathBuf> {
+    /// Files `repo` synced before that are gone upstream but still present locally,
+    /// unless `.guardyignore` opted them out since
+    fn orphans(
+        &self,
+        manifest: &SyncManifest,
+        repo: &SyncRepo,
+        upstream: &BTreeSet<PathBuf>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut orphans = manifest.orphans(&repo.name, upstream);
+        let ignore = GuardyIgnore::load(
+            Path::new(&repo.dest_path),
+            orphans.iter().map(PathBuf::as_path),
+        )?;
+        orphans.retain(|orphan| !ignore.is_ignored(orphan));
+        Ok(orphans)
+    }
+
+    /// Check which files differ between source and destination - in content,
+    /// executable bit or, for preserved links, target
+    fn files_differ(&self, repo: &SyncRepo, files: &[PathBuf], src: &Path) -> Vec<PathBuf> {
         let mut changed = Vec::new();
 
         for f in files {
             let src_file = src.join(f);
-            let dst_file = dst.join(f);
+            let dst_file = repo.destination(f);
 
             tracing::trace!("Checking file: {:?}", f);
             tracing::trace!("  Source: {:?}", src_file);
             tracing::trace!("  Dest: {:?}", dst_file);
 
-            if !dst_file.exists() {
+            if !links::exists(&dst_file) {
                 tracing::debug!("File {:?} doesn't exist in destination", f);
                 changed.push(f.clone());
                 continue;
             }
 
-            let src_meta = match fs::metadata(&src_file) {
-                Ok(m) => m,
-                Err(e) => {
-                    tracing::warn!("Failed to get metadata for source {:?}: {}", src_file, e);
-                    continue;
+            let link = links::preserved(src, f, repo.preserve_symlinks);
+            match links::matches(&dst_file, &src_file, link) {
+                // Same size is no proof: `a: 1` edited to `a: 2` must still be synced
+                Ok(false) => {
+                    tracing::debug!("File {:?} differs", f);
+                    changed.push(f.clone());
                 }
-            };
+                Ok(true) => tracing::trace!("File {:?} unchanged", f),
+                Err(e) => tracing::warn!("Failed to compare {:?}: {}", f, e),
+            }
+        }
 
-            let dst_meta = match fs::metadata(&dst_file) {
-                Ok(m) => m,
-                Err(e) => {
-                    tracing::warn!("Failed to get metadata for dest {:?}: {}", dst_file, e);
-                    continue;
-                }
-            };
+        changed
+    }
+
+    /// Directory to sync from: a local source as is, or the cached clone of a git
+    /// repository (or unpacked OCI artifact) after updating it from the remote
+    ///
+    /// `pin` is a commit or digest (from `guardy-sync.lock`) to check out instead of
+    /// `version`.
+    fn update_cache(&self, repo: &SyncRepo, pin: Option<&str>) -> Result<PathBuf> {
+        if let Some(path) = repo.local_source() {
+            if !path.is_dir() {
+                return Err(anyhow!(
+                    "Sync source '{}' is not a directory ({})",
+                    repo.name,
+                    path.display()
+                ));
+            }
+            return Ok(path);
+        }
+        if let Some(reference) = repo.oci_reference() {
+            let cache = self.cache_dir.join(reference.name());
+            oci::pull(&reference, &repo.version, pin, repo.auth.as_ref(), &cache)?;
+            self.record_checked(repo)?;
+            return Ok(cache);
+        }
+        if repo.version.is_empty() {
+            return Err(anyhow!(
+                "Sync repository '{}' needs a version (tag, branch or commit)",
+                repo.name
+            ));
+        }
 
-            let src_len = src_meta.len();
-            let dst_len = dst_meta.len();
+        let repo_name = self.extract_repo_name(&repo.repo);
+        let repo_path = self.cache_dir.join(&repo_name);
 
-            if src_len != dst_len {
-                tracing::debug!(
-                    "File {:?} size differs: src={}, dst={}",
-                    f,
-                    src_len,
-                    dst_len
-                );
-                changed.push(f.clone());
+        let sparse = self.sparse_paths(repo);
+        self.with_remote(repo, |remote_ops| {
+            if !repo_path.exists() {
+                // Clone if doesn't exist - pass the version we actually want
+                remote_ops.clone_repository(&repo.repo, &repo_name, &repo.version, &sparse)?;
+                match pin {
+                    Some(commit) => remote_ops.fetch_and_reset(&repo_name, commit),
+                    None => Ok(()),
+                }
             } else {
-                tracing::trace!("File {:?} unchanged (size={})", f, src_len);
+                // Only fetch and reset if repo already exists
+                remote_ops.sparse_checkout(&repo_name, &sparse)?;
+                remote_ops.fetch_and_reset(&repo_name, pin.unwrap_or(&repo.version))
             }
+        })?;
+        self.record_checked(repo)?;
+
+        Ok(repo_path)
+    }
+
+    /// `.guardy/sync-checked.json`, next to the cache
+    fn checked_path(&self) -> PathBuf {
+        self.cache_dir.with_file_name("sync-checked.json")
+    }
+
+    fn record_checked(&self, repo: &SyncRepo) -> Result<()> {
+        let mut checked = SyncChecked::load(&self.checked_path())?;
+        checked.record(&repo.name)?;
+        checked.save(&self.checked_path())
+    }
+
+    /// When `repo`'s source was last fetched (seconds since the epoch), if ever
+    pub fn last_checked(&self, repo: &SyncRepo) -> Result<Option<u64>> {
+        Ok(SyncChecked::load(&self.checked_path())?.get(&repo.name))
+    }
+
+    /// Fetch every repository's source into the cache, at its locked version when
+    /// there is one, so `check_sync_status` and `file_states` see upstream as it is now
+    pub fn refresh(&self) -> Result<()> {
+        let _lock = self.lock()?;
+        let lock = SyncLock::load(&self.lock_path())?;
+        for repo in &self.config.repos {
+            let pin = Self::locked_commit(&lock, repo);
+            self.update_cache(repo, pin.as_deref())?;
         }
+        Ok(())
+    }
 
-        changed
+    /// Directories to check out of `repo`'s clone: the `source_path` of every
+    /// repository sharing the clone, or none (the whole tree) when one syncs its root
+    fn sparse_paths(&self, repo: &SyncRepo) -> Vec<String> {
+        let clone = self.extract_repo_name(&repo.repo);
+        let mut paths = BTreeSet::new();
+        for other in &self.config.repos {
+            if self.extract_repo_name(&other.repo) != clone {
+                continue;
+            }
+            let path = other.source_path.trim_start_matches("./").trim_matches('/');
+            if path.is_empty() || path == "." {
+                return Vec::new();
+            }
+            paths.insert(path.to_string());
+        }
+        paths.into_iter().collect()
     }
 
-    /// Update cache from remote repository using git pull
-    fn update_cache(&self, repo: &SyncRepo) -> Result<PathBuf> {
-        let repo_name = self.extract_repo_name(&repo.repo);
-        let repo_path = self.cache_dir.join(&repo_name);
+    /// Run `f` with git operations carrying the repository's credentials
+    fn with_remote<T>(
+        &self,
+        repo: &SyncRepo,
+        f: impl FnOnce(&RemoteOperations) -> Result<T>,
+    ) -> Result<T> {
+        match &repo.auth {
+            // Credentials are resolved on every use, so minted tokens are always fresh
+            Some(auth) => {
+                f(&RemoteOperations::new(self.cache_dir.clone())
+                    .with_env(auth.git_env(&repo.repo)?))
+            }
+            None => f(&self.remote_ops),
+        }
+    }
 
-        if !repo_path.exists() {
-            // Clone if doesn't exist - pass the version we actually want
-            self.remote_ops
-                .clone_repository(&repo.repo, &repo_name, &repo.version)?;
-        } else {
-            // Only fetch and reset if repo already exists
-            self.remote_ops.fetch_and_reset(&repo_name, &repo.version)?;
+    /// `guardy-sync.lock`, next to the configuration
+    fn lock_path(&self) -> PathBuf {
+        PathBuf::from(LOCKFILE)
+    }
+
+    /// Commit `repo` is locked to, if any
+    fn locked_commit(lock: &SyncLock, repo: &SyncRepo) -> Option<String> {
+        lock.get(repo).and_then(|locked| locked.commit.clone())
+    }
+
+    /// Lock entry for `repo` as currently checked out in `repo_path`
+    fn resolve_lock_entry(
+        &self,
+        repo: &SyncRepo,
+        src: &Path,
+        files: &[PathBuf],
+    ) -> Result<LockedRepo> {
+        let (commit, committed_at) = match (repo.local_source(), repo.oci_reference()) {
+            (Some(_), _) => (None, None),
+            // Artifacts are locked by digest, which `update_cache` left next to them
+            (None, Some(reference)) => {
+                let cache = self.cache_dir.join(reference.name());
+                (oci::cached_digest(&cache), None)
+            }
+            (None, None) => {
+                let repo_name = self.extract_repo_name(&repo.repo);
+                let (sha, time) = self.remote_ops.head_commit(&repo_name)?;
+                (Some(sha), Some(time))
+            }
+        };
+        Ok(LockedRepo {
+            name: repo.name.clone(),
+            repo: repo.repo.clone(),
+            version: repo.version.clone(),
+            commit,
+            committed_at,
+            resolved_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs(),
+            files: lockfile::hash_files(src, files, |file| {
+                links::preserved(src, file, repo.preserve_symlinks)
+            })?,
+        })
+    }
+
+    /// Synced files edited locally: present at their destination, but other than the
+    /// pinned upstream version in `src`
+    fn files_edited(&self, repo: &SyncRepo, files: &[PathBuf], src: &Path) -> Result<Vec<PathBuf>> {
+        let mut edited = Vec::new();
+        for file in files {
+            let local = repo.destination(file);
+            let link = links::preserved(src, file, repo.preserve_symlinks);
+            if links::exists(&local) && !links::matches(&local, &src.join(file), link)? {
+                edited.push(file.clone());
+            }
         }
+        Ok(edited)
+    }
 
-        Ok(repo_path)
+    /// Offer local edits of `repo`'s files back upstream, returning the edited files
+    /// (as local paths)
+    ///
+    /// The edits are committed on top of the pinned version in the cached clone and
+    /// pushed to `push.branch`, replacing whatever an earlier push left there; the
+    /// clone is reset afterwards. With `push.dry_run` the diffs are only shown.
+    pub fn push_changes(&self, repo: &SyncRepo, push: &PushRequest) -> Result<Vec<PathBuf>> {
+        let _lock = self.lock()?;
+        let pin = Self::locked_commit(&SyncLock::load(&self.lock_path())?, repo);
+        let repo_path = self.update_cache(repo, pin.as_deref())?;
+        let src = repo_path.join(&repo.source_path);
+        let files = self.get_files(&src, repo)?;
+        let edited = self.files_edited(repo, &files, &src)?;
+        let local: Vec<PathBuf> = edited.iter().map(|file| repo.destination(file)).collect();
+        if edited.is_empty() || push.dry_run {
+            for (file, local) in edited.iter().zip(&local) {
+                println!();
+                output::styled!("  {}", (paths::display(local), "property"));
+                // Upstream first, so additions are the local edits
+                let link = links::preserved(&src, file, repo.preserve_symlinks);
+                self.show_diff(&src.join(file), local, link)?;
+            }
+            return Ok(local);
+        }
+
+        for (file, local) in edited.iter().zip(&local) {
+            if links::is_symlink(local) {
+                atomic_io::symlink_atomic(&fs::read_link(local)?, &src.join(file))?;
+            } else {
+                atomic_io::copy_atomic(local, &src.join(file))?;
+            }
+        }
+        let in_clone: Vec<PathBuf> = edited
+            .iter()
+            .map(|file| Path::new(&repo.source_path).join(file))
+            .collect();
+        let repo_name = self.extract_repo_name(&repo.repo);
+        self.with_remote(repo, |remote_ops| {
+            remote_ops.push_commit(&repo_name, &in_clone, &push.branch, &push.message)
+        })?;
+        Ok(local)
+    }
+
+    /// Open a pull request for a branch pushed by `push_changes`, returning its URL
+    ///
+    /// Uses the GitHub CLI (`gh`) from the cached clone, targeting the pinned branch
+    /// or, for tags and commits, the repository's default branch.
+    pub fn open_pull_request(
+        &self,
+        repo: &SyncRepo,
+        push: &PushRequest,
+        files: &[PathBuf],
+    ) -> Result<String> {
+        let repo_name = self.extract_repo_name(&repo.repo);
+        let title = push.message.lines().next().unwrap_or_default();
+        let mut body =
+            String::from("Local edits to synced files, pushed with `guardy sync push`:\n\n");
+        for file in files {
+            body.push_str(&format!("- `{}`\n", paths::display(file)));
+        }
+        self.with_remote(repo, |remote_ops| {
+            let base = (!remote_ops.is_immutable_version(&repo.version)).then_some(&*repo.version);
+            remote_ops.open_pull_request(&repo_name, &push.branch, base, title, &body)
+        })
     }
 
-    /// Copy a single file from source to destination
-    fn copy_file(&self, file: &Path, src: &Path, dst: &Path) -> Result<PathBuf> {
-        let src_file = src.join(file);
-        let dst_file = dst.join(file);
-        if let Some(parent) = dst_file.parent() {
-            fs::create_dir_all(parent)?;
+    /// Copy a single file from source to destination (atomically, so an interrupted
+    /// sync never leaves a truncated protected file), keeping its permissions or
+    /// recreating it as a link when preserved
+    fn copy_file(&self, repo: &SyncRepo, file: &Path, src: &Path) -> Result<PathBuf> {
+        let dst_file = repo.destination(file);
+        if links::preserved(src, file, repo.preserve_symlinks) {
+            atomic_io::symlink_atomic(&fs::read_link(src.join(file))?, &dst_file)?;
+            return Ok(dst_file);
         }
-        fs::copy(&src_file, &dst_file)?;
+        atomic_io::copy_atomic(&src.join(file), &dst_file)?;
+        self.base().record(&repo.name, &dst_file, &src.join(file))?;
         Ok(dst_file)
     }
 
+    /// Last-synced copies in `.guardy/sync-base`, next to the cache
+    fn base(&self) -> SyncBase {
+        SyncBase::new(self.cache_dir.with_file_name("sync-base"))
+    }
+
+    /// Apply `repo.on_conflict` to a locally edited file: `Some(true)` when it now has
+    /// upstream's changes, `Some(false)` when it was kept as is, and `None` when the
+    /// user should decide (merge conflicts in interactive syncs)
+    fn resolve_conflict(
+        &self,
+        repo: &SyncRepo,
+        file: &Path,
+        src: &Path,
+        interactive: bool,
+    ) -> Result<Option<bool>> {
+        let dst_file = repo.destination(file);
+        let upstream = src.join(file);
+        let kept = |reason: &str| {
+            output::styled!(
+                "{} Kept local changes to {} {}",
+                ("⚠️", "warning_symbol"),
+                (paths::display(&dst_file), "property"),
+                (format!("({reason})"), "muted")
+            );
+            Ok(Some(false))
+        };
+
+        match repo.on_conflict {
+            ConflictStrategy::Theirs => {
+                self.copy_file(repo, file, src)?;
+                output::styled!(
+                    "{} Overwrote local changes to {} {}",
+                    ("⚠️", "warning_symbol"),
+                    (paths::display(&dst_file), "property"),
+                    ("(on_conflict: theirs)", "muted")
+                );
+                Ok(Some(true))
+            }
+            ConflictStrategy::Ours | ConflictStrategy::Prompt => kept("on_conflict: ours"),
+            ConflictStrategy::Merge => match self.base().merge(&repo.name, &dst_file, &upstream)? {
+                Some(MergeOutcome::Clean(merged)) => {
+                    atomic_io::write_atomic(&dst_file, merged)?;
+                    self.base().record(&repo.name, &dst_file, &upstream)?;
+                    output::styled!(
+                        "{} Merged upstream changes into {}",
+                        ("🔀", "success_symbol"),
+                        (paths::display(&dst_file), "property")
+                    );
+                    Ok(Some(true))
+                }
+                _ if interactive => Ok(None),
+                Some(MergeOutcome::Conflicted(_)) => {
+                    kept("conflicts with upstream - run guardy sync to resolve")
+                }
+                None => kept("never synced, so there is no base to merge with"),
+            },
+        }
+    }
+
+    /// Hold `.guardy/sync.lock` so concurrent syncs can't race on the cache
+    fn lock(&self) -> Result<LockFile> {
+        LockFile::acquire(&self.cache_dir.with_file_name("sync.lock"))
+    }
+
+    /// `.guardy/sync-manifest.json`, next to the cache
+    fn manifest_path(&self) -> PathBuf {
+        self.cache_dir.with_file_name("sync-manifest.json")
+    }
+
     /// Check sync status of all repositories
     pub fn check_sync_status(&self) -> Result<SyncStatus> {
         if self.config.repos.is_empty() {
             return Ok(SyncStatus::NotConfigured);
         }
 
+        let manifest = SyncManifest::load(&self.manifest_path())?;
         let mut changed_files = Vec::new();
+        let mut orphaned_files = Vec::new();
         for repo in &self.config.repos {
-            let repo_path = self.cache_dir.join(self.extract_repo_name(&repo.repo));
+            let repo_path = repo
+                .local_source()
+                .unwrap_or_else(|| self.cache_dir.join(self.extract_repo_name(&repo.repo)));
             if repo_path.exists() {
                 let src = repo_path.join(&repo.source_path);
-                let dst = Path::new(&repo.dest_path);
                 let files = self.get_files(&src, repo)?;
-                let different = self.files_differ(&files, &src, dst);
-                // Convert to absolute paths for display
-                changed_files.extend(different.iter().map(|f| dst.join(f)));
+                let different = self.files_differ(repo, &files, &src);
+                // Convert to destination paths for display
+                changed_files.extend(different.iter().map(|f| repo.destination(f)));
+                let upstream = files.iter().map(|f| repo.destination(f)).collect();
+                orphaned_files.extend(self.orphans(&manifest, repo, &upstream)?);
             }
         }
 
-        if changed_files.is_empty() {
+        if changed_files.is_empty() && orphaned_files.is_empty() {
             Ok(SyncStatus::InSync)
         } else {
-            Ok(SyncStatus::OutOfSync { changed_files })
+            Ok(SyncStatus::OutOfSync {
+                changed_files,
+                orphaned_files,
+            })
+        }
+    }
+
+    /// Compare every repository's destination files with its pinned version byte for
+    /// byte, refreshing the cache first so a fresh CI checkout works too
+    pub fn check_drift(&self) -> Result<Vec<SyncDrift>> {
+        let _lock = self.lock()?;
+        let manifest = SyncManifest::load(&self.manifest_path())?;
+        let lock = SyncLock::load(&self.lock_path())?;
+        let mut drift = Vec::new();
+        for repo in &self.config.repos {
+            let pin = Self::locked_commit(&lock, repo);
+            let repo_path = self.update_cache(repo, pin.as_deref())?;
+            let src = repo_path.join(&repo.source_path);
+            let files = self.get_files(&src, repo)?;
+            let display = |file: &PathBuf| paths::display(&repo.destination(file));
+            let upstream = files.iter().map(|file| repo.destination(file)).collect();
+
+            drift.push(SyncDrift {
+                name: repo.name.clone(),
+                repo: repo.repo.clone(),
+                version: repo.version.clone(),
+                modified: self
+                    .files_edited(repo, &files, &src)?
+                    .iter()
+                    .map(display)
+                    .collect(),
+                missing: files
+                    .iter()
+                    .filter(|file| !links::exists(&repo.destination(file)))
+                    .map(display)
+                    .collect(),
+                orphaned: self
+                    .orphans(&manifest, repo, &upstream)?
+                    .iter()
+                    .map(|file| paths::display(file))
+                    .collect(),
+            });
         }
+        Ok(drift)
+    }
+
+    /// Per-file sync state of every repository, for `--format json`
+    ///
+    /// With `refresh` the caches are updated first (like `sync diff`) and each file
+    /// that isn't in sync carries a unified diff to upstream; otherwise only what is
+    /// cached already is compared (like `sync status`).
+    pub fn file_states(&self, refresh: bool) -> Result<Vec<RepoState>> {
+        let _lock = if refresh { Some(self.lock()?) } else { None };
+        let manifest = SyncManifest::load(&self.manifest_path())?;
+        let lock = SyncLock::load(&self.lock_path())?;
+        let checked = SyncChecked::load(&self.checked_path())?;
+        let base = self.base();
+
+        let mut states = Vec::new();
+        for repo in &self.config.repos {
+            let repo_path = if refresh {
+                let pin = Self::locked_commit(&lock, repo);
+                Some(self.update_cache(repo, pin.as_deref())?)
+            } else {
+                Some(
+                    repo.local_source()
+                        .unwrap_or_else(|| self.cache_dir.join(self.extract_repo_name(&repo.repo))),
+                )
+                .filter(|path| path.exists())
+            };
+            let mut state = RepoState {
+                name: repo.name.clone(),
+                repo: repo.repo.clone(),
+                version: repo.version.clone(),
+                cached: repo_path.is_some(),
+                last_checked: checked.get(&repo.name),
+                files: Vec::new(),
+                orphaned: Vec::new(),
+            };
+
+            if let Some(repo_path) = repo_path {
+                let src = repo_path.join(&repo.source_path);
+                let files = self.get_files(&src, repo)?;
+                for file in &files {
+                    let (local, upstream) = (repo.destination(file), src.join(file));
+                    let link = links::preserved(&src, file, repo.preserve_symlinks);
+                    let file_state = if !links::exists(&local) {
+                        FileSyncState::Missing
+                    } else if links::matches(&local, &upstream, link)? {
+                        FileSyncState::InSync
+                    } else if !link && base.locally_modified(&repo.name, &local)? {
+                        FileSyncState::Modified
+                    } else {
+                        FileSyncState::UpstreamChanged
+                    };
+                    let diff = (refresh && file_state != FileSyncState::InSync)
+                        .then(|| unified_diff(&local, &upstream, link))
+                        .transpose()?;
+                    state.files.push(FileState {
+                        path: paths::display(&local),
+                        state: file_state,
+                        diff,
+                    });
+                }
+                let upstream = files.iter().map(|file| repo.destination(file)).collect();
+                state.orphaned = self
+                    .orphans(&manifest, repo, &upstream)?
+                    .iter()
+                    .map(|file| paths::display(file))
+                    .collect();
+            }
+            states.push(state);
+        }
+        Ok(states)
+    }
+
+    /// Delete or keep local files whose upstream counterpart was removed, returning
+    /// the kept ones
+    ///
+    /// `prune` decides for every file, falling back to `repo.prune`; without either an
+    /// interactive sync asks and a forced one keeps them. Directories left empty by a
+    /// deletion are removed as well, up to `dest_path`.
+    fn handle_orphans(
+        &self,
+        repo: &SyncRepo,
+        orphans: &[PathBuf],
+        interactive: bool,
+        prune: Option<bool>,
+    ) -> Result<Vec<PathBuf>> {
+        let prune = prune.or(repo.prune.then_some(true));
+        let mut kept = Vec::new();
+        for orphan in orphans {
+            let delete = match prune {
+                Some(prune) => prune,
+                None if interactive => Confirm::new()
+                    .with_prompt(format!(
+                        "{} was removed upstream - delete the local copy?",
+                        paths::display(orphan)
+                    ))
+                    .default(false)
+                    .interact()?,
+                None => false,
+            };
+
+            if delete {
+                fs::remove_file(orphan)?;
+                remove_empty_parents(orphan, Path::new(&repo.dest_path));
+                output::styled!(
+                    "{} Deleted {} {}",
+                    ("🗑️", "info_symbol"),
+                    (paths::display(orphan), "property"),
+                    ("(removed upstream)", "muted")
+                );
+            } else {
+                output::styled!(
+                    "{} Kept {} {}",
+                    ("⚠️", "warning_symbol"),
+                    (paths::display(orphan), "property"),
+                    ("(removed upstream - sync with --prune to delete)", "muted")
+                );
+                kept.push(orphan.clone());
+            }
+        }
+        Ok(kept)
     }
 
     /// Main update function that handles both interactive and force modes
-    pub async fn update_all_repos(&mut self, interactive: bool) -> Result<Vec<PathBuf>> {
+    ///
+    /// Files removed upstream are pruned according to `prune` (see `handle_orphans`),
+    /// and `guardy-sync.lock` is followed or updated according to `lock_mode`.
+    pub async fn update_all_repos(
+        &mut self,
+        interactive: bool,
+        prune: Option<bool>,
+        lock_mode: LockMode,
+    ) -> Result<Vec<PathBuf>> {
         let mut all_updated_files = Vec::new();
         let mut all_skipped_files = Vec::new();
         let mut update_all_remaining = false;
         let mut skip_all_remaining = false;
+        let _lock = self.lock()?;
+        let mut manifest = SyncManifest::load(&self.manifest_path())?;
+        let mut lock = SyncLock::load(&self.lock_path())?;
+        let original_lock = lock.clone();
+        // The lock is opt-in: `guardy sync upgrade` creates it
+        let lock_mode = match lock_mode {
+            LockMode::Follow if !self.lock_path().exists() => LockMode::Unlocked,
+            lock_mode => lock_mode,
+        };
 
-        output::styled!("<chart> Analyzing sync status...");
+        let section = output::section!("Syncing protected files");
 
         // First check if there are any changes at all
         let mut has_any_changes = false;
+        // Repositories whose files changed, with the changed files, for `post_sync`
+        let mut post_sync_runs = Vec::new();
 
-        for repo in self.config.repos.clone() {
+        let repos = self.config.repos.clone();
+        for (index, repo) in repos.iter().enumerate() {
             tracing::info!("Processing repository: {}", repo.name);
+            let step = output::step!(index + 1, repos.len(), &repo.name);
+            let (updated_before, skipped_before) =
+                (all_updated_files.len(), all_skipped_files.len());
+
+            let locked = lock.get(repo).cloned();
+            let upgrading = match &lock_mode {
+                LockMode::Upgrade(only) => only.as_ref().is_none_or(|name| *name == repo.name),
+                LockMode::Unlocked => true,
+                LockMode::Follow | LockMode::Locked => false,
+            };
+            if lock_mode == LockMode::Locked && locked.is_none() {
+                return Err(anyhow!(
+                    "{LOCKFILE} has no entry for '{}' at {} - run `guardy sync upgrade {}`",
+                    repo.name,
+                    repo.version,
+                    repo.name
+                ));
+            }
 
             // Update cache from remote
-            let repo_path = self.update_cache(&repo)?;
+            let pin = locked
+                .as_ref()
+                .and_then(|locked| locked.commit.as_deref())
+                .filter(|_| !upgrading);
+            let repo_path = self.update_cache(repo, pin)?;
 
             // Get changed files
             let src = repo_path.join(&repo.source_path);
-            let dst = Path::new(&repo.dest_path);
-            let files = self.get_files(&src, &repo)?;
+            let files = self.get_files(&src, repo)?;
             tracing::debug!("Found {} files in source", files.len());
-            let changed_files = self.files_differ(&files, &src, dst);
+            if let Some(config) = &repo.verify {
+                verify::verify(config, &src, &files)
+                    .with_context(|| format!("Refusing to sync '{}'", repo.name))?;
+                output::styled!(
+                    "  {} {}",
+                    (output::human_count(files.len(), "file"), "property"),
+                    (format!("verified against {}", config.manifest), "muted")
+                );
+            }
+
+            if lock_mode != LockMode::Unlocked {
+                let entry = self.resolve_lock_entry(repo, &src, &files)?;
+                let unchanged = locked.as_ref().is_some_and(|locked| {
+                    locked.commit == entry.commit && locked.files == entry.files
+                });
+                if !unchanged && lock_mode == LockMode::Locked {
+                    return Err(anyhow!(
+                        "The files of '{}' no longer match {LOCKFILE} - run `guardy sync upgrade {}`",
+                        repo.name,
+                        repo.name
+                    ));
+                }
+                if !unchanged {
+                    lock.insert(entry);
+                }
+            }
+            let changed_files = self.files_differ(repo, &files, &src);
             tracing::debug!("Found {} changed files", changed_files.len());
+            let upstream: BTreeSet<PathBuf> = files.iter().map(|f| repo.destination(f)).collect();
+            let orphans = self.orphans(&manifest, repo, &upstream)?;
+            // Files already in sync become the base for later merges - links have no
+            // content to merge
+            let is_link = |file: &Path| links::preserved(&src, file, repo.preserve_symlinks);
+            let base = self.base();
+            for file in files
+                .iter()
+                .filter(|file| !changed_files.contains(file) && !is_link(file))
+            {
+                base.record(&repo.name, &repo.destination(file), &src.join(file))?;
+            }
 
-            if changed_files.is_empty() {
+            if changed_files.is_empty() && orphans.is_empty() {
                 tracing::info!("No changes detected for repository: {}", repo.name);
+                manifest.record(&repo.name, upstream, &[]);
+                step.success();
                 continue;
             }
 
             has_any_changes = true;
 
-            // Show repository info
-            output::styled!(
-                "\n{} Repository: {} ({} files changed)",
-                ("🔗", "info_symbol"),
-                (&repo.name, "property"),
-                (changed_files.len().to_string(), "property")
-            );
+            if !changed_files.is_empty() {
+                output::styled!(
+                    "  {} changed",
+                    (output::human_count(changed_files.len(), "file"), "property")
+                );
+            }
 
             // Process each changed file
             for (i, file) in changed_files.iter().enumerate() {
-                let dst_file = dst.join(file);
+                let dst_file = repo.destination(file);
 
                 // If we're in "update all" or "skip all" mode, handle accordingly
                 if skip_all_remaining {
                     output::styled!(
                         "{} Skipped {}",
                         ("⏭️", "info_symbol"),
-                        (dst_file.display().to_string(), "property")
+                        (paths::display(&dst_file), "property")
                     );
                     all_skipped_files.push(dst_file.clone());
                     continue;
                 }
 
+                // Local edits follow the repository's strategy; `prompt` continues below
+                let link = is_link(file);
+                let modified = !link && base.locally_modified(&repo.name, &dst_file)?;
+                if modified && repo.on_conflict != ConflictStrategy::Prompt {
+                    match self.resolve_conflict(repo, file, &src, interactive)? {
+                        Some(true) => {
+                            all_updated_files.push(dst_file.clone());
+                            continue;
+                        }
+                        Some(false) => {
+                            all_skipped_files.push(dst_file.clone());
+                            continue;
+                        }
+                        None => {}
+                    }
+                }
+
                 if update_all_remaining || !interactive {
                     // In force mode or "update all" mode, just update
-                    self.copy_file(file, &src, dst)?;
+                    self.copy_file(repo, file, &src)?;
                     all_updated_files.push(dst_file.clone());
                     if interactive {
                         output::styled!(
                             "{} Updated {}",
                             ("✅", "success_symbol"),
-                            (dst_file.display().to_string(), "property")
+                            (paths::display(&dst_file), "property")
                         );
                     }
                     continue;
@@ -303,43 +913,43 @@ impl SyncManager {
 
                 // Interactive mode: show diff and ask
                 println!();
-                output::styled!("{}", ("─".repeat(60), "muted"));
                 output::styled!(
-                    "File {}/{}: {}",
+                    "  File {}/{}: {}",
                     ((i + 1).to_string(), "muted"),
                     (changed_files.len().to_string(), "muted"),
-                    (dst_file.display().to_string(), "property")
+                    (paths::display(&dst_file), "property")
                 );
 
                 // Show diff
-                self.show_diff(&dst_file, &src.join(file))?;
+                self.show_diff(&dst_file, &src.join(file), link)?;
 
-                // Ask user what to do
-                match self.prompt_file_action()? {
+                // Ask user what to do - merging needs local edits and a base
+                let mergeable = modified && base.has_base(&repo.name, &dst_file);
+                match self.prompt_file_action(mergeable)? {
                     FileAction::Update => {
-                        self.copy_file(file, &src, dst)?;
+                        self.copy_file(repo, file, &src)?;
                         all_updated_files.push(dst_file.clone());
                         output::styled!(
                             "{} Updated {}",
                             ("✅", "success_symbol"),
-                            (dst_file.display().to_string(), "property")
+                            (paths::display(&dst_file), "property")
                         );
                     }
                     FileAction::Skip => {
                         output::styled!(
                             "{} Skipped {}",
                             ("⏭️", "info_symbol"),
-                            (dst_file.display().to_string(), "property")
+                            (paths::display(&dst_file), "property")
                         );
                         all_skipped_files.push(dst_file.clone());
                     }
                     FileAction::UpdateAll => {
-                        self.copy_file(file, &src, dst)?;
+                        self.copy_file(repo, file, &src)?;
                         all_updated_files.push(dst_file.clone());
                         output::styled!(
                             "{} Updated {}",
                             ("✅", "success_symbol"),
-                            (dst_file.display().to_string(), "property")
+                            (paths::display(&dst_file), "property")
                         );
                         update_all_remaining = true;
                     }
@@ -347,69 +957,145 @@ impl SyncManager {
                         output::styled!(
                             "{} Skipped {}",
                             ("⏭️", "info_symbol"),
-                            (dst_file.display().to_string(), "property")
+                            (paths::display(&dst_file), "property")
                         );
                         all_skipped_files.push(dst_file.clone());
                         skip_all_remaining = true;
                     }
+                    FileAction::Merge => {
+                        let upstream_file = src.join(file);
+                        match base.merge(&repo.name, &dst_file, &upstream_file)? {
+                            Some(MergeOutcome::Clean(merged)) => {
+                                atomic_io::write_atomic(&dst_file, merged)?;
+                                output::styled!(
+                                    "{} Merged upstream changes into {}",
+                                    ("🔀", "success_symbol"),
+                                    (paths::display(&dst_file), "property")
+                                );
+                            }
+                            Some(MergeOutcome::Conflicted(merged)) => {
+                                atomic_io::write_atomic(&dst_file, merged)?;
+                                output::styled!(
+                                    "{} Merged {} with conflicts - resolve the markers",
+                                    ("⚠️", "warning_symbol"),
+                                    (paths::display(&dst_file), "property")
+                                );
+                            }
+                            None => unreachable!("only offered with a base"),
+                        }
+                        base.record(&repo.name, &dst_file, &upstream_file)?;
+                        all_updated_files.push(dst_file.clone());
+                    }
                     FileAction::Quit => {
                         output::styled!("{} Update cancelled by user", ("ℹ️", "info_symbol"));
+                        events::emit(Event::sync_applied(
+                            &repo.name,
+                            &all_updated_files[updated_before..],
+                            &all_skipped_files[skipped_before..],
+                            &[],
+                        ));
+                        manifest.save(&self.manifest_path())?;
+                        if lock != original_lock {
+                            lock.save(&self.lock_path())?;
+                        }
+                        step.skip("cancelled");
+                        section.success();
                         return Ok(all_updated_files);
                     }
                 }
             }
+
+            let kept = self.handle_orphans(repo, &orphans, interactive, prune)?;
+            let pruned: Vec<PathBuf> = orphans
+                .into_iter()
+                .filter(|orphan| !kept.contains(orphan))
+                .collect();
+            events::emit(Event::sync_applied(
+                &repo.name,
+                &all_updated_files[updated_before..],
+                &all_skipped_files[skipped_before..],
+                &pruned,
+            ));
+            let mut changed = all_updated_files[updated_before..].to_vec();
+            changed.extend(pruned);
+            if !repo.post_sync.is_empty() && !changed.is_empty() {
+                post_sync_runs.push((repo, changed));
+            }
+            manifest.record(&repo.name, upstream, &kept);
+            step.success();
+        }
+        manifest.save(&self.manifest_path())?;
+        // A profile only sees some repositories - the others keep their entries
+        if self.config.active_profile.is_none() {
+            lock.retain(&self.config.repos);
+        }
+        if lock_mode != LockMode::Unlocked && lock != original_lock {
+            lock.save(&self.lock_path())?;
         }
 
         // If no changes at all, show message early
         if !has_any_changes {
             if interactive {
-                println!();
-                output::styled!("{}", ("═".repeat(60), "muted"));
                 output::styled!("{} Everything is up to date", ("✅", "success_symbol"));
             }
+            section.success();
             return Ok(all_updated_files);
         }
 
         // Show summary
         if interactive {
-            println!();
-            output::styled!("{}", ("═".repeat(60), "muted"));
-
             if all_updated_files.is_empty() && all_skipped_files.is_empty() {
                 // Nothing was changed and nothing was skipped = truly up to date
                 output::styled!("{} Everything is up to date", ("✅", "success_symbol"));
             } else if all_updated_files.is_empty() && !all_skipped_files.is_empty() {
                 // Nothing updated but files were skipped = files remain out of sync
                 output::styled!(
-                    "{}  {} files remain out of sync (skipped by user)",
+                    "{}  {} out of sync (skipped by user)",
                     ("⚠️", "warning_symbol"),
-                    (all_skipped_files.len().to_string(), "property")
+                    (
+                        output::human_count(all_skipped_files.len(), "file"),
+                        "property"
+                    )
                 );
             } else if !all_updated_files.is_empty() && all_skipped_files.is_empty() {
                 // Files updated and nothing skipped = all changes applied
                 output::styled!(
-                    "{}  {} files updated",
+                    "{}  {} updated",
                     ("✅", "success_symbol"),
-                    (all_updated_files.len().to_string(), "property")
+                    (
+                        output::human_count(all_updated_files.len(), "file"),
+                        "property"
+                    )
                 );
             } else {
                 // Both updated and skipped files
                 output::styled!(
-                    "{}  {} files updated, {} files remain out of sync (skipped)",
+                    "{}  {} updated, {} out of sync (skipped)",
                     ("⚠️", "warning_symbol"),
-                    (all_updated_files.len().to_string(), "property"),
-                    (all_skipped_files.len().to_string(), "property")
+                    (
+                        output::human_count(all_updated_files.len(), "file"),
+                        "property"
+                    ),
+                    (
+                        output::human_count(all_skipped_files.len(), "file"),
+                        "property"
+                    )
                 );
             }
         }
 
+        for (repo, changed) in post_sync_runs {
+            post_sync::run(&repo.name, &repo.post_sync, &changed)?;
+        }
+
+        section.success();
         Ok(all_updated_files)
     }
 
-    /// Show diff between source and destination files
-    fn show_diff(&self, dest_file: &Path, source_file: &Path) -> Result<()> {
-        let dest_content = fs::read_to_string(dest_file).unwrap_or_default();
-        let source_content = fs::read_to_string(source_file)?;
+    /// Show diff between source and destination files - the link targets when `link`
+    fn show_diff(&self, dest_file: &Path, source_file: &Path, link: bool) -> Result<()> {
+        let dest_content = diff_text(dest_file, link).unwrap_or_default();
+        let source_content = diff_text(source_file, link)?;
 
         println!();
         output::styled!("{}", ("─".repeat(60), "muted"));
@@ -478,14 +1164,23 @@ impl SyncManager {
     }
 
     /// Prompt user for action on a file
-    fn prompt_file_action(&self) -> Result<FileAction> {
-        let options = vec![
-            "Yes - Update this file",
-            "No - Skip this file",
-            "Yes to all remaining files",
-            "Skip all remaining files",
-            "Quit - Stop processing",
+    fn prompt_file_action(&self, mergeable: bool) -> Result<FileAction> {
+        let mut options = vec![
+            ("Yes - Update this file", FileAction::Update),
+            ("No - Skip this file", FileAction::Skip),
         ];
+        if mergeable {
+            options.push((
+                "Merge - Keep local changes and add upstream's",
+                FileAction::Merge,
+            ));
+        }
+        options.extend([
+            ("Yes to all remaining files", FileAction::UpdateAll),
+            ("Skip all remaining files", FileAction::SkipAll),
+            ("Quit - Stop processing", FileAction::Quit),
+        ]);
+        let labels: Vec<&str> = options.iter().map(|(label, _)| *label).collect();
 
         println!(); // Add newline before prompt
 
@@ -497,81 +1192,73 @@ impl SyncManager {
 
         let selection = Select::with_theme(&theme)
             .with_prompt("What would you like to do?")
-            .items(&options)
+            .items(&labels)
             .default(0)
             .interact()?;
 
-        Ok(match selection {
-            0 => FileAction::Update,
-            1 => FileAction::Skip,
-            2 => FileAction::UpdateAll,
-            3 => FileAction::SkipAll,
-            4 => FileAction::Quit,
-            _ => unreachable!(),
-        })
+        Ok(options.swap_remove(selection).1)
     }
 
     /// Show all diffs without any interactive prompts (read-only view)
     pub async fn show_all_diffs(&mut self) -> Result<()> {
-        output::styled!("<chart> Analyzing sync status...");
+        let _lock = self.lock()?;
+        let lock = SyncLock::load(&self.lock_path())?;
+        let section = output::section!("Comparing protected files");
 
         let mut has_any_changes = false;
 
-        for repo in self.config.repos.clone() {
+        let repos = self.config.repos.clone();
+        for (index, repo) in repos.iter().enumerate() {
             tracing::info!("Processing repository: {}", repo.name);
+            let step = output::step!(index + 1, repos.len(), &repo.name);
 
             // Update cache from remote
-            let repo_path = self.update_cache(&repo)?;
+            let pin = Self::locked_commit(&lock, repo);
+            let repo_path = self.update_cache(repo, pin.as_deref())?;
 
             // Get changed files
             let src = repo_path.join(&repo.source_path);
-            let dst = Path::new(&repo.dest_path);
-            let files = self.get_files(&src, &repo)?;
+            let files = self.get_files(&src, repo)?;
             tracing::debug!("Found {} files in source", files.len());
-            let changed_files = self.files_differ(&files, &src, dst);
+            let changed_files = self.files_differ(repo, &files, &src);
             tracing::debug!("Found {} changed files", changed_files.len());
 
             if changed_files.is_empty() {
                 tracing::info!("No changes detected for repository: {}", repo.name);
+                step.success();
                 continue;
             }
 
             has_any_changes = true;
 
-            // Show repository info
             output::styled!(
-                "\n{} Repository: {} ({} files changed)",
-                ("🔗", "info_symbol"),
-                (&repo.name, "property"),
-                (changed_files.len().to_string(), "property")
+                "  {} changed",
+                (output::human_count(changed_files.len(), "file"), "property")
             );
 
             // Show diff for each changed file (no prompts)
             for (i, file) in changed_files.iter().enumerate() {
-                let dst_file = dst.join(file);
+                let dst_file = repo.destination(file);
 
                 println!();
-                output::styled!("{}", ("─".repeat(60), "muted"));
                 output::styled!(
-                    "File {}/{}: {}",
+                    "  File {}/{}: {}",
                     ((i + 1).to_string(), "muted"),
                     (changed_files.len().to_string(), "muted"),
-                    (dst_file.display().to_string(), "property")
+                    (paths::display(&dst_file), "property")
                 );
 
                 // Show diff (no prompts)
-                self.show_diff(&dst_file, &src.join(file))?;
+                let link = links::preserved(&src, file, repo.preserve_symlinks);
+                self.show_diff(&dst_file, &src.join(file), link)?;
             }
+            step.success();
         }
 
         // Show summary
         if !has_any_changes {
-            println!();
-            output::styled!("{}", ("═".repeat(60), "muted"));
             output::styled!("{} Everything is up to date", ("✅", "success_symbol"));
         } else {
-            println!();
-            output::styled!("{}", ("═".repeat(60), "muted"));
             output::styled!(
                 "{} Showing diffs for {} repositories",
                 ("📝", "info_symbol"),
@@ -579,6 +1266,7 @@ impl SyncManager {
             );
         }
 
+        section.success();
         Ok(())
     }
 
@@ -589,6 +1277,9 @@ impl SyncManager {
 
     /// Extract repository name from URL
     pub fn extract_repo_name(&self, repo_url: &str) -> String {
+        if let Some(reference) = oci::OciReference::parse(repo_url) {
+            return reference.name().to_string();
+        }
         repo_url
             .trim_end_matches('/')
             .trim_end_matches(".git")
@@ -598,3 +1289,33 @@ impl SyncManager {
             .to_string()
     }
 }
+
+/// Remove the directories above `file` that are empty now, stopping at `root`
+fn remove_empty_parents(file: &Path, root: &Path) {
+    for dir in file.ancestors().skip(1) {
+        if !dir.starts_with(root) || dir == root || dir.as_os_str().is_empty() {
+            break;
+        }
+        // Fails, ending the walk, once a directory still has entries
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+}
+
+/// Unified diff from `local` (possibly missing) to `upstream`
+fn unified_diff(local: &Path, upstream: &Path, link: bool) -> Result<String> {
+    let old = diff_text(local, link).unwrap_or_default();
+    let new = diff_text(upstream, link)?;
+    Ok(TextDiff::from_lines(&old, &new)
+        .unified_diff()
+        .header(&paths::display(local), "upstream")
+        .to_string())
+}
+
+/// What a diff shows of `path`: the target of a link when comparing `link`s,
+/// otherwise the content
+fn diff_text(path: &Path, link: bool) -> Result<String> {
+    let content = links::read(path, link && links::is_symlink(path))?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
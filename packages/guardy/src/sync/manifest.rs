@@ -0,0 +1,91 @@
+//! Record of the files each sync repository manages locally
+//!
+//! Kept in `.guardy/sync-manifest.json` and rewritten after every update, so a
+//! file that disappears upstream can be recognised as an orphan instead of
+//! silently staying behind.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::shared::atomic_io;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SyncManifest {
+    /// Destination paths managed by each repository, keyed by its `name`
+    #[serde(default)]
+    pub repos: BTreeMap<String, BTreeSet<PathBuf>>,
+}
+
+impl SyncManifest {
+    /// The manifest at `path`, empty when there is none yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        atomic_io::write_atomic(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Files `repo` managed before that are no longer in `upstream` but still
+    /// exist locally
+    pub fn orphans(&self, repo: &str, upstream: &BTreeSet<PathBuf>) -> Vec<PathBuf> {
+        self.repos
+            .get(repo)
+            .map(|managed| {
+                managed
+                    .difference(upstream)
+                    .filter(|file| file.exists())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record what `repo` manages now: the upstream files plus any orphans the
+    /// user kept, so they are reported again next time
+    pub fn record(&mut self, repo: &str, upstream: BTreeSet<PathBuf>, kept: &[PathBuf]) {
+        let mut managed = upstream;
+        managed.extend(kept.iter().cloned());
+        self.repos.insert(repo.to_string(), managed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphans_are_files_gone_upstream_but_present_locally() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("old.yml");
+        let removed = dir.path().join("deleted-locally.yml");
+        let current = dir.path().join("ci.yml");
+        std::fs::write(&kept, "").unwrap();
+        std::fs::write(&current, "").unwrap();
+
+        let mut manifest = SyncManifest::default();
+        manifest.record(
+            "shared",
+            BTreeSet::from([kept.clone(), removed, current.clone()]),
+            &[],
+        );
+        let upstream = BTreeSet::from([current.clone()]);
+
+        assert_eq!(manifest.orphans("shared", &upstream), vec![kept.clone()]);
+        assert!(manifest.orphans("other", &upstream).is_empty());
+
+        manifest.record("shared", upstream.clone(), std::slice::from_ref(&kept));
+        assert_eq!(manifest.orphans("shared", &upstream), vec![kept]);
+
+        let path = dir.path().join("sync-manifest.json");
+        manifest.save(&path).unwrap();
+        assert_eq!(SyncManifest::load(&path).unwrap(), manifest);
+    }
+}
@@ -0,0 +1,207 @@
+//! Path mapping rules: where upstream files land below `dest_path`
+//!
+//! Each rule reads `<pattern> -> <target>`, e.g. `workflows/*.yml ->
+//! .github/workflows/*.yml` or `**/*.yaml -> **/*.yml`. `*` matches within one path
+//! segment, `**/` any number of leading directories; the target repeats the
+//! pattern's wildcards in the same order and each takes what its counterpart
+//! matched. The first matching rule wins, files matching none keep their path.
+
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MapRule {
+    pattern: String,
+    target: String,
+    regex: Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wildcard {
+    /// `**/` - any number of directories, possibly none
+    Directories,
+    /// `*` - anything within a segment
+    Segment,
+}
+
+/// Split `glob` into literal text and wildcards
+fn tokens(glob: &str) -> Vec<Result<Wildcard, String>> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = glob;
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("**/") {
+            (Some(Wildcard::Directories), 3)
+        } else if rest.starts_with('*') {
+            (Some(Wildcard::Segment), 1)
+        } else {
+            (None, rest.chars().next().map_or(1, char::len_utf8))
+        };
+        match token {
+            Some(wildcard) => {
+                if !literal.is_empty() {
+                    tokens.push(Err(std::mem::take(&mut literal)));
+                }
+                tokens.push(Ok(wildcard));
+            }
+            None => literal.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+    if !literal.is_empty() {
+        tokens.push(Err(literal));
+    }
+    tokens
+}
+
+fn wildcards(glob: &str) -> Vec<Wildcard> {
+    tokens(glob).into_iter().filter_map(Result::ok).collect()
+}
+
+impl MapRule {
+    pub fn parse(rule: &str) -> Result<Self> {
+        let Some((pattern, target)) = rule.split_once("->") else {
+            return Err(anyhow!(
+                "Invalid map rule '{rule}': expected '<pattern> -> <target>'"
+            ));
+        };
+        let (pattern, target) = (pattern.trim(), target.trim());
+        if pattern.is_empty() || target.is_empty() {
+            return Err(anyhow!(
+                "Invalid map rule '{rule}': expected '<pattern> -> <target>'"
+            ));
+        }
+        if wildcards(pattern) != wildcards(target) {
+            return Err(anyhow!(
+                "Invalid map rule '{rule}': the target must use the pattern's wildcards in the same order"
+            ));
+        }
+        // Mapped files stay below `dest_path`
+        if Path::new(target)
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(anyhow!(
+                "Invalid map rule '{rule}': the target must be a relative path inside dest_path"
+            ));
+        }
+
+        let mut regex = String::from("^");
+        for token in tokens(pattern) {
+            regex.push_str(&match token {
+                Ok(Wildcard::Directories) => "(?:(.*)/)?".to_string(),
+                Ok(Wildcard::Segment) => "([^/]*)".to_string(),
+                Err(literal) => regex::escape(&literal),
+            });
+        }
+        regex.push('$');
+        Ok(Self {
+            pattern: pattern.to_string(),
+            target: target.to_string(),
+            regex: Regex::new(&regex)?,
+        })
+    }
+
+    /// Where `file` (relative to the source) goes, if the rule matches it
+    pub fn apply(&self, file: &Path) -> Option<PathBuf> {
+        let file = file.to_string_lossy().replace('\\', "/");
+        let captures = self.regex.captures(&file)?;
+        let mut matched = captures
+            .iter()
+            .skip(1)
+            .map(|capture| capture.map_or("", |capture| capture.as_str()));
+
+        let mut mapped = String::new();
+        for token in tokens(&self.target) {
+            match token {
+                Ok(Wildcard::Directories) => {
+                    let directories = matched.next().unwrap_or_default();
+                    if !directories.is_empty() {
+                        mapped.push_str(directories);
+                        mapped.push('/');
+                    }
+                }
+                Ok(Wildcard::Segment) => mapped.push_str(matched.next().unwrap_or_default()),
+                Err(literal) => mapped.push_str(&literal),
+            }
+        }
+        Some(PathBuf::from(mapped))
+    }
+}
+
+impl TryFrom<String> for MapRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: String) -> Result<Self> {
+        Self::parse(&rule)
+    }
+}
+
+impl From<MapRule> for String {
+    fn from(rule: MapRule) -> Self {
+        format!("{} -> {}", rule.pattern, rule.target)
+    }
+}
+
+/// Where `file` goes according to the first matching rule of `rules`
+pub fn apply(rules: &[MapRule], file: &Path) -> PathBuf {
+    rules
+        .iter()
+        .find_map(|rule| rule.apply(file))
+        .unwrap_or_else(|| file.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(rules: &[&str], file: &str) -> PathBuf {
+        let rules: Vec<MapRule> = rules
+            .iter()
+            .map(|rule| MapRule::parse(rule).unwrap())
+            .collect();
+        apply(&rules, Path::new(file))
+    }
+
+    #[test]
+    fn test_rules_rename_and_rewrite_extensions() {
+        let rules = [
+            "workflows/*.yml -> .github/workflows/*.yml",
+            "**/*.yaml -> **/*.yml",
+            "editorconfig -> .editorconfig",
+        ];
+
+        assert_eq!(
+            map(&rules, "workflows/ci.yml"),
+            PathBuf::from(".github/workflows/ci.yml")
+        );
+        assert_eq!(
+            map(&rules, "lint/rules.yaml"),
+            PathBuf::from("lint/rules.yml")
+        );
+        assert_eq!(map(&rules, "ci.yaml"), PathBuf::from("ci.yml"));
+        assert_eq!(map(&rules, "editorconfig"), PathBuf::from(".editorconfig"));
+        // `*` stays within a segment, unmatched files keep their path
+        assert_eq!(
+            map(&rules, "workflows/nested/ci.yml"),
+            PathBuf::from("workflows/nested/ci.yml")
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        for (rule, error) in [
+            ("workflows/*.yml", "expected '<pattern> -> <target>'"),
+            ("*.yml -> ci.yml", "same order"),
+            ("**/*.yml -> *.yml", "same order"),
+            ("*.yml -> ../*.yml", "inside dest_path"),
+            ("*.yml -> /etc/*.yml", "inside dest_path"),
+        ] {
+            let message = MapRule::parse(rule).unwrap_err().to_string();
+            assert!(message.contains(error), "{rule}: {message}");
+        }
+    }
+}
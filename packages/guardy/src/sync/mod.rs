@@ -6,9 +6,45 @@
 //! ## Features
 //!
 //! - Version pinning to specific tags, branches, or commits
-//! - Selective sync with include/exclude patterns
+//! - Lockfile: `guardy-sync.lock` records the commit, file hashes and timestamps each
+//!   repository resolved to; updates follow it, `--locked` enforces it and
+//!   `guardy sync upgrade` advances it
+//! - Private repositories: `auth` selects an SSH key, an HTTPS token from the
+//!   environment, a credential helper or a GitHub App installation token
+//! - Local sources: `repo` may be a directory (`file:///mnt/shared/configs` or a
+//!   relative path) that is synced in place, without git
+//! - OCI artifacts: `repo: oci://ghcr.io/org/shared-configs:v1` pulls a config
+//!   bundle from a container registry with the ORAS CLI, locked by digest
+//! - Push-back: `guardy sync push` commits local edits of synced files on top of
+//!   the pinned version and pushes them to a branch, optionally opening a pull request
+//! - Selective sync with include/exclude patterns, and `.guardyignore` files in
+//!   destination directories opting individual files out
+//! - Path mapping: `map` rules like `workflows/*.yml -> .github/workflows/*.yml`
+//!   rename files between the upstream layout and the destination
+//! - Sparse clones: a `source_path` below the repository root is all that gets
+//!   checked out (shallow partial clone plus cone-mode sparse checkout)
+//! - Verification: `verify` checks a minisign or cosign signed `SHA256SUMS`
+//!   manifest, and every file against it, before anything is applied
 //! - Automatic restoration of modified protected files
+//! - File modes and symlinks: executable bits are synced like content, and
+//!   `preserve_symlinks: true` recreates relative links that stay inside the source
+//! - Conflict strategies: `on_conflict: theirs | ours | prompt | merge` decides what
+//!   happens to locally edited files; `merge` is a three-way merge over the
+//!   last-synced copy kept in `.guardy/sync-base`
+//! - Offline status: `guardy sync status` compares with the cached sources without
+//!   network access and says when each was last fetched; `--refresh` fetches first
+//! - Drift checks: `guardy sync check` compares every file with the pinned version
+//!   byte for byte and exits non-zero with a text or JSON summary, for scheduled CI
+//! - Orphan cleanup: `.guardy/sync-manifest.json` records the files each repository
+//!   manages, so files removed upstream are reported and can be pruned - always,
+//!   with `prune: true`
+//! - Post-sync commands: `post_sync` runs commands (regenerating lockfiles,
+//!   formatting) after an update changed a repository's files
 //! - Multi-repository configuration support
+//! - Profiles: `profiles: { ci: [shared-ci] }` names subsets of the repositories,
+//!   selected with `--profile ci` (or `GUARDY_SYNC_PROFILE`) on any sync command
+//! - Crash-safe updates: files are replaced atomically, clones are staged before
+//!   entering `.guardy/cache`, and `.guardy/sync.lock` prevents concurrent syncs
 //!
 //! ## Hook Integration
 //!
@@ -45,22 +81,89 @@
 //!
 //! # Force sync all changes
 //! guardy sync update --force
+//!
+//! # Also delete local copies of files removed upstream
+//! guardy sync update --force --prune
+//!
+//! # Reproduce guardy-sync.lock exactly, or move it to the latest versions
+//! guardy sync update --force --locked
+//! guardy sync upgrade
+//!
+//! # Fail when files drifted from the pinned versions (CI)
+//! guardy sync check --format json
+//!
+//! # Offer local edits back upstream
+//! guardy sync push --pr
 //! ```
 
+pub mod auth;
+pub mod base;
+pub mod checked;
+pub mod guardyignore;
+pub mod links;
+pub mod lockfile;
 pub mod manager;
+pub mod manifest;
+pub mod map;
+pub mod oci;
+pub mod post_sync;
 pub mod status;
+pub mod verify;
 
+use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct SyncConfig {
     pub repos: Vec<SyncRepo>,
+    /// Named subsets of `repos` (by `name`), selected with `--profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Vec<String>>,
+    /// Profile `repos` has been narrowed to
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+}
+
+impl SyncConfig {
+    /// Narrow `repos` to those listed by profile `name`
+    pub fn select_profile(&mut self, name: &str) -> Result<()> {
+        let Some(members) = self.profiles.get(name) else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            return Err(anyhow!(
+                "Unknown sync profile '{name}' (configured: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            ));
+        };
+        if let Some(unknown) = members
+            .iter()
+            .find(|member| !self.repos.iter().any(|repo| &repo.name == *member))
+        {
+            return Err(anyhow!(
+                "Sync profile '{name}' lists unknown repository '{unknown}'"
+            ));
+        }
+
+        self.repos.retain(|repo| members.contains(&repo.name));
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SyncRepo {
     pub name: String,
+    /// Git URL, OCI artifact (`oci://ghcr.io/org/configs:v1`) or local directory
+    /// (`file:///shared/configs`, `../platform/configs`)
     pub repo: String,
+    /// Tag, branch or commit - required for git repositories, unused for local ones;
+    /// the tag of OCI artifacts whose reference has none
+    #[serde(default)]
     pub version: String,
     #[serde(default = "default_source_path")]
     pub source_path: String,
@@ -70,6 +173,63 @@ pub struct SyncRepo {
     pub include: Vec<String>,
     #[serde(default)]
     pub exclude: Vec<String>,
+    /// Rename rules (`workflows/*.yml -> .github/workflows/*.yml`) from paths in the
+    /// source to paths below `dest_path`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub map: Vec<map::MapRule>,
+    /// Credentials for a private repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<auth::SyncAuth>,
+    /// What happens to files edited locally when upstream changes them
+    #[serde(default)]
+    pub on_conflict: base::ConflictStrategy,
+    /// Delete local copies of files removed upstream without asking, unless
+    /// `--no-prune` is passed
+    #[serde(default)]
+    pub prune: bool,
+    /// Recreate relative symlinks that stay inside the source instead of following
+    /// them (see [`links`])
+    #[serde(default)]
+    pub preserve_symlinks: bool,
+    /// Commands run after `guardy sync update` changed this repository's files
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_sync: Vec<post_sync::PostSyncCommand>,
+    /// Signed checksum manifest every file must match before it is applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<verify::SyncVerify>,
+}
+
+impl SyncRepo {
+    /// The directory `repo` names when it is a local path rather than a git URL
+    ///
+    /// Local sources are read in place - no clone, no `version` - so a mounted
+    /// monorepo or network share syncs like any repository. Relative paths are
+    /// resolved from the current directory, like `dest_path`.
+    pub fn local_source(&self) -> Option<PathBuf> {
+        if let Some(path) = self.repo.strip_prefix("file://") {
+            return Some(PathBuf::from(path));
+        }
+        // `git@github.com:org/configs.git` - but not a drive letter like `C:/configs`
+        let scp_like = self
+            .repo
+            .split_once(':')
+            .is_some_and(|(host, _)| host.len() > 1 && !host.contains('/'));
+        if self.repo.contains("://") || scp_like {
+            return None;
+        }
+        Some(PathBuf::from(&self.repo))
+    }
+
+    /// Where `file` (relative to the source) is synced to: below `dest_path`, renamed
+    /// by the first matching `map` rule
+    pub fn destination(&self, file: &Path) -> PathBuf {
+        Path::new(&self.dest_path).join(map::apply(&self.map, file))
+    }
+
+    /// The artifact `repo` names when it is an `oci://` reference
+    pub fn oci_reference(&self) -> Option<oci::OciReference> {
+        oci::OciReference::parse(&self.repo)
+    }
 }
 
 #[derive(Debug)]
@@ -77,10 +237,73 @@ pub enum SyncStatus {
     InSync,
     OutOfSync {
         changed_files: Vec<std::path::PathBuf>,
+        /// Files synced earlier whose upstream counterpart has been removed
+        orphaned_files: Vec<std::path::PathBuf>,
     },
     NotConfigured,
 }
 
+/// How far one repository's destination files have drifted from its pinned upstream
+/// version, as reported by `guardy sync check`
+#[derive(Debug, Serialize)]
+pub struct SyncDrift {
+    pub name: String,
+    pub repo: String,
+    /// Pinned version, empty for local directories
+    pub version: String,
+    /// Files whose content differs from upstream
+    pub modified: Vec<String>,
+    /// Upstream files missing locally
+    pub missing: Vec<String>,
+    /// Files removed upstream that are still present locally
+    pub orphaned: Vec<String>,
+}
+
+impl SyncDrift {
+    pub fn in_sync(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// Sync state of one repository's files, as reported by `--format json`
+#[derive(Debug, Serialize)]
+pub struct RepoState {
+    pub name: String,
+    pub repo: String,
+    pub version: String,
+    /// Whether the source was available to compare with; `sync status` only looks at
+    /// what is cached already
+    pub cached: bool,
+    /// When the source was last fetched, in seconds since the epoch; absent for local
+    /// directories and sources never fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_checked: Option<u64>,
+    pub files: Vec<FileState>,
+    /// Files removed upstream that are still present locally
+    pub orphaned: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileState {
+    pub path: String,
+    pub state: FileSyncState,
+    /// Unified diff from the local file to upstream (`sync diff` only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSyncState {
+    InSync,
+    /// Edited locally since the last sync
+    Modified,
+    /// Not present locally
+    Missing,
+    /// Unchanged locally, but upstream has a newer version
+    UpstreamChanged,
+}
+
 // Default values for serde
 fn default_source_path() -> String {
     ".".to_string()
@@ -89,3 +312,71 @@ fn default_source_path() -> String {
 fn default_dest_path() -> String {
     ".".to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_sources_are_told_apart_from_git_urls() {
+        let source = |repo: &str| {
+            SyncRepo {
+                name: "shared".to_string(),
+                repo: repo.to_string(),
+                version: String::new(),
+                source_path: default_source_path(),
+                dest_path: default_dest_path(),
+                include: Vec::new(),
+                exclude: Vec::new(),
+                map: Vec::new(),
+                auth: None,
+                on_conflict: Default::default(),
+                prune: false,
+                preserve_symlinks: false,
+                post_sync: Vec::new(),
+                verify: None,
+            }
+            .local_source()
+        };
+
+        assert_eq!(
+            source("file:///mnt/shared/configs"),
+            Some(PathBuf::from("/mnt/shared/configs"))
+        );
+        assert_eq!(
+            source("../platform/configs"),
+            Some(PathBuf::from("../platform/configs"))
+        );
+        assert_eq!(
+            source("C:/shared/configs"),
+            Some(PathBuf::from("C:/shared/configs"))
+        );
+        assert_eq!(source("https://github.com/org/shared-configs"), None);
+        assert_eq!(source("git@github.com:org/shared-configs.git"), None);
+        assert_eq!(source("ssh://git@host/org/configs.git"), None);
+        assert_eq!(source("oci://ghcr.io/org/shared-configs:v1"), None);
+    }
+
+    #[test]
+    fn test_profiles_select_repositories_by_name() {
+        let config: SyncConfig = serde_json::from_value(serde_json::json!({
+            "repos": [
+                { "name": "editor", "repo": "../editor" },
+                { "name": "ci", "repo": "../ci" },
+            ],
+            "profiles": { "ci": ["ci"], "broken": ["ci", "release"] },
+        }))
+        .unwrap();
+
+        let mut ci = config.clone();
+        ci.select_profile("ci").unwrap();
+        assert_eq!(ci.repos.len(), 1);
+        assert_eq!(ci.repos[0].name, "ci");
+        assert_eq!(ci.active_profile.as_deref(), Some("ci"));
+
+        let error = config.clone().select_profile("broken").unwrap_err();
+        assert!(error.to_string().contains("unknown repository 'release'"));
+        let error = config.clone().select_profile("dev").unwrap_err();
+        assert!(error.to_string().contains("configured: broken, ci"));
+    }
+}
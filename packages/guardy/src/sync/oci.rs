@@ -0,0 +1,222 @@
+//! OCI artifact sources: `repo: oci://ghcr.io/org/shared-configs:v1`
+//!
+//! Artifacts are pulled with the ORAS CLI (`oras`), so a directory published with
+//! `oras push ghcr.io/org/shared-configs:v1 configs/` is unpacked back into one.
+//! Tags are resolved to a digest first: the digest is what `guardy-sync.lock`
+//! records, and an artifact already cached at that digest isn't pulled again.
+//! Registry credentials come from `oras login` (or `docker login`), or from
+//! `auth.token_env` / `auth.github_app`.
+
+use anyhow::{Context, Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::auth::{self, SyncAuth};
+use crate::shared::atomic_io::{self, TempDirGuard};
+
+pub const SCHEME: &str = "oci://";
+
+/// An `oci://` repository, split into the registry repository and its tag or digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OciReference {
+    /// `ghcr.io/org/shared-configs`
+    pub repository: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl OciReference {
+    pub fn parse(repo: &str) -> Option<Self> {
+        let reference = repo.strip_prefix(SCHEME)?;
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) => (rest, Some(digest.to_string())),
+            None => (reference, None),
+        };
+        // Only the last segment has a tag - `localhost:5000/configs` is a port
+        let (repository, tag) = match rest.rsplit_once(':') {
+            Some((repository, tag)) if !tag.contains('/') => (repository, Some(tag.to_string())),
+            _ => (rest, None),
+        };
+        Some(Self {
+            repository: repository.to_string(),
+            tag,
+            digest,
+        })
+    }
+
+    /// Last path segment, naming the artifact's directory in the cache
+    pub fn name(&self) -> &str {
+        self.repository
+            .rsplit('/')
+            .next()
+            .unwrap_or(&self.repository)
+    }
+
+    /// Tag or digest to resolve: the one in `repo`, else the configured `version`
+    pub fn version<'a>(&'a self, version: &'a str) -> Option<&'a str> {
+        self.digest
+            .as_deref()
+            .or(self.tag.as_deref())
+            .or((!version.is_empty()).then_some(version))
+    }
+
+    /// `repository:tag` or `repository@digest`
+    fn at(&self, version: &str) -> String {
+        if version.contains(':') {
+            format!("{}@{version}", self.repository)
+        } else {
+            format!("{}:{version}", self.repository)
+        }
+    }
+}
+
+/// Make `cache` hold the artifact at `pin` (a locked digest) or at the configured
+/// version, returning its digest
+///
+/// The artifact is unpacked into a staging directory and swapped in once complete;
+/// its digest is kept next to it as `<cache>.digest`.
+pub fn pull(
+    reference: &OciReference,
+    version: &str,
+    pin: Option<&str>,
+    auth: Option<&SyncAuth>,
+    cache: &Path,
+) -> Result<String> {
+    let oras = Oras::new(auth)?;
+    let digest = match pin {
+        Some(digest) => digest.to_string(),
+        None => {
+            let version = reference.version(version).ok_or_else(|| {
+                anyhow!(
+                    "OCI source {SCHEME}{} needs a tag or digest (in the reference or as version)",
+                    reference.repository
+                )
+            })?;
+            oras.resolve(&reference.at(version))?
+        }
+    };
+    if cache.is_dir() && cached_digest(cache).as_deref() == Some(digest.as_str()) {
+        return Ok(digest);
+    }
+
+    let parent = cache.parent().unwrap_or(Path::new("."));
+    let staging = TempDirGuard::new_in(parent, "pull")?;
+    oras.pull(&reference.at(&digest), staging.path())?;
+    if cache.exists() {
+        fs::remove_dir_all(cache)
+            .with_context(|| format!("Failed to replace {}", cache.display()))?;
+    }
+    staging.persist(cache)?;
+    atomic_io::write_atomic(&digest_path(cache), format!("{digest}\n"))?;
+    Ok(digest)
+}
+
+/// Digest of the artifact unpacked in `cache`, if any
+pub fn cached_digest(cache: &Path) -> Option<String> {
+    fs::read_to_string(digest_path(cache))
+        .ok()
+        .map(|digest| digest.trim().to_string())
+}
+
+fn digest_path(cache: &Path) -> PathBuf {
+    let mut path = cache.as_os_str().to_owned();
+    path.push(".digest");
+    PathBuf::from(path)
+}
+
+/// The `oras` CLI, logged in with the repository's `auth` when it has credentials
+struct Oras {
+    credentials: Option<(String, String)>,
+}
+
+impl Oras {
+    fn new(auth: Option<&SyncAuth>) -> Result<Self> {
+        which::which("oras")
+            .map_err(|_| anyhow!("OCI sources need the ORAS CLI (`oras`) on PATH"))?;
+        let Some(auth) = auth else {
+            return Ok(Self { credentials: None });
+        };
+        if auth.ssh_key.is_some() || auth.credential_helper.is_some() {
+            return Err(anyhow!(
+                "OCI sources take auth.token_env or auth.github_app - use `oras login` for other credentials"
+            ));
+        }
+        let credentials = match (&auth.github_app, &auth.token_env) {
+            (Some(app), _) => Some(("x-access-token".to_string(), app.installation_token()?)),
+            (None, Some(var)) => Some((
+                auth.token_user.clone(),
+                std::env::var(var).map_err(|_| anyhow!("auth.token_env: ${var} is not set"))?,
+            )),
+            (None, None) => None,
+        };
+        Ok(Self { credentials })
+    }
+
+    /// Run `oras <args>`, the password (if any) going through stdin
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let mut command = Command::new("oras");
+        command.args(args);
+        let password = match &self.credentials {
+            Some((user, password)) => {
+                command.args(["--username", user, "--password-stdin"]);
+                password.as_bytes()
+            }
+            None => &[],
+        };
+        let output = auth::run_with_input(&mut command, password)
+            .with_context(|| format!("oras {} failed", args[0]))?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Digest `reference` currently points to
+    fn resolve(&self, reference: &str) -> Result<String> {
+        let digest = self.run(&["resolve", reference])?.trim().to_string();
+        if !digest.contains(':') {
+            return Err(anyhow!("oras resolve {reference} returned '{digest}'"));
+        }
+        Ok(digest)
+    }
+
+    fn pull(&self, reference: &str, into: &Path) -> Result<()> {
+        let into = into.to_string_lossy();
+        self.run(&["pull", "--output", &into, reference])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_references_split_into_repository_tag_and_digest() {
+        let reference = OciReference::parse("oci://ghcr.io/org/shared-configs:v1").unwrap();
+        assert_eq!(reference.repository, "ghcr.io/org/shared-configs");
+        assert_eq!(reference.tag.as_deref(), Some("v1"));
+        assert_eq!(reference.name(), "shared-configs");
+        assert_eq!(reference.version("v2"), Some("v1"));
+
+        let reference = OciReference::parse("oci://localhost:5000/configs").unwrap();
+        assert_eq!(reference.repository, "localhost:5000/configs");
+        assert_eq!(reference.tag, None);
+        assert_eq!(reference.version(""), None);
+        assert_eq!(reference.version("v2"), Some("v2"));
+        assert_eq!(reference.at("v2"), "localhost:5000/configs:v2");
+
+        let reference = OciReference::parse("oci://ghcr.io/org/configs:v1@sha256:abc").unwrap();
+        assert_eq!(reference.digest.as_deref(), Some("sha256:abc"));
+        assert_eq!(reference.version(""), Some("sha256:abc"));
+        assert_eq!(reference.at("sha256:abc"), "ghcr.io/org/configs@sha256:abc");
+
+        assert_eq!(OciReference::parse("https://github.com/org/configs"), None);
+    }
+
+    #[test]
+    fn test_digest_is_kept_next_to_the_cache() {
+        assert_eq!(
+            digest_path(Path::new(".guardy/cache/configs")),
+            PathBuf::from(".guardy/cache/configs.digest")
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Commands run after a sync changed a repository's files
+//!
+//! `post_sync` lists shell commands per sync repository - regenerating lockfiles,
+//! formatting the synced files - that `guardy sync update` runs from the current
+//! directory once the repository's files have been updated or pruned. They see the
+//! repository as `GUARDY_SYNC_REPO` and the changed files, one per line, as
+//! `GUARDY_SYNC_FILES`.
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::cli::output;
+use crate::shared::paths;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PostSyncCommand {
+    pub command: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    /// Fail `guardy sync update` when the command fails; otherwise it is only reported
+    #[serde(default = "default_fail_on_error")]
+    pub fail_on_error: bool,
+}
+
+impl PostSyncCommand {
+    fn label(&self) -> &str {
+        if self.description.is_empty() {
+            &self.command
+        } else {
+            &self.description
+        }
+    }
+}
+
+/// Run `commands` of repository `repo` after its `files` changed, stopping at the
+/// first failure of a command with `fail_on_error`
+pub fn run(repo: &str, commands: &[PostSyncCommand], files: &[PathBuf]) -> Result<()> {
+    let files: Vec<String> = files.iter().map(|file| paths::display(file)).collect();
+    for post_sync in commands {
+        let mut command = if cfg!(target_os = "windows") {
+            let mut c = Command::new("cmd");
+            c.args(["/C", &post_sync.command]);
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.args(["-c", &post_sync.command]);
+            c
+        };
+
+        // Inherit stdio so formatter and installer output stays visible
+        let status = command
+            .env("GUARDY_SYNC_REPO", repo)
+            .env("GUARDY_SYNC_FILES", files.join("\n"))
+            .status()
+            .with_context(|| format!("Failed to run '{}'", post_sync.command))?;
+
+        if status.success() {
+            output::styled!(
+                "{} {} {}",
+                ("✅", "success_symbol"),
+                (post_sync.label(), "property"),
+                ("(post_sync)", "muted")
+            );
+            continue;
+        }
+        let failure = match status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "terminated by a signal".to_string(),
+        };
+        if post_sync.fail_on_error {
+            return Err(anyhow!(
+                "post_sync command '{}' of '{repo}' failed ({failure})",
+                post_sync.label()
+            ));
+        }
+        output::styled!(
+            "{} {} failed {}",
+            ("⚠️", "warning_symbol"),
+            (post_sync.label(), "property"),
+            (format!("({failure}, fail_on_error: false)"), "muted")
+        );
+    }
+    Ok(())
+}
+
+fn default_fail_on_error() -> bool {
+    true
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn command(command: &str, fail_on_error: bool) -> PostSyncCommand {
+        PostSyncCommand {
+            command: command.to_string(),
+            description: String::new(),
+            fail_on_error,
+        }
+    }
+
+    #[test]
+    fn test_failures_only_stop_the_sync_with_fail_on_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("log");
+        let record = command(
+            &format!(
+                "echo \"$GUARDY_SYNC_REPO:$GUARDY_SYNC_FILES\" >> {}",
+                log.display()
+            ),
+            true,
+        );
+        let files = [PathBuf::from("ci.yml"), PathBuf::from("deny.toml")];
+
+        run(
+            "shared",
+            &[command("exit 3", false), record.clone()],
+            &files,
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&log).unwrap(),
+            "shared:ci.yml\ndeny.toml\n"
+        );
+
+        let error = run("shared", &[command("exit 3", true), record], &files).unwrap_err();
+        assert!(error.to_string().contains("failed (exit code 3)"));
+        assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 2);
+    }
+}
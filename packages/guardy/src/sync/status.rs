@@ -1,5 +1,6 @@
-use super::{SyncStatus, manager::SyncManager};
+use super::{SyncStatus, checked, manager::SyncManager};
 use crate::cli::output;
+use crate::shared::paths;
 use anyhow::Result;
 
 pub struct StatusDisplay<'a> {
@@ -30,6 +31,9 @@ impl<'a> StatusDisplay<'a> {
 
         output::styled!("{} Sync Configuration", ("📋", "info_symbol"));
         println!("  Repositories: {}", self.manager.config.repos.len());
+        if let Some(profile) = &self.manager.config.active_profile {
+            println!("  Profile: {}", output::property_name(profile.clone()));
+        }
         println!(
             "  Cache Directory: {}",
             self.manager.get_cache_dir().display()
@@ -40,10 +44,25 @@ impl<'a> StatusDisplay<'a> {
         for repo in &self.manager.config.repos {
             output::styled!("  {} {}", ("📦", "info_symbol"), (&repo.name, "property"));
             println!("      Repository: {}", output::file_path(repo.repo.clone()));
-            println!(
-                "      Version:    {}",
-                output::property_name(repo.version.clone())
-            );
+            let version = if repo.local_source().is_some() {
+                "(local directory)".to_string()
+            } else if let Some(reference) = repo.oci_reference() {
+                reference
+                    .version(&repo.version)
+                    .unwrap_or_default()
+                    .to_string()
+            } else {
+                repo.version.clone()
+            };
+            println!("      Version:    {}", output::property_name(version));
+            // Local directories are read in place, so they are always current
+            if repo.local_source().is_none() {
+                let checked = match self.manager.last_checked(repo)? {
+                    Some(checked) => checked::age(checked),
+                    None => "never".to_string(),
+                };
+                println!("      Checked:    {checked}");
+            }
             println!("      Source:     {}", repo.source_path);
             println!("      Dest:       {}", repo.dest_path);
 
@@ -56,29 +75,65 @@ impl<'a> StatusDisplay<'a> {
             println!();
         }
 
-        // Check sync status
+        // Check sync status - against the caches as they are, without network access
         let status = self.manager.check_sync_status()?;
+        let offline = self
+            .manager
+            .config
+            .repos
+            .iter()
+            .any(|repo| repo.local_source().is_none());
 
         match status {
             SyncStatus::InSync => {
                 output::styled!("{} All files are in sync", ("✅", "success_symbol"));
             }
-            SyncStatus::OutOfSync { changed_files } => {
-                output::styled!(
-                    "{} {} files are out of sync:",
-                    ("⚠️", "warning_symbol"),
-                    (changed_files.len().to_string(), "property")
-                );
-                for file in &changed_files {
-                    println!("      • {}", output::file_path(file.display().to_string()));
+            SyncStatus::OutOfSync {
+                changed_files,
+                orphaned_files,
+            } => {
+                if !changed_files.is_empty() {
+                    output::styled!(
+                        "{} {} out of sync:",
+                        ("⚠️", "warning_symbol"),
+                        (output::human_count(changed_files.len(), "file"), "property")
+                    );
+                    for file in &changed_files {
+                        println!("      • {}", output::file_path(paths::display(file)));
+                    }
+                    println!();
+                    output::styled!("  Run {} to update", ("guardy sync update", "property"));
+                }
+                if !orphaned_files.is_empty() {
+                    output::styled!(
+                        "{} {} removed upstream but still present locally:",
+                        ("⚠️", "warning_symbol"),
+                        (
+                            output::human_count(orphaned_files.len(), "file"),
+                            "property"
+                        )
+                    );
+                    for file in &orphaned_files {
+                        println!("      • {}", output::file_path(paths::display(file)));
+                    }
+                    println!();
+                    output::styled!(
+                        "  Run {} to delete them",
+                        ("guardy sync update --prune", "property")
+                    );
                 }
-                println!();
-                output::styled!("  Run {} to update", ("guardy sync update", "property"));
             }
             SyncStatus::NotConfigured => {
                 // Already handled above
             }
         }
+        if offline {
+            output::styled!(
+                "{} Compared offline with the cached sources - {} fetches them first",
+                ("ℹ️", "info_symbol"),
+                ("--refresh", "property")
+            );
+        }
 
         Ok(())
     }
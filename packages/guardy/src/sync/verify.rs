@@ -0,0 +1,249 @@
+//! Signed checksum manifests: `verify` checks upstream before anything is applied
+//!
+//! The source publishes a manifest in `sha256sum` format (`SHA256SUMS` by default,
+//! paths relative to the source directory) and a signature of it. guardy checks the
+//! signature with minisign or Sigstore's cosign, then requires every synced file to
+//! be listed with a matching checksum - a tampered shared-config repository fails
+//! the sync instead of reaching the destination. The manifest and signature
+//! themselves are not synced.
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::auth;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SyncVerify {
+    /// Checksum manifest in the source directory
+    #[serde(default = "default_manifest")]
+    pub manifest: String,
+    /// Signature of the manifest [default: `<manifest>.minisig` for minisign,
+    /// `<manifest>.sigstore.json` (a bundle) for cosign]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// minisign public key (`RWQ...`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minisign_key: Option<String>,
+    /// cosign public key file, for signatures made with a key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosign_key: Option<PathBuf>,
+    /// Signer identity of keyless cosign signatures (with `cosign_issuer`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosign_identity: Option<String>,
+    /// OIDC issuer of keyless cosign signatures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosign_issuer: Option<String>,
+}
+
+enum Signer<'a> {
+    Minisign { key: &'a str },
+    CosignKey { key: &'a Path },
+    CosignKeyless { identity: &'a str, issuer: &'a str },
+}
+
+impl SyncVerify {
+    fn signer(&self) -> Result<Signer<'_>> {
+        match (
+            &self.minisign_key,
+            &self.cosign_key,
+            &self.cosign_identity,
+            &self.cosign_issuer,
+        ) {
+            (Some(key), None, None, None) => Ok(Signer::Minisign { key }),
+            (None, Some(key), None, None) => Ok(Signer::CosignKey { key }),
+            (None, None, Some(identity), Some(issuer)) => {
+                Ok(Signer::CosignKeyless { identity, issuer })
+            }
+            _ => Err(anyhow!(
+                "verify needs exactly one of minisign_key, cosign_key, or cosign_identity with cosign_issuer"
+            )),
+        }
+    }
+
+    /// Path of the signature, relative to the source directory
+    pub fn signature(&self) -> String {
+        match &self.signature {
+            Some(signature) => signature.clone(),
+            None if self.minisign_key.is_some() => format!("{}.minisig", self.manifest),
+            None => format!("{}.sigstore.json", self.manifest),
+        }
+    }
+
+    /// Whether `file` (relative to the source) is the manifest or its signature
+    pub fn is_metadata(&self, file: &Path) -> bool {
+        file == Path::new(&self.manifest) || file == Path::new(&self.signature())
+    }
+}
+
+/// Check the manifest's signature in `src`, then that it lists each of `files` with
+/// its checksum
+pub fn verify(config: &SyncVerify, src: &Path, files: &[PathBuf]) -> Result<()> {
+    let manifest = src.join(&config.manifest);
+    let signature = src.join(config.signature());
+    for path in [&manifest, &signature] {
+        if !path.is_file() {
+            return Err(anyhow!("{} is missing", path.display()));
+        }
+    }
+    check_signature(&config.signer()?, &manifest, &signature)?;
+
+    let content = fs::read_to_string(&manifest)
+        .with_context(|| format!("Failed to read {}", manifest.display()))?;
+    check_checksums(&parse_manifest(&content)?, src, files)
+        .with_context(|| format!("Files don't match {}", config.manifest))
+}
+
+fn check_signature(signer: &Signer, manifest: &Path, signature: &Path) -> Result<()> {
+    let cosign = || {
+        let mut command = Command::new("cosign");
+        command.args(["verify-blob", "--bundle"]).arg(signature);
+        command
+    };
+    let mut command = match signer {
+        Signer::Minisign { key } => {
+            let mut command = Command::new("minisign");
+            command.args(["-V", "-q", "-P", key, "-x"]).arg(signature);
+            command.arg("-m");
+            command
+        }
+        Signer::CosignKey { key } => {
+            let mut command = cosign();
+            command.arg("--key").arg(key);
+            command
+        }
+        Signer::CosignKeyless { identity, issuer } => {
+            let mut command = cosign();
+            command.args([
+                "--certificate-identity",
+                identity,
+                "--certificate-oidc-issuer",
+                issuer,
+            ]);
+            command
+        }
+    };
+    command.arg(manifest);
+
+    let tool = command.get_program().to_string_lossy().into_owned();
+    which::which(&tool).map_err(|_| anyhow!("verify needs {tool} on PATH"))?;
+    auth::run_with_input(&mut command, &[]).with_context(|| {
+        format!(
+            "The signature of {} is not valid ({tool})",
+            manifest.display()
+        )
+    })?;
+    Ok(())
+}
+
+/// `sha256sum` output: `<hex digest>  <path>`, or `<hex digest> *<path>` for binary
+/// mode, keyed by path
+fn parse_manifest(content: &str) -> Result<BTreeMap<String, String>> {
+    let mut checksums = BTreeMap::new();
+    for (number, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parsed = line.split_once(' ').and_then(|(digest, path)| {
+            let path = path.strip_prefix([' ', '*'])?;
+            let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+            valid.then(|| (path.trim_start_matches("./"), digest))
+        });
+        let Some((path, digest)) = parsed else {
+            return Err(anyhow!(
+                "Line {} is not '<sha256>  <path>': {line}",
+                number + 1
+            ));
+        };
+        checksums.insert(path.to_string(), digest.to_ascii_lowercase());
+    }
+    Ok(checksums)
+}
+
+fn check_checksums(
+    checksums: &BTreeMap<String, String>,
+    src: &Path,
+    files: &[PathBuf],
+) -> Result<()> {
+    let mut problems = Vec::new();
+    for file in files {
+        let key = file.to_string_lossy().replace('\\', "/");
+        let Some(expected) = checksums.get(&key) else {
+            problems.push(format!("{key}: not listed"));
+            continue;
+        };
+        let path = src.join(file);
+        let content =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        if &format!("{:x}", Sha256::digest(&content)) != expected {
+            problems.push(format!("{key}: checksum mismatch"));
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!("{}", problems.join(", ")))
+    }
+}
+
+fn default_manifest() -> String {
+    "SHA256SUMS".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_file_needs_a_matching_checksum() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("lint")).unwrap();
+        fs::write(dir.path().join("ci.yml"), "cache: true\n").unwrap();
+        fs::write(dir.path().join("lint/rustfmt.toml"), "edition = \"2024\"\n").unwrap();
+        let checksums = parse_manifest(
+            "# sha256sum ci.yml lint/rustfmt.toml\n\
+             6c2d5b1c4ebf1b2faf6f3fe3c0eb1d7a7b1b0ad5c5f6ff0d29e1d9c39c4b4d5e  ./ci.yml\n\
+             0000000000000000000000000000000000000000000000000000000000000000 *lint/rustfmt.toml\n",
+        )
+        .unwrap();
+        assert_eq!(checksums.len(), 2);
+
+        let actual = format!("{:x}", Sha256::digest(b"cache: true\n"));
+        let mut valid = checksums.clone();
+        valid.insert("ci.yml".to_string(), actual);
+        let files = [PathBuf::from("ci.yml"), PathBuf::from("lint/rustfmt.toml")];
+        check_checksums(&valid, dir.path(), &files[..1]).unwrap();
+
+        let error = check_checksums(&valid, dir.path(), &files).unwrap_err();
+        assert_eq!(error.to_string(), "lint/rustfmt.toml: checksum mismatch");
+        let error = check_checksums(&BTreeMap::new(), dir.path(), &files[..1]).unwrap_err();
+        assert_eq!(error.to_string(), "ci.yml: not listed");
+        assert!(parse_manifest("ci.yml abc\n").is_err());
+    }
+
+    #[test]
+    fn test_exactly_one_signer_is_configured() {
+        let config = |json: serde_json::Value| serde_json::from_value::<SyncVerify>(json).unwrap();
+
+        let minisign = config(serde_json::json!({ "minisign_key": "RWQ" }));
+        assert!(matches!(minisign.signer(), Ok(Signer::Minisign { .. })));
+        assert_eq!(minisign.signature(), "SHA256SUMS.minisig");
+        assert!(minisign.is_metadata(Path::new("SHA256SUMS.minisig")));
+
+        let keyless = config(serde_json::json!({
+            "manifest": "checksums.txt",
+            "cosign_identity": "release@example.com",
+            "cosign_issuer": "https://token.actions.githubusercontent.com",
+        }));
+        assert!(matches!(keyless.signer(), Ok(Signer::CosignKeyless { .. })));
+        assert_eq!(keyless.signature(), "checksums.txt.sigstore.json");
+
+        assert!(config(serde_json::json!({})).signer().is_err());
+        let both = config(serde_json::json!({ "minisign_key": "RWQ", "cosign_key": "k.pub" }));
+        assert!(both.signer().is_err());
+    }
+}
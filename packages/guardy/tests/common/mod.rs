@@ -0,0 +1,212 @@
+//! Shared harness for end-to-end tests
+//!
+//! `TestRepo` builds a throwaway git repository (with an isolated HOME so user
+//! level git/guardy config never leaks in) and drives the real `guardy` binary.
+//! Git is run with the freshly built binary first on `PATH`, so installed hook
+//! stubs (`exec guardy run <hook> "$@"`) call the binary under test.
+
+#![allow(dead_code)] // Each test crate uses a different subset of the harness
+
+use assert_cmd::Command;
+use assert_cmd::assert::Assert;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Path of the guardy binary built for this test run
+pub fn guardy_bin() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_guardy"))
+}
+
+pub struct TestRepo {
+    dir: TempDir,
+    home: TempDir,
+}
+
+impl TestRepo {
+    /// Create a repository on `main` with an initial commit (made without hooks)
+    pub fn new() -> Self {
+        let repo = Self::empty();
+        repo.write("README.md", "# test repo\n");
+        repo.git(&["add", "README.md"]).assert().success();
+        repo.git(&["commit", "--no-verify", "-m", "chore: initial commit"])
+            .assert()
+            .success();
+        repo
+    }
+
+    /// Create a repository without any commits
+    pub fn empty() -> Self {
+        let repo = Self {
+            dir: TempDir::new().expect("create repo dir"),
+            home: TempDir::new().expect("create home dir"),
+        };
+
+        repo.git(&["init", "--initial-branch=main"])
+            .assert()
+            .success();
+        repo.git(&["config", "user.name", "Guardy Test"])
+            .assert()
+            .success();
+        repo.git(&["config", "user.email", "test@guardy.dev"])
+            .assert()
+            .success();
+        repo.git(&["config", "commit.gpgsign", "false"])
+            .assert()
+            .success();
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn hooks_dir(&self) -> PathBuf {
+        self.path().join(".git").join("hooks")
+    }
+
+    /// Write a file relative to the repository root, creating parent directories
+    pub fn write(&self, relative: &str, content: &str) -> &Self {
+        let path = self.path().join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent dirs");
+        }
+        fs::write(path, content).expect("write file");
+        self
+    }
+
+    pub fn remove(&self, relative: &str) -> &Self {
+        fs::remove_file(self.path().join(relative)).expect("remove file");
+        self
+    }
+
+    pub fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.path().join(relative)).expect("read file")
+    }
+
+    /// Write `guardy.yaml` at the repository root
+    pub fn config(&self, yaml: &str) -> &Self {
+        self.write("guardy.yaml", yaml)
+    }
+
+    /// Write the personal `~/.config/guardy/hooks.yaml` of the test home
+    pub fn user_hooks(&self, yaml: &str) -> &Self {
+        let path = self.home.path().join(".config/guardy/hooks.yaml");
+        fs::create_dir_all(path.parent().expect("hooks file has a parent dir"))
+            .expect("create config dir");
+        fs::write(path, yaml).expect("write user hooks");
+        self
+    }
+
+    pub fn stage(&self, paths: &[&str]) -> &Self {
+        let mut args = vec!["add", "--"];
+        args.extend_from_slice(paths);
+        self.git(&args).assert().success();
+        self
+    }
+
+    pub fn stage_all(&self) -> &Self {
+        self.git(&["add", "-A"]).assert().success();
+        self
+    }
+
+    /// Run `git commit -m <message>` with hooks enabled and return the assertion
+    pub fn commit(&self, message: &str) -> Assert {
+        self.git(&["commit", "-m", message]).assert()
+    }
+
+    /// Install all guardy hooks, overwriting any existing ones
+    pub fn install_hooks(&self) -> &Self {
+        self.guardy(&["install", "--force"]).assert().success();
+        self
+    }
+
+    /// Number of commits reachable from HEAD
+    pub fn commit_count(&self) -> usize {
+        let output = self
+            .git(&["rev-list", "--count", "HEAD"])
+            .output()
+            .expect("run git rev-list");
+        String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .unwrap_or(0)
+    }
+
+    /// Create a bare repository and register it as `origin`
+    pub fn add_bare_remote(&self) -> TempDir {
+        let remote = TempDir::new().expect("create remote dir");
+        self.git(&["init", "--bare", &remote.path().to_string_lossy()])
+            .assert()
+            .success();
+        self.git(&["remote", "add", "origin", &remote.path().to_string_lossy()])
+            .assert()
+            .success();
+        remote
+    }
+
+    /// Make git fetch `url` from the local repository at `target` (via the isolated
+    /// HOME's `url.<target>.insteadOf`), so remote-looking URLs work offline
+    pub fn alias_url(&self, url: &str, target: &Path) -> &Self {
+        let key = format!("url.{}.insteadOf", target.display());
+        self.git(&["config", "--global", &key, url])
+            .assert()
+            .success();
+        self
+    }
+
+    /// Add `other` as a submodule at `relative` and commit it without hooks
+    pub fn add_submodule(&self, other: &TestRepo, relative: &str) -> &Self {
+        self.git(&[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            &other.path().to_string_lossy(),
+            relative,
+        ])
+        .assert()
+        .success();
+        self.git(&["commit", "--no-verify", "-m", "chore: add submodule"])
+            .assert()
+            .success();
+        self
+    }
+
+    /// A `git` command running in the repository with the test environment
+    pub fn git(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("git");
+        command.args(args);
+        self.prepare(&mut command);
+        command
+    }
+
+    /// A `guardy` command running in the repository with the test environment
+    pub fn guardy(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(guardy_bin());
+        command.args(args);
+        self.prepare(&mut command);
+        command
+    }
+
+    fn prepare(&self, command: &mut Command) {
+        let bin_dir = guardy_bin()
+            .parent()
+            .expect("binary has a parent dir")
+            .to_path_buf();
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let mut paths = vec![bin_dir];
+        paths.extend(std::env::split_paths(&path));
+
+        command
+            .current_dir(self.path())
+            .env("PATH", std::env::join_paths(paths).expect("join PATH"))
+            .env("HOME", self.home.path())
+            .env("XDG_CONFIG_HOME", self.home.path().join(".config"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("NO_COLOR", "1")
+            .env_remove("GIT_DIR")
+            .env_remove("GIT_WORK_TREE")
+            .env_remove("GIT_INDEX_FILE");
+    }
+}
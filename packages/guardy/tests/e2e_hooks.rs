@@ -0,0 +1,2035 @@
+//! End-to-end hook flows: install → commit → hook failure → fix → push
+//!
+//! Every test builds its own throwaway repository through `common::TestRepo`
+//! and lets git invoke the installed hook stubs, exactly like a real checkout.
+
+mod common;
+
+use common::TestRepo;
+use predicates::prelude::*;
+
+/// Split so this file never trips a secret scanner itself
+fn github_token() -> String {
+    format!("ghp_{}", "wJbFxR9mK3qL7sP2vN8dH5zC4gY6tA1eXyZ9")
+}
+
+#[test]
+fn install_writes_hook_stubs_that_forward_arguments() {
+    let repo = TestRepo::new();
+    repo.install_hooks();
+
+    let stub = std::fs::read_to_string(repo.hooks_dir().join("commit-msg")).unwrap();
+    assert!(stub.contains("guardy run commit-msg \"$@\""));
+
+    repo.guardy(&["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed hooks"))
+        .stdout(predicate::str::contains("Unpushed: 1 commit"))
+        .stdout(predicate::str::contains("chore: initial commit"));
+}
+
+#[test]
+fn hooks_installed_from_a_linked_worktree_guard_its_commits() {
+    let repo = TestRepo::new();
+    let worktree = tempfile::TempDir::new().unwrap();
+    let checkout = worktree.path().join("feature");
+    repo.git(&["worktree", "add", "-q", "-b", "feature"])
+        .arg(&checkout)
+        .assert()
+        .success();
+
+    repo.guardy(&["install"])
+        .current_dir(&checkout)
+        .assert()
+        .success();
+    assert!(repo.hooks_dir().join("pre-commit").exists());
+    assert!(!checkout.join(".git").is_dir());
+    repo.guardy(&["status"])
+        .current_dir(&checkout)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Linked worktree of"))
+        .stdout(predicate::str::contains("Installed hooks"));
+
+    std::fs::write(
+        checkout.join("config.env"),
+        format!("GITHUB_TOKEN={}\n", github_token()),
+    )
+    .unwrap();
+    repo.git(&["add", "config.env"])
+        .current_dir(&checkout)
+        .assert()
+        .success();
+    repo.git(&["commit", "-m", "feat: add config"])
+        .current_dir(&checkout)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Secrets detected"));
+}
+
+#[test]
+fn status_suggests_ignoring_tracked_build_output() {
+    let repo = TestRepo::new();
+    repo.write("Cargo.toml", "[package]\nname = \"demo\"\n")
+        .write("target/debug/demo", "binary\n")
+        .stage_all();
+    repo.git(&["commit", "-qm", "chore: add build output"])
+        .assert()
+        .success();
+
+    repo.guardy(&["status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "1 file tracked in target/ (Rust build output)",
+        ))
+        .stdout(predicate::str::contains("git rm -r --cached target"));
+}
+
+#[test]
+fn pre_commit_blocks_secrets_until_fixed() {
+    let repo = TestRepo::new();
+    repo.install_hooks();
+
+    repo.write("config.env", &format!("GITHUB_TOKEN={}\n", github_token()))
+        .stage(&["config.env"]);
+    repo.commit("feat: add config")
+        .failure()
+        .stderr(predicate::str::contains("Secrets detected"));
+    assert_eq!(repo.commit_count(), 1);
+
+    repo.write("config.env", "GITHUB_TOKEN=${GITHUB_TOKEN}\n")
+        .stage(&["config.env"]);
+    repo.commit("feat: add config").success();
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn truncated_secret_scans_pass_with_a_warning_unless_configured_to_fail() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+scanner:
+  limits:
+    max_duration: 0s
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["scan_secrets"]
+"#,
+    )
+    .install_hooks();
+
+    repo.write("config.env", "MODE=dev\n")
+        .stage(&["config.env"]);
+    repo.commit("feat: add config")
+        .success()
+        .stderr(predicate::str::contains("no secrets found").not())
+        .stderr(predicate::str::contains(
+            "No secrets in the 0 files scanned, 1 not scanned",
+        ));
+
+    repo.config(
+        r#"
+scanner:
+  limits:
+    max_duration: 0s
+    fail_on_truncation: true
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["scan_secrets"]
+"#,
+    );
+    repo.write("config.env", "MODE=prod\n")
+        .stage(&["config.env"]);
+    repo.commit("feat: switch to prod")
+        .failure()
+        .stderr(predicate::str::contains("Secret scan stopped early"));
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn secrets_in_commit_messages_and_tags_are_found() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: false
+  commit-msg:
+    enabled: true
+    builtin: ["scan_secrets"]
+"#,
+    )
+    .install_hooks();
+
+    repo.stage_all();
+    repo.commit(&format!("chore: add config\n\ntoken {}", github_token()))
+        .failure()
+        .stderr(predicate::str::contains(
+            "Secrets detected in commit message",
+        ));
+    repo.commit("chore: add config").success();
+
+    repo.git(&[
+        "tag",
+        "-a",
+        "v1.0.0",
+        "-m",
+        &format!("release with {}", github_token()),
+    ])
+    .assert()
+    .success();
+    repo.guardy(&["scan", "--git-metadata", "--format", "json", "guardy.yaml"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("tag v1.0.0 annotation"));
+}
+
+#[test]
+fn commit_msg_hook_receives_message_file() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  commit-msg:
+    enabled: true
+    builtin: ["validate_commit_msg"]
+"#,
+    )
+    .install_hooks();
+
+    repo.write("notes.txt", "hello\n").stage_all();
+    repo.commit("added some notes").failure();
+    repo.commit("docs: add notes").success();
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn pre_push_custom_command_gates_push() {
+    let repo = TestRepo::new();
+    let _remote = repo.add_bare_remote();
+    repo.config(
+        r#"
+hooks:
+  pre-push:
+    enabled: true
+    parallel: false
+    custom:
+      - command: "test -f RELEASE_NOTES.md"
+        description: "Require release notes"
+"#,
+    )
+    .install_hooks();
+
+    repo.stage_all();
+    repo.commit("chore: add guardy config").success();
+    repo.git(&["push", "origin", "main"]).assert().failure();
+
+    repo.write("RELEASE_NOTES.md", "- initial release\n")
+        .stage_all();
+    repo.commit("docs: add release notes").success();
+    repo.git(&["push", "origin", "main"]).assert().success();
+}
+
+#[test]
+fn require_signed_commits_rejects_unsigned_pushes() {
+    let repo = TestRepo::new();
+    let _remote = repo.add_bare_remote();
+    let keys = tempfile::TempDir::new().unwrap();
+    let key = keys.path().join("id_ed25519");
+    let keygen = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "guardy-test", "-f"])
+        .arg(&key)
+        .status()
+        .expect("run ssh-keygen");
+    assert!(keygen.success());
+    let public_key = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+
+    repo.config(
+        r#"
+hooks:
+  pre-push:
+    enabled: true
+    builtin: ["require_signed_commits"]
+signed_commits:
+  allowed_signers_file: allowed_signers
+"#,
+    )
+    .write("allowed_signers", &format!("test@guardy.dev {public_key}"))
+    .install_hooks();
+
+    repo.stage_all();
+    repo.commit("chore: add guardy config").success();
+    repo.git(&["push", "origin", "main"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("not signed"));
+
+    repo.git(&["config", "gpg.format", "ssh"])
+        .assert()
+        .success();
+    repo.git(&["config", "user.signingkey", &key.to_string_lossy()])
+        .assert()
+        .success();
+    repo.git(&[
+        "rebase",
+        "--root",
+        "--exec",
+        "git commit --amend --no-edit --no-verify -S",
+    ])
+    .assert()
+    .success();
+    repo.git(&["push", "origin", "main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 pushed commits signed"));
+}
+
+#[test]
+fn validate_branch_name_suggests_a_rename() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["validate_branch_name"]
+"#,
+    )
+    .install_hooks();
+    repo.git(&["checkout", "-b", "Add_Login"])
+        .assert()
+        .success();
+
+    repo.stage_all();
+    repo.commit("chore: add guardy config")
+        .failure()
+        .stderr(predicate::str::contains(
+            "git branch -m Add_Login feature/add-login",
+        ));
+
+    repo.git(&["branch", "-m", "Add_Login", "feature/add-login"])
+        .assert()
+        .success();
+    repo.commit("chore: add guardy config").success();
+}
+
+#[test]
+fn check_merge_conflicts_blocks_markers_outside_excludes() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["check_merge_conflicts"]
+merge_conflicts:
+  exclude: ["docs/**"]
+"#,
+    )
+    .install_hooks();
+
+    let conflicted = "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\n";
+    repo.write("docs/merging.md", conflicted)
+        .write("src/lib.txt", conflicted)
+        .stage_all();
+    repo.commit("feat: add lib")
+        .failure()
+        .stderr(predicate::str::contains("src/lib.txt:1"))
+        .stderr(predicate::str::contains("docs/merging.md").not());
+
+    // Resolving only in the working tree still commits the markers
+    repo.write("src/lib.txt", "ours\n");
+    repo.commit("feat: add lib")
+        .failure()
+        .stderr(predicate::str::contains("src/lib.txt:1"));
+
+    repo.stage_all();
+    repo.commit("feat: add lib").success();
+}
+
+#[test]
+fn stash_unstaged_hides_working_tree_changes_from_checks() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    stash_unstaged: true
+    custom:
+      - command: "test \"$(cat app.txt)\" = ok"
+        name: "check-app"
+"#,
+    )
+    .install_hooks();
+    repo.write("app.txt", "ok").stage_all();
+    repo.commit("chore: add app").success();
+
+    // Only the staged notes are committed; the broken app.txt isn't what's checked
+    repo.write("notes.txt", "notes\n").stage(&["notes.txt"]);
+    repo.write("app.txt", "broken");
+    repo.commit("docs: add notes")
+        .success()
+        .stderr(predicate::str::contains(
+            "Hiding unstaged changes to 1 file",
+        ));
+    assert_eq!(repo.read("app.txt"), "broken");
+    assert!(!repo.path().join(".git/guardy-unstaged.patch").exists());
+
+    // A run killed after hiding the changes leaves its patch for the next one
+    let patch = repo
+        .git(&["diff", "--binary"])
+        .output()
+        .expect("run git diff")
+        .stdout;
+    std::fs::write(repo.path().join(".git/guardy-unstaged.patch"), patch).unwrap();
+    repo.git(&["checkout", "--", "app.txt"]).assert().success();
+    repo.write("todo.txt", "todo\n").stage(&["todo.txt"]);
+    repo.commit("docs: add todo")
+        .success()
+        .stderr(predicate::str::contains(
+            "Restored unstaged changes from an interrupted hook run",
+        ));
+    assert_eq!(repo.read("app.txt"), "broken");
+    assert!(!repo.path().join(".git/guardy-unstaged.patch").exists());
+}
+
+#[test]
+fn stash_unstaged_merges_unstaged_changes_with_fixes() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    stash_unstaged: true
+    custom:
+      - command: "sed -i 's/^fixme$/fixed/' app.txt other.txt"
+        name: "fix"
+"#,
+    )
+    .install_hooks();
+    repo.write("app.txt", "fixme\nkeep\nuser\n")
+        .write("other.txt", "fixme\n")
+        .stage_all();
+    repo.git(&["commit", "--no-verify", "-m", "chore: add files"])
+        .assert()
+        .success();
+
+    // The fix and the unstaged change are a line apart in app.txt, and other.txt
+    // only has the fix: both survive
+    repo.write("notes.txt", "notes\n").stage(&["notes.txt"]);
+    repo.write("app.txt", "fixme\nkeep\nUSER\n");
+    repo.commit("docs: add notes").success();
+    assert_eq!(repo.read("app.txt"), "fixed\nkeep\nUSER\n");
+    assert_eq!(repo.read("other.txt"), "fixed\n");
+    assert!(!repo.path().join(".git/guardy-unstaged.patch").exists());
+    assert!(!repo.path().join(".git/guardy-hook-edits.patch").exists());
+}
+
+#[test]
+fn block_large_files_checks_the_staged_content() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["block_large_files"]
+large_files:
+  max_size_kb: 1
+"#,
+    )
+    .install_hooks();
+
+    repo.write("data/dump.sql", &"x".repeat(4096)).stage_all();
+    // Shrinking the working tree copy doesn't change what would be committed
+    repo.write("data/dump.sql", "x");
+    repo.commit("feat: add dump")
+        .failure()
+        .stderr(predicate::str::contains("data/dump.sql"))
+        .stderr(predicate::str::contains("git lfs track \"*.sql\""));
+
+    repo.stage_all();
+    repo.commit("feat: add dump").success();
+}
+
+#[test]
+fn custom_commands_get_their_env_and_env_files() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "echo \"$GREETING\" > .git/greeting"
+        name: "greet"
+        env_file: [".env.test", ".env.missing"]
+        env:
+          GREETING: "hello ${NAME} from ${PLACE:-guardy}"
+"#,
+    )
+    .install_hooks();
+    repo.write(".env.test", "NAME='world'\n").stage_all();
+
+    repo.commit("chore: add env").success();
+    let greeting = std::fs::read_to_string(repo.path().join(".git/greeting")).unwrap();
+    assert_eq!(greeting, "hello world from guardy\n");
+}
+
+#[test]
+fn commits_succeed_in_repositories_with_submodules() {
+    let library = TestRepo::new();
+    let repo = TestRepo::new();
+    repo.add_submodule(&library, "vendor/library")
+        .install_hooks();
+
+    repo.write("src/main.txt", "no secrets here\n").stage_all();
+    repo.commit("feat: add main").success();
+    assert_eq!(repo.commit_count(), 3);
+}
+
+#[test]
+fn secrets_inside_submodules_are_scanned_unless_skipped() {
+    let library = TestRepo::new();
+    let repo = TestRepo::new();
+    repo.add_submodule(&library, "libs/shared").install_hooks();
+
+    // The submodule moves to a commit adding a secret
+    let submodule = repo.path().join("libs/shared");
+    std::fs::write(
+        submodule.join("config.env"),
+        format!("GITHUB_TOKEN={}\n", github_token()),
+    )
+    .unwrap();
+    for args in [
+        &["add", "config.env"][..],
+        &[
+            "-c",
+            "user.name=Guardy Test",
+            "-c",
+            "user.email=test@guardy.dev",
+            "commit",
+            "--no-verify",
+            "-qm",
+            "feat: add config",
+        ],
+    ] {
+        repo.git(args).current_dir(&submodule).assert().success();
+    }
+    repo.stage(&["libs/shared"]);
+    repo.commit("chore: bump library")
+        .failure()
+        .stderr(predicate::str::contains("Secrets detected"));
+    repo.guardy(&["scan", "--format", "files"])
+        .assert()
+        .stdout(predicate::str::contains("libs/shared/config.env"));
+
+    repo.config("scanner:\n  submodules: skip\n");
+    repo.guardy(&["scan", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Submodule libs/shared skipped"));
+    repo.commit("chore: bump library")
+        .success()
+        .stderr(predicate::str::contains("Skipped submodule libs/shared"));
+}
+
+#[test]
+fn uninstall_removes_only_guardy_hooks() {
+    let repo = TestRepo::new();
+    repo.install_hooks();
+    repo.write(".git/hooks/pre-rebase", "#!/bin/sh\nexit 0\n");
+
+    repo.guardy(&["uninstall", "--yes"]).assert().success();
+
+    assert!(!repo.hooks_dir().join("pre-commit").exists());
+    assert!(repo.hooks_dir().join("pre-rebase").exists());
+}
+
+#[test]
+fn guardy_skip_bypasses_named_commands() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: false
+    custom:
+      - command: "false"
+        name: "lint"
+        description: "Always fails"
+"#,
+    )
+    .install_hooks();
+
+    repo.stage_all();
+    repo.commit("chore: add guardy config").failure();
+
+    repo.git(&["commit", "-m", "chore: add guardy config"])
+        .env("GUARDY_SKIP", "lint")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Skipped lint (GUARDY_SKIP)"));
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn pre_commit_config_runs_local_system_hooks() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    builtin: ["pre_commit_config"]
+"#,
+    )
+    .write(
+        ".pre-commit-config.yaml",
+        r#"
+repos:
+  - repo: local
+    hooks:
+      - id: no-todos
+        name: no TODOs
+        entry: "! grep -n TODO --"
+        language: system
+        files: \.txt$
+      - id: black
+        entry: black
+        language: python
+        types: [python]
+"#,
+    )
+    .install_hooks();
+
+    repo.write("notes.txt", "TODO: write notes\n").stage_all();
+    repo.commit("chore: add notes")
+        .failure()
+        .stderr(predicate::str::contains("no TODOs"))
+        .stderr(predicate::str::contains(
+            "language 'python' is not supported",
+        ));
+
+    repo.write("notes.txt", "notes\n").stage_all();
+    repo.commit("chore: add notes").success();
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn run_plan_lists_commands_and_files_without_running_them() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: false
+    builtin: ["scan_secrets"]
+    custom:
+      - command: "touch ran.marker"
+        description: "Touch marker"
+        glob: ["*.rs"]
+"#,
+    );
+    repo.write("src/lib.rs", "pub fn answer() -> u32 { 42 }\n")
+        .write("notes.txt", "notes\n")
+        .stage_all();
+
+    repo.guardy(&["run", "pre-commit", "--plan"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("scan_secrets"))
+        .stdout(predicate::str::contains("Touch marker"))
+        .stdout(predicate::str::contains("src/lib.rs"))
+        .stdout(predicate::str::contains("notes.txt").not())
+        .stdout(predicate::str::contains("sequential (parallel: false)"));
+    assert!(!repo.path().join("ran.marker").exists());
+}
+
+#[test]
+fn run_json_format_reports_per_command_timings() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: false
+    custom:
+      - command: "true"
+        name: "ok"
+        description: "Passes"
+      - command: "false"
+        name: "lenient"
+        description: "Fails without blocking"
+        fail_on_error: false
+"#,
+    );
+
+    let output = repo
+        .guardy(&["run", "pre-commit", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["hook"], "pre-commit");
+    assert_eq!(report["success"], true);
+    assert!(report["total_ms"].is_u64());
+
+    let step = |name: &str| {
+        report["steps"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|step| step["name"] == name)
+            .cloned()
+            .unwrap_or_else(|| panic!("no step named {name}"))
+    };
+    assert_eq!(step("ok")["outcome"], "passed");
+    assert_eq!(step("lenient")["outcome"], "non_fatal");
+    assert_eq!(step("lenient")["kind"], "custom");
+    assert!(step("lenient")["duration_ms"].is_u64());
+}
+
+#[test]
+fn depends_on_orders_commands_and_blocks_dependents_of_failures() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: true
+    custom:
+      - command: "echo clippy >> order.log"
+        name: "clippy"
+        depends_on: ["fmt"]
+      - command: "sleep 0.3 && echo fmt >> order.log"
+        name: "fmt"
+      - command: "false"
+        name: "broken"
+      - command: "touch blocked.marker"
+        name: "after-broken"
+        depends_on: ["broken"]
+"#,
+    );
+
+    repo.guardy(&["run", "pre-commit"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "Not run after a failure: after-broken",
+        ));
+
+    let order = std::fs::read_to_string(repo.path().join("order.log")).unwrap();
+    assert_eq!(order, "fmt\nclippy\n");
+    assert!(!repo.path().join("blocked.marker").exists());
+}
+
+#[test]
+fn flaky_command_is_retried_and_attempts_are_reported() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "if [ -f tried ]; then exit 0; fi; touch tried; echo 'registry ETIMEDOUT' >&2; exit 1"
+        name: "license-check"
+        retries: 2
+        retry_backoff_ms: 10
+        retry_on:
+          output: "ETIMEDOUT"
+"#,
+    );
+
+    let output = repo
+        .guardy(&["run", "pre-commit", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let step = report["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|step| step["name"] == "license-check")
+        .unwrap();
+    assert_eq!(step["outcome"], "passed");
+    assert_eq!(step["attempts"], 2);
+}
+
+#[test]
+fn files_commands_are_skipped_when_no_file_matches() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "touch ran.txt {files}"
+        name: "js-only"
+        glob: ["*.js"]
+"#,
+    );
+    repo.write("notes.txt", "notes\n").stage(&["notes.txt"]);
+
+    repo.guardy(&["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(no matching files)"));
+    assert!(!repo.path().join("ran.txt").exists());
+}
+
+#[test]
+fn add_preset_detects_the_project_and_skips_commands_without_files() {
+    let repo = TestRepo::new();
+    repo.write("package.json", "{}\n").write("yarn.lock", "\n");
+
+    repo.guardy(&["hooks", "add-preset"]).assert().success();
+
+    let config = repo.read("guardy.yaml");
+    assert!(config.contains("yarn eslint {files}"));
+    assert!(config.contains("yarn test"));
+
+    // Nothing staged matches the JS globs, so the preset commands never need yarn
+    repo.guardy(&["run", "pre-commit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(no matching files)"));
+
+    repo.guardy(&["hooks", "add-preset", "node"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("guardy.yaml"), config);
+}
+
+#[test]
+fn languages_filter_detects_scripts_without_extensions() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "echo {files} > python-files.txt"
+        name: "python-lint"
+        languages: ["python"]
+"#,
+    );
+    repo.write(
+        "scripts/deploy",
+        "#!/usr/bin/env python3\nprint('deploy')\n",
+    )
+    .write("scripts/build.sh", "#!/bin/sh\necho build\n")
+    .write("notes.txt", "notes\n")
+    .stage_all();
+
+    repo.guardy(&["run", "pre-commit"]).assert().success();
+
+    let seen = repo.read("python-files.txt");
+    assert!(seen.contains("scripts/deploy"));
+    assert!(!seen.contains("build.sh"));
+    assert!(!seen.contains("notes.txt"));
+}
+
+#[test]
+fn file_placeholders_with_modifiers() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "ls {staged_files:relative|quoted} > staged.txt"
+        name: "staged"
+        glob: ["*.md"]
+      - command: "tr '\\0' '\\n' < {all_files:null-separated} > tracked.txt"
+        name: "tracked"
+"#,
+    );
+    repo.write("docs/my notes.md", "# Notes\n")
+        .write("src/lib.rs", "pub fn lib() {}\n")
+        .stage_all();
+
+    repo.guardy(&["run", "pre-commit"]).assert().success();
+
+    assert_eq!(repo.read("staged.txt"), "docs/my notes.md\n");
+    let tracked = repo.read("tracked.txt");
+    assert!(tracked.lines().any(|line| line.ends_with("src/lib.rs")));
+    assert!(
+        tracked
+            .lines()
+            .any(|line| line.ends_with("docs/my notes.md"))
+    );
+}
+
+#[test]
+fn group_output_keeps_each_commands_lines_together() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    output: group
+    custom:
+      - command: "echo a1; sleep 0.2; echo a2"
+        name: "first"
+      - command: "echo b1; sleep 0.1; echo b2"
+        name: "second"
+"#,
+    );
+    repo.write("notes.txt", "notes\n").stage_all();
+
+    let output = repo.guardy(&["run", "pre-commit"]).output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("┌─ first\n│ a1\n│ a2\n└─"), "{stdout}");
+    assert!(stdout.contains("┌─ second\n│ b1\n│ b2\n└─"), "{stdout}");
+}
+
+#[test]
+fn user_hooks_add_personal_checks_to_every_repo() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "touch repo-check.txt"
+        name: "repo-check"
+"#,
+    )
+    .user_hooks(
+        r#"
+commit-msg:
+  custom:
+    - command: "! grep -qi '^wip' .git/COMMIT_EDITMSG"
+      name: "no-wip"
+"#,
+    )
+    .install_hooks();
+
+    repo.write("notes.txt", "hello\n").stage_all();
+    repo.commit("WIP notes").failure();
+    assert!(repo.path().join("repo-check.txt").exists());
+
+    repo.commit("docs: add notes").success();
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn recorded_session_replays_staged_content_after_local_changes() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    custom:
+      - command: "! grep -q TODO notes.txt"
+        name: "no-todo"
+"#,
+    );
+    repo.write("notes.txt", "TODO: finish\n").stage_all();
+
+    repo.guardy(&["run", "pre-commit", "--record", "session.json"])
+        .assert()
+        .failure();
+    let session = repo.read("session.json");
+    assert!(session.contains("\"notes.txt\""));
+    assert!(session.contains("\"git\""));
+
+    // The working copy moves on, the recorded index doesn't
+    repo.git(&["reset", "-q"]).assert().success();
+    repo.write("notes.txt", "done\n");
+
+    repo.guardy(&["replay", "session.json"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Reproduced"));
+
+    let worktrees = repo.git(&["worktree", "list"]).output().unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&worktrees.stdout).lines().count(),
+        1
+    );
+    let branches = repo.git(&["branch", "--list"]).output().unwrap();
+    assert!(!String::from_utf8_lossy(&branches.stdout).contains("guardy-replay"));
+}
+
+#[test]
+fn workspaces_command_runs_once_per_affected_package() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: false
+    custom:
+      - command: "echo {package_dir}: {files} >> packages.log"
+        name: "per-package"
+        workspaces: true
+"#,
+    )
+    .write("pnpm-workspace.yaml", "packages:\n  - \"packages/*\"\n")
+    .write("packages/ui/package.json", "{}")
+    .write("packages/api/package.json", "{}")
+    .write("packages/docs/package.json", "{}");
+    repo.stage_all();
+    repo.git(&["commit", "-q", "-m", "workspace", "--no-verify"])
+        .assert()
+        .success();
+
+    repo.write("packages/ui/button.tsx", "export {}\n")
+        .write("packages/api/server.ts", "export {}\n")
+        .write("README.md", "monorepo\n")
+        .stage_all();
+
+    repo.guardy(&["run", "pre-commit"]).assert().success();
+
+    let log = repo.read("packages.log");
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2, "{log}");
+    assert!(lines[0].starts_with("packages/api:") && lines[0].ends_with("packages/api/server.ts"));
+    assert!(lines[1].starts_with("packages/ui:") && lines[1].ends_with("packages/ui/button.tsx"));
+}
+
+#[test]
+fn hook_runs_append_to_the_event_stream() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  pre-commit:
+    enabled: true
+    parallel: false
+    builtin: ["scan_secrets"]
+    custom:
+      - command: "true"
+        name: "noop"
+"#,
+    )
+    .install_hooks();
+    let events = repo.path().join(".git/guardy-events.jsonl");
+
+    // Each hook process appends to the same stream
+    repo.stage_all();
+    repo.git(&["commit", "-qm", "chore: add guardy config"])
+        .env("GUARDY_EVENTS", &events)
+        .assert()
+        .success();
+    repo.write("config.env", &format!("GITHUB_TOKEN={}\n", github_token()))
+        .stage_all();
+    repo.git(&["commit", "-qm", "feat: add config"])
+        .env("GUARDY_EVENTS", &events)
+        .assert()
+        .failure();
+
+    let stream = std::fs::read_to_string(&events).unwrap();
+    assert!(!stream.contains(&github_token()), "{stream}");
+    let types: Vec<String> = stream
+        .lines()
+        .map(|line| {
+            let event: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(event["version"], 1);
+            event["type"].as_str().unwrap().to_string()
+        })
+        .collect();
+    for expected in [
+        "hook_command_started",
+        "hook_command_finished",
+        "scan_started",
+        "scan_finding",
+        "scan_completed",
+    ] {
+        assert!(types.iter().any(|t| t == expected), "{expected}: {stream}");
+    }
+}
+
+#[test]
+fn git_am_runs_the_applypatch_hooks() {
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+hooks:
+  applypatch-msg:
+    enabled: true
+    builtin: ["validate_commit_msg"]
+  pre-applypatch:
+    enabled: true
+    builtin: ["scan_secrets"]
+"#,
+    )
+    .install_hooks();
+    repo.stage_all();
+    repo.commit("chore: add guardy config").success();
+
+    // Patches written elsewhere, without guardy's hooks
+    repo.write("config.env", &format!("GITHUB_TOKEN={}\n", github_token()))
+        .stage_all();
+    repo.git(&["commit", "-qm", "feat: add config", "--no-verify"])
+        .assert()
+        .success();
+    repo.write("notes.txt", "meeting notes\n").stage_all();
+    repo.git(&["commit", "-qm", "added some notes", "--no-verify"])
+        .assert()
+        .success();
+    repo.git(&["format-patch", "-q", "-2", "-o", ".git/patches"])
+        .assert()
+        .success();
+    repo.git(&["reset", "-q", "--hard", "HEAD~2"])
+        .assert()
+        .success();
+    assert_eq!(repo.commit_count(), 2);
+
+    repo.git(&["am", ".git/patches/0001-feat-add-config.patch"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Secrets detected"));
+    repo.git(&["am", "--abort"]).assert().success();
+
+    repo.git(&["am", ".git/patches/0002-added-some-notes.patch"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("conventional"));
+    repo.git(&["am", "--abort"]).assert().success();
+    assert_eq!(repo.commit_count(), 2);
+}
+
+#[test]
+fn protect_branches_only_allows_fast_forwards_to_main() {
+    let repo = TestRepo::new();
+    let _remote = repo.add_bare_remote();
+    repo.git(&["push", "-q", "origin", "main"])
+        .assert()
+        .success();
+    repo.config(
+        r#"
+hooks:
+  pre-push:
+    enabled: true
+    builtin: ["protect_branches"]
+branch_protection:
+  protected_branches: ["main", "release/*"]
+  push_policy: fast_forward
+"#,
+    )
+    .install_hooks();
+    repo.stage_all();
+    repo.commit("chore: add guardy config").success();
+
+    repo.git(&["push", "origin", "main"]).assert().success();
+
+    repo.git(&["commit", "-q", "--amend", "-m", "chore: configure guardy"])
+        .assert()
+        .success();
+    repo.git(&["push", "--force", "origin", "main"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("main - not a fast-forward"));
+    repo.git(&["push", "origin", "main:release/1.0", ":main"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(
+            "main - deleting a protected branch",
+        ));
+    repo.git(&["push", "origin", "main:feature/rewrite"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn sync_reads_local_directories_without_git() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(shared.path().join("lint")).unwrap();
+    std::fs::write(shared.path().join("lint/.eslintrc.json"), "{}\n").unwrap();
+    std::fs::write(shared.path().join("lint/notes.local.md"), "draft\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      source_path: "lint"
+      dest_path: "config"
+      exclude: ["*.local.*"]
+"#,
+        shared.path().display()
+    ));
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    assert_eq!(repo.read("config/.eslintrc.json"), "{}\n");
+    assert!(!repo.path().join("config/notes.local.md").exists());
+    // The source is only read
+    let mut source: Vec<_> = std::fs::read_dir(shared.path().join("lint"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    source.sort();
+    assert_eq!(source, [".eslintrc.json", "notes.local.md"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_keeps_executable_bits_and_preserves_links_inside_the_source() {
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    let shared = tempfile::TempDir::new().unwrap();
+    let source = shared.path().join("configs");
+    std::fs::create_dir_all(source.join("scripts")).unwrap();
+    std::fs::write(source.join("scripts/lint.sh"), "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(
+        source.join("scripts/lint.sh"),
+        std::fs::Permissions::from_mode(0o755),
+    )
+    .unwrap();
+    std::fs::write(shared.path().join("secret"), "token\n").unwrap();
+    symlink("scripts/lint.sh", source.join("lint")).unwrap();
+    symlink("scripts", source.join("bin")).unwrap();
+    symlink("../secret", source.join("escape")).unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+      preserve_symlinks: true
+"#,
+        source.display()
+    ));
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    let config = repo.path().join("config");
+    let mode = std::fs::metadata(config.join("scripts/lint.sh"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o111, 0o111);
+    assert_eq!(
+        std::fs::read_link(config.join("lint")).unwrap(),
+        std::path::Path::new("scripts/lint.sh")
+    );
+    assert_eq!(
+        std::fs::read_link(config.join("bin")).unwrap(),
+        std::path::Path::new("scripts")
+    );
+    // Links leaving the source are followed, never recreated
+    assert!(!config.join("escape").is_symlink());
+    assert_eq!(repo.read("config/escape"), "token\n");
+
+    // Dropping the executable bit locally is drift like an edit
+    std::fs::set_permissions(
+        config.join("scripts/lint.sh"),
+        std::fs::Permissions::from_mode(0o644),
+    )
+    .unwrap();
+    repo.guardy(&["sync", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("config/scripts/lint.sh"));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    repo.guardy(&["sync", "check"]).assert().success();
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_runs_post_sync_commands_after_files_changed() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::write(shared.path().join("ci.yml"), "cache: false\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+      post_sync:
+        - command: "echo \"$GUARDY_SYNC_REPO $GUARDY_SYNC_FILES\" >> post-sync.log"
+        - command: "exit 4"
+          description: "Optional formatter"
+          fail_on_error: false
+"#,
+        shared.path().display()
+    ));
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Optional formatter failed"));
+    assert_eq!(repo.read("post-sync.log"), "platform config/ci.yml\n");
+
+    // Nothing changed, nothing to run
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("post-sync.log"), "platform config/ci.yml\n");
+
+    std::fs::write(shared.path().join("ci.yml"), "cache: true\n").unwrap();
+    repo.config(
+        &repo
+            .read("guardy.yaml")
+            .replace("fail_on_error: false", "fail_on_error: true"),
+    );
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "post_sync command 'Optional formatter' of 'platform' failed (exit code 4)",
+        ));
+    assert_eq!(repo.read("config/ci.yml"), "cache: true\n");
+}
+
+#[test]
+fn sync_maps_upstream_paths_to_the_destination_layout() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(shared.path().join("workflows")).unwrap();
+    std::fs::write(shared.path().join("workflows/ci.yml"), "on: push\n").unwrap();
+    std::fs::write(shared.path().join("lint.yaml"), "rules: []\n").unwrap();
+    std::fs::write(shared.path().join("README.md"), "shared\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      map:
+        - "workflows/*.yml -> .github/workflows/*.yml"
+        - "**/*.yaml -> config/**/*.yml"
+"#,
+        shared.path().display()
+    ));
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    assert_eq!(repo.read(".github/workflows/ci.yml"), "on: push\n");
+    assert_eq!(repo.read("config/lint.yml"), "rules: []\n");
+    assert_eq!(repo.read("README.md"), "shared\n");
+    assert!(!repo.path().join("workflows").exists());
+
+    repo.write(".github/workflows/ci.yml", "on: pull_request\n");
+    repo.guardy(&["sync", "check"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains(".github/workflows/ci.yml"));
+
+    repo.config(
+        "sync:\n  repos:\n    - name: x\n      repo: ../x\n      map: [\"*.yml -> ../*.yml\"]\n",
+    );
+    repo.guardy(&["sync", "status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("inside dest_path"));
+}
+
+#[test]
+fn sync_leaves_files_listed_in_guardyignore_alone() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::write(shared.path().join("ci.yml"), "cache: true\n").unwrap();
+    std::fs::write(shared.path().join("deny.toml"), "[bans]\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+"#,
+        shared.path().display()
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    // The destination keeps its own deny.toml from now on
+    repo.write("config/.guardyignore", "# ours\ndeny.toml\n")
+        .write("config/deny.toml", "[bans]\nmultiple-versions = \"deny\"\n");
+    std::fs::write(shared.path().join("ci.yml"), "cache: false\n").unwrap();
+    std::fs::remove_file(shared.path().join("deny.toml")).unwrap();
+
+    repo.guardy(&["sync", "update", "--force", "--prune"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+    assert_eq!(
+        repo.read("config/deny.toml"),
+        "[bans]\nmultiple-versions = \"deny\"\n"
+    );
+    repo.guardy(&["sync", "check"]).assert().success();
+}
+
+#[test]
+fn sync_prunes_files_removed_upstream_when_configured() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(shared.path().join("workflows/legacy")).unwrap();
+    std::fs::write(shared.path().join("workflows/ci.yml"), "on: push\n").unwrap();
+    std::fs::write(
+        shared.path().join("workflows/legacy/nightly.yml"),
+        "on: schedule\n",
+    )
+    .unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+      prune: true
+"#,
+        shared.path().display()
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    assert!(
+        repo.path()
+            .join("config/workflows/legacy/nightly.yml")
+            .exists()
+    );
+
+    std::fs::remove_dir_all(shared.path().join("workflows/legacy")).unwrap();
+    // `--no-prune` still wins over the configuration
+    repo.guardy(&["sync", "update", "--force", "--no-prune"])
+        .assert()
+        .success();
+    assert!(
+        repo.path()
+            .join("config/workflows/legacy/nightly.yml")
+            .exists()
+    );
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted"));
+    assert!(!repo.path().join("config/workflows/legacy").exists());
+    assert_eq!(repo.read("config/workflows/ci.yml"), "on: push\n");
+}
+
+#[test]
+fn sync_push_offers_local_edits_upstream() {
+    let upstream = TestRepo::new();
+    upstream.write("shared/ci.yml", "cache: false\n");
+    upstream.stage_all().commit("ci: shared pipeline").success();
+
+    let repo = TestRepo::new();
+    let url = "https://git.example.test/org/shared.git";
+    repo.alias_url(url, upstream.path());
+    for (key, value) in [
+        ("user.name", "Guardy Test"),
+        ("user.email", "test@guardy.dev"),
+    ] {
+        repo.git(&["config", "--global", key, value])
+            .assert()
+            .success();
+    }
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "shared"
+      repo: "{url}"
+      version: "main"
+      source_path: "shared"
+      dest_path: "config"
+"#
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    repo.write("config/ci.yml", "cache: true\n");
+
+    repo.guardy(&["sync", "push", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("config/ci.yml"))
+        .stdout(predicate::str::contains(
+            "would push 1 file to guardy/sync-from-tmp",
+        ));
+    upstream
+        .git(&["rev-parse", "--verify", "--quiet", "sync-fix"])
+        .assert()
+        .failure();
+
+    repo.guardy(&[
+        "sync",
+        "push",
+        "--branch",
+        "sync-fix",
+        "-m",
+        "ci: enable caching",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Pushed 1 file to sync-fix"));
+
+    upstream
+        .git(&["show", "sync-fix:shared/ci.yml"])
+        .assert()
+        .success()
+        .stdout("cache: true\n");
+    upstream
+        .git(&["log", "-1", "--format=%s", "sync-fix"])
+        .assert()
+        .success()
+        .stdout("ci: enable caching\n");
+    // The cache is back on the pinned version, so the edit still shows as drift
+    repo.guardy(&["sync", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ci.yml"));
+}
+
+#[test]
+fn sync_check_fails_on_drift_with_a_json_summary() {
+    let shared = tempfile::TempDir::new().unwrap();
+    std::fs::write(shared.path().join("rustfmt.toml"), "edition = \"2024\"\n").unwrap();
+    std::fs::write(shared.path().join("deny.toml"), "[bans]\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+"#,
+        shared.path().display()
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    repo.guardy(&["sync", "check"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("platform matches"));
+
+    // Same size, different content
+    repo.write("config/rustfmt.toml", "edition = \"2021\"\n");
+    repo.remove("config/deny.toml");
+    let output = repo
+        .guardy(&["sync", "check", "--format", "json"])
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let summary: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(summary["in_sync"], false);
+    assert_eq!(
+        summary["repos"][0]["modified"],
+        serde_json::json!(["config/rustfmt.toml"])
+    );
+    assert_eq!(
+        summary["repos"][0]["missing"],
+        serde_json::json!(["config/deny.toml"])
+    );
+}
+
+#[test]
+fn sync_status_and_diff_report_file_states_as_json() {
+    let shared = tempfile::TempDir::new().unwrap();
+    for file in ["clippy.toml", "deny.toml", "rustfmt.toml", "taplo.toml"] {
+        std::fs::write(shared.path().join(file), "original = true\n").unwrap();
+    }
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+"#,
+        shared.path().display()
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    repo.write("config/clippy.toml", "original = false\n");
+    repo.remove("config/deny.toml");
+    std::fs::write(shared.path().join("rustfmt.toml"), "upstream = true\n").unwrap();
+
+    let states = |args: &[&str]| {
+        let output = repo
+            .guardy(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        report["repos"][0]["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|file| (file["path"].as_str().unwrap().to_string(), file.clone()))
+            .collect::<std::collections::BTreeMap<_, _>>()
+    };
+    let status = states(&["sync", "status", "--format", "json"]);
+    let state = |path: &str| status[path]["state"].as_str().unwrap().to_string();
+    assert_eq!(state("config/clippy.toml"), "modified");
+    assert_eq!(state("config/deny.toml"), "missing");
+    assert_eq!(state("config/rustfmt.toml"), "upstream_changed");
+    assert_eq!(state("config/taplo.toml"), "in_sync");
+    assert!(status["config/rustfmt.toml"].get("diff").is_none());
+
+    let diff = states(&["sync", "diff", "--format", "json"]);
+    assert!(
+        diff["config/rustfmt.toml"]["diff"]
+            .as_str()
+            .unwrap()
+            .contains("+upstream = true")
+    );
+    assert!(diff["config/taplo.toml"].get("diff").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_pulls_oci_artifacts_by_digest() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for the ORAS CLI: `v1` resolves to one digest, pulls unpack one file
+    let bin = tempfile::TempDir::new().unwrap();
+    let oras = bin.path().join("oras");
+    std::fs::write(
+        &oras,
+        r#"#!/bin/sh
+echo "$@" >> "$ORAS_LOG"
+case "$1" in
+  resolve) echo "sha256:0123abcd" ;;
+  pull) mkdir -p "$3/lint" && echo "max_width = 100" > "$3/lint/rustfmt.toml" ;;
+esac
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&oras, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let log = bin.path().join("oras.log");
+
+    let repo = TestRepo::new();
+    repo.config(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "oci://registry.example.com/org/platform-configs:v1"
+      source_path: "lint"
+      dest_path: "."
+"#,
+    );
+    let path = std::env::join_paths(
+        std::iter::once(bin.path().to_path_buf())
+            .chain(common::guardy_bin().parent().map(Into::into))
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+    let sync = |args: &[&str]| {
+        repo.guardy(args)
+            .env("PATH", &path)
+            .env("ORAS_LOG", &log)
+            .assert()
+            .success();
+    };
+
+    sync(&["sync", "upgrade", "--force"]);
+    assert_eq!(repo.read("rustfmt.toml"), "max_width = 100\n");
+    assert!(
+        repo.read("guardy-sync.lock")
+            .contains("commit = \"sha256:0123abcd\"")
+    );
+
+    // The locked digest is cached already, so nothing is resolved or pulled again
+    sync(&["sync", "update", "--force", "--locked"]);
+    let calls = std::fs::read_to_string(&log).unwrap();
+    let calls: Vec<&str> = calls.lines().collect();
+    assert_eq!(calls.len(), 2, "{calls:?}");
+    assert_eq!(
+        calls[0],
+        "resolve registry.example.com/org/platform-configs:v1"
+    );
+    assert!(calls[1].starts_with("pull --output "), "{}", calls[1]);
+    assert!(
+        calls[1].ends_with(" registry.example.com/org/platform-configs@sha256:0123abcd"),
+        "{}",
+        calls[1]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_verifies_signed_checksum_manifests_before_applying() {
+    use sha2::{Digest, Sha256};
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for minisign: signatures reading "trusted" are valid
+    let bin = tempfile::TempDir::new().unwrap();
+    let minisign = bin.path().join("minisign");
+    std::fs::write(
+        &minisign,
+        "#!/bin/sh\ngrep -q trusted \"$6\" || { echo bad signature >&2; exit 1; }\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&minisign, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = std::env::join_paths(
+        std::iter::once(bin.path().to_path_buf())
+            .chain(std::env::split_paths(&std::env::var_os("PATH").unwrap())),
+    )
+    .unwrap();
+
+    let shared = tempfile::TempDir::new().unwrap();
+    let publish = |content: &str, signature: &str| {
+        std::fs::write(shared.path().join("ci.yml"), content).unwrap();
+        let digest = format!("{:x}", Sha256::digest(content.as_bytes()));
+        std::fs::write(
+            shared.path().join("SHA256SUMS"),
+            format!("{digest}  ci.yml\n"),
+        )
+        .unwrap();
+        std::fs::write(shared.path().join("SHA256SUMS.minisig"), signature).unwrap();
+    };
+    publish("cache: false\n", "trusted");
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "platform"
+      repo: "file://{}"
+      dest_path: "config"
+      verify:
+        minisign_key: "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+"#,
+        shared.path().display()
+    ));
+    let sync = || {
+        repo.guardy(&["sync", "update", "--force"])
+            .env("PATH", &path)
+            .assert()
+    };
+
+    sync()
+        .success()
+        .stdout(predicate::str::contains("verified against SHA256SUMS"));
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+    // The manifest and its signature are not synced
+    assert!(!repo.path().join("config/SHA256SUMS").exists());
+
+    // Tampered content no longer matches the signed manifest
+    std::fs::write(shared.path().join("ci.yml"), "cache: true\n").unwrap();
+    sync()
+        .failure()
+        .stderr(predicate::str::contains("ci.yml: checksum mismatch"));
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+
+    publish("cache: true\n", "forged");
+    sync()
+        .failure()
+        .stderr(predicate::str::contains("is not valid (minisign)"));
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+}
+
+#[test]
+fn sync_status_works_offline_until_refreshed() {
+    let upstream = TestRepo::new();
+    upstream.write("shared/ci.yml", "cache: false\n");
+    upstream.stage_all().commit("ci: shared pipeline").success();
+
+    let repo = TestRepo::new();
+    let url = "https://git.example.test/org/shared.git";
+    repo.alias_url(url, upstream.path());
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "shared"
+      repo: "{url}"
+      version: "main"
+      source_path: "shared"
+      dest_path: "config"
+"#
+    ));
+
+    repo.guardy(&["sync", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checked:    never"))
+        .stdout(predicate::str::contains("--refresh"));
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    upstream.write("shared/ci.yml", "cache: true\n");
+    upstream.stage_all().commit("ci: enable caching").success();
+
+    // Without fetching, the cache still matches
+    repo.guardy(&["sync", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Checked:    just now"))
+        .stdout(predicate::str::contains("All files are in sync"));
+
+    repo.guardy(&["sync", "status", "--refresh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 file out of sync"));
+    let output = repo
+        .guardy(&["sync", "status", "--format", "json"])
+        .output()
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["repos"][0]["last_checked"].is_u64(), "{report}");
+    assert_eq!(report["repos"][0]["files"][0]["state"], "upstream_changed");
+}
+
+#[test]
+fn sync_lockfile_pins_commits_until_upgraded() {
+    let upstream = TestRepo::new();
+    upstream.write("shared/ci.yml", "cache: false\n");
+    upstream.stage_all().commit("ci: shared pipeline").success();
+    let head = |upstream: &TestRepo| {
+        let output = upstream.git(&["rev-parse", "HEAD"]).output().unwrap();
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    let first = head(&upstream);
+
+    let repo = TestRepo::new();
+    let url = "https://git.example.test/org/shared.git";
+    repo.alias_url(url, upstream.path());
+    let config = |version: &str| {
+        format!(
+            r#"
+sync:
+  repos:
+    - name: "shared"
+      repo: "{url}"
+      version: "{version}"
+      source_path: "shared"
+      dest_path: "config"
+"#
+        )
+    };
+    repo.config(&config("main"));
+
+    repo.guardy(&["sync", "upgrade", "--force"])
+        .assert()
+        .success();
+    let lock = repo.read("guardy-sync.lock");
+    assert!(lock.contains(&format!("commit = \"{first}\"")), "{lock}");
+    // Files are recorded by their git blob id
+    assert!(
+        lock.contains("\"ci.yml\" = \"d7783cb361c2737c07ecfff0613c98e33dc7694c\""),
+        "{lock}"
+    );
+
+    upstream.write("shared/ci.yml", "cache: true\n");
+    upstream.stage_all().commit("ci: enable caching").success();
+
+    // Updates stay on the locked commit, even from an empty cache
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+    std::fs::remove_dir_all(repo.path().join(".guardy/cache")).unwrap();
+    repo.guardy(&["sync", "update", "--force", "--locked"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+
+    repo.guardy(&["sync", "upgrade", "shared", "--force"])
+        .assert()
+        .success();
+    assert_eq!(repo.read("config/ci.yml"), "cache: true\n");
+    assert!(
+        repo.read("guardy-sync.lock")
+            .contains(&format!("commit = \"{}\"", head(&upstream)))
+    );
+
+    // A version the lock wasn't resolved from can't be synced --locked
+    repo.config(&config("release"));
+    repo.guardy(&["sync", "update", "--force", "--locked"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "guardy-sync.lock has no entry for 'shared'",
+        ));
+}
+
+#[test]
+fn sync_checks_out_only_the_source_path() {
+    let upstream = TestRepo::new();
+    upstream.write("shared/ci.yml", "cache: false\n");
+    upstream.write("services/api/schema.json", "{}\n");
+    upstream.stage_all().commit("ci: shared pipeline").success();
+
+    let repo = TestRepo::new();
+    let url = "https://git.example.test/org/monorepo.git";
+    repo.alias_url(url, upstream.path());
+    let config = |source_path: &str| {
+        format!(
+            r#"
+sync:
+  repos:
+    - name: "shared"
+      repo: "{url}"
+      version: "main"
+      source_path: "{source_path}"
+      dest_path: "config"
+"#
+        )
+    };
+    repo.config(&config("./shared/"));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    let clone = repo.path().join(".guardy/cache/monorepo");
+    assert_eq!(repo.read("config/ci.yml"), "cache: false\n");
+    assert!(clone.join("shared/ci.yml").exists());
+    assert!(!clone.join("services").exists());
+
+    // Syncing from the root widens the clone again
+    repo.config(&config("."));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+    assert!(clone.join("services/api/schema.json").exists());
+}
+
+#[test]
+fn sync_profiles_select_repositories() {
+    let editor = tempfile::TempDir::new().unwrap();
+    std::fs::write(editor.path().join(".editorconfig"), "root = true\n").unwrap();
+    let ci = tempfile::TempDir::new().unwrap();
+    std::fs::write(ci.path().join("ci.yml"), "cache: true\n").unwrap();
+
+    let repo = TestRepo::new();
+    repo.config(&format!(
+        r#"
+sync:
+  repos:
+    - name: "editor"
+      repo: "file://{}"
+    - name: "ci"
+      repo: "file://{}"
+      dest_path: ".github"
+  profiles:
+    ci: ["ci"]
+"#,
+        editor.path().display(),
+        ci.path().display()
+    ));
+
+    repo.guardy(&["sync", "update", "--force", "--profile", "ci"])
+        .assert()
+        .success();
+    assert_eq!(repo.read(".github/ci.yml"), "cache: true\n");
+    assert!(!repo.path().join(".editorconfig").exists());
+
+    repo.guardy(&["sync", "check"])
+        .env("GUARDY_SYNC_PROFILE", "ci")
+        .assert()
+        .success();
+    repo.guardy(&["sync", "check"]).assert().code(1);
+    repo.guardy(&["sync", "status", "--profile", "dev"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown sync profile 'dev' (configured: ci)",
+        ));
+}
+
+#[test]
+fn sync_resolves_local_edits_with_the_conflict_strategy() {
+    let shared = tempfile::TempDir::new().unwrap();
+    let write = |name: &str, content: &str| {
+        let path = shared.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write("ci/ci.yml", "a: 1\nb: 2\nc: 3\n");
+    write("deny/deny.toml", "[bans]\n");
+
+    let repo = TestRepo::new();
+    let entry = |name: &str, strategy: &str| {
+        format!(
+            r#"
+    - name: "{name}"
+      repo: "file://{}/{name}"
+      on_conflict: {strategy}"#,
+            shared.path().display()
+        )
+    };
+    repo.config(&format!(
+        "sync:\n  repos:{}{}\n",
+        entry("ci", "merge"),
+        entry("deny", "ours")
+    ));
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success();
+
+    repo.write("ci.yml", "a: local\nb: 2\nc: 3\n");
+    repo.write("deny.toml", "[bans]\nmultiple-versions = \"deny\"\n");
+    write("ci/ci.yml", "a: 1\nb: 2\nc: upstream\n");
+    write("deny/deny.toml", "[licenses]\n");
+
+    repo.guardy(&["sync", "update", "--force"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Merged upstream changes into ./ci.yml",
+        ))
+        .stdout(predicate::str::contains(
+            "Kept local changes to ./deny.toml",
+        ));
+    assert_eq!(repo.read("ci.yml"), "a: local\nb: 2\nc: upstream\n");
+    assert_eq!(
+        repo.read("deny.toml"),
+        "[bans]\nmultiple-versions = \"deny\"\n"
+    );
+}
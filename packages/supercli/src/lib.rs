@@ -31,6 +31,17 @@
 //! - Support for unlimited styling parameters
 //! - Automatic output mode adaptation
 //!
+//! ### 🧱 Grouped Output
+//! - `section!("Running pre-commit hook")` - `── Title ───` header, closed with a
+//!   `✔ ... completed in 1.25s` / `✗ ... failed after 1.25s` trailer
+//! - `step!(3, 5, "lint")` - `[3/5] lint` header, closed with `✔ lint (120ms)`,
+//!   `✗ lint failed (120ms)`, a warning or a skip note
+//!
+//! ### 🔢 Humanized Values
+//! - `human_duration()` - `850ms`, `2.35s`, `3m 05s`, `1h 02m`
+//! - `human_bytes()` - `512 B`, `1.5 KB`, `10 MB`
+//! - `human_count()` - `1 file`, `1,204 files`, `3 directories`
+//!
 //! ### 🎛️ Output Mode Management
 //! - `GUARDY_OUTPUT_STYLE`: color, monochrome, none
 //! - `NO_COLOR` standard compliance
@@ -114,6 +125,7 @@
 //!
 //! - **Zero-dependency core**: Only requires starbase-styles
 //! - **Optional clap integration**: Enable with `clap` feature
+//! - **Optional prompts**: `multi_select` and `confirm` with the `prompts` feature
 //! - **Environment aware**: Respects NO_COLOR and custom style variables
 //! - **Cross-platform**: Works on Windows, macOS, and Linux
 //! - **Performance focused**: Minimal runtime overhead
@@ -125,6 +137,9 @@ pub mod prelude;
 #[cfg(feature = "clap")]
 pub mod clap;
 
+#[cfg(feature = "prompts")]
+pub mod prompts;
+
 // Re-export starbase_styles for full compatibility
 pub use starbase_styles;
 
@@ -0,0 +1,107 @@
+//! Human-readable formatting for durations, byte sizes and counts
+//!
+//! These keep numbers in CLI output consistent across commands - a scan summary,
+//! a sync report and a hook timing line should all say "1.25s", "3.4 MB" and
+//! "1,204 files" the same way.
+
+use std::time::Duration;
+
+/// Format a duration with a unit that suits its size
+///
+/// ```rust
+/// use std::time::Duration;
+/// use supercli::prelude::*;
+///
+/// assert_eq!(human_duration(Duration::from_millis(850)), "850ms");
+/// assert_eq!(human_duration(Duration::from_millis(2350)), "2.35s");
+/// assert_eq!(human_duration(Duration::from_secs(185)), "3m 05s");
+/// assert_eq!(human_duration(Duration::from_secs(3720)), "1h 02m");
+/// ```
+pub fn human_duration(duration: Duration) -> String {
+    let total_ms = duration.as_millis();
+    let total_secs = duration.as_secs();
+
+    if total_ms < 1_000 {
+        format!("{total_ms}ms")
+    } else if total_secs < 60 {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else if total_secs < 3_600 {
+        format!("{}m {:02}s", total_secs / 60, total_secs % 60)
+    } else {
+        format!("{}h {:02}m", total_secs / 3_600, (total_secs % 3_600) / 60)
+    }
+}
+
+/// Format a byte size using 1024-based units (B, KB, MB, GB, TB)
+///
+/// ```rust
+/// use supercli::prelude::*;
+///
+/// assert_eq!(human_bytes(512), "512 B");
+/// assert_eq!(human_bytes(1536), "1.5 KB");
+/// assert_eq!(human_bytes(10 * 1024 * 1024), "10 MB");
+/// ```
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    let formatted = format!("{value:.1}");
+    let formatted = formatted.strip_suffix(".0").unwrap_or(&formatted);
+    format!("{formatted} {}", UNITS[unit])
+}
+
+/// Format a count with thousands separators and a pluralized noun
+///
+/// Regular English plurals are derived from the singular (`file` → `files`,
+/// `match` → `matches`, `directory` → `directories`).
+///
+/// ```rust
+/// use supercli::prelude::*;
+///
+/// assert_eq!(human_count(1, "file"), "1 file");
+/// assert_eq!(human_count(1204, "file"), "1,204 files");
+/// assert_eq!(human_count(0, "match"), "0 matches");
+/// assert_eq!(human_count(3, "directory"), "3 directories");
+/// ```
+pub fn human_count(count: usize, noun: &str) -> String {
+    let noun = if count == 1 {
+        noun.to_string()
+    } else {
+        pluralize(noun)
+    };
+    format!("{} {noun}", group_thousands(count))
+}
+
+fn pluralize(noun: &str) -> String {
+    if let Some(stem) = noun.strip_suffix('y')
+        && !stem.ends_with(['a', 'e', 'i', 'o', 'u'])
+    {
+        return format!("{stem}ies");
+    }
+    if noun.ends_with(['s', 'x', 'z']) || noun.ends_with("ch") || noun.ends_with("sh") {
+        return format!("{noun}es");
+    }
+    format!("{noun}s")
+}
+
+fn group_thousands(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
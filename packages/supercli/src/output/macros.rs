@@ -10,6 +10,9 @@ use starbase_styles::color::owo::OwoColorize;
 
 /// Internal implementation for success messages
 pub fn success_impl(message: &str, symbol: &str) {
+    if crate::output::is_quiet() {
+        return;
+    }
     #[cfg(feature = "clap")]
     {
         match crate::clap::get_output_style() {
@@ -45,6 +48,9 @@ macro_rules! success {
 
 /// Internal implementation for warning messages
 pub fn warning_impl(message: &str, symbol: &str) {
+    if crate::output::is_quiet() {
+        return;
+    }
     #[cfg(feature = "clap")]
     {
         match crate::clap::get_output_style() {
@@ -80,6 +86,9 @@ macro_rules! warning {
 
 /// Internal implementation for info messages
 pub fn info_impl(message: &str, symbol: &str) {
+    if crate::output::is_quiet() {
+        return;
+    }
     #[cfg(feature = "clap")]
     {
         match crate::clap::get_output_style() {
@@ -116,6 +125,9 @@ macro_rules! info {
 
 /// Internal implementation for error messages
 pub fn error_impl(message: &str, symbol: &str) {
+    if crate::output::is_quiet() {
+        return;
+    }
     #[cfg(feature = "clap")]
     {
         match crate::clap::get_output_style() {
@@ -4,8 +4,35 @@
 //! - Semantic macros (success!, warning!, info!, error!)
 //! - Fine-grained styling (styled! macro)  
 //! - Symbol constants
+//! - Human-readable durations, byte sizes and counts
+//! - Section headers and numbered steps (section!, step!)
 //! - Output mode handling
+//! - Quiet mode for commands that print machine-readable output
 
+pub mod humanize;
 pub mod macros;
+pub mod sections;
 pub mod styling;
 pub mod symbols;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Silence every output macro, e.g. while a command writes JSON to stdout
+///
+/// ```rust
+/// use supercli::prelude::*;
+///
+/// set_quiet(true);
+/// info!("not printed");
+/// set_quiet(false);
+/// ```
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether output macros are currently silenced
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
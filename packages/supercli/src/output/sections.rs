@@ -0,0 +1,198 @@
+//! Grouped output: section headers and numbered steps
+//!
+//! Multi-step commands open a [`Section`] and report each unit of work as a
+//! [`Step`]. Both end with a success or failure trailer carrying the elapsed
+//! time, so installs, syncs and hook runs share one visual structure instead of
+//! hand-rolled separators.
+//!
+//! ```text
+//! ── Running pre-commit hook ─────────────────────────────────
+//! [1/2] scan_secrets
+//!   ✔ scan_secrets (120ms)
+//! [2/2] lint
+//!   ✗ lint failed (1.25s)
+//! ✗ Running pre-commit hook failed after 1.38s
+//! ```
+
+use std::time::{Duration, Instant};
+
+use super::humanize::human_duration;
+use super::symbols::{CHECK_MARK, CROSS_MARK, WARNING_SIGN};
+
+/// Width of a section header, rule included
+pub const RULE_WIDTH: usize = 60;
+
+/// Symbol of a skipped step
+pub const SKIP_SIGN: &str = "⏭";
+
+/// A titled group of output, timed from its header to its trailer
+#[derive(Debug)]
+#[must_use = "end the section with success(), fail() or finish()"]
+pub struct Section {
+    title: String,
+    started: Instant,
+}
+
+impl Section {
+    /// Print the `── Title ───` header and start the clock
+    pub fn start(title: &str) -> Self {
+        let fill = RULE_WIDTH.saturating_sub(title.chars().count() + 4);
+        crate::styled!(
+            "\n{} {} {}",
+            ("──", "muted"),
+            (title, "property"),
+            ("─".repeat(fill), "muted")
+        );
+        Self {
+            title: title.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// `✔ Title completed in 1.25s`
+    pub fn success(self) {
+        crate::styled!(
+            "{} {} {}",
+            (CHECK_MARK, "success_symbol"),
+            (format!("{} completed in", self.title), "success"),
+            (human_duration(self.elapsed()), "time")
+        );
+    }
+
+    /// `✗ Title failed after 1.25s`
+    pub fn fail(self) {
+        crate::styled!(
+            "{} {} {}",
+            (CROSS_MARK, "error_symbol"),
+            (format!("{} failed after", self.title), "error"),
+            (human_duration(self.elapsed()), "time")
+        );
+    }
+
+    /// The success or failure trailer, depending on `succeeded`
+    pub fn finish(self, succeeded: bool) {
+        if succeeded {
+            self.success()
+        } else {
+            self.fail()
+        }
+    }
+}
+
+/// One numbered unit of work, e.g. `[3/5] lint`
+#[derive(Debug)]
+#[must_use = "end the step with success(), fail(), warn() or skip()"]
+pub struct Step {
+    label: String,
+    started: Instant,
+}
+
+impl Step {
+    /// Print the `[current/total] label` header and start the clock
+    pub fn start(current: usize, total: usize, label: &str) -> Self {
+        crate::styled!(
+            "{} {}",
+            (format!("[{current}/{total}]"), "muted"),
+            (label, "property")
+        );
+        Self {
+            label: label.to_string(),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// `  ✔ label (120ms)`
+    pub fn success(self) {
+        self.trailer(CHECK_MARK, "success_symbol", "");
+    }
+
+    /// `  ✗ label failed (120ms)`
+    pub fn fail(self) {
+        self.trailer(CROSS_MARK, "error_symbol", " failed");
+    }
+
+    /// `  ⚠ label (note, 120ms)` - the step had problems that don't count as a failure
+    pub fn warn(self, note: &str) {
+        crate::styled!(
+            "  {} {} {}",
+            (WARNING_SIGN, "warning_symbol"),
+            (&self.label, "primary"),
+            (
+                format!("({note}, {})", human_duration(self.elapsed())),
+                "muted"
+            )
+        );
+    }
+
+    /// `  ⏭ label skipped (reason)`
+    pub fn skip(self, reason: &str) {
+        crate::styled!(
+            "  {} {} {}",
+            (SKIP_SIGN, "muted"),
+            (format!("{} skipped", self.label), "primary"),
+            (format!("({reason})"), "muted")
+        );
+    }
+
+    /// The success or failure trailer, depending on `succeeded`
+    pub fn finish(self, succeeded: bool) {
+        if succeeded {
+            self.success()
+        } else {
+            self.fail()
+        }
+    }
+
+    fn trailer(&self, symbol: &str, symbol_style: &str, verdict: &str) {
+        crate::styled!(
+            "  {} {} {}",
+            (symbol, symbol_style),
+            (format!("{}{verdict}", self.label), "primary"),
+            (format!("({})", human_duration(self.elapsed())), "time")
+        );
+    }
+}
+
+/// Open a section: print a `── Title ───` header and return the [`Section`] to
+/// close with a trailer
+///
+/// # Examples
+/// ```rust
+/// use supercli::section;
+///
+/// let section = section!("Installing hooks");
+/// // ... the section's output ...
+/// section.success(); // ✔ Installing hooks completed in 12ms
+/// ```
+#[macro_export]
+macro_rules! section {
+    ($title:expr) => {
+        $crate::output::sections::Section::start($title)
+    };
+}
+
+/// Start step `current` of `total`: print `[current/total] label` and return the
+/// [`Step`] to close with a trailer
+///
+/// # Examples
+/// ```rust
+/// use supercli::step;
+///
+/// let step = step!(3, 5, "lint");
+/// // ... the step's output ...
+/// step.success(); //   ✔ lint (1.25s)
+/// ```
+#[macro_export]
+macro_rules! step {
+    ($current:expr, $total:expr, $label:expr) => {
+        $crate::output::sections::Step::start($current, $total, $label)
+    };
+}
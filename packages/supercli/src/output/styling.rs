@@ -151,7 +151,7 @@ pub fn apply_style<T: AsRef<str>>(text: T, style: &str) -> String {
 macro_rules! styled {
     // Single string with symbol replacement
     ($text:expr) => {
-        {
+        if !$crate::output::is_quiet() {
             let result = $crate::output::styling::replace_symbols($text);
             println!("{}", result);
         }
@@ -159,7 +159,7 @@ macro_rules! styled {
 
     // Fine-grained styling with tuples (existing functionality)
     ($format:expr, $(($text:expr, $style:expr)),+ $(,)?) => {
-        {
+        if !$crate::output::is_quiet() {
             // First replace any symbols in the format string
             let mut result = $crate::output::styling::replace_symbols($format);
 
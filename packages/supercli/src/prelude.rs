@@ -6,11 +6,20 @@
 //! ```
 
 // SuperCLI semantic macros - core functionality
-pub use crate::{error, info, styled, success, warning};
+pub use crate::{error, info, section, step, styled, success, warning};
+
+// Grouped output returned by section! and step!
+pub use crate::output::sections::{Section, Step};
+
+// Quiet mode toggle
+pub use crate::output::set_quiet;
 
 // Symbol constants
 pub use crate::output::symbols;
 
+// Human-readable formatting helpers
+pub use crate::output::humanize::{human_bytes, human_count, human_duration};
+
 // Re-export all starbase_styles functions directly
 pub use starbase_styles::color::*;
 
@@ -0,0 +1,62 @@
+//! Interactive prompts with SuperCLI's look
+//!
+//! Thin wrappers around [dialoguer](https://docs.rs/dialoguer) that drop its `?`
+//! prompt prefix and report failures as [`std::io::Error`], so tools don't need
+//! to depend on dialoguer themselves. Prompts need a terminal - check
+//! `std::io::IsTerminal` before calling them.
+
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, MultiSelect};
+use std::io;
+
+/// One toggleable entry of a [`multi_select`] prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Choice {
+    pub label: String,
+    /// Checked when the prompt opens
+    pub selected: bool,
+}
+
+impl Choice {
+    pub fn new(label: impl Into<String>, selected: bool) -> Self {
+        Self {
+            label: label.into(),
+            selected,
+        }
+    }
+}
+
+fn theme() -> ColorfulTheme {
+    ColorfulTheme {
+        prompt_prefix: dialoguer::console::style(String::new()),
+        ..ColorfulTheme::default()
+    }
+}
+
+fn io_error(error: dialoguer::Error) -> io::Error {
+    match error {
+        dialoguer::Error::IO(error) => error,
+    }
+}
+
+/// Let the user toggle `choices` (space to toggle, enter to accept) and return
+/// the indices of the checked ones, in order
+pub fn multi_select(prompt: &str, choices: &[Choice]) -> io::Result<Vec<usize>> {
+    let labels: Vec<&str> = choices.iter().map(|choice| choice.label.as_str()).collect();
+    let defaults: Vec<bool> = choices.iter().map(|choice| choice.selected).collect();
+    MultiSelect::with_theme(&theme())
+        .with_prompt(format!("{prompt} (space to toggle, enter to accept)"))
+        .items(&labels)
+        .defaults(&defaults)
+        .interact()
+        .map_err(io_error)
+}
+
+/// Ask a yes/no question, pre-selecting `default`
+pub fn confirm(prompt: &str, default: bool) -> io::Result<bool> {
+    Confirm::with_theme(&theme())
+        .with_prompt(prompt)
+        .default(default)
+        .interact()
+        .map_err(io_error)
+}